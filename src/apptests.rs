@@ -0,0 +1,174 @@
+//! The App state machine end to end against wiremock servers standing in for Nyaa,
+//! Real-Debrid and the download host: keys go through `handle_key_event`, and the
+//! messages background tasks send are fed to `handle_message` as the event loop does.
+
+use super::*;
+use std::ffi::OsString;
+use std::time::Duration;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Held by tests that change the environment or build an App from it, so tests running
+/// in parallel never see each other's settings
+pub static ENV: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+const API: &str = "/rest/1.0";
+const MAGNET: &str = "magnet:?xt=urn:btih:0123456789abcdef0123456789abcdef01234567&dn=Test.Show.01";
+const EPISODE: &str = "episode bytes";
+
+/// Environment variables set for one test, put back as they were when dropped
+struct Env(Vec<(&'static str, Option<OsString>)>);
+
+impl Env {
+    fn set(vars: &[(&'static str, &std::ffi::OsStr)]) -> Self {
+        let saved = vars.iter().map(|(key, _)| (*key, std::env::var_os(key))).collect();
+        for (key, value) in vars {
+            std::env::set_var(key, value);
+        }
+        Env(saved)
+    }
+}
+
+impl Drop for Env {
+    fn drop(&mut self) {
+        for (key, value) in &self.0 {
+            match value {
+                Some(value) => std::env::set_var(key, value),
+                None => std::env::remove_var(key),
+            }
+        }
+    }
+}
+
+/// Answer `verb endpoint` with `status` and `body`, `times` times (or always)
+async fn route(server: &MockServer, verb: &str, endpoint: &str, status: u16, body: &str, times: Option<u64>) {
+    let mock = Mock::given(method(verb)).and(path(endpoint)).respond_with(ResponseTemplate::new(status).set_body_string(body));
+    match times {
+        Some(n) => mock.up_to_n_times(n).mount(server).await,
+        None => mock.mount(server).await,
+    }
+}
+
+/// A Nyaa search page with one result
+fn search_page() -> String {
+    format!(
+        r#"<html><body><table class="table torrent-list"><tbody>
+        <tr>
+            <td><a href="/?c=1_2" title="Anime - English-translated"></a></td>
+            <td colspan="2"><a href="/view/1" title="[SubsPlease] Test Show - 01 (1080p).mkv">x</a></td>
+            <td class="text-center"><a href="{}">m</a></td>
+            <td class="text-center">1.4 GiB</td>
+            <td class="text-center">2023-11-14 22:13</td>
+            <td class="text-center">120</td>
+            <td class="text-center">4</td>
+            <td class="text-center">900</td>
+        </tr>
+        </tbody></table></body></html>"#,
+        MAGNET.replace('&', "&amp;")
+    )
+}
+
+/// Answer a key like the event loop, then run what it does after each event
+async fn press(app: &mut App, code: KeyCode, tx: &mpsc::UnboundedSender<AppMessage>) {
+    handle_key_event(app, code, KeyModifiers::NONE, tx.clone()).await;
+    if std::mem::take(&mut app.autostart_pending) {
+        start_pending_downloads(app, tx);
+    }
+}
+
+/// Handle background messages until `done` holds
+async fn settle(
+    app: &mut App,
+    rx: &mut mpsc::UnboundedReceiver<AppMessage>,
+    tx: &mpsc::UnboundedSender<AppMessage>,
+    done: impl Fn(&App) -> bool,
+) {
+    while !done(app) {
+        let msg = tokio::time::timeout(Duration::from_secs(10), rx.recv())
+            .await
+            .unwrap_or_else(|_| panic!("stuck in {:?}: {}", app.mode, app.status))
+            .expect("channel open");
+        handle_message(app, msg);
+        if std::mem::take(&mut app.autostart_pending) {
+            start_pending_downloads(app, tx);
+        }
+    }
+}
+
+#[tokio::test]
+async fn search_select_download() {
+    let _env = ENV.lock().await;
+    let host = MockServer::start().await;
+    let server = MockServer::start().await;
+    let dir = std::env::temp_dir().join(format!("littlejohn-app-tests-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("download dir");
+    let api_url = format!("{}{}", server.uri(), API);
+    let _vars = Env::set(&[
+        ("XDG_CONFIG_HOME", dir.join("config").as_os_str()),
+        ("DOWNLOAD_DIR", dir.as_os_str()),
+        ("NYAA_URL", server.uri().as_ref()),
+        ("RD_API_TOKEN", "mock-token".as_ref()),
+        ("RD_API_URL", api_url.as_ref()),
+        ("RD_POLL_INTERVAL", "1".as_ref()),
+    ]);
+
+    let files = r#"[{"id": 1, "path": "/Test Show/Test.Show.01.mkv", "bytes": 13, "selected": 0}]"#;
+    let waiting = format!(r#"{{"id": "T1", "status": "waiting_files_selection", "files": {}}}"#, files);
+    let downloaded = format!(
+        r#"{{"id": "T1", "status": "downloaded", "progress": 100, "files": {}, "links": ["https://hoster.example/ep1"]}}"#,
+        files.replace(r#""selected": 0"#, r#""selected": 1"#)
+    );
+    let unrestricted = format!(
+        r#"{{"filename": "Test.Show.01.mkv", "download": "{}/d/Test.Show.01.mkv", "filesize": 13}}"#,
+        host.uri()
+    );
+    let api = |endpoint: &str| format!("{}{}", API, endpoint);
+    route(&host, "GET", "/d/Test.Show.01.mkv", 200, EPISODE, None).await;
+    route(&server, "GET", "/", 200, &search_page(), None).await;
+    route(&server, "GET", &api("/torrents"), 200, "[]", None).await;
+    route(&server, "POST", &api("/torrents/addMagnet"), 201, r#"{"id": "T1", "uri": ""}"#, None).await;
+    // Waiting for a selection while the files are listed and picked, then cached
+    route(&server, "GET", &api("/torrents/info/T1"), 200, &waiting, Some(2)).await;
+    route(&server, "GET", &api("/torrents/info/T1"), 200, &downloaded, None).await;
+    route(&server, "POST", &api("/torrents/selectFiles/T1"), 204, "", None).await;
+    route(&server, "GET", &api("/hosts/status"), 200, "{}", None).await;
+    route(&server, "POST", &api("/unrestrict/link"), 200, &unrestricted, None).await;
+
+    let mut app = App::new();
+    app.telegram = None;
+    app.group_titles = false;
+    app.enabled_sources = ["nyaa".to_string()].into();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    // Enter on the search screen runs the search task against the mock Nyaa
+    app.search_input = "test show".to_string();
+    app.cursor_pos = app.search_input.len();
+    press(&mut app, KeyCode::Enter, &tx).await;
+    assert_eq!(app.mode, AppMode::Processing);
+    settle(&mut app, &mut rx, &tx, |app| app.mode == AppMode::Results).await;
+    assert_eq!(app.results.len(), 1);
+    assert_eq!(app.results[0].magnet, MAGNET);
+
+    // Enter adds the magnet and lists its files; the only file is preselected
+    press(&mut app, KeyCode::Enter, &tx).await;
+    settle(&mut app, &mut rx, &tx, |app| app.mode == AppMode::FileSelect).await;
+    assert_eq!(app.selected_files.len(), 1);
+
+    // Enter selects it on RD and queues the unrestricted link
+    press(&mut app, KeyCode::Enter, &tx).await;
+    settle(&mut app, &mut rx, &tx, |app| !app.downloads.is_empty()).await;
+    assert_eq!(app.mode, AppMode::Results);
+    assert_eq!(app.downloads[0].filename, "Test.Show.01.mkv");
+
+    press(&mut app, KeyCode::Char('d'), &tx).await;
+    press(&mut app, KeyCode::Char('S'), &tx).await;
+    settle(&mut app, &mut rx, &tx, |app| !app.downloads[0].status.is_active()).await;
+    assert_eq!(app.downloads[0].status, DownloadStatus::Completed);
+    assert_eq!(std::fs::read_to_string(&app.downloads[0].dest_path).expect("downloaded file"), EPISODE);
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    let add = requests.iter().find(|r| r.url.path() == api("/torrents/addMagnet")).expect("addMagnet");
+    assert!(String::from_utf8_lossy(&add.body).starts_with("magnet=magnet%3A"));
+    assert!(requests.iter().any(|r| r.url.path() == api("/torrents/selectFiles/T1")));
+    let _ = std::fs::remove_dir_all(&dir);
+}
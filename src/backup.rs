@@ -0,0 +1,154 @@
+//! Moving littlejohn to another machine: `littlejohn config export <file>` bundles the
+//! config file (settings, source choices and priority), the search and download
+//! histories, scraper stats and plugins into one JSON archive, and `config import <file>`
+//! unpacks it into this machine's config directory.
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Archive layout version, bumped if the format changes
+const FORMAT: u32 = 1;
+
+/// Files carried over, relative to the config directory (plugins are added separately).
+/// Mirror health, login sessions and caches belong to the machine and stay behind.
+const FILES: &[&str] = &["config.toml", "search_history.json", "download_history.json", "scraper_stats.json"];
+
+const PLUGIN_DIR: &str = "plugins";
+
+#[derive(Serialize, Deserialize)]
+struct Archive {
+    format: u32,
+    /// littlejohn version that wrote it
+    version: String,
+    created: String,
+    /// Contents by path relative to the config directory, base64-encoded
+    files: BTreeMap<String, String>,
+}
+
+fn config_dir() -> Result<PathBuf> {
+    Ok(dirs::config_dir().context("Config directory not found")?.join("littlejohn"))
+}
+
+/// Whether `name` is a file the archive may hold, so an import can't write elsewhere
+fn allowed(name: &str) -> bool {
+    if FILES.contains(&name) {
+        return true;
+    }
+    match name.strip_prefix(PLUGIN_DIR).and_then(|rest| rest.strip_prefix('/')) {
+        Some(plugin) => plugin.ends_with(".wasm") && !plugin.contains(['/', '\\']) && !plugin.starts_with('.'),
+        None => false,
+    }
+}
+
+/// Write the archive to `dest`; returns a summary of what went in
+pub fn export(dest: &Path) -> Result<String> {
+    let dir = config_dir()?;
+    let engine = base64::engine::general_purpose::STANDARD;
+    let mut files = BTreeMap::new();
+
+    let mut names: Vec<String> = FILES.iter().map(|name| name.to_string()).collect();
+    if let Ok(entries) = std::fs::read_dir(dir.join(PLUGIN_DIR)) {
+        names.extend(
+            entries
+                .flatten()
+                .map(|entry| format!("{}/{}", PLUGIN_DIR, entry.file_name().to_string_lossy()))
+                .filter(|name| allowed(name)),
+        );
+    }
+    for name in names {
+        match std::fs::read(dir.join(&name)) {
+            Ok(bytes) => {
+                files.insert(name, engine.encode(bytes));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.join(&name).display())),
+        }
+    }
+    if files.is_empty() {
+        bail!("Nothing to export: {} has no settings or history", dir.display());
+    }
+
+    let archive = Archive {
+        format: FORMAT,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        created: chrono::Local::now().to_rfc3339(),
+        files,
+    };
+    // It holds API tokens, so only the owner may read it
+    crate::config::write_private(dest, &serde_json::to_string_pretty(&archive)?)
+        .with_context(|| format!("Failed to write {}", dest.display()))?;
+    Ok(format!(
+        "Exported {} to {}\n",
+        archive.files.keys().cloned().collect::<Vec<_>>().join(", "),
+        dest.display()
+    ))
+}
+
+/// Free name to keep a replaced file under: `<file>.bak`, then `<file>.bak.1`, `.bak.2`...
+/// so an earlier import's backups aren't overwritten
+fn backup_path(path: &Path) -> PathBuf {
+    let base = format!("{}.bak", path.display());
+    std::iter::once(PathBuf::from(&base))
+        .chain((1..).map(|n| PathBuf::from(format!("{}.{}", base, n))))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| PathBuf::from(base))
+}
+
+/// Unpack the archive at `src` into the config directory. Files it replaces are kept
+/// next to the new ones with a `.bak` suffix. The whole archive is checked before
+/// anything is written, so a bad one leaves the config as it was. Returns a summary
+/// of what was restored.
+pub fn import(src: &Path) -> Result<String> {
+    let text = std::fs::read_to_string(src).with_context(|| format!("Failed to read {}", src.display()))?;
+    let archive: Archive =
+        serde_json::from_str(&text).with_context(|| format!("{} is not a littlejohn export", src.display()))?;
+    if archive.format > FORMAT {
+        bail!(
+            "{} was exported by littlejohn {} in a newer format; update littlejohn to import it",
+            src.display(),
+            archive.version
+        );
+    }
+
+    let engine = base64::engine::general_purpose::STANDARD;
+    let mut files = Vec::new();
+    for (name, data) in &archive.files {
+        if !allowed(name) {
+            bail!("{} holds an unexpected file: {}", src.display(), name);
+        }
+        let bytes = engine.decode(data).with_context(|| format!("{} is damaged ({})", src.display(), name))?;
+        files.push((name.as_str(), bytes));
+    }
+
+    let dir = config_dir()?;
+    let mut restored = Vec::new();
+    let mut replaced = 0;
+    for (name, bytes) in files {
+        let path = dir.join(name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if path.exists() {
+            let backup = backup_path(&path);
+            std::fs::rename(&path, &backup).with_context(|| format!("Failed to move {} aside", path.display()))?;
+            replaced += 1;
+        }
+        // Text files may hold tokens; plugins are binary
+        match std::str::from_utf8(&bytes) {
+            Ok(text) => crate::config::write_private(&path, text),
+            Err(_) => std::fs::write(&path, &bytes),
+        }
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+        restored.push(name);
+    }
+
+    let mut summary = format!("Imported {} into {}", restored.join(", "), dir.display());
+    if replaced > 0 {
+        summary.push_str(&format!(" ({} existing file(s) kept as .bak)", replaced));
+    }
+    summary.push('\n');
+    Ok(summary)
+}
@@ -0,0 +1,305 @@
+//! Non-interactive batch mode: queries or magnets read from stdin
+//!
+//! Progress goes to stderr; results go to stdout, one per line:
+//! - queries: `seeders<TAB>leechers<TAB>size<TAB>source<TAB>name<TAB>magnet`
+//! - magnets: `filename<TAB>url` for every file (all files are selected)
+//!
+//! With `--json`, each input line produces one JSON object instead and logs are silenced.
+//!
+//! With `--download`, magnet files are also downloaded to the download directory. Combined
+//! with `--json`, progress is streamed as newline-delimited JSON events (objects with an
+//! `event` key) ahead of each magnet's result object.
+
+use anyhow::{anyhow, Result};
+use std::io::BufRead;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::detached::{self, Job};
+use crate::realdebrid::{self, RealDebridClient};
+use crate::release::Category;
+use crate::scrapers::SourceStatus;
+use crate::{run_search, scrapers};
+
+/// Batch output options
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// No progress on stderr
+    pub quiet: bool,
+    /// Structured output (implies quiet)
+    pub json: bool,
+    /// Download the files of resolved magnets
+    pub download: bool,
+}
+
+/// Minimum time between JSON progress events for a download
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Process exit status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Results found for every input
+    Found = 0,
+    /// At least one input found nothing
+    NoResults = 1,
+    /// Missing or rejected configuration (e.g. Real-Debrid token)
+    ConfigError = 2,
+    /// Network or I/O failure (all sources failed, a Real-Debrid request failed, or the
+    /// input couldn't be read)
+    NetworkError = 3,
+}
+
+impl Outcome {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    /// Severity when combining outcomes: config > network > no results > found
+    fn severity(self) -> u8 {
+        match self {
+            Outcome::Found => 0,
+            Outcome::NoResults => 1,
+            Outcome::NetworkError => 2,
+            Outcome::ConfigError => 3,
+        }
+    }
+
+    fn worst(self, other: Outcome) -> Outcome {
+        if other.severity() > self.severity() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+/// Classify a Real-Debrid error
+fn error_outcome(e: &anyhow::Error) -> Outcome {
+    if realdebrid::is_auth_error(e) {
+        Outcome::ConfigError
+    } else if e.downcast_ref::<reqwest::Error>().is_some() {
+        Outcome::NetworkError
+    } else {
+        Outcome::NoResults
+    }
+}
+
+/// Process every non-empty line of `input` and return the overall outcome
+pub async fn run(input: impl BufRead, options: Options) -> Result<Outcome> {
+    let quiet = options.quiet || options.json;
+    scrapers::log::set_quiet(quiet);
+    let progress = |msg: &str| {
+        if !quiet {
+            eprintln!("{}", msg);
+        }
+    };
+
+    let enabled_sources = scrapers::default_enabled();
+    let rd_client = RealDebridClient::new();
+    let mut outcome = Outcome::Found;
+
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with("magnet:") {
+            let on_status = |status: &str| {
+                if options.json && options.download {
+                    emit(serde_json::json!({ "event": "rd_status", "magnet": line, "status": status }));
+                } else {
+                    progress(status);
+                }
+            };
+            let result = match &rd_client {
+                Ok(client) => resolve_magnet(client, line, &on_status).await,
+                Err(e) => Err(anyhow!("Real-Debrid not configured: {}", e)),
+            };
+
+            let mut download_failed = false;
+            if let (true, Ok(links)) = (options.download, &result) {
+                let client = crate::http::transfer_client();
+                for (filename, url) in links {
+                    if !download_file(&client, filename, url, options, &progress).await {
+                        download_failed = true;
+                    }
+                }
+            }
+
+            let line_outcome = match (&rd_client, &result) {
+                (Err(_), _) => Outcome::ConfigError,
+                (_, Ok(_)) if download_failed => Outcome::NetworkError,
+                (_, Ok(_)) => Outcome::Found,
+                (_, Err(e)) => error_outcome(e),
+            };
+            outcome = outcome.worst(line_outcome);
+
+            if options.json {
+                let value = match &result {
+                    Ok(links) => serde_json::json!({
+                        "magnet": line,
+                        "files": links
+                            .iter()
+                            .map(|(filename, url)| serde_json::json!({ "filename": filename, "url": url }))
+                            .collect::<Vec<_>>(),
+                    }),
+                    Err(e) => serde_json::json!({ "magnet": line, "error": e.to_string() }),
+                };
+                println!("{}", value);
+            } else {
+                match result {
+                    Ok(links) => {
+                        for (filename, url) in links {
+                            println!("{}\t{}", filename, url);
+                        }
+                    }
+                    Err(e) => eprintln!("Magnet failed: {}", e),
+                }
+            }
+        } else {
+            progress(&format!("Searching for '{}'...", line));
+            let (results, statuses) = run_search(line, 1, &enabled_sources, false, false, &CancellationToken::new()).await;
+            progress(&format!("{} results", results.len()));
+
+            let all_failed = !statuses.is_empty()
+                && statuses.values().all(|s| *s == SourceStatus::Failed);
+            let line_outcome = if !results.is_empty() {
+                Outcome::Found
+            } else if all_failed {
+                Outcome::NetworkError
+            } else {
+                Outcome::NoResults
+            };
+            outcome = outcome.worst(line_outcome);
+
+            if options.json {
+                let sources: serde_json::Map<String, serde_json::Value> = statuses
+                    .iter()
+                    .map(|(source, status)| {
+                        let status = match status {
+                            SourceStatus::Ok(_) => "ok",
+                            SourceStatus::Empty => "empty",
+                            SourceStatus::Failed => "failed",
+                        };
+                        (source.clone(), status.into())
+                    })
+                    .collect();
+                let value = serde_json::json!({
+                    "query": line,
+                    "results": results,
+                    "sources": sources,
+                });
+                println!("{}", value);
+            } else {
+                for r in &results {
+                    println!(
+                        "{}\t{}\t{}\t{}\t{}\t{}",
+                        r.seeders, r.leechers, r.size, r.source, r.name, r.magnet
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(outcome)
+}
+
+/// Add a magnet to RD, select all files and return the download links
+async fn resolve_magnet(
+    client: &RealDebridClient,
+    magnet: &str,
+    progress: &impl Fn(&str),
+) -> Result<Vec<(String, String)>> {
+    progress("Adding magnet to Real-Debrid...");
+    let (torrent_id, files) = client.get_torrent_files(magnet).await?;
+    if files.is_empty() {
+        let _ = client.delete_torrent(&torrent_id).await;
+        return Err(anyhow!("Torrent has no files"));
+    }
+
+    let file_ids: Vec<u32> = files.iter().map(|f| f.id).collect();
+    client
+        .download_selected_files_with_callback(&torrent_id, &file_ids, |status| progress(status))
+        .await
+}
+
+/// Print one NDJSON event on stdout
+fn emit(event: serde_json::Value) {
+    println!("{}", event);
+}
+
+/// Download one file into the download directory (ON_FILE_EXISTS decides what happens to
+/// a file already there). With `--json`,
+/// status changes and throttled progress (bytes, speed, ETA) are emitted as events.
+/// Returns false if the download failed.
+async fn download_file(
+    client: &reqwest::Client,
+    filename: &str,
+    url: &str,
+    options: Options,
+    progress: &impl Fn(&str),
+) -> bool {
+    let mut job = Job::new(url.to_string(), crate::paths::dest_path(&crate::category_dir(Category::guess(None, filename)), filename));
+    let path = job.dest_path.display().to_string();
+    if options.json {
+        emit(serde_json::json!({ "event": "status", "file": filename, "path": path, "status": "downloading" }));
+    } else {
+        progress(&format!("Downloading {}", path));
+    }
+
+    let mut last: Option<(Instant, u64)> = None;
+    let result = detached::download(client, &mut job, |downloaded, total| {
+        if !options.json {
+            return;
+        }
+        let now = Instant::now();
+        let speed = match last {
+            Some((at, _)) if now.duration_since(at) < PROGRESS_INTERVAL => return,
+            Some((at, bytes)) => downloaded.saturating_sub(bytes) as f64 / now.duration_since(at).as_secs_f64(),
+            None => 0.0,
+        };
+        last = Some((now, downloaded));
+
+        let eta = (speed > 0.0 && total > downloaded).then(|| ((total - downloaded) as f64 / speed).round() as u64);
+        emit(serde_json::json!({
+            "event": "progress",
+            "file": filename,
+            "downloaded": downloaded,
+            "total": total,
+            "speed": speed.round() as u64,
+            "eta": eta,
+        }));
+    })
+    .await;
+
+    // ON_FILE_EXISTS may have picked another name
+    let path = job.dest_path.display().to_string();
+    match result {
+        Ok(None) => {
+            if options.json {
+                emit(serde_json::json!({ "event": "status", "file": filename, "path": path, "status": "skipped" }));
+            } else {
+                progress(&format!("Skipped {}: the file already exists", path));
+            }
+            true
+        }
+        Ok(Some(bytes)) => {
+            if options.json {
+                emit(serde_json::json!({ "event": "status", "file": filename, "path": path, "status": "completed", "bytes": bytes }));
+            } else {
+                progress(&format!("Completed {} ({} bytes)", path, bytes));
+            }
+            true
+        }
+        Err(e) => {
+            if options.json {
+                emit(serde_json::json!({ "event": "status", "file": filename, "path": path, "status": "failed", "error": e.to_string() }));
+            } else {
+                eprintln!("Download failed: {}: {}", path, e);
+            }
+            false
+        }
+    }
+}
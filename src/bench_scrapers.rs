@@ -0,0 +1,151 @@
+//! Benchmark scrapers: run N queries against each source and report latency
+//! percentiles and parse success as JSON
+//!
+//! Usage: bench_scrapers [--runs N] [--out FILE] [--source NAME]... [QUERY]...
+
+use littlejohn::scrapers;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+const DEFAULT_QUERIES: &[&str] = &["ubuntu", "matrix 1999", "big buck bunny"];
+
+/// A single scraper run
+struct Sample {
+    latency: Duration,
+    results: Option<usize>,
+}
+
+/// Nearest-rank percentile of sorted latencies in milliseconds
+fn percentile(sorted_ms: &[u64], pct: f64) -> u64 {
+    if sorted_ms.is_empty() {
+        return 0;
+    }
+    let rank = ((pct / 100.0) * sorted_ms.len() as f64).ceil() as usize;
+    sorted_ms[rank.clamp(1, sorted_ms.len()) - 1]
+}
+
+fn source_report(source: &str, samples: &[Sample]) -> serde_json::Value {
+    let mut latencies: Vec<u64> = samples.iter().map(|s| s.latency.as_millis() as u64).collect();
+    latencies.sort_unstable();
+
+    let runs = samples.len();
+    let failures = samples.iter().filter(|s| s.results.is_none()).count();
+    let empty = samples.iter().filter(|s| s.results == Some(0)).count();
+    let parsed = runs - failures - empty;
+    let total_results: usize = samples.iter().filter_map(|s| s.results).sum();
+
+    serde_json::json!({
+        "source": source,
+        "runs": runs,
+        "parsed": parsed,
+        "empty": empty,
+        "failed": failures,
+        "parse_success_rate": if runs == 0 { 0.0 } else { parsed as f64 / runs as f64 },
+        "total_results": total_results,
+        "latency_ms": {
+            "min": latencies.first().copied().unwrap_or(0),
+            "p50": percentile(&latencies, 50.0),
+            "p90": percentile(&latencies, 90.0),
+            "p99": percentile(&latencies, 99.0),
+            "max": latencies.last().copied().unwrap_or(0),
+        },
+    })
+}
+
+#[tokio::main]
+async fn main() {
+    // Load settings - .env in the current directory first, then the config file
+    if let Err(e) = littlejohn::config::load() {
+        eprintln!("Error: {:#}", e);
+        std::process::exit(1);
+    }
+
+    let mut runs: usize = 1;
+    let mut out: Option<String> = None;
+    let mut sources: Vec<String> = Vec::new();
+    let mut queries: Vec<String> = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--runs" | "-n" => {
+                runs = args.next().and_then(|n| n.parse().ok()).unwrap_or(1).max(1);
+            }
+            "--out" | "-o" => out = args.next(),
+            "--source" | "-s" => sources.extend(args.next()),
+            _ => queries.push(arg),
+        }
+    }
+
+    if queries.is_empty() {
+        queries = DEFAULT_QUERIES.iter().map(|q| q.to_string()).collect();
+    }
+    if sources.is_empty() {
+        sources = scrapers::SCRAPERS.iter().map(|s| s.name().to_string()).collect();
+    }
+
+    let client = match scrapers::create_client() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Failed to create HTTP client: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut samples: Vec<Vec<Sample>> = sources.iter().map(|_| Vec::new()).collect();
+    let started = chrono::Local::now();
+
+    for run in 1..=runs {
+        for query in &queries {
+            eprintln!("[run {}/{}] {}", run, runs, query);
+
+            // All sources in parallel for each query
+            let outcomes = futures::future::join_all(sources.iter().map(|source| {
+                let client = &client;
+                async move {
+                    let start = Instant::now();
+                    let results = scrapers::scrape_source(client, source, query, 1, false, &CancellationToken::new()).await;
+                    Sample { latency: start.elapsed(), results: results.map(|r| r.len()) }
+                }
+            }))
+            .await;
+
+            for (i, sample) in outcomes.into_iter().enumerate() {
+                eprintln!(
+                    "  {:15} {:>6} ms  {}",
+                    sources[i],
+                    sample.latency.as_millis(),
+                    match sample.results {
+                        Some(n) => format!("{} results", n),
+                        None => "FAILED".to_string(),
+                    }
+                );
+                samples[i].push(sample);
+            }
+        }
+    }
+
+    let report = serde_json::json!({
+        "started_at": started.to_rfc3339(),
+        "version": env!("CARGO_PKG_VERSION"),
+        "runs": runs,
+        "queries": queries,
+        "sources": sources
+            .iter()
+            .zip(&samples)
+            .map(|(source, s)| source_report(source, s))
+            .collect::<Vec<_>>(),
+    });
+
+    let json = serde_json::to_string_pretty(&report).unwrap_or_default();
+    match out {
+        Some(path) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                eprintln!("Failed to write {}: {}", path, e);
+                std::process::exit(1);
+            }
+            eprintln!("Report written to {}", path);
+        }
+        None => println!("{}", json),
+    }
+}
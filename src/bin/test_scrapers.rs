@@ -24,12 +24,10 @@ fn print_results(name: &str, results: &Option<Vec<TorrentResult>>) {
 
 #[tokio::main]
 async fn main() {
-    // Load .env file - check current directory first, then config directory
-    if dotenvy::dotenv().is_err() {
-        if let Some(config_dir) = dirs::config_dir() {
-            let config_env = config_dir.join("littlejohn").join(".env");
-            dotenvy::from_path(&config_env).ok();
-        }
+    // Load settings - .env in the current directory first, then the config file
+    if let Err(e) = littlejohn::config::load() {
+        eprintln!("Error: {:#}", e);
+        std::process::exit(1);
     }
 
     let query = std::env::args().nth(1).unwrap_or_else(|| "matrix 1999".to_string());
@@ -45,7 +43,7 @@ async fn main() {
 
     // Test each scraper individually
     println!("\n--- Testing 1337x ---");
-    let r1337x = scrapers::x1337::scrape_1337x(&client, &query, 1).await;
+    let r1337x = scrapers::x1337::scrape_1337x(&client, &query, 1, false).await;
     print_results("1337x", &r1337x);
 
     println!("\n--- Testing TPB ---");
@@ -57,11 +55,11 @@ async fn main() {
     print_results("BitSearch", &bitsearch);
 
     println!("\n--- Testing YTS ---");
-    let yts = scrapers::yts::scrape_yts(&client, &query, 1).await;
+    let yts = scrapers::yts::scrape_yts(&client, &query, 1, false).await;
     print_results("YTS", &yts);
 
     println!("\n--- Testing ilCorsaroNero ---");
-    let ilcorsaronero = scrapers::ilcorsaronero::scrape_ilcorsaronero(&client, &query, 1).await;
+    let ilcorsaronero = scrapers::ilcorsaronero::scrape_ilcorsaronero(&client, &query, 1, false).await;
     print_results("ilCorsaroNero", &ilcorsaronero);
 
     // Test combined search
@@ -0,0 +1,73 @@
+//! Blackhole export: instead of going through Real-Debrid, picked magnets are written
+//! into a directory another torrent client watches (BLACKHOLE_DIR), e.g. on a seedbox
+
+use anyhow::{anyhow, Context, Result};
+use std::path::PathBuf;
+
+use crate::{import, paths, scrapers};
+
+/// Public torrent cache serving .torrent files by info hash
+const TORRENT_CACHE_URL: &str = "https://itorrents.org/torrent";
+
+/// Directory the other client watches, if blackhole export is configured
+pub fn dir() -> Option<PathBuf> {
+    std::env::var("BLACKHOLE_DIR")
+        .ok()
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| PathBuf::from(d.trim()))
+}
+
+/// Whether to fetch a .torrent file instead of writing a .magnet (BLACKHOLE_FORMAT=torrent)
+fn wants_torrent() -> bool {
+    std::env::var("BLACKHOLE_FORMAT").is_ok_and(|f| f.trim().eq_ignore_ascii_case("torrent"))
+}
+
+/// The .torrent for `magnet` from the torrent cache, if it has it
+async fn fetch_torrent(magnet: &str) -> Option<Vec<u8>> {
+    let hash = scrapers::magnet_info_hash(magnet)?;
+    let url = format!("{}/{}.torrent", TORRENT_CACHE_URL, hash.to_uppercase());
+    let response = crate::http::scraper_client().get(&url).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    let bytes = response.bytes().await.ok()?;
+    // A bencoded dictionary, not an HTML error page
+    bytes.starts_with(b"d").then(|| bytes.to_vec())
+}
+
+/// Write `magnet` into the blackhole directory as `<name>.magnet`, or as `<name>.torrent`
+/// with BLACKHOLE_FORMAT=torrent when the cache has it. `name` defaults to the magnet's
+/// display name. Returns the file written.
+pub async fn export(magnet: &str, name: Option<&str>) -> Result<PathBuf> {
+    let dir = dir().ok_or_else(|| anyhow!("BLACKHOLE_DIR not set"))?;
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => import::Entry::Magnet(magnet.to_string()).label(),
+    };
+    // Without a display name the label is the whole magnet, so use the hash
+    let name = if name.starts_with("magnet:") {
+        scrapers::magnet_info_hash(magnet).unwrap_or_else(|| "magnet".to_string())
+    } else {
+        name
+    };
+
+    let torrent = if wants_torrent() { fetch_torrent(magnet).await } else { None };
+    let (content, extension) = match torrent {
+        Some(data) => (data, "torrent"),
+        None => (magnet.as_bytes().to_vec(), "magnet"),
+    };
+    let path = paths::dest_path(&dir, &format!("{}.{}", name, extension));
+
+    // Write aside and rename, so the watching client never picks up a partial file
+    let partial = path.with_extension(format!("{}.part", extension));
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .with_context(|| format!("Failed to create {}", dir.display()))?;
+    tokio::fs::write(&partial, content)
+        .await
+        .with_context(|| format!("Failed to write {}", partial.display()))?;
+    tokio::fs::rename(&partial, &path)
+        .await
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
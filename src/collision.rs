@@ -0,0 +1,45 @@
+//! What to do when a download's destination file already exists
+
+use std::path::{Path, PathBuf};
+
+/// Policy for existing destination files (ON_FILE_EXISTS)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Policy {
+    /// Replace the existing file
+    Overwrite,
+    /// Keep the existing file and don't download
+    Skip,
+    /// Save as "name (1).ext", "name (2).ext", ...
+    Rename,
+    /// Continue a partial file with a range request (complete files are kept)
+    Resume,
+}
+
+impl Policy {
+    /// Policy from ON_FILE_EXISTS, defaulting to rename
+    pub fn from_env() -> Self {
+        match std::env::var("ON_FILE_EXISTS").unwrap_or_default().trim().to_lowercase().as_str() {
+            "overwrite" => Policy::Overwrite,
+            "skip" => Policy::Skip,
+            "resume" => Policy::Resume,
+            _ => Policy::Rename,
+        }
+    }
+}
+
+/// First "name (n).ext" next to `path` that doesn't exist yet
+pub fn unique_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("download");
+    let ext = path.extension().and_then(|s| s.to_str());
+
+    (1..)
+        .map(|n| {
+            let name = match ext {
+                Some(ext) => format!("{} ({}).{}", stem, n, ext),
+                None => format!("{} ({})", stem, n),
+            };
+            path.with_file_name(name)
+        })
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| path.to_path_buf())
+}
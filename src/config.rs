@@ -0,0 +1,373 @@
+//! Config file at `<config dir>/littlejohn/config.toml`, with a schema version and
+//! migrations from older layouts
+//!
+//! - Version 1: a `.env` file with upper-case keys (`RD_API_TOKEN=...`)
+//! - Version 2: TOML with a `version` field and lower-case keys (`rd_api_token = "..."`)
+//!
+//! Settings are exported to the environment, so the rest of the app keeps reading
+//! them with `std::env::var`. Variables already set in the environment win.
+//! Source credentials live in `[auth.<source>]` tables and are read with [`source_auth`].
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Schema version written by this build
+pub const VERSION: i64 = 2;
+
+const FILE_NAME: &str = "config.toml";
+
+/// Version 1 config file, replaced by config.toml on first start
+const LEGACY_FILE_NAME: &str = ".env";
+
+/// Keys edited in the settings screen
+pub const SETTINGS_KEYS: &[&str] = &["RD_API_TOKEN", "FIRECRAWL_API_KEY", "DOWNLOAD_DIR", "VIM_MODE", "PALETTE"];
+
+/// Config keys that are only set by editing the config file, carried over when settings are saved
+pub const PRESERVED_ENV_KEYS: &[&str] = &[
+    "QUERY_TEMPLATES",
+    "DISABLED_SOURCES",
+    "SOURCE_PRIORITY",
+    "SOURCE_LIMITS",
+    "YTS_EXPAND",
+    "FAST_SEARCH",
+    "DEEP_SEARCH",
+    "DEEP_SEARCH_RESULTS",
+    "DEEP_SEARCH_PAGES",
+    "RESULT_SORT",
+    "FIRECRAWL_CONCURRENCY",
+    "FIRECRAWL_PER_MINUTE",
+    "FIRECRAWL_WARN_CREDITS",
+    "CHROME_PATH",
+    "UPDATE_CHECK",
+    "PERSIST_STATS",
+    "HTTP_TIMEOUT",
+    "HTTP_CONNECT_TIMEOUT",
+    "HTTP_READ_TIMEOUT",
+    "REQUEST_JITTER",
+    "FIXED_USER_AGENT",
+    "DOH_URL",
+    "RD_MAGNET_TIMEOUT",
+    "RD_DOWNLOAD_TIMEOUT",
+    "RD_POLL_INTERVAL",
+    "RD_API_URL",
+    "NYAA_URL",
+    "ON_FILE_EXISTS",
+    "CHECKSUMS",
+    "DOWNLOAD_REMOTE",
+    "MIN_FREE_SPACE",
+    "MAX_DOWNLOAD_SPEED",
+    "SLOW_TRANSFER_SPEED",
+    "SLOW_TRANSFER_SECONDS",
+    "DOWNLOAD_WINDOW",
+    "DOWNLOAD_SPEED",
+    "CATEGORY_DIRS",
+    "ORGANIZE_TV",
+    "BLACKHOLE_DIR",
+    "BLACKHOLE_FORMAT",
+    "TELEGRAM_BOT_TOKEN",
+    "TELEGRAM_CHAT_ID",
+    "PREFER_CODECS",
+    "EXCLUDE_CODECS",
+    "TRAKT_CLIENT_ID",
+    "TRAKT_ACCESS_TOKEN",
+    "TRAKT_COLLECT",
+    "SELECTOR_PACK_URL",
+    "SELECTOR_PACK_KEY",
+    "WATCH_DIR",
+    "TMDB_API_KEY",
+    "GRAPHICS_PROTOCOL",
+];
+
+/// Credentials for a source, from an `[auth.<source>]` table: a fixed cookie, a login
+/// form, or both
+#[derive(Debug, Clone, Deserialize)]
+pub struct SourceAuth {
+    /// Cookie header sent as is (e.g. `uid=1; pass=abc`)
+    pub cookie: Option<String>,
+    /// Login form to POST the username and password to for a session cookie (built-in
+    /// sources that need an account know theirs)
+    pub login_url: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub username_field: Option<String>,
+    pub password_field: Option<String>,
+    /// Other fields the login form needs
+    #[serde(default)]
+    pub form: BTreeMap<String, String>,
+    /// Cookie that proves the login worked
+    pub session_cookie: Option<String>,
+}
+
+/// Credentials by source name
+type AuthSections = BTreeMap<String, SourceAuth>;
+
+static AUTH: OnceLock<AuthSections> = OnceLock::new();
+
+/// Credentials configured for `source`
+pub fn source_auth(source: &str) -> Option<&'static SourceAuth> {
+    AUTH.get()?.iter().find(|(name, _)| name.eq_ignore_ascii_case(source)).map(|(_, auth)| auth)
+}
+
+/// Path of the config file
+pub fn path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("littlejohn").join(FILE_NAME))
+}
+
+/// Load settings into the environment: a `.env` in the current directory if there is
+/// one, else the config file, migrating it first if it's from an older version.
+/// Returns notices for the user (migrations done, unknown keys ignored); fails if the
+/// config file is from a newer littlejohn or can't be parsed.
+pub fn load() -> Result<Vec<String>> {
+    if dotenvy::dotenv().is_ok() {
+        return Ok(Vec::new());
+    }
+    let Some(path) = path() else {
+        return Ok(Vec::new());
+    };
+
+    let mut notices = Vec::new();
+    let legacy = path.with_file_name(LEGACY_FILE_NAME);
+    let (values, auth, version) = if path.exists() {
+        read(&path)?
+    } else if legacy.exists() {
+        let text = std::fs::read_to_string(&legacy).with_context(|| format!("Failed to read {}", legacy.display()))?;
+        (parse_env(&text), BTreeMap::new(), 1)
+    } else {
+        return Ok(Vec::new());
+    };
+
+    let _ = AUTH.set(auth);
+
+    let values = if version < VERSION {
+        let values = migrate(values, version);
+        write(&path, &values)?;
+        if version == 1 {
+            let backup = legacy.with_file_name(".env.bak");
+            std::fs::rename(&legacy, &backup)
+                .with_context(|| format!("Failed to move {} aside", legacy.display()))?;
+            notices.push(format!("Moved settings to {} (old file kept as {})", path.display(), backup.display()));
+        } else {
+            notices.push(format!("Upgraded {} from version {} to {}", path.display(), version, VERSION));
+        }
+        values
+    } else {
+        values
+    };
+
+    let mut unknown = Vec::new();
+    for (key, value) in values {
+        let var = key.to_uppercase();
+        if !SETTINGS_KEYS.contains(&var.as_str()) && !PRESERVED_ENV_KEYS.contains(&var.as_str()) {
+            unknown.push(key);
+            continue;
+        }
+        if std::env::var_os(&var).is_none() {
+            std::env::set_var(&var, value);
+        }
+    }
+    if !unknown.is_empty() {
+        notices.push(format!("Ignored unknown settings in {}: {}", path.display(), unknown.join(", ")));
+    }
+    Ok(notices)
+}
+
+/// Parse the config file into its settings, source credentials and schema version
+fn read(path: &Path) -> Result<(BTreeMap<String, String>, AuthSections, i64)> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut table: toml::Table = text.parse().with_context(|| format!("{} is not valid TOML", path.display()))?;
+
+    // A hand-written file without a version is taken to be current
+    let version = match table.remove("version") {
+        None => VERSION,
+        Some(toml::Value::Integer(v)) => v,
+        Some(other) => bail!("{}: version must be a number, found {}", path.display(), other),
+    };
+    if version > VERSION {
+        bail!(
+            "{} is config version {}, but this littlejohn only understands version {} or older. \
+             Update littlejohn, or remove the file to start over.",
+            path.display(),
+            version,
+            VERSION
+        );
+    }
+
+    let auth = match table.remove("auth") {
+        None => BTreeMap::new(),
+        Some(auth) => auth
+            .try_into()
+            .with_context(|| format!("{}: invalid [auth] section", path.display()))?,
+    };
+
+    let mut values = BTreeMap::new();
+    for (key, value) in table {
+        let value = match value {
+            toml::Value::String(s) => s,
+            toml::Value::Boolean(_) | toml::Value::Integer(_) | toml::Value::Float(_) => value.to_string(),
+            other => bail!("{}: {} must be a string, number or boolean, found {}", path.display(), key, other),
+        };
+        values.insert(key, value);
+    }
+    Ok((values, auth, version))
+}
+
+/// Parse a version 1 `.env` file. Older builds wrote values unquoted even when they
+/// had spaces, so this is looser than dotenv: everything after the `=` is the value.
+fn parse_env(text: &str) -> BTreeMap<String, String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (key, value) = line.strip_prefix("export ").unwrap_or(line).split_once('=')?;
+            let value = value.trim();
+            let unquoted = ['"', '\'']
+                .iter()
+                .find_map(|q| value.strip_prefix(*q)?.strip_suffix(*q))
+                .unwrap_or(value);
+            Some((key.trim().to_string(), unquoted.to_string()))
+        })
+        .collect()
+}
+
+/// Bring settings from `version` up to the current layout, one version at a time
+fn migrate(mut values: BTreeMap<String, String>, version: i64) -> BTreeMap<String, String> {
+    for from in version..VERSION {
+        values = match from {
+            // .env -> TOML: keys become lower-case
+            1 => values.into_iter().map(|(k, v)| (k.to_lowercase(), v)).collect(),
+            _ => values,
+        };
+    }
+    values
+}
+
+/// Write settings as the current version; keys are env var names in any case. An
+/// `[auth]` section already in the file is kept.
+fn write(path: &Path, values: &BTreeMap<String, String>) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let auth = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| text.parse::<toml::Table>().ok())
+        .and_then(|mut table| table.remove("auth"));
+    let mut content = String::from("# littlejohn configuration\n\n");
+    content.push_str(&format!("version = {}\n\n", VERSION));
+
+    // Settings-screen keys first, then the rest in the documented order
+    let order = SETTINGS_KEYS.iter().chain(PRESERVED_ENV_KEYS);
+    let rank = |key: &str| order.clone().position(|k| k.eq_ignore_ascii_case(key)).unwrap_or(usize::MAX);
+    let mut keys: Vec<&String> = values.keys().collect();
+    keys.sort_by_key(|k| rank(k));
+    for key in keys {
+        let value = toml::Value::String(values[key].clone());
+        content.push_str(&format!("{} = {}\n", key.to_lowercase(), value));
+    }
+    if let Some(auth) = auth {
+        let section = toml::Table::from_iter([("auth".to_string(), auth)]);
+        content.push('\n');
+        content.push_str(&toml::to_string(&section)?);
+    }
+
+    write_private(path, &content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Write a file only the owner can read, as it holds tokens and passwords
+pub fn write_private(path: &Path, content: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // Files created by older versions keep their mode unless it's changed
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    use std::io::Write;
+    options.open(path)?.write_all(content.as_bytes())
+}
+
+/// Save settings (env var name to value) as the config file
+pub fn save(values: &BTreeMap<String, String>) -> Result<()> {
+    let path = path().context("Config directory not found")?;
+    write(&path, values)
+}
+
+/// Short names accepted by `littlejohn config` besides the setting names themselves
+const ALIASES: &[(&str, &str)] = &[
+    ("rd_token", "RD_API_TOKEN"),
+    ("firecrawl_key", "FIRECRAWL_API_KEY"),
+    ("tmdb_key", "TMDB_API_KEY"),
+];
+
+/// Env var name of a setting given as `rd_api_token`, `RD_API_TOKEN` or an alias
+fn setting_key(name: &str) -> Result<&'static str> {
+    if let Some((_, key)) = ALIASES.iter().find(|(alias, _)| alias.eq_ignore_ascii_case(name)) {
+        return Ok(key);
+    }
+    SETTINGS_KEYS
+        .iter()
+        .chain(PRESERVED_ENV_KEYS)
+        .find(|key| key.eq_ignore_ascii_case(name))
+        .copied()
+        .with_context(|| format!("Unknown setting '{}' (`littlejohn config keys` lists them)", name))
+}
+
+/// Settings in the config file as lower-case keys, migrated if the file is older
+fn file_values(path: &Path) -> Result<BTreeMap<String, String>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let (values, _, version) = read(path)?;
+    Ok(migrate(values, version).into_iter().map(|(k, v)| (k.to_lowercase(), v)).collect())
+}
+
+/// `littlejohn config <get [key] | set <key> <value> | unset <key> | keys | path>`: read
+/// and change the config file from scripts, without the TUI. Returns what to print.
+pub fn command(args: &[String]) -> Result<String> {
+    let path = path().context("Config directory not found")?;
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["get"] => Ok(file_values(&path)?
+            .iter()
+            .map(|(key, value)| format!("{} = {}\n", key, value))
+            .collect()),
+        ["get", name] => {
+            let key = setting_key(name)?.to_lowercase();
+            match file_values(&path)?.remove(&key) {
+                Some(value) => Ok(format!("{}\n", value)),
+                None => bail!("{} is not set", key),
+            }
+        }
+        ["set", name, value] => {
+            let key = setting_key(name)?.to_lowercase();
+            let mut values = file_values(&path)?;
+            values.insert(key.clone(), value.to_string());
+            write(&path, &values)?;
+            Ok(format!("Set {} in {}\n", key, path.display()))
+        }
+        ["unset", name] => {
+            let key = setting_key(name)?.to_lowercase();
+            let mut values = file_values(&path)?;
+            if values.remove(&key).is_none() {
+                bail!("{} is not set", key);
+            }
+            write(&path, &values)?;
+            Ok(format!("Removed {} from {}\n", key, path.display()))
+        }
+        ["keys"] => {
+            let mut keys: String = SETTINGS_KEYS.iter().chain(PRESERVED_ENV_KEYS).map(|key| format!("{}\n", key.to_lowercase())).collect();
+            for (alias, key) in ALIASES {
+                keys.push_str(&format!("{} (same as {})\n", alias, key.to_lowercase()));
+            }
+            Ok(keys)
+        }
+        ["path"] => Ok(format!("{}\n", path.display())),
+        _ => bail!("Usage: littlejohn config get [key] | set <key> <value> | unset <key> | keys | path | export <file> | import <file>"),
+    }
+}
@@ -0,0 +1,64 @@
+//! Panic handling for the TUI: a panic anywhere (a download task included) restores the
+//! terminal and writes a crash report with a backtrace to the config directory, instead
+//! of leaving the terminal in raw mode.
+
+use std::backtrace::Backtrace;
+use std::io::Write;
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+
+/// Directory crash reports are written to
+fn reports_dir() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("littlejohn").join("crashes"))
+}
+
+/// Install the hook; call once the terminal is in raw mode. The UI can't carry on
+/// with the terminal restored, so the process exits after reporting.
+pub fn install() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        ratatui::restore();
+        let report = report(info);
+        match write_report(&report) {
+            Some(path) => eprintln!("littlejohn crashed. Crash report: {}", path.display()),
+            None => eprintln!("littlejohn crashed.\n{}", report),
+        }
+        previous(info);
+        std::process::exit(101);
+    }));
+}
+
+/// Text of the report: version, where and why it panicked, and the backtrace
+fn report(info: &PanicHookInfo) -> String {
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    let location = info
+        .location()
+        .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+        .unwrap_or_else(|| "unknown".to_string());
+    format!(
+        "littlejohn {} ({} {})\nTime: {}\nThread: {}\nPanic: {}\nLocation: {}\n\nBacktrace:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        chrono::Local::now().to_rfc3339(),
+        std::thread::current().name().unwrap_or("unnamed"),
+        message,
+        location,
+        Backtrace::force_capture(),
+    )
+}
+
+/// Save the report as crash-<time>.txt; None if it couldn't be written
+fn write_report(report: &str) -> Option<PathBuf> {
+    let dir = reports_dir()?;
+    std::fs::create_dir_all(&dir).ok()?;
+    let path = dir.join(format!("crash-{}.txt", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+    let mut file = std::fs::File::create(&path).ok()?;
+    file.write_all(report.as_bytes()).ok()?;
+    Some(path)
+}
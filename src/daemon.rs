@@ -0,0 +1,478 @@
+//! Long-running download daemon (`littlejohn daemon`) and its control client (`littlejohn ctl`)
+//!
+//! The daemon runs queued magnets and links through Real-Debrid and downloads them,
+//! picks up magnet lists dropped into WATCH_DIR, and takes one JSON request per
+//! connection on a unix socket in the config directory.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::Notify;
+
+use crate::detached::{self, Job};
+use crate::import;
+use crate::realdebrid::RealDebridClient;
+use crate::release::Category;
+use crate::schedule;
+
+const CTL_USAGE: &str = "Usage: littlejohn ctl status | queue <magnet|link>... | pause | resume";
+
+/// How often WATCH_DIR is scanned for new magnet lists
+const WATCH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Files changed more recently than this may still be being written and wait for the next scan
+const WATCH_SETTLE: Duration = Duration::from_secs(5);
+
+/// A request from `littlejohn ctl`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "lowercase")]
+pub enum Request {
+    Status,
+    Queue { items: Vec<String> },
+    Pause,
+    Resume,
+}
+
+/// The daemon's reply, always with the current queue
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    pub message: String,
+    pub paused: bool,
+    pub jobs: Vec<JobStatus>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Queued,
+    Resolving,
+    Downloading,
+    Completed,
+    Failed,
+}
+
+/// One queued magnet or link
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub item: String,
+    pub state: JobState,
+    /// File being downloaded
+    pub file: Option<String>,
+    pub downloaded: u64,
+    pub total: u64,
+    pub error: Option<String>,
+}
+
+/// Control socket path
+pub fn socket_path() -> Result<PathBuf> {
+    Ok(dirs::config_dir()
+        .context("Config directory not found")?
+        .join("littlejohn")
+        .join("daemon.sock"))
+}
+
+/// Print a timestamped line (the service log)
+fn log(message: &str) {
+    println!("[{}] {}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S"), message);
+}
+
+#[derive(Default)]
+struct State {
+    jobs: Vec<JobStatus>,
+    paused: bool,
+}
+
+/// Queue state shared by the worker, the watch folder and the control socket
+#[derive(Default)]
+struct Daemon {
+    state: Mutex<State>,
+    /// Wakes the worker on new jobs and pause/resume
+    wake: Notify,
+}
+
+impl Daemon {
+    fn with_state<R>(&self, f: impl FnOnce(&mut State) -> R) -> R {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut state)
+    }
+
+    fn is_paused(&self) -> bool {
+        self.with_state(|s| s.paused)
+    }
+
+    fn update(&self, index: usize, f: impl FnOnce(&mut JobStatus)) {
+        self.with_state(|s| {
+            if let Some(job) = s.jobs.get_mut(index) {
+                f(job);
+            }
+        });
+    }
+
+    /// Queue items, skipping ones already waiting. Returns how many were added.
+    fn queue(&self, items: impl IntoIterator<Item = String>) -> usize {
+        let added = self.with_state(|s| {
+            let mut added = 0;
+            for item in items {
+                let waiting = s.jobs.iter().any(|j| j.item == item && j.state != JobState::Completed && j.state != JobState::Failed);
+                if !waiting {
+                    s.jobs.push(JobStatus {
+                        item,
+                        state: JobState::Queued,
+                        file: None,
+                        downloaded: 0,
+                        total: 0,
+                        error: None,
+                    });
+                    added += 1;
+                }
+            }
+            added
+        });
+        self.wake.notify_one();
+        added
+    }
+
+    fn handle(&self, request: Request) -> Response {
+        let message = match request {
+            Request::Status => String::new(),
+            Request::Queue { items } => {
+                let items: Vec<String> = import::parse(&items.join("\n")).into_iter().map(|e| e.as_str().to_string()).collect();
+                if items.is_empty() {
+                    return self.response(false, "No magnets or links given".to_string());
+                }
+                format!("Queued {} item(s)", self.queue(items))
+            }
+            Request::Pause => {
+                self.with_state(|s| s.paused = true);
+                self.wake.notify_one();
+                "Paused".to_string()
+            }
+            Request::Resume => {
+                self.with_state(|s| s.paused = false);
+                self.wake.notify_one();
+                "Resumed".to_string()
+            }
+        };
+        self.response(true, message)
+    }
+
+    fn response(&self, ok: bool, message: String) -> Response {
+        self.with_state(|s| Response {
+            ok,
+            message,
+            paused: s.paused,
+            jobs: s.jobs.clone(),
+        })
+    }
+}
+
+/// Run the daemon until it's stopped
+pub async fn run() -> Result<()> {
+    let rd_client = RealDebridClient::new().context("Real-Debrid is not configured")?;
+    let listener = bind(&socket_path()?).await?;
+    let daemon = Arc::new(Daemon::default());
+    log(&format!("Daemon started, downloading to {}", crate::download_dir().display()));
+
+    if let Some(dir) = std::env::var("WATCH_DIR").ok().filter(|d| !d.trim().is_empty()) {
+        log(&format!("Watching {} for magnet lists", dir));
+        tokio::spawn(watch_folder(daemon.clone(), PathBuf::from(dir)));
+    }
+    tokio::spawn(worker(daemon.clone(), rd_client));
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let daemon = daemon.clone();
+        tokio::spawn(async move {
+            if let Err(e) = serve(&daemon, stream).await {
+                log(&format!("Control connection failed: {}", e));
+            }
+        });
+    }
+}
+
+/// Bind the control socket, replacing a stale one left by a crashed daemon
+async fn bind(path: &Path) -> Result<UnixListener> {
+    if UnixStream::connect(path).await.is_ok() {
+        bail!("A littlejohn daemon is already running ({})", path.display());
+    }
+    let _ = std::fs::remove_file(path);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let listener = UnixListener::bind(path).with_context(|| format!("Cannot bind {}", path.display()))?;
+
+    // Only the owner may control the daemon
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+/// Answer one request
+async fn serve(daemon: &Daemon, stream: UnixStream) -> Result<()> {
+    let (read, mut write) = stream.into_split();
+    let mut line = String::new();
+    BufReader::new(read).read_line(&mut line).await?;
+
+    let response = match serde_json::from_str::<Request>(&line) {
+        Ok(request) => daemon.handle(request),
+        Err(e) => daemon.response(false, format!("Bad request: {}", e)),
+    };
+    write.write_all(format!("{}\n", serde_json::to_string(&response)?).as_bytes()).await?;
+    Ok(())
+}
+
+/// Process queued jobs one at a time
+async fn worker(daemon: Arc<Daemon>, rd_client: RealDebridClient) {
+    loop {
+        let next = daemon.with_state(|s| {
+            if s.paused {
+                None
+            } else {
+                s.jobs.iter().position(|j| j.state == JobState::Queued)
+            }
+        });
+        match next {
+            Some(index) => process(&daemon, &rd_client, index).await,
+            None => daemon.wake.notified().await,
+        }
+    }
+}
+
+/// Resolve one job through RD and download its files. Pausing stops the current
+/// transfer; it resumes from the partial file afterwards.
+async fn process(daemon: &Daemon, rd_client: &RealDebridClient, index: usize) {
+    let item = daemon.with_state(|s| s.jobs[index].item.clone());
+    daemon.update(index, |j| j.state = JobState::Resolving);
+    log(&format!("Resolving {}", item));
+
+    let fail = |error: String| {
+        log(&format!("Failed {}: {}", item, error));
+        daemon.update(index, |j| {
+            j.state = JobState::Failed;
+            j.error = Some(error);
+        });
+    };
+
+    let Some(entry) = import::parse(&item).into_iter().next() else {
+        fail("Not a magnet or link".to_string());
+        return;
+    };
+    let links = match import::resolve(rd_client, &entry, |_| {}).await {
+        Ok(links) => links,
+        Err(e) => {
+            fail(e.to_string());
+            return;
+        }
+    };
+
+    // Real-Debrid has the files now; the transfer itself waits for DOWNLOAD_WINDOW
+    if !schedule::is_open() {
+        daemon.update(index, |j| j.state = JobState::Queued);
+        log(&format!(
+            "Waiting for the download window to open at {}: {}",
+            schedule::opens_at().unwrap_or_default(),
+            item
+        ));
+        schedule::wait_until_open().await;
+    }
+
+    let client = crate::http::transfer_client();
+    for (filename, url) in links {
+        let mut job = Job::new(url, crate::paths::dest_path(&crate::category_dir(Category::guess(None, &filename)), &filename));
+        daemon.update(index, |j| {
+            j.state = JobState::Downloading;
+            j.file = Some(filename.clone());
+            j.downloaded = 0;
+            j.total = 0;
+        });
+        log(&format!("Downloading {}", job.dest_path.display()));
+
+        let mut skipped = false;
+        loop {
+            while daemon.is_paused() {
+                daemon.wake.notified().await;
+            }
+
+            let download = detached::download(&client, &mut job, |downloaded, total| {
+                daemon.update(index, |j| {
+                    j.downloaded = downloaded;
+                    j.total = total;
+                });
+            });
+            let paused = async {
+                while !daemon.is_paused() {
+                    daemon.wake.notified().await;
+                }
+            };
+
+            tokio::select! {
+                result = download => match result {
+                    Ok(Some(_)) => break,
+                    Ok(None) => {
+                        log(&format!("Skipped {}: the file already exists", filename));
+                        skipped = true;
+                        break;
+                    }
+                    Err(e) => {
+                        fail(format!("{}: {}", filename, e));
+                        return;
+                    }
+                },
+                _ = paused => log(&format!("Paused {}", filename)),
+            }
+        }
+        if !skipped {
+            organize(&job.dest_path, &filename);
+        }
+    }
+
+    log(&format!("Completed {}", item));
+    daemon.update(index, |j| j.state = JobState::Completed);
+}
+
+/// Move a downloaded TV episode into its show and season folder when ORGANIZE_TV is set
+fn organize(path: &std::path::Path, filename: &str) {
+    let mode = crate::organize::Mode::from_env();
+    if mode == crate::organize::Mode::Off || Category::guess(None, filename) != Category::Tv {
+        return;
+    }
+    let Some(target) = crate::organize::target(&crate::category_dir(Category::Tv), path) else { return };
+    if mode == crate::organize::Mode::DryRun {
+        log(&format!("Would move {} to {}", path.display(), target.display()));
+    } else if let Err(e) = crate::organize::move_file(path, &target) {
+        log(&format!("Cannot organize {}: {:#}", path.display(), e));
+    } else {
+        log(&format!("Moved {} to {}", path.display(), target.display()));
+    }
+}
+
+/// Queue the magnets and links in files dropped into `dir`; handled files get a `.queued` suffix.
+/// Files that can't be read are left in place and tried again once they change.
+async fn watch_folder(daemon: Arc<Daemon>, dir: PathBuf) {
+    let mut interval = tokio::time::interval(WATCH_INTERVAL);
+    let mut failed: HashMap<PathBuf, SystemTime> = HashMap::new();
+    loop {
+        interval.tick().await;
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for path in entries.filter_map(|e| e.ok().map(|e| e.path())) {
+            let is_list = path.extension().is_some_and(|ext| ext == "txt" || ext == "magnet");
+            if !path.is_file() || !is_list {
+                continue;
+            }
+            let Ok(modified) = std::fs::metadata(&path).and_then(|m| m.modified()) else { continue };
+            if modified.elapsed().unwrap_or_default() < WATCH_SETTLE || failed.get(&path) == Some(&modified) {
+                continue;
+            }
+            let items: Vec<String> = match import::read(&path) {
+                Ok(entries) => entries.into_iter().map(|e| e.as_str().to_string()).collect(),
+                Err(e) => {
+                    log(&format!("Leaving {} in place: {}", path.display(), e));
+                    failed.insert(path, modified);
+                    continue;
+                }
+            };
+            failed.remove(&path);
+            let added = daemon.queue(items);
+            log(&format!("Queued {} item(s) from {}", added, path.display()));
+
+            let mut done = path.clone().into_os_string();
+            done.push(".queued");
+            if let Err(e) = std::fs::rename(&path, &done) {
+                log(&format!("Cannot rename {}: {}", path.display(), e));
+            }
+        }
+    }
+}
+
+/// `littlejohn ctl ...`: send one request to the daemon and print the reply
+pub async fn ctl(args: &[String]) -> Result<()> {
+    let request = match args.first().map(String::as_str) {
+        None | Some("status") => Request::Status,
+        Some("queue") if args.len() > 1 => Request::Queue { items: args[1..].to_vec() },
+        Some("pause") => Request::Pause,
+        Some("resume") => Request::Resume,
+        _ => bail!(CTL_USAGE),
+    };
+
+    let path = socket_path()?;
+    let stream = UnixStream::connect(&path)
+        .await
+        .with_context(|| format!("littlejohn daemon is not running (no socket at {})", path.display()))?;
+    let (read, mut write) = stream.into_split();
+    write.write_all(format!("{}\n", serde_json::to_string(&request)?).as_bytes()).await?;
+
+    let mut line = String::new();
+    BufReader::new(read).read_line(&mut line).await?;
+    let response: Response = serde_json::from_str(&line).context("Invalid reply from the daemon")?;
+    if !response.ok {
+        return Err(anyhow!(response.message));
+    }
+
+    if !response.message.is_empty() {
+        println!("{}", response.message);
+    }
+    if matches!(request, Request::Status) {
+        println!("{}", if response.paused { "Paused" } else { "Running" });
+        for job in &response.jobs {
+            print_job(job);
+        }
+    }
+    Ok(())
+}
+
+fn print_job(job: &JobStatus) {
+    let state = match job.state {
+        JobState::Queued => "queued",
+        JobState::Resolving => "resolving",
+        JobState::Downloading => "downloading",
+        JobState::Completed => "completed",
+        JobState::Failed => "failed",
+    };
+    let progress = if job.state == JobState::Downloading && job.total > 0 {
+        format!("{:>5.1}%", job.downloaded as f64 / job.total as f64 * 100.0)
+    } else {
+        String::new()
+    };
+    let name = job.file.as_deref().unwrap_or(&job.item);
+    println!("{:<12}{:>7}  {}", state, progress, name);
+    if let Some(error) = &job.error {
+        println!("{:<19}  {}", "", error);
+    }
+}
+
+/// Install a systemd user unit that runs the daemon. Returns a description of what was installed.
+pub fn install_service() -> Result<String> {
+    let exe = std::env::current_exe().context("Could not locate the littlejohn executable")?;
+    let unit_dir = dirs::config_dir().context("Config directory not found")?.join("systemd").join("user");
+    std::fs::create_dir_all(&unit_dir)?;
+
+    let unit_path = unit_dir.join("littlejohn.service");
+    let unit = format!(
+        "[Unit]\n\
+         Description=littlejohn download daemon\n\
+         After=network-online.target\n\
+         \n\
+         [Service]\n\
+         ExecStart=\"{}\" daemon\n\
+         Restart=on-failure\n\
+         \n\
+         [Install]\n\
+         WantedBy=default.target\n",
+        exe.display()
+    );
+    std::fs::write(&unit_path, unit)?;
+
+    // Not fatal if systemd isn't available
+    let _ = std::process::Command::new("systemctl").args(["--user", "daemon-reload"]).status();
+
+    Ok(format!(
+        "Installed {}\nStart it with: systemctl --user enable --now littlejohn",
+        unit_path.display()
+    ))
+}
@@ -0,0 +1,204 @@
+//! Detached download worker - keeps downloads running after the TUI quits
+
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::{collision, schedule};
+
+/// Hidden subcommand that runs the background worker
+pub const WORKER_ARG: &str = "detached-worker";
+
+/// A download handed off to the worker
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Job {
+    pub url: String,
+    pub dest_path: PathBuf,
+    /// Bytes of `dest_path` this session downloaded itself, once it has settled on the
+    /// file. Only those are resumed; None means ON_FILE_EXISTS decides what to do with
+    /// a file that's already there.
+    #[serde(default)]
+    pub written: Option<u64>,
+    /// Held for DOWNLOAD_WINDOW: the worker starts it once the window opens
+    #[serde(default)]
+    pub scheduled: bool,
+}
+
+impl Job {
+    /// A download nothing has been written for yet
+    pub fn new(url: String, dest_path: PathBuf) -> Self {
+        Job { url, dest_path, written: None, scheduled: false }
+    }
+}
+
+/// Directory holding job files and the worker log
+fn state_dir() -> Result<PathBuf> {
+    let dir = dirs::config_dir()
+        .context("Config directory not found")?
+        .join("littlejohn");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Print a timestamped line to stderr (the worker log; batch downloads keep stdout for results)
+fn log(message: &str) {
+    eprintln!("[{}] {}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S"), message);
+}
+
+/// Write the job list and start a detached worker process. Returns the log path.
+pub fn spawn(jobs: &[Job]) -> Result<PathBuf> {
+    let dir = state_dir()?;
+    let jobs_path = dir.join(format!("detached-{}.json", std::process::id()));
+    std::fs::write(&jobs_path, serde_json::to_string_pretty(jobs)?)?;
+
+    let log_path = dir.join("detached.log");
+    let log_file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)?;
+
+    let mut cmd = Command::new(std::env::current_exe()?);
+    cmd.arg(WORKER_ARG)
+        .arg(&jobs_path)
+        .stdin(Stdio::null())
+        .stdout(log_file.try_clone()?)
+        .stderr(log_file);
+
+    // Own process group so closing the terminal doesn't take the worker down
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        cmd.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    }
+
+    cmd.spawn().context("Failed to start background worker")?;
+    Ok(log_path)
+}
+
+/// Worker entry point: download every job in turn, resuming partial files
+pub async fn run(jobs_path: &Path) -> Result<()> {
+    let jobs: Vec<Job> = serde_json::from_str(&std::fs::read_to_string(jobs_path)?)?;
+
+    // Give the TUI a moment to exit and release its partial files
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    log(&format!("Worker started with {} download(s)", jobs.len()));
+    let client = crate::http::transfer_client();
+    let mut failed = 0;
+
+    let total = jobs.len();
+    for mut job in jobs {
+        if job.scheduled && !schedule::is_open() {
+            log(&format!(
+                "Waiting for the download window to open at {}",
+                schedule::opens_at().unwrap_or_default()
+            ));
+            schedule::wait_until_open().await;
+        }
+        log(&format!("Downloading {}", job.dest_path.display()));
+        match download(&client, &mut job, |_, _| {}).await {
+            Ok(Some(bytes)) => log(&format!("Completed {} ({} bytes)", job.dest_path.display(), bytes)),
+            Ok(None) => log(&format!("Skipped {}: the file already exists", job.dest_path.display())),
+            Err(e) => {
+                failed += 1;
+                log(&format!("Failed {}: {}", job.dest_path.display(), e));
+            }
+        }
+    }
+
+    let _ = std::fs::remove_file(jobs_path);
+    log(&format!("Worker finished: {} ok, {} failed", total - failed, failed));
+
+    if failed > 0 {
+        anyhow::bail!("{} download(s) failed", failed);
+    }
+    Ok(())
+}
+
+/// Download one job. Bytes this session already wrote (`job.written`) are resumed with a
+/// range request when the server allows it; a file that was already there is handled by
+/// ON_FILE_EXISTS, as in the TUI, which may point `dest_path` at a new name. `job` keeps
+/// track of what was written, so calling again after the future is dropped picks up from
+/// there. With DOWNLOAD_REMOTE set, the file is streamed to the remote instead (no resume).
+/// `on_progress` gets the bytes written so far and the total size (0 if unknown).
+/// Returns the size of the file, or None if ON_FILE_EXISTS said to skip it.
+pub async fn download(client: &reqwest::Client, job: &mut Job, mut on_progress: impl FnMut(u64, u64)) -> Result<Option<u64>> {
+    let remote_target = crate::remote::from_env().map(|base| {
+        let filename = job.dest_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        crate::remote::target(&base, &filename)
+    });
+
+    let mut existing = 0;
+    if remote_target.is_none() {
+        match (job.written, tokio::fs::metadata(&job.dest_path).await) {
+            // Ours: drop anything past what was counted, which may be a torn write
+            (Some(written), Ok(meta)) if meta.len() >= written => {
+                if meta.len() > written {
+                    std::fs::OpenOptions::new().write(true).open(&job.dest_path)?.set_len(written)?;
+                }
+                existing = written;
+            }
+            (Some(_), _) => job.written = Some(0),
+            (None, Ok(meta)) => match collision::Policy::from_env() {
+                collision::Policy::Overwrite => {}
+                collision::Policy::Skip => return Ok(None),
+                collision::Policy::Rename => {
+                    job.dest_path = collision::unique_path(&job.dest_path);
+                    log(&format!("File exists, saving as {}", job.dest_path.display()));
+                }
+                collision::Policy::Resume => existing = meta.len(),
+            },
+            (None, Err(_)) => {}
+        }
+    }
+
+    let mut request = client.get(&job.url);
+    if existing > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", existing));
+    }
+
+    let response = request.send().await?;
+    if response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // File is already complete
+        return Ok(Some(existing));
+    }
+    let response = response.error_for_status()?;
+
+    let resumed = existing > 0 && response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    if resumed {
+        log(&format!("Resuming at {} bytes", existing));
+    }
+    let mut sink = match &remote_target {
+        Some(target) => crate::remote::Sink::remote(target)?,
+        None => crate::remote::Sink::file(&job.dest_path, resumed).await?,
+    };
+
+    let mut written = if resumed { existing } else { 0 };
+    if remote_target.is_none() {
+        job.written = Some(written);
+    }
+    let total = response.content_length().map(|len| len + written).unwrap_or(0);
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        sink.write_all(&chunk).await?;
+        written += chunk.len() as u64;
+        if remote_target.is_none() {
+            job.written = Some(written);
+        }
+        on_progress(written, total);
+    }
+    sink.finish().await?;
+
+    Ok(Some(written))
+}
@@ -0,0 +1,57 @@
+//! Pause downloads when the destination volume runs low on free space
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// How often free space is checked while downloads are active
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Set while transfers are paused for lack of space
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Minimum free space from MIN_FREE_SPACE (bytes, or with a K/M/G/T suffix).
+/// Defaults to 1 GiB; 0 disables the guard.
+pub fn threshold() -> u64 {
+    std::env::var("MIN_FREE_SPACE")
+        .ok()
+        .and_then(|v| parse_size(&v))
+        .unwrap_or(1024 * 1024 * 1024)
+}
+
+/// Parse "500M", "2G", "1.5T" or a plain byte count
+pub fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim().to_uppercase();
+    let value = value.trim_end_matches("IB").trim_end_matches('B');
+    let (number, multiplier) = match value.char_indices().last()? {
+        (i, 'K') => (&value[..i], 1u64 << 10),
+        (i, 'M') => (&value[..i], 1 << 20),
+        (i, 'G') => (&value[..i], 1 << 30),
+        (i, 'T') => (&value[..i], 1 << 40),
+        _ => (value, 1),
+    };
+    let number: f64 = number.trim().parse().ok()?;
+    (number >= 0.0).then_some((number * multiplier as f64) as u64)
+}
+
+/// Free space available on the volume holding `dir` (the nearest existing
+/// ancestor if `dir` hasn't been created yet)
+pub fn free_space(dir: &Path) -> Option<u64> {
+    let existing = dir.ancestors().find(|p| p.exists())?;
+    fs2::available_space(existing).ok()
+}
+
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+pub fn set_paused(paused: bool) {
+    PAUSED.store(paused, Ordering::Relaxed);
+}
+
+/// Wait until the guard lets transfers continue
+pub async fn wait_until_resumed() {
+    while is_paused() {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
@@ -0,0 +1,139 @@
+//! DNS-over-HTTPS resolver, for ISPs that DNS-block torrent sites
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Cloudflare's JSON endpoint, addressed by IP so it doesn't need DNS itself
+const CLOUDFLARE_URL: &str = "https://1.1.1.1/dns-query";
+/// Google's JSON endpoint, addressed by IP
+const GOOGLE_URL: &str = "https://8.8.8.8/resolve";
+
+/// DNS record types
+const TYPE_A: u16 = 1;
+const TYPE_AAAA: u16 = 28;
+
+#[derive(Debug, Deserialize)]
+struct DohAnswer {
+    #[serde(rename = "type")]
+    record_type: u16,
+    #[serde(rename = "TTL", default)]
+    ttl: u64,
+    data: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DohResponse {
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DohAnswer>,
+}
+
+/// Cached addresses per host, with their expiry
+type DnsCache = HashMap<String, (Vec<IpAddr>, Instant)>;
+
+/// Resolver that queries a DoH JSON endpoint, falling back to system DNS on failure
+#[derive(Clone)]
+pub struct DohResolver {
+    url: String,
+    client: reqwest::Client,
+    cache: Arc<Mutex<DnsCache>>,
+}
+
+impl DohResolver {
+    /// Resolver configured by DOH_URL (`cloudflare`, `google` or a JSON endpoint URL).
+    /// None if DoH is not enabled.
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var("DOH_URL").ok()?;
+        let url = match value.trim().to_lowercase().as_str() {
+            "" | "0" | "false" | "no" | "off" => return None,
+            "1" | "true" | "yes" | "on" | "cloudflare" => CLOUDFLARE_URL.to_string(),
+            "google" => GOOGLE_URL.to_string(),
+            _ => value.trim().to_string(),
+        };
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .ok()?;
+
+        Some(Self {
+            url,
+            client,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Query one record type
+    async fn query(&self, host: &str, record_type: u16) -> Option<(Vec<IpAddr>, u64)> {
+        let response: DohResponse = self
+            .client
+            .get(&self.url)
+            .query(&[("name", host), ("type", &record_type.to_string())])
+            .header("accept", "application/dns-json")
+            .send()
+            .await
+            .ok()?
+            .json()
+            .await
+            .ok()?;
+
+        let answers: Vec<&DohAnswer> = response
+            .answer
+            .iter()
+            .filter(|a| a.record_type == record_type)
+            .collect();
+        let ips: Vec<IpAddr> = answers.iter().filter_map(|a| a.data.parse().ok()).collect();
+        let ttl = answers.iter().map(|a| a.ttl).min().unwrap_or(60);
+
+        if ips.is_empty() {
+            None
+        } else {
+            Some((ips, ttl))
+        }
+    }
+
+    /// Resolve via DoH (A, then AAAA), using cached answers while their TTL lasts
+    async fn lookup(&self, host: &str) -> Option<Vec<IpAddr>> {
+        if let Ok(cache) = self.cache.lock() {
+            if let Some((ips, expires)) = cache.get(host) {
+                if Instant::now() < *expires {
+                    return Some(ips.clone());
+                }
+            }
+        }
+
+        let (ips, ttl) = match self.query(host, TYPE_A).await {
+            Some(found) => found,
+            None => self.query(host, TYPE_AAAA).await?,
+        };
+
+        if let Ok(mut cache) = self.cache.lock() {
+            let expires = Instant::now() + Duration::from_secs(ttl.max(30));
+            cache.insert(host.to_string(), (ips.clone(), expires));
+        }
+        Some(ips)
+    }
+}
+
+impl Resolve for DohResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+
+            // Port is filled in by reqwest from the URL
+            if let Some(ips) = resolver.lookup(&host).await {
+                let addrs: Addrs = Box::new(ips.into_iter().map(|ip| SocketAddr::new(ip, 0)));
+                return Ok(addrs);
+            }
+
+            crate::scrapers::log_info("doh", &format!("DoH lookup failed for {}, using system DNS", host));
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            let addrs: Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
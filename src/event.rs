@@ -0,0 +1,54 @@
+//! The app's single event stream: terminal input, messages from background tasks and
+//! the UI tick, merged with one `tokio::select!`
+
+use anyhow::{anyhow, Result};
+use crossterm::event::{Event, EventStream};
+use futures::StreamExt;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::{Interval, MissedTickBehavior};
+
+use crate::AppMessage;
+
+/// Messages taken off the channel at once, so a burst of progress updates costs one redraw
+const MAX_BATCH: usize = 256;
+
+pub enum AppEvent {
+    /// Time to advance animations (spinners, progress)
+    Tick,
+    Input(Event),
+    /// Every message waiting on the channel, oldest first
+    Messages(Vec<AppMessage>),
+}
+
+pub struct Events {
+    input: EventStream,
+    messages: mpsc::UnboundedReceiver<AppMessage>,
+    tick: Interval,
+}
+
+impl Events {
+    pub fn new(messages: mpsc::UnboundedReceiver<AppMessage>, tick_rate: Duration) -> Self {
+        let mut tick = tokio::time::interval(tick_rate);
+        // After a slow frame, carry on from now rather than firing the missed ticks
+        tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        Self {
+            input: EventStream::new(),
+            messages,
+            tick,
+        }
+    }
+
+    /// Wait for the next event
+    pub async fn next(&mut self) -> Result<AppEvent> {
+        let mut batch = Vec::new();
+        tokio::select! {
+            _ = self.tick.tick() => Ok(AppEvent::Tick),
+            Some(event) = self.input.next() => Ok(AppEvent::Input(event?)),
+            count = self.messages.recv_many(&mut batch, MAX_BATCH) => match count {
+                0 => Err(anyhow!("Message channel closed")),
+                _ => Ok(AppEvent::Messages(batch)),
+            },
+        }
+    }
+}
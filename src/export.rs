@@ -0,0 +1,74 @@
+//! Export search results to JSON or CSV
+
+use crate::scrapers::TorrentResult;
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// Export file format
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Csv => "csv",
+        }
+    }
+}
+
+/// Export path in `dir`, named after the query and the current time
+pub fn default_path(dir: &Path, query: &str, format: Format) -> PathBuf {
+    let slug: String = query
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let slug = slug.split('-').filter(|s| !s.is_empty()).collect::<Vec<_>>().join("-");
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    dir.join(format!("littlejohn-{}-{}.{}", slug, stamp, format.extension()))
+}
+
+/// Write results to `path`
+pub fn write(results: &[TorrentResult], path: &Path, format: Format) -> Result<()> {
+    let content = match format {
+        Format::Json => serde_json::to_string_pretty(results)?,
+        Format::Csv => to_csv(results),
+    };
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// CSV with a header row (RFC 4180 quoting)
+fn to_csv(results: &[TorrentResult]) -> String {
+    let mut out = String::from("name,size,seeders,leechers,source,category,audio,subtitles,url,info_hash,magnet\n");
+    for r in results {
+        let fields = [
+            r.name.clone(),
+            r.size.clone(),
+            r.seeders.to_string(),
+            r.leechers.to_string(),
+            r.source.clone(),
+            r.category.clone().unwrap_or_default(),
+            r.audio.join("+"),
+            r.subtitles.join("+"),
+            r.url.clone().unwrap_or_default(),
+            r.info_hash().unwrap_or_default(),
+            r.magnet.clone(),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,206 @@
+//! Inline images (result cover art) with the kitty, iTerm2 or sixel graphics
+//! protocols, and an ASCII-art fallback for other terminals
+
+use base64::Engine;
+use image::{imageops::FilterType, DynamicImage, GenericImageView, ImageFormat};
+use std::io::Cursor;
+
+/// Cell size assumed when the terminal doesn't report its pixel size
+const DEFAULT_CELL_PIXELS: (u32, u32) = (10, 20);
+
+/// Kitty payloads are sent in chunks of at most this many base64 bytes
+const KITTY_CHUNK: usize = 4096;
+
+/// Shades for ASCII art, darkest first
+const ASCII_RAMP: &[u8] = b" .:-=+*#%@";
+
+/// Terminal graphics protocol used for inline images
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Protocol {
+    Kitty,
+    Iterm2,
+    Sixel,
+    /// ASCII art only
+    None,
+}
+
+impl Protocol {
+    /// Protocol from GRAPHICS_PROTOCOL (kitty, iterm2, sixel or none), detected from
+    /// the terminal when unset
+    pub fn from_env() -> Self {
+        match std::env::var("GRAPHICS_PROTOCOL").unwrap_or_default().trim().to_lowercase().as_str() {
+            "kitty" => Protocol::Kitty,
+            "iterm2" | "iterm" => Protocol::Iterm2,
+            "sixel" => Protocol::Sixel,
+            "none" | "ascii" | "off" => Protocol::None,
+            _ => Self::detect(),
+        }
+    }
+
+    fn detect() -> Self {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        let (term, program) = (var("TERM"), var("TERM_PROGRAM"));
+        if std::env::var_os("KITTY_WINDOW_ID").is_some() || term == "xterm-kitty" || program == "ghostty" {
+            Protocol::Kitty
+        } else if program == "iTerm.app" || program == "WezTerm" {
+            Protocol::Iterm2
+        } else if term.contains("sixel") || term.starts_with("foot") || term == "mlterm" || program == "contour" {
+            Protocol::Sixel
+        } else {
+            Protocol::None
+        }
+    }
+}
+
+/// Pixel size of a terminal cell
+fn cell_pixels() -> (u32, u32) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => (
+            (size.width / size.columns).max(1) as u32,
+            (size.height / size.rows).max(1) as u32,
+        ),
+        _ => DEFAULT_CELL_PIXELS,
+    }
+}
+
+/// Escape sequence drawing `image` at the cursor, scaled to fit `cols`×`rows` cells.
+/// None for `Protocol::None` or if the image can't be encoded.
+pub fn encode(protocol: Protocol, image: &DynamicImage, cols: u16, rows: u16) -> Option<String> {
+    if protocol == Protocol::None || cols == 0 || rows == 0 {
+        return None;
+    }
+    let (cell_w, cell_h) = cell_pixels();
+    let fitted = image.resize(cols as u32 * cell_w, rows as u32 * cell_h, FilterType::Triangle);
+
+    match protocol {
+        Protocol::Kitty => {
+            let data = base64::engine::general_purpose::STANDARD.encode(png(&fitted)?);
+            let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK).collect();
+            let mut out = String::new();
+            for (i, chunk) in chunks.iter().enumerate() {
+                let more = u8::from(i + 1 < chunks.len());
+                let chunk = std::str::from_utf8(chunk).ok()?;
+                if i == 0 {
+                    // Transmit and display a PNG, no replies, don't move the cursor
+                    out.push_str(&format!("\x1b_Ga=T,f=100,q=2,C=1,m={};{}\x1b\\", more, chunk));
+                } else {
+                    out.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+                }
+            }
+            Some(out)
+        }
+        Protocol::Iterm2 => {
+            let data = png(&fitted)?;
+            Some(format!(
+                "\x1b]1337;File=inline=1;size={};width={}px;height={}px;preserveAspectRatio=1:{}\x07",
+                data.len(),
+                fitted.width(),
+                fitted.height(),
+                base64::engine::general_purpose::STANDARD.encode(&data)
+            ))
+        }
+        Protocol::Sixel => Some(sixel(&fitted)),
+        Protocol::None => None,
+    }
+}
+
+/// Escape sequence removing images that clearing the screen leaves behind
+pub fn clear_sequence(protocol: Protocol) -> &'static str {
+    match protocol {
+        Protocol::Kitty => "\x1b_Ga=d,q=2\x1b\\",
+        _ => "",
+    }
+}
+
+fn png(image: &DynamicImage) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    image.write_to(&mut Cursor::new(&mut data), ImageFormat::Png).ok()?;
+    Some(data)
+}
+
+/// Sixel image using a 6×6×6 color cube
+fn sixel(image: &DynamicImage) -> String {
+    let rgb = image.to_rgb8();
+    let (width, height) = rgb.dimensions();
+    let level = |c: u8| (c as u32 * 5 + 127) / 255;
+    let index: Vec<u8> = rgb
+        .pixels()
+        .map(|p| (level(p[0]) * 36 + level(p[1]) * 6 + level(p[2])) as u8)
+        .collect();
+
+    let mut out = format!("\x1bPq\"1;1;{};{}", width, height);
+    for i in 0..216u32 {
+        let pct = |v: u32| v * 100 / 5;
+        out.push_str(&format!("#{};2;{};{};{}", i, pct(i / 36), pct(i / 6 % 6), pct(i % 6)));
+    }
+
+    for band in (0..height).step_by(6) {
+        let rows = band..(band + 6).min(height);
+        let mut colors: Vec<u8> = rows
+            .clone()
+            .flat_map(|y| index[(y * width) as usize..((y + 1) * width) as usize].iter().copied())
+            .collect();
+        colors.sort_unstable();
+        colors.dedup();
+
+        for color in colors {
+            out.push_str(&format!("#{}", color));
+            let mut run: Option<(char, usize)> = None;
+            for x in 0..width {
+                let bits = rows
+                    .clone()
+                    .filter(|&y| index[(y * width + x) as usize] == color)
+                    .fold(0u8, |acc, y| acc | 1 << (y - band));
+                let ch = (63 + bits) as char;
+                run = match run {
+                    Some((c, n)) if c == ch => Some((c, n + 1)),
+                    Some((c, n)) => {
+                        push_run(&mut out, c, n);
+                        Some((ch, 1))
+                    }
+                    None => Some((ch, 1)),
+                };
+            }
+            if let Some((c, n)) = run {
+                push_run(&mut out, c, n);
+            }
+            // Back to the start of the band for the next color
+            out.push('$');
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+fn push_run(out: &mut String, ch: char, count: usize) {
+    if count > 3 {
+        out.push_str(&format!("!{}{}", count, ch));
+    } else {
+        out.extend(std::iter::repeat_n(ch, count));
+    }
+}
+
+/// ASCII-art rendering of `image` fitting `cols`×`rows` cells (cells are about twice
+/// as tall as they are wide)
+pub fn ascii_art(image: &DynamicImage, cols: u16, rows: u16) -> Vec<String> {
+    if cols == 0 || rows == 0 {
+        return Vec::new();
+    }
+    let (width, height) = image.dimensions();
+    let scale = (cols as f64 / width as f64).min(rows as f64 * 2.0 / height as f64);
+    let out_cols = ((width as f64 * scale).round() as u32).clamp(1, cols as u32);
+    let out_rows = ((height as f64 * scale / 2.0).round() as u32).clamp(1, rows as u32);
+
+    let gray = image.resize_exact(out_cols, out_rows, FilterType::Triangle).to_luma8();
+    (0..out_rows)
+        .map(|y| {
+            (0..out_cols)
+                .map(|x| {
+                    let luma = gray.get_pixel(x, y)[0] as usize;
+                    ASCII_RAMP[luma * (ASCII_RAMP.len() - 1) / 255] as char
+                })
+                .collect()
+        })
+        .collect()
+}
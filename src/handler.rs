@@ -0,0 +1,78 @@
+//! magnet: protocol handler registration
+
+use anyhow::{bail, Context, Result};
+use std::process::Command;
+
+/// Register littlejohn as the system handler for magnet: links.
+/// Returns a description of what was installed.
+pub fn register() -> Result<String> {
+    let exe = std::env::current_exe().context("Could not locate the littlejohn executable")?;
+    let exe = exe.to_string_lossy().to_string();
+
+    if cfg!(target_os = "windows") {
+        register_windows(&exe)
+    } else if cfg!(target_os = "macos") {
+        bail!("Registering a magnet: handler requires an app bundle on macOS; open magnets with `littlejohn \"magnet:?...\"` instead")
+    } else {
+        register_xdg(&exe)
+    }
+}
+
+/// Install a .desktop entry and make it the default x-scheme-handler/magnet
+fn register_xdg(exe: &str) -> Result<String> {
+    let apps_dir = dirs::data_dir()
+        .context("Data directory not found")?
+        .join("applications");
+    std::fs::create_dir_all(&apps_dir)?;
+
+    let desktop_path = apps_dir.join("littlejohn-magnet.desktop");
+    let entry = format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=littlejohn\n\
+         Comment=Send magnet links to Real-Debrid\n\
+         Exec=\"{}\" %u\n\
+         Terminal=true\n\
+         NoDisplay=true\n\
+         MimeType=x-scheme-handler/magnet;\n",
+        exe
+    );
+    std::fs::write(&desktop_path, entry)?;
+
+    let status = Command::new("xdg-mime")
+        .args(["default", "littlejohn-magnet.desktop", "x-scheme-handler/magnet"])
+        .status()
+        .context("Failed to run xdg-mime")?;
+    if !status.success() {
+        bail!("xdg-mime exited with {}", status);
+    }
+
+    // Refresh the desktop database where available (not fatal if missing)
+    let _ = Command::new("update-desktop-database").arg(&apps_dir).status();
+
+    Ok(format!("Installed {}", desktop_path.display()))
+}
+
+/// Register the magnet: URL protocol for the current user
+fn register_windows(exe: &str) -> Result<String> {
+    let command = format!("\"{}\" \"%1\"", exe);
+    let entries: [&[&str]; 3] = [
+        &["HKCU\\Software\\Classes\\magnet", "/ve", "/d", "URL:Magnet Protocol"],
+        &["HKCU\\Software\\Classes\\magnet", "/v", "URL Protocol", "/d", ""],
+        &["HKCU\\Software\\Classes\\magnet\\shell\\open\\command", "/ve", "/d", &command],
+    ];
+
+    for args in entries {
+        let status = Command::new("reg")
+            .arg("add")
+            .args(args)
+            .arg("/f")
+            .status()
+            .context("Failed to run reg")?;
+        if !status.success() {
+            bail!("reg add {} exited with {}", args[0], status);
+        }
+    }
+
+    Ok("Registered magnet: handler in HKCU\\Software\\Classes\\magnet".to_string())
+}
@@ -0,0 +1,157 @@
+//! Completed download history, with optional checksums
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha1::Digest;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A completed download
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub filename: String,
+    pub path: PathBuf,
+    pub size: u64,
+    /// Completion time (RFC 3339)
+    pub completed_at: String,
+    pub sha1: Option<String>,
+    pub md5: Option<String>,
+}
+
+/// Result of re-checking a file against its recorded checksums
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verification {
+    Match,
+    Mismatch,
+    /// No checksums were recorded for this file
+    NotRecorded,
+}
+
+/// Whether checksums are computed for completed downloads (CHECKSUMS=true)
+pub fn checksums_enabled() -> bool {
+    matches!(
+        std::env::var("CHECKSUMS").map(|v| v.to_lowercase()).as_deref(),
+        Ok("1") | Ok("true") | Ok("yes")
+    )
+}
+
+/// Path of the history file
+fn history_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("littlejohn").join("download_history.json"))
+}
+
+/// Held while the history file is read for an update and written back, so downloads
+/// finishing together don't drop each other's entries
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Recorded downloads; an empty list if there's no history yet, an error if it can't be read
+fn read() -> Result<Vec<Entry>> {
+    let path = history_path().context("Config directory not found")?;
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    serde_json::from_str(&text).with_context(|| format!("{} is damaged", path.display()))
+}
+
+/// All recorded downloads, oldest first (none if the history can't be read)
+pub fn load() -> Vec<Entry> {
+    read().unwrap_or_default()
+}
+
+/// Write aside and rename, so a reader never sees a half-written history
+fn save(entries: &[Entry]) -> Result<()> {
+    let path = history_path().context("Config directory not found")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let partial = path.with_extension("json.part");
+    std::fs::write(&partial, serde_json::to_string_pretty(entries)?)?;
+    std::fs::rename(&partial, &path)?;
+    Ok(())
+}
+
+/// Record a completed download, replacing any earlier entry for the same path. A history
+/// that can't be read is left alone rather than replaced by this one entry.
+pub fn record(path: &Path, checksums: Option<(String, String)>) -> Result<()> {
+    let size = std::fs::metadata(path)?.len();
+    let (sha1, md5) = match checksums {
+        Some((sha1, md5)) => (Some(sha1), Some(md5)),
+        None => (None, None),
+    };
+
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = read()?;
+    entries.retain(|e| e.path != path);
+    entries.push(Entry {
+        filename: path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        path: path.to_path_buf(),
+        size,
+        completed_at: chrono::Local::now().to_rfc3339(),
+        sha1,
+        md5,
+    });
+    save(&entries)
+}
+
+/// The last `n` completed downloads, newest first
+pub fn recent(entries: &[Entry], n: usize) -> Vec<Entry> {
+    entries.iter().rev().take(n).cloned().collect()
+}
+
+/// Search for the episode after the latest one downloaded, e.g. "Show Name S01E06"
+/// after Show.Name.S01E05 (the show's furthest episode in the history if several were grabbed)
+pub fn next_episode(entries: &[Entry]) -> Option<String> {
+    let episodes: Vec<(String, u32, u32)> = entries.iter().filter_map(|e| crate::release::episode(&e.filename)).collect();
+    let (show, _, _) = episodes.last()?;
+    let (season, episode) = episodes
+        .iter()
+        .filter(|(other, _, _)| other.eq_ignore_ascii_case(show))
+        .map(|(_, season, episode)| (*season, *episode))
+        .max()?;
+    Some(format!("{} S{:02}E{:02}", show, season, episode + 1))
+}
+
+/// SHA-1 and MD5 of a file as lowercase hex (reads the file once)
+pub fn compute_checksums(path: &Path) -> Result<(String, String)> {
+    let mut file = std::fs::File::open(path)?;
+    let mut sha1 = sha1::Sha1::new();
+    let mut md5 = md5::Md5::new();
+    let mut buf = vec![0u8; 1024 * 1024];
+
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        sha1.update(&buf[..n]);
+        md5.update(&buf[..n]);
+    }
+
+    Ok((hex(&sha1.finalize()), hex(&md5.finalize())))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Recompute a file's checksums and compare them with the recorded ones
+pub fn verify(path: &Path) -> Result<Verification> {
+    let entry = load().into_iter().find(|e| e.path == path);
+    let (sha1, md5) = match entry {
+        Some(Entry { sha1: Some(sha1), md5: Some(md5), .. }) => (sha1, md5),
+        _ => return Ok(Verification::NotRecorded),
+    };
+
+    let (actual_sha1, actual_md5) = compute_checksums(path)?;
+    if actual_sha1 == sha1 && actual_md5 == md5 {
+        Ok(Verification::Match)
+    } else {
+        Ok(Verification::Mismatch)
+    }
+}
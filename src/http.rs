@@ -0,0 +1,133 @@
+//! Shared HTTP clients, so connections are reused across requests
+
+use crate::doh::DohResolver;
+use reqwest::header::{HeaderMap, HeaderValue, COOKIE};
+use reqwest::redirect::Policy;
+use reqwest::{Client, ClientBuilder};
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+/// Current desktop browsers, rotated between scraper requests. The first one is kept
+/// wherever a site may tie a session to it (logins, cookies, FIXED_USER_AGENT).
+const USER_AGENTS: &[&str] = &[
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:133.0) Gecko/20100101 Firefox/133.0",
+    "Mozilla/5.0 (X11; Linux x86_64; rv:133.0) Gecko/20100101 Firefox/133.0",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/18.1 Safari/605.1.15",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36 Edg/131.0.0.0",
+];
+
+/// One scraper client per user agent, in `USER_AGENTS` order
+static SCRAPER_CLIENTS: OnceLock<Vec<Client>> = OnceLock::new();
+static TRANSFER_CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// Duration in seconds from an env var, falling back to `default`
+fn env_secs(key: &str, default: u64) -> Duration {
+    let secs = std::env::var(key)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|s| *s > 0)
+        .unwrap_or(default);
+    Duration::from_secs(secs)
+}
+
+/// Connect timeout for all clients (HTTP_CONNECT_TIMEOUT, default 10s)
+fn connect_timeout() -> Duration {
+    env_secs("HTTP_CONNECT_TIMEOUT", 10)
+}
+
+/// Total request timeout for scraper requests (HTTP_TIMEOUT, default 15s)
+pub fn request_timeout() -> Duration {
+    env_secs("HTTP_TIMEOUT", 15)
+}
+
+/// A random number below `n` (0 if `n` is 0), from the std hasher's random keys
+pub fn random_below(n: u64) -> u64 {
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u64(n);
+    hasher.finish().checked_rem(n).unwrap_or(0)
+}
+
+/// Client settings shared by the scraper clients: browser user agent, total request
+/// timeout, gzip/brotli, and DNS-over-HTTPS when DOH_URL is set
+fn scraper_builder() -> ClientBuilder {
+    scraper_builder_with(USER_AGENTS[0])
+}
+
+fn scraper_builder_with(user_agent: &str) -> ClientBuilder {
+    let mut builder = Client::builder()
+        .user_agent(user_agent)
+        .timeout(request_timeout())
+        .connect_timeout(connect_timeout())
+        .gzip(true)
+        .brotli(true);
+    if let Some(resolver) = DohResolver::from_env() {
+        builder = builder.dns_resolver(Arc::new(resolver));
+    }
+    builder
+}
+
+fn scraper_clients() -> &'static [Client] {
+    SCRAPER_CLIENTS.get_or_init(|| {
+        USER_AGENTS
+            .iter()
+            .map(|agent| scraper_builder_with(agent).build().unwrap_or_default())
+            .collect()
+    })
+}
+
+/// Shared client for scraping
+pub fn scraper_client() -> Client {
+    scraper_clients()[0].clone()
+}
+
+/// Whether `source` is listed in FIXED_USER_AGENT (comma-separated, `*` for all), so its
+/// requests always send the same user agent
+fn fixed_user_agent(source: &str) -> bool {
+    std::env::var("FIXED_USER_AGENT")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .any(|name| name == "*" || name.eq_ignore_ascii_case(source))
+}
+
+/// Shared client for scraping `source`, with a user agent picked at random from the pool
+/// unless the source is in FIXED_USER_AGENT
+pub fn source_client(source: &str) -> Client {
+    let clients = scraper_clients();
+    if fixed_user_agent(source) {
+        return clients[0].clone();
+    }
+    clients[random_below(clients.len() as u64) as usize].clone()
+}
+
+/// Scraper client sending `cookie` with every request, for sources that need a session
+pub fn scraper_client_with_cookie(cookie: &str) -> Client {
+    let mut headers = HeaderMap::new();
+    if let Ok(value) = HeaderValue::from_str(cookie) {
+        headers.insert(COOKIE, value);
+    }
+    scraper_builder().default_headers(headers).build().unwrap_or_default()
+}
+
+/// Scraper client that doesn't follow redirects, so a login response's cookies can be read
+pub fn login_client() -> Client {
+    scraper_builder().redirect(Policy::none()).build().unwrap_or_default()
+}
+
+/// Shared client for API calls and file downloads. Only connect and read (idle)
+/// timeouts are set, so large transfers are not cut off (HTTP_READ_TIMEOUT, default 60s).
+/// No compression, so content lengths and resume offsets match the file on disk.
+pub fn transfer_client() -> Client {
+    TRANSFER_CLIENT
+        .get_or_init(|| {
+            Client::builder()
+                .connect_timeout(connect_timeout())
+                .read_timeout(env_secs("HTTP_READ_TIMEOUT", 60))
+                .build()
+                .unwrap_or_default()
+        })
+        .clone()
+}
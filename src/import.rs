@@ -0,0 +1,123 @@
+//! Import a list of magnets and links and run them through Real-Debrid
+
+use crate::realdebrid::{RdUpdate, RealDebridClient, WaitTimeout};
+use crate::scrapers;
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+/// One line of an import file
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entry {
+    Magnet(String),
+    /// Hoster link, unrestricted directly
+    Link(String),
+}
+
+impl Entry {
+    /// The magnet or link as written in the file
+    pub fn as_str(&self) -> &str {
+        match self {
+            Entry::Magnet(s) | Entry::Link(s) => s,
+        }
+    }
+
+    /// Name to show for the entry: a magnet's display name (`dn`) if it has one
+    pub fn label(&self) -> String {
+        let name = match self {
+            Entry::Magnet(magnet) => magnet
+                .split(['?', '&'])
+                .find_map(|param| param.strip_prefix("dn="))
+                .and_then(|dn| urlencoding::decode(&dn.replace('+', " ")).ok().map(|s| s.into_owned())),
+            Entry::Link(_) => None,
+        };
+        name.unwrap_or_else(|| self.as_str().to_string())
+    }
+}
+
+/// Parse magnets and http(s) links, one per line. Blank lines, `#` comments and
+/// anything else are skipped; duplicates are dropped.
+pub fn parse(text: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for line in text.lines().map(str::trim) {
+        let entry = if line.starts_with("magnet:") {
+            Entry::Magnet(line.to_string())
+        } else if line.starts_with("http://") || line.starts_with("https://") {
+            Entry::Link(line.to_string())
+        } else {
+            continue;
+        };
+        if !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    }
+    entries
+}
+
+/// Read and parse an import file (a leading `~/` is the home directory)
+pub fn read(path: &Path) -> Result<Vec<Entry>> {
+    let expanded = match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    };
+    let path = expanded.as_path();
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Cannot read {}: {}", path.display(), e))?;
+    let entries = parse(&text);
+    if entries.is_empty() {
+        return Err(anyhow!("No magnets or links found in {}", path.display()));
+    }
+    Ok(entries)
+}
+
+/// Run one entry through Real-Debrid and return its download links. Torrents
+/// already on the account are reused; otherwise every file is selected and RD
+/// is polled without a time limit.
+pub async fn resolve(
+    client: &RealDebridClient,
+    entry: &Entry,
+    mut on_update: impl FnMut(RdUpdate),
+) -> Result<Vec<(String, String)>> {
+    let magnet = match entry {
+        Entry::Link(link) => {
+            on_update(RdUpdate::Status("Unrestricting link..."));
+            return Ok(vec![client.unrestrict(link).await?]);
+        }
+        Entry::Magnet(magnet) => magnet,
+    };
+
+    let existing = match scrapers::magnet_info_hash(magnet) {
+        Some(hash) => client.find_torrent_by_hash(&hash).await.ok().flatten(),
+        None => None,
+    };
+
+    let (torrent_id, files) = match existing {
+        Some(torrent) => {
+            on_update(RdUpdate::Status("Reusing torrent already on Real-Debrid..."));
+            let files = client.wait_for_files(&torrent.id).await?;
+            (torrent.id, files)
+        }
+        None => {
+            on_update(RdUpdate::Status("Adding magnet to Real-Debrid..."));
+            client.get_torrent_files(magnet).await?
+        }
+    };
+
+    // Keep an existing selection, otherwise take everything
+    let mut file_ids: Vec<u32> = files.iter().filter(|f| f.selected).map(|f| f.id).collect();
+    if file_ids.is_empty() {
+        file_ids = files.iter().map(|f| f.id).collect();
+    }
+    if file_ids.is_empty() {
+        return Err(anyhow!("Torrent has no files"));
+    }
+
+    loop {
+        match client
+            .download_selected_files_with_updates(&torrent_id, &file_ids, &mut on_update)
+            .await
+        {
+            Err(e) if e.is::<WaitTimeout>() => continue,
+            result => return result,
+        }
+    }
+}
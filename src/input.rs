@@ -0,0 +1,104 @@
+//! Readline-style editing for single-line text inputs
+//!
+//! Cursor positions are byte offsets that always sit on a char boundary.
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+/// Byte offset of the char before `pos`
+fn prev_char(text: &str, pos: usize) -> usize {
+    text[..pos].char_indices().next_back().map(|(i, _)| i).unwrap_or(0)
+}
+
+/// Byte offset of the char after `pos`
+fn next_char(text: &str, pos: usize) -> usize {
+    text[pos..].chars().next().map(|c| pos + c.len_utf8()).unwrap_or(pos)
+}
+
+/// Start of the word before `pos` (skipping any whitespace first)
+fn prev_word(text: &str, pos: usize) -> usize {
+    let mut i = pos;
+    while i > 0 && text[..i].ends_with(char::is_whitespace) {
+        i = prev_char(text, i);
+    }
+    while i > 0 && !text[..i].ends_with(char::is_whitespace) {
+        i = prev_char(text, i);
+    }
+    i
+}
+
+/// End of the word after `pos` (skipping any whitespace first)
+fn next_word(text: &str, pos: usize) -> usize {
+    let mut i = pos;
+    while i < text.len() && text[i..].starts_with(char::is_whitespace) {
+        i = next_char(text, i);
+    }
+    while i < text.len() && !text[i..].starts_with(char::is_whitespace) {
+        i = next_char(text, i);
+    }
+    i
+}
+
+/// Number of chars before the cursor (terminal column offset)
+pub fn cursor_column(text: &str, cursor: usize) -> u16 {
+    text[..cursor.min(text.len())].chars().count() as u16
+}
+
+/// Apply an editing key to `text`. Returns false if the key is not an editing key.
+///
+/// Supports Ctrl+A/E (start/end), Ctrl+W and Alt+Backspace (delete word),
+/// Ctrl+U (clear to start), Ctrl+K (clear to end) and Alt+B/F or
+/// Ctrl+Left/Right (word movement) on top of the usual keys.
+pub fn edit(text: &mut String, cursor: &mut usize, code: KeyCode, modifiers: KeyModifiers) -> bool {
+    let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+    let alt = modifiers.contains(KeyModifiers::ALT);
+    *cursor = (*cursor).min(text.len());
+
+    match code {
+        KeyCode::Char('a') if ctrl => *cursor = 0,
+        KeyCode::Char('e') if ctrl => *cursor = text.len(),
+        KeyCode::Char('u') if ctrl => {
+            text.replace_range(..*cursor, "");
+            *cursor = 0;
+        }
+        KeyCode::Char('k') if ctrl => text.truncate(*cursor),
+        KeyCode::Char('w') if ctrl => {
+            let start = prev_word(text, *cursor);
+            text.replace_range(start..*cursor, "");
+            *cursor = start;
+        }
+        KeyCode::Backspace if alt || ctrl => {
+            let start = prev_word(text, *cursor);
+            text.replace_range(start..*cursor, "");
+            *cursor = start;
+        }
+        KeyCode::Char('b') if alt => *cursor = prev_word(text, *cursor),
+        KeyCode::Char('f') if alt => *cursor = next_word(text, *cursor),
+        KeyCode::Left if alt || ctrl => *cursor = prev_word(text, *cursor),
+        KeyCode::Right if alt || ctrl => *cursor = next_word(text, *cursor),
+        KeyCode::Char(_) if ctrl || alt => return false,
+        KeyCode::Char(c) => {
+            text.insert(*cursor, c);
+            *cursor += c.len_utf8();
+        }
+        KeyCode::Backspace => {
+            if *cursor > 0 {
+                let start = prev_char(text, *cursor);
+                text.replace_range(start..*cursor, "");
+                *cursor = start;
+            }
+        }
+        KeyCode::Delete => {
+            if *cursor < text.len() {
+                let end = next_char(text, *cursor);
+                text.replace_range(*cursor..end, "");
+            }
+        }
+        KeyCode::Left => *cursor = prev_char(text, *cursor),
+        KeyCode::Right => *cursor = next_char(text, *cursor),
+        KeyCode::Home => *cursor = 0,
+        KeyCode::End => *cursor = text.len(),
+        _ => return false,
+    }
+
+    true
+}
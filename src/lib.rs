@@ -1,4 +1,8 @@
 //! littlejohn - Library exports
 
+pub mod config;
+pub mod doh;
+pub mod http;
 pub mod scrapers;
 pub mod realdebrid;
+pub mod release;
@@ -2,19 +2,63 @@
 
 #![allow(dead_code)]
 
+mod backup;
+mod batch;
+#[cfg(test)]
+mod apptests;
+mod blackhole;
+mod collision;
+mod config;
+mod crash;
+#[cfg(unix)]
+mod daemon;
+mod detached;
+mod diskguard;
+mod doh;
+mod event;
+mod export;
+mod graphics;
+mod handler;
+mod history;
+mod http;
+mod import;
+mod input;
+mod organize;
+mod paths;
+#[cfg(test)]
+mod proptests;
 mod realdebrid;
+mod release;
+mod remote;
+mod schedule;
 mod scrapers;
+mod selftest;
+mod slowlink;
+mod sort;
+mod suggest;
+#[cfg(unix)]
+mod suspend;
+mod tasks;
+mod telegram;
+mod theme;
+mod throttle;
+mod trakt;
 mod ui;
+mod update;
 
 use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use futures::StreamExt;
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::io::Stdout;
+use std::io::{IsTerminal, Stdout};
 use std::path::PathBuf;
 use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 
-use realdebrid::{RealDebridClient, TorrentFile};
-use scrapers::TorrentResult;
+use config::PRESERVED_ENV_KEYS;
+use realdebrid::{ExistingTorrent, RdProgress, RdUpdate, RealDebridClient, TorrentFile, WaitTimeout};
+use release::Category;
+use scrapers::{SourceStatus, SourceStatuses, TorrentResult};
 
 /// Download status
 #[derive(Debug, Clone, PartialEq)]
@@ -24,14 +68,42 @@ pub enum DownloadStatus {
     Completed,
     Failed(String),
     Cancelled,
+    /// Destination already existed (ON_FILE_EXISTS=skip)
+    Skipped,
+    /// Held by the low-disk guard until space is freed
+    Paused,
+    /// Queued outside DOWNLOAD_WINDOW, starts when it opens
+    Scheduled,
 }
 
+impl DownloadStatus {
+    /// Still has work to do (queued, transferring, paused or scheduled)
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            DownloadStatus::Pending | DownloadStatus::Downloading | DownloadStatus::Paused | DownloadStatus::Scheduled
+        )
+    }
+}
+
+/// Stable ID of a download, unaffected by the list being cleared or reordered
+pub type DownloadId = uuid::Uuid;
+
 /// A download in progress
 #[derive(Debug, Clone)]
 pub struct Download {
+    pub id: DownloadId,
     pub url: String,
     pub filename: String,
     pub dest_path: PathBuf,
+    /// Picks the destination directory (CATEGORY_DIRS)
+    pub category: Category,
+    /// Trakt "Up next" entry to mark collected once downloaded
+    pub trakt: Option<trakt::Item>,
+    /// Real-Debrid hoster link `url` was unrestricted from, for a fresh URL once it expires
+    pub rd_link: Option<String>,
+    /// Background task transferring the file, while it runs
+    pub task: Option<tasks::TaskId>,
     pub status: DownloadStatus,
     pub total_bytes: u64,
     pub downloaded_bytes: u64,
@@ -78,42 +150,308 @@ pub fn format_time(seconds: f64) -> String {
 
 type Tui = Terminal<CrosstermBackend<Stdout>>;
 
+/// Interval between UI ticks (spinner frames, progress redraws)
+const TICK_RATE_MS: u64 = 100;
+
+/// How long background tasks get to clean up on quit
+const TASK_SHUTDOWN_GRACE: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Status messages are cleared after this long (they stay in the history)
+const STATUS_TTL: std::time::Duration = std::time::Duration::from_secs(8);
+
+/// Status messages kept for the history popup
+const STATUS_HISTORY: usize = 200;
+
+/// A torrent Real-Debrid is fetching in the background, listed on the downloads screen
+#[derive(Debug, Clone)]
+pub struct RdTransfer {
+    /// Torrent ID, or the magnet or link of an import
+    pub key: String,
+    /// Shown until RD reports the torrent's name
+    pub label: String,
+    pub status: String,
+    /// RD-side progress while it downloads the torrent
+    pub progress: Option<RdProgress>,
+    /// Category for the downloads; guessed per file when unset
+    pub category: Option<Category>,
+}
+
+impl RdTransfer {
+    fn new(key: String, label: String, status: &str) -> Self {
+        Self { key, label, status: status.to_string(), progress: None, category: None }
+    }
+
+    pub fn name(&self) -> &str {
+        match &self.progress {
+            Some(p) if !p.name.is_empty() => &p.name,
+            _ => &self.label,
+        }
+    }
+}
+
 /// Application mode/screen
 #[derive(Debug, Clone, PartialEq)]
 pub enum AppMode {
     Setup,      // First-run setup wizard
+    SelfTest,   // Connectivity checks at the end of setup
     Settings,   // Settings screen (accessible anytime)
     Search,
     Results,
     FileSelect,
     SourceSelect,
     Downloads,
+    Stats,
+    Log,        // Scraper log viewer
     Processing,
+    ConfirmQuit, // Quit requested while downloads are active
+    ConfirmDuplicate, // Magnet is already on the RD account
+    ConfirmKeepWaiting, // RD wait timed out
+    ImportPrompt, // Entering the path of a magnet list to import
+    Details,    // Details and cover art of the selected result
+    QualitySelect, // Picking which quality of a movie to add
+    Messages,   // Status message history
+    Changelog,  // Release notes of a newer version
+    UpNext,     // Trakt next episodes and watchlist
     Error(String),
 }
 
+impl AppMode {
+    /// Screens the user navigates between, as opposed to progress, prompts and errors
+    /// shown over them (which are never returned to)
+    pub fn is_screen(&self) -> bool {
+        !matches!(
+            self,
+            AppMode::Setup
+                | AppMode::SelfTest
+                | AppMode::Processing
+                | AppMode::ConfirmQuit
+                | AppMode::ConfirmDuplicate
+                | AppMode::ConfirmKeepWaiting
+                | AppMode::Error(_)
+        )
+    }
+
+    /// Name shown in the breadcrumb
+    pub fn label(&self) -> &'static str {
+        match self {
+            AppMode::Setup => "Setup",
+            AppMode::SelfTest => "Self-test",
+            AppMode::Settings => "Settings",
+            AppMode::Search => "Search",
+            AppMode::Results => "Results",
+            AppMode::FileSelect => "Files",
+            AppMode::SourceSelect => "Sources",
+            AppMode::Downloads => "Downloads",
+            AppMode::Stats => "Stats",
+            AppMode::Log => "Log",
+            AppMode::Processing => "Working",
+            AppMode::ConfirmQuit => "Quit?",
+            AppMode::ConfirmDuplicate => "Duplicate",
+            AppMode::ConfirmKeepWaiting => "Timed out",
+            AppMode::ImportPrompt => "Import",
+            AppMode::Details => "Details",
+            AppMode::QualitySelect => "Quality",
+            AppMode::Messages => "Messages",
+            AppMode::Changelog => "Changelog",
+            AppMode::UpNext => "Up next",
+            AppMode::Error(_) => "Error",
+        }
+    }
+}
+
+/// Past queries and completed downloads listed on the search screen, per kind
+const RECENT_ITEMS: usize = 5;
+
+/// An entry of the search screen's Recent panel
+#[derive(Debug, Clone)]
+pub enum RecentItem {
+    /// A past query, searched again on Enter
+    Query(String),
+    /// A completed download, opened on Enter
+    Download(history::Entry),
+}
+
+/// A row of the results table
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResultRow {
+    /// A result, by index into `App::results`
+    Release(usize),
+    /// A result listed under its expanded title
+    Member(usize),
+    /// Several releases of one title in a single row
+    Title(TitleGroup),
+}
+
+/// Releases of the same title across sources
+#[derive(Debug, Clone, PartialEq)]
+pub struct TitleGroup {
+    /// Key for `App::expanded_titles` (lower-case title and year)
+    pub key: String,
+    /// Display title, e.g. "Dune (2021)"
+    pub title: String,
+    /// Indices into `App::results`, best first
+    pub releases: Vec<usize>,
+    pub expanded: bool,
+}
+
+/// Group results (the given indices) by title and year. Titles with a single release
+/// stay plain rows.
+fn title_rows(
+    results: &[TorrentResult],
+    indices: &[usize],
+    expanded: &std::collections::HashSet<String>,
+) -> Vec<ResultRow> {
+    let mut groups: Vec<TitleGroup> = Vec::new();
+    for &i in indices {
+        let result = &results[i];
+        let (title, year) = release::title_and_year(&result.name);
+        let key = format!("{} {}", title.to_lowercase(), year.as_deref().unwrap_or(""));
+        match groups.iter_mut().find(|g| g.key == key) {
+            Some(group) => group.releases.push(i),
+            None => groups.push(TitleGroup {
+                expanded: expanded.contains(&key),
+                key,
+                title: match year {
+                    Some(year) => format!("{} ({})", title, year),
+                    None => title,
+                },
+                releases: vec![i],
+            }),
+        }
+    }
+
+    let mut rows = Vec::new();
+    for group in groups {
+        if group.releases.len() == 1 {
+            rows.push(ResultRow::Release(group.releases[0]));
+            continue;
+        }
+        let members: Vec<ResultRow> = if group.expanded {
+            group.releases.iter().map(|&i| ResultRow::Member(i)).collect()
+        } else {
+            Vec::new()
+        };
+        rows.push(ResultRow::Title(group));
+        rows.extend(members);
+    }
+    rows
+}
+
+/// Cover art shown in the result details
+#[derive(Debug, Clone)]
+pub enum Cover {
+    Loading,
+    Image(std::sync::Arc<image::DynamicImage>),
+    /// No cover URL, or it couldn't be fetched
+    Missing,
+}
+
+/// What an RD wait was for, so it can be resumed after a timeout
+#[derive(Debug, Clone)]
+pub enum RdWait {
+    /// Magnet resolution (file list)
+    Files,
+    /// RD caching the selected files (download links)
+    Links(Vec<u32>),
+}
+
 /// Settings field being edited
 #[derive(Debug, Clone, PartialEq)]
 pub enum SettingsField {
     RdApiToken,
     FirecrawlApiKey,
     DownloadDir,
+    VimMode,
+    Palette,
 }
 
+/// Most recent scraper log lines loaded into the log screen
+const LOG_SCREEN_LINES: usize = 5000;
+
 /// Source priority order (matching Python implementation)
-pub const SOURCE_PRIORITY: &[&str] = &["yts", "ilcorsaronero", "tpb", "bitsearch", "1337x", "extto"];
+pub const SOURCE_PRIORITY: &[&str] = &["yts", "ilcorsaronero", "tpb", "bitsearch", "1337x", "rutracker", "nyaa", "extto"];
+
+/// Source priority order outside anime mode: SOURCE_PRIORITY from the config, then
+/// the other sources in the default order
+pub fn source_priority() -> Vec<String> {
+    let configured = std::env::var("SOURCE_PRIORITY").unwrap_or_default();
+    let sources = scrapers::all_sources();
+    let mut order: Vec<String> = Vec::new();
+    let candidates = configured.split(',').map(str::trim).chain(SOURCE_PRIORITY.iter().copied()).chain(sources.iter().copied());
+    for name in candidates {
+        if let Some(source) = sources.iter().find(|s| s.eq_ignore_ascii_case(name)) {
+            if !order.iter().any(|o| o == source) {
+                order.push(source.to_string());
+            }
+        }
+    }
+    order
+}
+
+/// Source priority order in anime mode
+pub const ANIME_SOURCE_PRIORITY: &[&str] = &["nyaa", "tpb", "bitsearch", "1337x", "rutracker", "ilcorsaronero", "yts"];
+
+/// Query templates used when QUERY_TEMPLATES is not set (`%s` is replaced by the query)
+pub const DEFAULT_QUERY_TEMPLATES: &[&str] = &["%s 1080p", "%s 2160p", "%s 1080p x265"];
+
+/// Load query templates from env (`|`-separated), falling back to the defaults
+fn load_query_templates() -> Vec<String> {
+    match std::env::var("QUERY_TEMPLATES") {
+        Ok(v) if !v.trim().is_empty() => v
+            .split('|')
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect(),
+        _ => DEFAULT_QUERY_TEMPLATES.iter().map(|t| t.to_string()).collect(),
+    }
+}
+
+/// Whether a boolean env option is set (true, 1 or yes)
+fn env_flag(key: &str) -> bool {
+    matches!(
+        std::env::var(key).map(|v| v.to_lowercase()).as_deref(),
+        Ok("1") | Ok("true") | Ok("yes")
+    )
+}
+
+/// Apply a query template to a single query
+fn apply_template(template: &str, query: &str) -> String {
+    if template.contains("%s") {
+        template.replace("%s", query)
+    } else {
+        format!("{} {}", query, template)
+    }
+}
 
 /// Application state
 pub struct App {
     /// Current mode/screen
     pub mode: AppMode,
+    /// Screens under the current one, the last is returned to on Esc
+    pub nav: Vec<AppMode>,
     /// Search input
     pub search_input: String,
     /// Cursor position in search input
     pub cursor_pos: usize,
     /// Search results
     pub results: Vec<TorrentResult>,
-    /// Selected result index
+    /// Rows of the results table: results, or titles grouping several of them
+    pub result_rows: Vec<ResultRow>,
+    /// Collapse releases of the same title into one row
+    pub group_titles: bool,
+    /// Column the results are sorted by
+    pub sort: sort::Sort,
+    /// Grouped titles shown expanded, by title key
+    pub expanded_titles: std::collections::HashSet<String>,
+    /// Results pinned to the top of the list, kept across searches and pages
+    pub pinned: Vec<TorrentResult>,
+    /// Connection speed from DOWNLOAD_SPEED (bytes/s), used for the results' ETA column
+    pub configured_speed: Option<u64>,
+    /// Combined speed of the active downloads, smoothed (bytes/s; 0 until measured)
+    pub measured_speed: f64,
+    /// Only show results with this audio language (e.g. "IT")
+    pub language_filter: Option<String>,
+    /// Selected row of the results table
     pub selected_index: usize,
     /// Scroll offset for results list
     pub scroll_offset: usize,
@@ -129,6 +467,67 @@ pub struct App {
     pub file_scroll_offset: usize,
     /// Torrent ID (for RD)
     pub torrent_id: Option<String>,
+    /// Category of the torrent being added, guessed from its result or files
+    pub torrent_category: Option<Category>,
+    /// The torrent was already on the account, so it's kept when backing out
+    pub torrent_reused: bool,
+    /// Magnet awaiting a reuse/add decision, and the matching RD torrent
+    pub duplicate: Option<(String, ExistingTorrent)>,
+    /// RD wait that timed out, awaiting a keep waiting/give up decision
+    pub timed_out: Option<(RdWait, WaitTimeout)>,
+    /// Torrent and files of the in-flight download-links wait, if that's the current task
+    pub links_wait: Option<(String, Vec<u32>)>,
+    /// Latest RD-side progress of the in-flight links wait
+    pub rd_progress: Option<RdProgress>,
+    /// Import prompt: path of the magnet list
+    pub import_path: String,
+    /// Cursor position in the import path
+    pub import_cursor: usize,
+    /// Log screen: lines loaded from the scraper log
+    pub log_lines: Vec<String>,
+    /// Log screen: only show lines from this source
+    pub log_source: Option<String>,
+    /// Log screen: case-insensitive text filter
+    pub log_search: String,
+    /// Cursor position in the log search
+    pub log_search_cursor: usize,
+    /// Log screen: typing into the search
+    pub log_searching: bool,
+    /// Log screen: lines scrolled up from the newest entry
+    pub log_scroll: usize,
+    /// Torrents RD is caching in the background, oldest first
+    pub background_waits: Vec<RdTransfer>,
+    /// Free bytes left on the download volume while the low-disk guard has downloads paused
+    pub disk_low: Option<u64>,
+    /// Telegram bot for notifications and remote commands
+    pub telegram: Option<telegram::Bot>,
+    /// Start pending downloads on the next loop pass (set by remote grabs)
+    pub autostart_pending: bool,
+    /// Magnet to add to RD on the next loop pass (fetched for a fast-search result)
+    pub pending_magnet: Option<String>,
+    /// Past search queries, most recent first
+    pub search_history: Vec<String>,
+    /// Search for the episode after the last one downloaded, offered on the search screen
+    pub next_episode: Option<String>,
+    /// Last completed downloads, newest first, for the Recent panel
+    pub recent_downloads: Vec<history::Entry>,
+    /// Highlighted entry of the Recent panel
+    pub recent_index: Option<usize>,
+    /// Suggestions shown under the search input
+    pub suggestions: Vec<suggest::Suggestion>,
+    /// Highlighted suggestion (None = the typed text)
+    pub suggestion_index: Option<usize>,
+    /// TMDB titles for `tmdb_query`
+    pub tmdb_titles: Vec<String>,
+    pub tmdb_query: String,
+    /// Latest TMDB lookup, so replies to older keystrokes are dropped
+    pub tmdb_seq: u64,
+    /// Graphics protocol for cover art
+    pub graphics: graphics::Protocol,
+    /// Cover art by result (dedup key)
+    pub covers: std::collections::HashMap<String, Cover>,
+    /// Cover currently painted on screen with the graphics protocol, and where
+    pub shown_cover: Option<(String, ratatui::layout::Rect)>,
     /// Status message
     pub status: String,
     /// Should quit
@@ -141,8 +540,16 @@ pub struct App {
     pub enabled_sources: std::collections::HashSet<String>,
     /// Source selector cursor
     pub source_cursor: usize,
+    /// Qualities of the movie being picked from (one torrent each)
+    pub quality_choices: Vec<TorrentResult>,
+    /// Quality picker cursor
+    pub quality_cursor: usize,
+    /// Sources in priority order, as listed in the source selector
+    pub source_order: Vec<String>,
     /// Downloads list
     pub downloads: Vec<Download>,
+    /// Position of each download in `downloads`, by ID; background tasks report by ID
+    download_ids: std::collections::HashMap<DownloadId, usize>,
     /// Download cursor
     pub download_cursor: usize,
     /// Current settings field being edited
@@ -155,26 +562,109 @@ pub struct App {
     pub settings_download_dir: String,
     /// Cursor position in current settings input
     pub settings_cursor: usize,
+    /// Banner shown above the settings form (e.g. token expired)
+    pub settings_banner: Option<String>,
+    /// Firecrawl credits left (or why they couldn't be fetched), shown in settings
+    pub firecrawl_credits: Option<String>,
+    /// Newer release found by the update check, announced in the header
+    pub update: Option<update::Release>,
+    /// Scroll offset of the changelog popup
+    pub changelog_scroll: u16,
+    /// Suspend to the shell on the next loop pass (Ctrl+Z)
+    pub suspend_requested: bool,
+    /// Set up the terminal again and redraw it on the next loop pass (after a stop)
+    pub resumed: bool,
+    /// Checks of the setup self-test and how they went
+    pub self_test: Vec<selftest::Item>,
+    /// Query whose estimated Firecrawl cost was warned about; Enter again searches
+    pub costly_search: Option<String>,
+    /// Configured query templates
+    pub query_templates: Vec<String>,
+    /// Active query template index (None = plain query)
+    pub active_template: Option<usize>,
+    /// Anime mode (Nyaa first, batches grouped separately)
+    pub anime_mode: bool,
+    /// Fast search: skip detail pages, fetch a result's magnet when it's picked
+    pub fast_search: bool,
+    /// Keep reading result pages until there are enough results
+    pub deep_search: bool,
+    /// Blackhole mode: picked magnets go to BLACKHOLE_DIR instead of Real-Debrid
+    pub blackhole_mode: bool,
+    /// Per-source outcome of the last search
+    pub source_statuses: SourceStatuses,
+    /// Cancellation token for the in-flight background operation
+    pub task_token: Option<CancellationToken>,
+    /// Searches, Real-Debrid waits, downloads and other tracked background work
+    pub tasks: tasks::TaskManager,
+    /// UI tick counter, advanced every TICK_RATE_MS
+    pub tick: u64,
+    /// Past status messages with the time they were shown, oldest first
+    pub status_history: std::collections::VecDeque<(chrono::DateTime<chrono::Local>, String)>,
+    /// Status message last seen by [`App::track_status`] and when it appeared
+    status_since: (String, std::time::Instant),
+    /// First row of the message history shown
+    pub messages_scroll: usize,
+    /// Trakt "Up next" entries, None until loaded
+    pub up_next: Option<Vec<trakt::Item>>,
+    pub up_next_cursor: usize,
+    /// "Up next" entry being searched for, marked collected if its downloads complete
+    pub trakt_search: Option<trakt::Item>,
+    /// Settings input: vim-style navigation
+    pub settings_vim_mode: bool,
+    /// Vim-style navigation enabled (VIM_MODE)
+    pub vim_mode: bool,
+    /// Search input is in insert mode (vim mode only)
+    pub vim_insert: bool,
+    /// Pending vim count prefix (0 = none)
+    pub vim_count: usize,
+    /// A first `g` of `gg` was pressed
+    pub vim_pending_g: bool,
+    /// Result number being typed on the results screen (`42` or `:42`)
+    pub jump_input: Option<String>,
+    /// Hand active downloads to a background worker on exit
+    pub detach_on_quit: bool,
+    /// Status color palette
+    pub palette: theme::Palette,
+    /// Settings input: status color palette
+    pub settings_palette: theme::Palette,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl App {
     pub fn new() -> Self {
         let rd_client = RealDebridClient::new().ok();
 
-        // All sources enabled by default
-        let enabled_sources: std::collections::HashSet<String> =
-            scrapers::SCRAPERS.iter().map(|s| s.to_string()).collect();
+        let enabled_sources = scrapers::default_enabled();
 
         // Load current settings from env
         let settings_rd_token = std::env::var("RD_API_TOKEN").unwrap_or_default();
         let settings_firecrawl_key = std::env::var("FIRECRAWL_API_KEY").unwrap_or_default();
         let settings_download_dir = std::env::var("DOWNLOAD_DIR").unwrap_or_default();
+        let vim_mode = env_flag("VIM_MODE");
+        let downloaded = history::load();
 
         Self {
             mode: AppMode::Search,
+            nav: Vec::new(),
             search_input: String::new(),
             cursor_pos: 0,
             results: Vec::new(),
+            result_rows: Vec::new(),
+            group_titles: true,
+            sort: sort::Sort::from_env(),
+            expanded_titles: std::collections::HashSet::new(),
+            pinned: Vec::new(),
+            configured_speed: std::env::var("DOWNLOAD_SPEED")
+                .ok()
+                .and_then(|v| diskguard::parse_size(&v))
+                .filter(|&speed| speed > 0),
+            measured_speed: 0.0,
+            language_filter: None,
             selected_index: 0,
             scroll_offset: 0,
             page: 1,
@@ -183,42 +673,542 @@ impl App {
             file_cursor: 0,
             file_scroll_offset: 0,
             torrent_id: None,
+            torrent_category: None,
+            torrent_reused: false,
+            duplicate: None,
+            timed_out: None,
+            links_wait: None,
+            rd_progress: None,
+            import_path: String::new(),
+            import_cursor: 0,
+            log_lines: Vec::new(),
+            log_source: None,
+            log_search: String::new(),
+            log_search_cursor: 0,
+            log_searching: false,
+            log_scroll: 0,
+            background_waits: Vec::new(),
+            disk_low: None,
+            telegram: telegram::Bot::from_env(),
+            autostart_pending: false,
+            pending_magnet: None,
+            search_history: suggest::load_history(),
+            next_episode: history::next_episode(&downloaded),
+            recent_downloads: history::recent(&downloaded, RECENT_ITEMS),
+            recent_index: None,
+            suggestions: Vec::new(),
+            suggestion_index: None,
+            tmdb_titles: Vec::new(),
+            tmdb_query: String::new(),
+            tmdb_seq: 0,
+            graphics: graphics::Protocol::from_env(),
+            covers: std::collections::HashMap::new(),
+            shown_cover: None,
             status: String::new(),
             should_quit: false,
             rd_client,
             processing_status: String::new(),
             enabled_sources,
             source_cursor: 0,
+            quality_choices: Vec::new(),
+            quality_cursor: 0,
+            source_order: source_priority(),
             downloads: Vec::new(),
+            download_ids: std::collections::HashMap::new(),
             download_cursor: 0,
             settings_field: SettingsField::RdApiToken,
             settings_rd_token,
             settings_firecrawl_key,
             settings_download_dir,
             settings_cursor: 0,
+            settings_banner: None,
+            firecrawl_credits: None,
+            update: None,
+            changelog_scroll: 0,
+            suspend_requested: false,
+            resumed: false,
+            self_test: Vec::new(),
+            costly_search: None,
+            query_templates: load_query_templates(),
+            active_template: None,
+            anime_mode: false,
+            fast_search: env_flag("FAST_SEARCH"),
+            deep_search: env_flag("DEEP_SEARCH"),
+            blackhole_mode: blackhole::dir().is_some(),
+            source_statuses: SourceStatuses::new(),
+            task_token: None,
+            tasks: tasks::TaskManager::default(),
+            tick: 0,
+            status_history: std::collections::VecDeque::new(),
+            status_since: (String::new(), std::time::Instant::now()),
+            messages_scroll: 0,
+            up_next: None,
+            up_next_cursor: 0,
+            trakt_search: None,
+            settings_vim_mode: vim_mode,
+            vim_mode,
+            vim_insert: true,
+            vim_count: 0,
+            vim_pending_g: false,
+            jump_input: None,
+            detach_on_quit: false,
+            palette: theme::Palette::from_env(),
+            settings_palette: theme::Palette::from_env(),
+        }
+    }
+
+    /// Switch to `mode`, remembering the current screen to come back to. A screen that
+    /// is already in the stack is returned to, dropping the ones opened after it.
+    pub fn open(&mut self, mode: AppMode) {
+        if let Some(pos) = self.nav.iter().position(|m| *m == mode) {
+            self.nav.truncate(pos);
+        } else if self.mode.is_screen() && self.mode != mode {
+            self.nav.push(self.mode.clone());
+        }
+        self.mode = mode;
+    }
+
+    /// Return to the previous screen (search if there is none)
+    pub fn back(&mut self) {
+        self.mode = self.nav.pop().unwrap_or(AppMode::Search);
+    }
+
+    /// Return to the results list, or search if there are no results, dropping the
+    /// screens opened since (after a torrent is done with or given up on)
+    pub fn back_to_results(&mut self) {
+        if self.results.is_empty() {
+            self.go_home();
+        } else {
+            self.nav = vec![AppMode::Search];
+            self.mode = AppMode::Results;
+        }
+    }
+
+    /// Return to the search screen, dropping every other screen
+    pub fn go_home(&mut self) {
+        self.nav.clear();
+        self.mode = AppMode::Search;
+    }
+
+    /// Screens from search to the current one, e.g. "Search › Results › Files"
+    pub fn breadcrumb(&self) -> String {
+        self.nav
+            .iter()
+            .chain(std::iter::once(&self.mode))
+            .map(AppMode::label)
+            .collect::<Vec<_>>()
+            .join(" › ")
+    }
+
+    /// Add a new status message to the history, and clear the status once it has
+    /// been shown for STATUS_TTL
+    pub fn track_status(&mut self) -> bool {
+        if self.status != self.status_since.0 {
+            if !self.status.is_empty() {
+                if self.status_history.len() == STATUS_HISTORY {
+                    self.status_history.pop_front();
+                }
+                self.status_history.push_back((chrono::Local::now(), self.status.clone()));
+            }
+            self.status_since = (self.status.clone(), std::time::Instant::now());
+            true
+        } else if !self.status.is_empty() && self.status_since.1.elapsed() >= STATUS_TTL {
+            self.status.clear();
+            self.status_since.0.clear();
+            true
+        } else {
+            false
         }
     }
 
+    /// Whether the screen has an animation that needs redrawing on every tick
+    pub fn animating(&self) -> bool {
+        match self.mode {
+            AppMode::Processing => self.rd_progress.is_none(),
+            AppMode::SelfTest => self.self_test.iter().any(|item| item.outcome.is_none()),
+            _ => false,
+        }
+    }
+
+    /// Open the log screen at the newest entries
+    pub fn open_log(&mut self) {
+        self.log_lines = scrapers::log::read_recent_logs(LOG_SCREEN_LINES);
+        self.log_scroll = 0;
+        self.open(AppMode::Log);
+    }
+
+    /// Sources that appear in the loaded log, sorted
+    pub fn log_sources(&self) -> Vec<String> {
+        let mut sources: Vec<String> = self
+            .log_lines
+            .iter()
+            .filter_map(|l| scrapers::log::line_source(l))
+            .map(String::from)
+            .collect();
+        sources.sort();
+        sources.dedup();
+        sources
+    }
+
+    /// Log lines passing the source filter and search
+    pub fn filtered_log(&self) -> Vec<&str> {
+        let search = self.log_search.to_lowercase();
+        self.log_lines
+            .iter()
+            .filter(|l| match &self.log_source {
+                Some(source) => scrapers::log::line_source(l) == Some(source.as_str()),
+                None => true,
+            })
+            .filter(|l| search.is_empty() || l.to_lowercase().contains(&search))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// The filtered log lines that fit in `height` rows at the current scroll position
+    pub fn log_window(&self, height: usize) -> Vec<&str> {
+        let lines = self.filtered_log();
+        let end = lines.len().saturating_sub(self.log_scroll.min(lines.len().saturating_sub(height)));
+        let start = end.saturating_sub(height);
+        lines[start..end].to_vec()
+    }
+
     pub fn visible_height(&self) -> usize {
         20 // Approximate visible rows
     }
 
+    /// Rebuild the results table rows after the results or grouping changed. Pinned
+    /// results come first, whatever the sort and filter.
+    pub fn refresh_rows(&mut self) {
+        let pinned: Vec<usize> = self
+            .pinned
+            .iter()
+            .filter_map(|p| self.results.iter().position(|r| r.dedup_key() == p.dedup_key()))
+            .collect();
+        let shown: Vec<usize> = (0..self.results.len())
+            .filter(|i| !pinned.contains(i))
+            .filter(|&i| match &self.language_filter {
+                Some(code) => self.results[i].audio.contains(code),
+                None => true,
+            })
+            .collect();
+
+        // Anime releases are already grouped by batch; title grouping would fight it
+        let rows = if self.group_titles && !self.anime_mode {
+            title_rows(&self.results, &shown, &self.expanded_titles)
+        } else {
+            shown.into_iter().map(ResultRow::Release).collect()
+        };
+        self.result_rows = pinned.into_iter().map(ResultRow::Release).chain(rows).collect();
+        self.selected_index = self.selected_index.min(self.result_rows.len().saturating_sub(1));
+    }
+
+    /// The results the rows show, in row order; a collapsed title stands for all its releases
+    pub fn shown_results(&self) -> Vec<TorrentResult> {
+        self.result_rows
+            .iter()
+            .flat_map(|row| match row {
+                ResultRow::Release(i) | ResultRow::Member(i) => vec![*i],
+                ResultRow::Title(group) if !group.expanded => group.releases.clone(),
+                // Expanded titles are followed by their member rows
+                ResultRow::Title(_) => Vec::new(),
+            })
+            .filter_map(|i| self.results.get(i).cloned())
+            .collect()
+    }
+
+    /// Switch the audio language filter to the next language found in the results
+    /// (after the last one, the filter is cleared)
+    pub fn cycle_language_filter(&mut self) {
+        let mut codes: Vec<&String> = self.results.iter().flat_map(|r| &r.audio).collect();
+        codes.sort();
+        codes.dedup();
+        let next = match &self.language_filter {
+            None => codes.first(),
+            Some(current) => codes.iter().skip_while(|c| **c != current).nth(1),
+        };
+        self.language_filter = next.map(|c| c.to_string());
+        self.selected_index = 0;
+        self.scroll_offset = 0;
+        self.refresh_rows();
+    }
+
+    /// Index into `results` of the selected row, unless it's a title row
+    pub fn selected_result_index(&self) -> Option<usize> {
+        match self.result_rows.get(self.selected_index)? {
+            ResultRow::Release(i) | ResultRow::Member(i) => Some(*i),
+            ResultRow::Title(_) => None,
+        }
+    }
+
+    /// The selected result, unless a title row is selected
+    pub fn selected_result(&self) -> Option<&TorrentResult> {
+        self.results.get(self.selected_result_index()?)
+    }
+
+    /// Whether a result is pinned to the top
+    pub fn is_pinned(&self, result: &TorrentResult) -> bool {
+        let key = result.dedup_key();
+        self.pinned.iter().any(|p| p.dedup_key() == key)
+    }
+
+    /// Pin the selected result, or unpin it if it's pinned
+    pub fn toggle_pin(&mut self) {
+        let Some(result) = self.selected_result().cloned() else { return };
+        let key = result.dedup_key();
+        if self.is_pinned(&result) {
+            self.pinned.retain(|p| p.dedup_key() != key);
+            self.status = format!("Unpinned ({} pinned)", self.pinned.len());
+        } else {
+            self.pinned.push(result);
+            self.status = format!("Pinned ({} pinned, [A] adds them all)", self.pinned.len());
+        }
+        self.refresh_rows();
+        if let Some(i) = self.results.iter().position(|r| r.dedup_key() == key) {
+            self.jump_to_result(i + 1);
+        }
+    }
+
+    /// Add pinned results missing from the current results (after a new search or page)
+    fn keep_pinned(&mut self) {
+        let missing: Vec<TorrentResult> = self
+            .pinned
+            .iter()
+            .filter(|p| !self.results.iter().any(|r| r.dedup_key() == p.dedup_key()))
+            .cloned()
+            .collect();
+        self.results.extend(missing);
+    }
+
+    /// Sort the results by the chosen column (relevance order first, so ties keep it),
+    /// staying on the selected result
+    pub fn apply_sort(&mut self) {
+        let selected = self.selected_result().map(TorrentResult::dedup_key);
+        sort_results(&mut self.results, self.anime_mode);
+        self.sort.apply(&mut self.results);
+        self.refresh_rows();
+        self.selected_index = 0;
+        self.scroll_offset = 0;
+        if let Some(i) = selected.and_then(|key| self.results.iter().position(|r| r.dedup_key() == key)) {
+            self.jump_to_result(i + 1);
+        }
+    }
+
+    /// Expand or collapse the selected title row
+    pub fn toggle_selected_title(&mut self) {
+        let Some(ResultRow::Title(group)) = self.result_rows.get(self.selected_index) else { return };
+        let key = group.key.clone();
+        if !self.expanded_titles.remove(&key) {
+            self.expanded_titles.insert(key);
+        }
+        self.refresh_rows();
+    }
+
+    /// Move the results cursor to a 1-based result number, scrolling it into view
+    /// (expanding its title if it's grouped)
+    pub fn jump_to_result(&mut self, number: usize) {
+        if number == 0 || number > self.results.len() {
+            self.status = format!("No result #{} (1-{})", number, self.results.len());
+            return;
+        }
+        let find = |rows: &[ResultRow]| {
+            rows.iter()
+                .position(|row| matches!(row, ResultRow::Release(i) | ResultRow::Member(i) if *i == number - 1))
+        };
+        let row = match find(&self.result_rows) {
+            Some(row) => row,
+            None => {
+                let group = self.result_rows.iter().find_map(|row| match row {
+                    ResultRow::Title(g) if g.releases.contains(&(number - 1)) => Some(g.key.clone()),
+                    _ => None,
+                });
+                self.expanded_titles.extend(group);
+                self.refresh_rows();
+                find(&self.result_rows).unwrap_or(0)
+            }
+        };
+        let visible_height = self.visible_height();
+        self.selected_index = row;
+        if self.selected_index < self.scroll_offset {
+            self.scroll_offset = self.selected_index;
+        } else if self.selected_index >= self.scroll_offset + visible_height {
+            self.scroll_offset = self.selected_index + 1 - visible_height;
+        }
+    }
+
+    /// Create a cancellation token for a new background operation
+    pub fn new_task_token(&mut self) -> CancellationToken {
+        let token = CancellationToken::new();
+        self.task_token = Some(token.clone());
+        self.links_wait = None;
+        self.rd_progress = None;
+        token
+    }
+
+    /// Cancel the in-flight background operation, if any
+    pub fn cancel_task(&mut self) {
+        if let Some(token) = self.task_token.take() {
+            token.cancel();
+        }
+    }
+
+    /// Enabled sources that failed in the last search
+    pub fn failed_sources(&self) -> Vec<String> {
+        scrapers::all_sources()
+            .iter()
+            .filter(|s| self.enabled_sources.contains(**s))
+            .filter(|s| self.source_statuses.get(**s) == Some(&SourceStatus::Failed))
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    /// Speed download times are estimated with: DOWNLOAD_SPEED, else the measured one
+    pub fn transfer_speed(&self) -> Option<f64> {
+        match self.configured_speed {
+            Some(speed) => Some(speed as f64),
+            None => (self.measured_speed > 0.0).then_some(self.measured_speed),
+        }
+    }
+
+    /// Estimated time to download a release of `size` (as shown in the results);
+    /// None without a known speed or a readable size
+    pub fn download_eta(&self, size: &str) -> Option<String> {
+        let bytes = scrapers::normalize::size_bytes(size)?;
+        Some(format_time(bytes as f64 / self.transfer_speed()?))
+    }
+
+    /// Fold the current combined download speed into the measured one
+    fn measure_speed(&mut self) {
+        let total: f64 = self
+            .downloads
+            .iter()
+            .filter(|d| d.status == DownloadStatus::Downloading)
+            .map(|d| d.speed)
+            .sum();
+        if total <= 0.0 {
+            return;
+        }
+        self.measured_speed = if self.measured_speed > 0.0 {
+            self.measured_speed * 0.9 + total * 0.1
+        } else {
+            total
+        };
+    }
+
+    /// How many of the searched sources worked, e.g. "4/5 sources ok (tpb failed)";
+    /// None before a search
+    pub fn sources_summary(&self) -> Option<String> {
+        let searched = scrapers::all_sources()
+            .iter()
+            .filter(|s| self.enabled_sources.contains(**s) && self.source_statuses.contains_key(**s))
+            .count();
+        if searched == 0 {
+            return None;
+        }
+        let failed = self.failed_sources();
+        let mut summary = format!("{}/{} sources ok", searched - failed.len(), searched);
+        if !failed.is_empty() {
+            summary.push_str(&format!(" ({} failed)", failed.join(", ")));
+        }
+        Some(summary)
+    }
+
+    /// Currently selected query template, if any
+    pub fn current_template(&self) -> Option<&str> {
+        self.active_template
+            .and_then(|i| self.query_templates.get(i))
+            .map(String::as_str)
+    }
+
+    /// Cycle to the next query template (wrapping back to no template)
+    pub fn cycle_template(&mut self) {
+        self.active_template = match self.active_template {
+            None if !self.query_templates.is_empty() => Some(0),
+            Some(i) if i + 1 < self.query_templates.len() => Some(i + 1),
+            _ => None,
+        };
+    }
+
+    /// Entries of the search screen's Recent panel: past queries, then completed downloads
+    pub fn recent_items(&self) -> Vec<RecentItem> {
+        let queries = self.search_history.iter().take(RECENT_ITEMS).cloned().map(RecentItem::Query);
+        let downloads = self.recent_downloads.iter().cloned().map(RecentItem::Download);
+        queries.chain(downloads).collect()
+    }
+
+    /// Rebuild the suggestion list for the current search input: matching past
+    /// queries, then TMDB titles if they were looked up for this input
+    pub fn refresh_suggestions(&mut self) {
+        let input = self.search_input.trim();
+        self.suggestion_index = None;
+        self.recent_index = None;
+        if input.is_empty() || input.starts_with("magnet:") {
+            self.suggestions.clear();
+            return;
+        }
+
+        let mut suggestions: Vec<suggest::Suggestion> = suggest::from_history(&self.search_history, input)
+            .into_iter()
+            .map(|text| suggest::Suggestion { text, tmdb: false })
+            .collect();
+        if self.tmdb_query == input {
+            for title in &self.tmdb_titles {
+                if !title.eq_ignore_ascii_case(input) && !suggestions.iter().any(|s| s.text.eq_ignore_ascii_case(title)) {
+                    suggestions.push(suggest::Suggestion { text: title.clone(), tmdb: true });
+                }
+            }
+        }
+        self.suggestions = suggestions;
+    }
+
+    /// Search input with the active template applied to each comma-separated query
+    pub fn effective_query(&self) -> String {
+        match self.current_template() {
+            Some(template) => self
+                .search_input
+                .split(',')
+                .map(str::trim)
+                .filter(|q| !q.is_empty())
+                .map(|q| apply_template(template, q))
+                .collect::<Vec<_>>()
+                .join(", "),
+            None => self.search_input.clone(),
+        }
+    }
+
     /// Get the current settings field input
     pub fn current_settings_input(&self) -> &str {
         match self.settings_field {
             SettingsField::RdApiToken => &self.settings_rd_token,
             SettingsField::FirecrawlApiKey => &self.settings_firecrawl_key,
             SettingsField::DownloadDir => &self.settings_download_dir,
+            SettingsField::VimMode | SettingsField::Palette => "",
         }
     }
 
-    /// Get the current settings field input mutably
-    pub fn current_settings_input_mut(&mut self) -> &mut String {
-        match self.settings_field {
+    /// Apply an editing key to the current settings field input
+    pub fn edit_settings_input(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        let text = match self.settings_field {
             SettingsField::RdApiToken => &mut self.settings_rd_token,
             SettingsField::FirecrawlApiKey => &mut self.settings_firecrawl_key,
             SettingsField::DownloadDir => &mut self.settings_download_dir,
-        }
+            SettingsField::VimMode => {
+                // Toggle field: Space or Left/Right flips it
+                let toggle = matches!(code, KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right);
+                if toggle {
+                    self.settings_vim_mode = !self.settings_vim_mode;
+                }
+                return toggle;
+            }
+            SettingsField::Palette => {
+                // Choice field: Space or Left/Right cycles it
+                let cycle = matches!(code, KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right);
+                if cycle {
+                    self.settings_palette = self.settings_palette.next();
+                }
+                return cycle;
+            }
+        };
+        input::edit(text, &mut self.settings_cursor, code, modifiers)
     }
 
     /// Move to next settings field
@@ -226,7 +1216,9 @@ impl App {
         self.settings_field = match self.settings_field {
             SettingsField::RdApiToken => SettingsField::FirecrawlApiKey,
             SettingsField::FirecrawlApiKey => SettingsField::DownloadDir,
-            SettingsField::DownloadDir => SettingsField::RdApiToken,
+            SettingsField::DownloadDir => SettingsField::VimMode,
+            SettingsField::VimMode => SettingsField::Palette,
+            SettingsField::Palette => SettingsField::RdApiToken,
         };
         self.settings_cursor = self.current_settings_input().len();
     }
@@ -234,39 +1226,160 @@ impl App {
     /// Move to previous settings field
     pub fn prev_settings_field(&mut self) {
         self.settings_field = match self.settings_field {
-            SettingsField::RdApiToken => SettingsField::DownloadDir,
+            SettingsField::RdApiToken => SettingsField::Palette,
             SettingsField::FirecrawlApiKey => SettingsField::RdApiToken,
             SettingsField::DownloadDir => SettingsField::FirecrawlApiKey,
+            SettingsField::VimMode => SettingsField::DownloadDir,
+            SettingsField::Palette => SettingsField::VimMode,
         };
         self.settings_cursor = self.current_settings_input().len();
     }
 
     /// Save settings to config file
-    pub fn save_settings(&self) -> std::io::Result<()> {
-        let config_dir = dirs::config_dir()
-            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "Config directory not found"))?
-            .join("littlejohn");
+    pub fn save_settings(&self) -> Result<()> {
+        let mut values = std::collections::BTreeMap::new();
+        if !self.settings_rd_token.is_empty() {
+            values.insert("RD_API_TOKEN".to_string(), self.settings_rd_token.clone());
+        }
+        if !self.settings_firecrawl_key.is_empty() {
+            values.insert("FIRECRAWL_API_KEY".to_string(), self.settings_firecrawl_key.clone());
+        }
+        if !self.settings_download_dir.is_empty() {
+            values.insert("DOWNLOAD_DIR".to_string(), self.settings_download_dir.clone());
+        }
+        if self.settings_vim_mode {
+            values.insert("VIM_MODE".to_string(), "true".to_string());
+        }
+        if self.settings_palette != theme::DEFAULT {
+            values.insert("PALETTE".to_string(), self.settings_palette.name.to_string());
+        }
 
-        // Create directory if it doesn't exist
-        std::fs::create_dir_all(&config_dir)?;
+        // Keep options that are not editable in the settings form
+        for key in PRESERVED_ENV_KEYS {
+            if let Ok(value) = std::env::var(key) {
+                if !value.is_empty() {
+                    values.insert(key.to_string(), value);
+                }
+            }
+        }
 
-        let config_path = config_dir.join(".env");
+        config::save(&values)
+    }
 
-        let mut content = String::new();
-        content.push_str("# littlejohn configuration\n\n");
+    /// Downloads that are running or queued
+    pub fn active_downloads(&self) -> Vec<&Download> {
+        self.downloads
+            .iter()
+            .filter(|d| d.status.is_active())
+            .collect()
+    }
 
-        if !self.settings_rd_token.is_empty() {
-            content.push_str(&format!("RD_API_TOKEN={}\n", self.settings_rd_token));
+    /// Aggregate download progress for the header, e.g. "2/5 downloads · 43% · 12.3 MB/s".
+    /// None when nothing is downloading or queued.
+    pub fn download_summary(&self) -> Option<String> {
+        let caching = match self.background_waits.len() {
+            0 => None,
+            n => Some(format!("{} caching on RD", n)),
+        };
+        if self.active_downloads().is_empty() {
+            return caching;
         }
-        if !self.settings_firecrawl_key.is_empty() {
-            content.push_str(&format!("FIRECRAWL_API_KEY={}\n", self.settings_firecrawl_key));
+
+        let batch: Vec<&Download> = self
+            .downloads
+            .iter()
+            .filter(|d| d.status != DownloadStatus::Cancelled)
+            .collect();
+        let done = batch
+            .iter()
+            .filter(|d| matches!(d.status, DownloadStatus::Completed | DownloadStatus::Skipped))
+            .count();
+        let total_bytes: u64 = batch.iter().map(|d| d.total_bytes).sum();
+        let downloaded_bytes: u64 = batch.iter().map(|d| d.downloaded_bytes.min(d.total_bytes)).sum();
+        let speed: f64 = batch
+            .iter()
+            .filter(|d| d.status == DownloadStatus::Downloading)
+            .map(|d| d.speed)
+            .sum();
+
+        let mut parts = vec![format!("{}/{} downloads", done, batch.len())];
+        if total_bytes > 0 {
+            parts.push(format!("{:.0}%", downloaded_bytes as f64 / total_bytes as f64 * 100.0));
         }
-        if !self.settings_download_dir.is_empty() {
-            content.push_str(&format!("DOWNLOAD_DIR={}\n", self.settings_download_dir));
+        if speed > 0.0 {
+            parts.push(format_bytes(speed) + "/s");
+        }
+        parts.extend(caching);
+        Some(parts.join(" · "))
+    }
+
+    /// Directory downloads are saved to (DOWNLOAD_DIR, else the system download directory)
+    pub fn download_dir(&self) -> PathBuf {
+        download_dir()
+    }
+
+    /// Add download links to the download list. Without a `category`, each file's is
+    /// guessed from its name.
+    pub fn queue_downloads(&mut self, links: Vec<(String, String)>, category: Option<Category>) {
+        let status = if schedule::is_open() { DownloadStatus::Pending } else { DownloadStatus::Scheduled };
+
+        for (filename, url) in links {
+            // Print links to console (they'll be visible after exit)
+            eprintln!("\n{}", filename);
+            eprintln!("{}", url);
+
+            let category = category.unwrap_or_else(|| Category::guess(None, &filename));
+            let dest_path = paths::dest_path(&category_dir(category), &filename);
+            let rd_link = realdebrid::source_link(&url);
+            self.downloads.push(Download {
+                id: uuid::Uuid::new_v4(),
+                url,
+                filename,
+                dest_path,
+                category,
+                trakt: None,
+                rd_link,
+                task: None,
+                status: status.clone(),
+                total_bytes: 0,
+                downloaded_bytes: 0,
+                speed: 0.0,
+            });
+        }
+        self.index_downloads();
+    }
+
+    /// Count of unfinished background tasks by name, e.g. "2 tasks: search, download"
+    pub fn task_summary(&self) -> Option<String> {
+        let mut names: Vec<&str> = Vec::new();
+        for task in self.tasks.active() {
+            if !names.contains(&task.name.as_str()) {
+                names.push(&task.name);
+            }
         }
+        let count = self.tasks.active().count();
+        (count > 0).then(|| {
+            format!("{} task{}: {}", count, if count == 1 { "" } else { "s" }, names.join(", "))
+        })
+    }
+
+    /// Where the download with `id` is in the list now
+    pub fn download_index(&self, id: DownloadId) -> Option<usize> {
+        self.download_ids.get(&id).copied()
+    }
+
+    pub fn download(&self, id: DownloadId) -> Option<&Download> {
+        self.downloads.get(self.download_index(id)?)
+    }
+
+    pub fn download_mut(&mut self, id: DownloadId) -> Option<&mut Download> {
+        let index = self.download_index(id)?;
+        self.downloads.get_mut(index)
+    }
 
-        std::fs::write(&config_path, content)?;
-        Ok(())
+    /// Rebuild the ID registry after downloads were added or removed
+    fn index_downloads(&mut self) {
+        self.download_ids = self.downloads.iter().enumerate().map(|(i, dl)| (dl.id, i)).collect();
     }
 
     /// Reinitialize RD client with current token
@@ -276,62 +1389,306 @@ impl App {
             self.rd_client = RealDebridClient::new().ok();
         }
     }
+
+    /// Apply the saved vim mode and palette settings
+    pub fn apply_ui_settings(&mut self) {
+        std::env::set_var("PALETTE", self.settings_palette.name);
+        self.palette = self.settings_palette;
+        std::env::set_var("VIM_MODE", if self.settings_vim_mode { "true" } else { "false" });
+        self.vim_mode = self.settings_vim_mode;
+        self.vim_insert = true;
+        self.vim_count = 0;
+        self.vim_pending_g = false;
+    }
 }
 
 /// Messages for async operations
 #[derive(Debug)]
 pub enum AppMessage {
-    SearchResults(Vec<TorrentResult>),
+    SearchResults(Vec<TorrentResult>, SourceStatuses),
+    /// Last page a (deep) search read, so `n` continues after it
+    SearchPage(u32),
+    TmdbTitles(u64, String, Vec<String>), // (lookup seq, query, titles)
+    Cover(String, Option<std::sync::Arc<image::DynamicImage>>), // (result dedup key, image)
+    RetryResults(Vec<TorrentResult>, SourceStatuses),
     SearchError(String),
     TorrentFiles(String, Vec<TorrentFile>),
+    DuplicateTorrent(String, ExistingTorrent), // (magnet, torrent on the account)
+    WaitTimeout(RdWait, WaitTimeout),
+    // Background RD waits, keyed by torrent ID
+    BackgroundStatus(String, String),
+    BackgroundProgress(String, RdProgress),
+    BackgroundLinks(String, Vec<(String, String)>),
+    BackgroundFailed(String, String),
     TorrentError(String),
     DownloadLinks(Vec<(String, String)>), // (filename, url)
     DownloadError(String),
+    AuthExpired(String),
     StatusUpdate(String),
+    RdProgress(RdProgress),
     // Download manager messages
     DownloadProgress {
-        index: usize,
+        id: DownloadId,
         downloaded: u64,
         total: u64,
         speed: f64,
     },
-    DownloadComplete(usize),
-    DownloadFailed(usize, String),
+    DownloadComplete(DownloadId),
+    DownloadFailed(DownloadId, String),
+    DownloadSkipped(DownloadId),
+    /// Held by the low-disk guard
+    DownloadPaused(DownloadId),
+    /// Low-disk guard state changed: free bytes while paused, None once resumed
+    DiskSpace(Option<u64>),
+    /// Links grabbed through the Telegram bot, to queue and start right away
+    RemoteGrab(String, Vec<(String, String)>),
+    /// The Telegram bot asked for the download status
+    TelegramStatus,
+    /// Saved under a new name because the destination existed
+    DownloadRenamed(DownloadId, PathBuf),
+    /// Message for the status bar from a background task
+    Notice(String),
+    /// A failed download's link was unrestricted again; it's restarting with this URL
+    LinkRenewed(DownloadId, String),
+    /// A completed download was added to the history
+    HistoryRecorded,
+    /// Trakt "Up next" entries, or why they couldn't be loaded
+    UpNext(Result<Vec<trakt::Item>, String>),
+    /// Links of the best result for a Trakt entry, to queue and start right away
+    TraktGrab(trakt::Item, String, Vec<(String, String)>),
+    /// SHA-1 of a completed download was recorded
+    ChecksumRecorded(DownloadId, String),
+    VerifyResult(DownloadId, Result<history::Verification, String>),
+    FirecrawlCredits(Result<i64, String>),
+    /// A newer release is out
+    UpdateAvailable(update::Release),
+    /// Stop the app like a shell job (SIGTSTP from outside)
+    Suspend,
+    /// The process was continued after a stop; the screen needs a full redraw
+    Resumed,
+    /// A setup self-test check finished
+    SelfTest(selftest::Check, Result<String, String>),
+    /// Fast-search result (by dedup key) with what its detail page gave: itself with a
+    /// magnet, or YTS qualities; None if the page couldn't be read
+    Resolved(String, Option<Vec<TorrentResult>>),
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Load .env file - check current directory first, then config directory
-    if dotenvy::dotenv().is_err() {
-        if let Some(config_dir) = dirs::config_dir() {
-            let config_env = config_dir.join("littlejohn").join(".env");
-            dotenvy::from_path(&config_env).ok();
+    // Moving settings between machines works on the files alone, so a broken config
+    // can still be replaced by an import
+    let args: Vec<String> = std::env::args().collect();
+    if let (Some("config"), Some(action @ ("export" | "import")), Some(file)) =
+        (args.get(1).map(String::as_str), args.get(2).map(String::as_str), args.get(3))
+    {
+        let file = std::path::Path::new(file);
+        let output = if action == "export" { backup::export(file)? } else { backup::import(file)? };
+        print!("{}", output);
+        return Ok(());
+    }
+
+    // Load settings - .env in the current directory first, then the config file
+    let config_notices = match config::load() {
+        Ok(notices) => notices,
+        // Scripts piping into batch mode tell a configuration error by its exit code
+        Err(e) if !std::io::stdin().is_terminal() => {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(batch::Outcome::ConfigError.code());
+        }
+        Err(e) => return Err(e),
+    };
+    for notice in &config_notices {
+        eprintln!("{}", notice);
+    }
+
+    // Background worker for downloads handed off on quit
+    if let [_, cmd, jobs_path] = args.as_slice() {
+        if cmd == detached::WORKER_ARG {
+            return detached::run(std::path::Path::new(jobs_path)).await;
+        }
+    }
+
+    if args.get(1).map(String::as_str) == Some("register-handler") {
+        let summary = handler::register()?;
+        println!("{}", summary);
+        return Ok(());
+    }
+
+    // Sort episodes already in the TV directory into show and season folders
+    if args.get(1).map(String::as_str) == Some("organize") {
+        let dry_run = args.iter().any(|a| a == "--dry-run");
+        return organize::run(&category_dir(Category::Tv), dry_run);
+    }
+
+    // Read and change settings from scripts, without the TUI
+    if args.get(1).map(String::as_str) == Some("config") {
+        let output = config::command(&args[2..])?;
+        print!("{}", output);
+        return Ok(());
+    }
+
+    // Long-running download service and its control client
+    if let Some(cmd @ ("daemon" | "ctl")) = args.get(1).map(String::as_str) {
+        #[cfg(unix)]
+        {
+            if cmd == "ctl" {
+                return daemon::ctl(&args[2..]).await;
+            }
+            if args.get(2).map(String::as_str) == Some("install") {
+                println!("{}", daemon::install_service()?);
+                return Ok(());
+            }
+            scrapers::init_log();
+            return daemon::run().await;
         }
+        #[cfg(not(unix))]
+        anyhow::bail!("`littlejohn {}` is only available on Unix", cmd);
+    }
+
+    // A magnet link passed on the command line (e.g. from a browser click)
+    let initial_magnet = args.iter().skip(1).find(|a| a.starts_with("magnet:")).cloned();
+
+    // A magnet list to import (--import FILE)
+    let import_file = args
+        .iter()
+        .position(|a| a == "--import")
+        .and_then(|i| args.get(i + 1))
+        .map(PathBuf::from);
+
+    // Piped input: run queries/magnets from stdin without the TUI
+    if initial_magnet.is_none() && import_file.is_none() && !std::io::stdin().is_terminal() {
+        let options = batch::Options {
+            quiet: args.iter().any(|a| a == "--quiet" || a == "-q"),
+            json: args.iter().any(|a| a == "--json"),
+            download: args.iter().any(|a| a == "--download"),
+        };
+        scrapers::init_log();
+        scrapers::selectors::load_pack().await;
+        let outcome = match batch::run(std::io::stdin().lock(), options).await {
+            Ok(outcome) => outcome,
+            // Only reading the input fails outright; 1 would read as "no results"
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                batch::Outcome::NetworkError
+            }
+        };
+        std::process::exit(outcome.code());
     }
 
     // Initialize scraper logging
     scrapers::init_log();
 
-    // Initialize terminal
+    // Initialize terminal, restoring it and writing a crash report on any panic
     let mut terminal = ratatui::init();
+    crash::install();
 
     // Create app
     let mut app = App::new();
+    if !config_notices.is_empty() {
+        app.status = config_notices.join(" | ");
+    }
 
-    // Show setup wizard if RD token is not set
-    if app.rd_client.is_none() && app.settings_rd_token.is_empty() {
+    // Show setup wizard if RD token is not set (not needed when magnets go to a blackhole)
+    if app.rd_client.is_none() && app.settings_rd_token.is_empty() && !app.blackhole_mode {
         app.mode = AppMode::Setup;
         app.settings_cursor = 0;
     }
 
     // Create channel for async messages
-    let (tx, mut rx) = mpsc::unbounded_channel::<AppMessage>();
+    let (tx, rx) = mpsc::unbounded_channel::<AppMessage>();
 
-    // Run app
-    let result = run_app(&mut terminal, &mut app, tx, &mut rx).await;
+    // Pick up selector fixes published since this release
+    tokio::spawn(scrapers::selectors::load_pack());
 
-    // Restore terminal
-    ratatui::restore();
+    // Keep mirror health fresh so searches start with a working domain
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(scrapers::mirrors::PROBE_INTERVAL);
+        loop {
+            interval.tick().await;
+            scrapers::probe_mirrors().await;
+        }
+    });
+
+    // Pause downloads before the destination volume fills up
+    tokio::spawn(watch_disk_space(tx.clone()));
+
+    // Suspend cleanly on SIGTSTP and redraw after SIGCONT
+    #[cfg(unix)]
+    tokio::spawn(suspend::watch(tx.clone()));
+
+    // Take commands from the Telegram bot
+    if let Some(bot) = app.telegram.clone() {
+        tokio::spawn(run_telegram(bot, tx.clone()));
+    }
+
+    // Trakt "Up next", offered on the search screen
+    if trakt::enabled() {
+        load_up_next(tx.clone());
+    }
+
+    // Announce a newer release (opt-in)
+    if update::enabled() {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            match update::check().await {
+                Ok(Some(release)) => {
+                    let _ = tx.send(AppMessage::UpdateAvailable(release));
+                }
+                Ok(None) => {}
+                Err(e) => scrapers::log_error("update", &format!("Update check failed: {:#}", e)),
+            }
+        });
+    }
+
+    // Go straight to file selection for a magnet given on the command line
+    if let Some(magnet) = initial_magnet {
+        app.cursor_pos = magnet.len();
+        app.search_input = magnet.clone();
+        if app.mode == AppMode::Search {
+            start_add_magnet(&mut app, magnet, tx.clone());
+        }
+    }
+
+    // Queue every entry of an import file
+    if let Some(path) = import_file {
+        match import::read(&path) {
+            Ok(entries) => start_import(&mut app, entries, tx.clone()),
+            Err(e) => app.status = e.to_string(),
+        }
+    }
+
+    // Run app
+    let result = run_app(&mut terminal, &mut app, tx, rx).await;
+
+    // Stop background work (downloads being detached are picked up by the worker)
+    app.tasks.shutdown(TASK_SHUTDOWN_GRACE).await;
+
+    // Restore terminal
+    ratatui::restore();
+
+    // Hand off active downloads to a detached worker
+    if app.detach_on_quit {
+        let jobs: Vec<detached::Job> = app
+            .active_downloads()
+            .into_iter()
+            .map(|d| detached::Job {
+                url: d.url.clone(),
+                dest_path: d.dest_path.clone(),
+                // Started ones own their file (ON_FILE_EXISTS already ran); the rest haven't
+                written: matches!(d.status, DownloadStatus::Downloading | DownloadStatus::Paused)
+                    .then_some(d.downloaded_bytes),
+                scheduled: d.status == DownloadStatus::Scheduled,
+            })
+            .collect();
+        match detached::spawn(&jobs) {
+            Ok(log_path) => println!(
+                "{} download(s) continue in the background. Log: {}",
+                jobs.len(),
+                log_path.display()
+            ),
+            Err(e) => eprintln!("Failed to detach downloads: {}", e),
+        }
+    }
 
     result
 }
@@ -340,27 +1697,92 @@ async fn run_app(
     terminal: &mut Tui,
     app: &mut App,
     tx: mpsc::UnboundedSender<AppMessage>,
-    rx: &mut mpsc::UnboundedReceiver<AppMessage>,
+    rx: mpsc::UnboundedReceiver<AppMessage>,
 ) -> Result<()> {
+    let mut events = event::Events::new(rx, std::time::Duration::from_millis(TICK_RATE_MS));
+    // Redraw only when something changed; idle ticks just check the clock
+    let mut dirty = true;
+    let mut drawn_tasks = None;
+
     loop {
+        // Images drawn with a graphics protocol aren't part of the buffer; wipe them
+        // once the details popup is gone
+        if app.mode != AppMode::Details && app.shown_cover.take().is_some() {
+            use std::io::Write;
+            terminal.backend_mut().write_all(graphics::clear_sequence(app.graphics).as_bytes())?;
+            terminal.clear()?;
+            dirty = true;
+        }
+
         // Draw UI
-        terminal.draw(|frame| ui::draw(frame, app))?;
+        dirty |= app.track_status();
+        if std::mem::take(&mut dirty) {
+            terminal.draw(|frame| ui::draw(frame, app))?;
+            if app.mode == AppMode::Details {
+                show_cover(terminal, app)?;
+            }
+            drawn_tasks = app.task_summary();
+        }
+
+        // Wait for a key event, async messages or the next tick
+        let event = events.next().await?;
+        dirty = !matches!(event, event::AppEvent::Tick);
+        match event {
+            event::AppEvent::Tick => {
+                app.tick = app.tick.wrapping_add(1);
+                // Tasks that end without a message still change the header
+                dirty = app.animating() || app.task_summary() != drawn_tasks;
+            }
+            event::AppEvent::Input(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                handle_key_event(app, key.code, key.modifiers, tx.clone()).await;
+            }
+            // The resize redraw wipes inline images
+            event::AppEvent::Input(Event::Resize(..)) => app.shown_cover = None,
+            event::AppEvent::Input(_) => {}
+            event::AppEvent::Messages(messages) => {
+                for msg in messages {
+                    handle_message(app, msg);
+                }
+            }
+        }
+
+        if let Some(magnet) = app.pending_magnet.take() {
+            start_add_magnet(app, magnet, tx.clone());
+        }
+
+        #[cfg(unix)]
+        if std::mem::take(&mut app.suspend_requested) {
+            suspend::suspend(terminal)?;
+            dirty = true;
+        }
+        #[cfg(unix)]
+        if std::mem::take(&mut app.resumed) {
+            suspend::resume(terminal)?;
+            dirty = true;
+        }
 
-        // Handle events with timeout to allow processing async messages
-        if crossterm::event::poll(std::time::Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    handle_key_event(app, key.code, key.modifiers, tx.clone()).await;
+        // Start downloads held for the off-peak window once it opens
+        if app.downloads.iter().any(|d| d.status == DownloadStatus::Scheduled) && schedule::is_open() {
+            for dl in &mut app.downloads {
+                if dl.status == DownloadStatus::Scheduled {
+                    dl.status = DownloadStatus::Pending;
                 }
             }
+            app.status = "Download window open, starting scheduled downloads".to_string();
+            app.autostart_pending = true;
         }
 
-        // Process any pending async messages
-        while let Ok(msg) = rx.try_recv() {
-            handle_message(app, msg);
+        if std::mem::take(&mut app.autostart_pending) {
+            start_pending_downloads(app, &tx);
         }
 
         if app.should_quit {
+            // Ask what to do with running downloads (a second quit request exits)
+            if app.mode != AppMode::ConfirmQuit && !app.active_downloads().is_empty() {
+                app.should_quit = false;
+                app.open(AppMode::ConfirmQuit);
+                continue;
+            }
             break;
         }
     }
@@ -380,70 +1802,252 @@ async fn handle_key_event(
         return;
     }
 
+    // Suspend to the shell; raw mode delivers Ctrl+Z as a key rather than a signal
+    if cfg!(unix) && code == KeyCode::Char('z') && modifiers.contains(KeyModifiers::CONTROL) {
+        app.suspend_requested = true;
+        return;
+    }
+
+    // Settings, downloads and messages from any browsing screen, returning there when closed
+    let global_shortcuts = matches!(
+        app.mode,
+        AppMode::Search | AppMode::Results | AppMode::FileSelect | AppMode::Downloads
+    );
+    if global_shortcuts && modifiers.contains(KeyModifiers::CONTROL) {
+        match code {
+            KeyCode::Char('s') => {
+                open_settings(app, &tx);
+                return;
+            }
+            KeyCode::Char('d') if app.mode != AppMode::Downloads => {
+                open_downloads(app);
+                return;
+            }
+            KeyCode::Char('n') => {
+                app.messages_scroll = 0;
+                app.open(AppMode::Messages);
+                return;
+            }
+            KeyCode::Char('r') if app.update.is_some() => {
+                app.changelog_scroll = 0;
+                app.open(AppMode::Changelog);
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    // Jump to a result number: `42` + Enter, `:42` + Enter, or `42G` in vim mode
+    if app.mode == AppMode::Results && modifiers.difference(KeyModifiers::SHIFT).is_empty() && handle_jump_keys(app, code) {
+        return;
+    }
+
+    // Vim counts and gg/G on list screens
+    let list_mode = matches!(
+        app.mode,
+        AppMode::Results | AppMode::FileSelect | AppMode::SourceSelect | AppMode::Downloads
+    );
+    let (code, count) = if app.vim_mode && list_mode && modifiers.is_empty() {
+        match vim_list_key(app, code) {
+            Some(key) => key,
+            None => return,
+        }
+    } else {
+        (code, 1)
+    };
+
     match &app.mode {
-        AppMode::Setup => handle_setup_keys(app, code),
-        AppMode::Settings => handle_settings_keys(app, code),
-        AppMode::Search => handle_search_keys(app, code, tx).await,
-        AppMode::Results => handle_results_keys(app, code, tx).await,
-        AppMode::FileSelect => handle_file_select_keys(app, code, tx).await,
-        AppMode::SourceSelect => handle_source_select_keys(app, code),
-        AppMode::Downloads => handle_downloads_keys(app, code, tx).await,
+        AppMode::Setup => handle_setup_keys(app, code, modifiers, &tx),
+        AppMode::SelfTest => handle_self_test_keys(app, code, &tx),
+        AppMode::Settings => handle_settings_keys(app, code, modifiers),
+        AppMode::Search => {
+            let before = app.search_input.clone();
+            handle_search_keys(app, code, modifiers, tx.clone()).await;
+            if app.search_input != before {
+                update_suggestions(app, &tx);
+            }
+        }
+        AppMode::Results => {
+            for _ in 0..count {
+                handle_results_keys(app, code, tx.clone()).await;
+            }
+        }
+        AppMode::FileSelect => {
+            for _ in 0..count {
+                handle_file_select_keys(app, code, tx.clone()).await;
+            }
+        }
+        AppMode::SourceSelect => {
+            for _ in 0..count {
+                handle_source_select_keys(app, code);
+            }
+        }
+        AppMode::Downloads => {
+            for _ in 0..count {
+                handle_downloads_keys(app, code, tx.clone()).await;
+            }
+        }
+        AppMode::Stats => handle_stats_keys(app, code),
+        AppMode::Log => handle_log_keys(app, code, modifiers),
         AppMode::Processing => {
-            // Only allow quit during processing
+            // Esc aborts the in-flight operation
             if code == KeyCode::Esc {
-                app.mode = AppMode::Results;
+                app.cancel_task();
+                // Magnet tasks delete the torrent they added; a links wait is cleaned up here
+                if let (Some((torrent_id, _)), Some(rd_client), false) =
+                    (app.links_wait.take(), &app.rd_client, app.torrent_reused)
+                {
+                    let rd_client = rd_client.clone();
+                    tokio::spawn(async move {
+                        let _ = rd_client.delete_torrent(&torrent_id).await;
+                    });
+                }
+                app.torrent_id = None;
+                app.files.clear();
+                app.selected_files.clear();
+                app.status = "Cancelled".to_string();
+                app.back_to_results();
+            } else if code == KeyCode::Char('b') {
+                // Keep waiting for RD in the background
+                if let Some((torrent_id, file_ids)) = app.links_wait.take() {
+                    app.cancel_task();
+                    start_background_wait(app, torrent_id, file_ids, tx);
+                }
             }
         }
+        AppMode::ConfirmDuplicate => handle_confirm_duplicate_keys(app, code, tx),
+        AppMode::QualitySelect => handle_quality_select_keys(app, code, tx),
+        AppMode::ConfirmKeepWaiting => handle_keep_waiting_keys(app, code, tx),
+        AppMode::ImportPrompt => handle_import_keys(app, code, modifiers, tx),
+        AppMode::Details => match code {
+            KeyCode::Esc | KeyCode::Char('i') | KeyCode::Char('q') | KeyCode::Enter => {
+                app.back();
+            }
+            _ => {}
+        },
+        AppMode::UpNext => handle_up_next_keys(app, code, tx).await,
+        AppMode::Messages => match code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                app.messages_scroll = (app.messages_scroll + 1).min(app.status_history.len().saturating_sub(1));
+            }
+            KeyCode::Up | KeyCode::Char('k') => app.messages_scroll = app.messages_scroll.saturating_sub(1),
+            KeyCode::Home | KeyCode::Char('g') => app.messages_scroll = 0,
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => app.back(),
+            _ => {}
+        },
+        AppMode::Changelog => match code {
+            KeyCode::Down | KeyCode::Char('j') => app.changelog_scroll = app.changelog_scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => app.changelog_scroll = app.changelog_scroll.saturating_sub(1),
+            KeyCode::Char('o') => {
+                if let Some(release) = &app.update {
+                    app.status = match open_in_browser(&release.url) {
+                        Ok(()) => "Opened the release page".to_string(),
+                        Err(e) => format!("Could not open the browser: {}", e),
+                    };
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => app.back(),
+            _ => {}
+        },
+        AppMode::ConfirmQuit => match code {
+            KeyCode::Char('d') | KeyCode::Char('b') => {
+                app.detach_on_quit = true;
+                app.should_quit = true;
+            }
+            KeyCode::Char('q') | KeyCode::Char('y') => {
+                app.should_quit = true;
+            }
+            KeyCode::Esc | KeyCode::Char('n') => {
+                app.back();
+            }
+            _ => {}
+        },
         AppMode::Error(_) => {
-            // Any key returns to previous mode
-            app.mode = AppMode::Search;
+            // Any key returns to the screen the error came up on
+            app.back();
             app.status.clear();
         }
     }
 }
 
-/// Handle setup wizard keys
-fn handle_setup_keys(app: &mut App, code: KeyCode) {
-    match code {
-        KeyCode::Tab | KeyCode::Down => {
-            app.next_settings_field();
+/// Handle result-number input on the results screen. Returns true if the key was consumed.
+fn handle_jump_keys(app: &mut App, code: KeyCode) -> bool {
+    if let Some(input) = app.jump_input.as_mut() {
+        match code {
+            KeyCode::Char(c) if c.is_ascii_digit() && input.len() < 6 => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => {
+                let number = input.parse().unwrap_or(0);
+                app.jump_input = None;
+                app.jump_to_result(number);
+            }
+            KeyCode::Esc => app.jump_input = None,
+            _ => {
+                // Any other key abandons the jump and is handled normally
+                app.jump_input = None;
+                return false;
+            }
         }
-        KeyCode::BackTab | KeyCode::Up => {
-            app.prev_settings_field();
+        return true;
+    }
+
+    match code {
+        KeyCode::Char(':') => {
+            app.jump_input = Some(String::new());
+            true
         }
-        KeyCode::Char(c) => {
-            let cursor = app.settings_cursor;
-            app.current_settings_input_mut().insert(cursor, c);
-            app.settings_cursor += 1;
+        // Vim mode: digits are a count, so the count + Enter/G jumps instead
+        KeyCode::Enter | KeyCode::Char('G') if app.vim_mode && app.vim_count > 0 => {
+            let number = std::mem::take(&mut app.vim_count);
+            app.vim_pending_g = false;
+            app.jump_to_result(number);
+            true
         }
-        KeyCode::Backspace => {
-            if app.settings_cursor > 0 {
-                app.settings_cursor -= 1;
-                let cursor = app.settings_cursor;
-                app.current_settings_input_mut().remove(cursor);
-            }
+        KeyCode::Char(c @ '1'..='9') if !app.vim_mode => {
+            app.jump_input = Some(c.to_string());
+            true
         }
-        KeyCode::Delete => {
-            let len = app.current_settings_input().len();
-            let cursor = app.settings_cursor;
-            if cursor < len {
-                app.current_settings_input_mut().remove(cursor);
-            }
+        _ => false,
+    }
+}
+
+/// Translate vim list keys: digits build a count, `gg`/`G` jump to the first/last item.
+/// Returns the key to dispatch and how many times, or None if the key was consumed.
+fn vim_list_key(app: &mut App, code: KeyCode) -> Option<(KeyCode, usize)> {
+    match code {
+        KeyCode::Char(c @ '0'..='9') if c != '0' || app.vim_count > 0 => {
+            let digit = c.to_digit(10).unwrap_or(0) as usize;
+            app.vim_count = (app.vim_count * 10 + digit).min(9999);
+            app.vim_pending_g = false;
+            None
         }
-        KeyCode::Left => {
-            app.settings_cursor = app.settings_cursor.saturating_sub(1);
+        KeyCode::Char('g') if !app.vim_pending_g => {
+            app.vim_pending_g = true;
+            None
         }
-        KeyCode::Right => {
-            let len = app.current_settings_input().len();
-            if app.settings_cursor < len {
-                app.settings_cursor += 1;
+        _ => {
+            let count = std::mem::take(&mut app.vim_count).max(1);
+            let pending_g = std::mem::take(&mut app.vim_pending_g);
+            match code {
+                KeyCode::Char('g') if pending_g => Some((KeyCode::Home, 1)),
+                KeyCode::Char('G') => Some((KeyCode::End, 1)),
+                KeyCode::Char('j') | KeyCode::Char('k') | KeyCode::Up | KeyCode::Down => Some((code, count)),
+                _ => Some((code, 1)),
             }
         }
-        KeyCode::Home => {
-            app.settings_cursor = 0;
+    }
+}
+
+/// Handle setup wizard keys
+fn handle_setup_keys(app: &mut App, code: KeyCode, modifiers: KeyModifiers, tx: &mpsc::UnboundedSender<AppMessage>) {
+    match code {
+        KeyCode::Tab | KeyCode::Down => {
+            app.next_settings_field();
         }
-        KeyCode::End => {
-            app.settings_cursor = app.current_settings_input().len();
+        KeyCode::BackTab | KeyCode::Up => {
+            app.prev_settings_field();
         }
         KeyCode::Enter => {
             // Save settings and continue
@@ -453,8 +2057,10 @@ fn handle_setup_keys(app: &mut App, code: KeyCode) {
                 match app.save_settings() {
                     Ok(_) => {
                         app.reinit_rd_client();
+                        app.apply_ui_settings();
                         app.status = "Settings saved!".to_string();
-                        app.mode = AppMode::Search;
+                        app.go_home();
+                        start_self_test(app, tx);
                     }
                     Err(e) => {
                         app.status = format!("Failed to save: {}", e);
@@ -464,15 +2070,58 @@ fn handle_setup_keys(app: &mut App, code: KeyCode) {
         }
         KeyCode::Esc => {
             // Skip setup (user can configure later)
-            app.mode = AppMode::Search;
+            app.go_home();
             app.status = "Setup skipped. Press Shift+S to configure settings.".to_string();
         }
+        _ => {
+            app.edit_settings_input(code, modifiers);
+        }
+    }
+}
+
+/// Handle self-test keys: run it again, or carry on to the app
+fn handle_self_test_keys(app: &mut App, code: KeyCode, tx: &mpsc::UnboundedSender<AppMessage>) {
+    match code {
+        KeyCode::Char('r') => start_self_test(app, tx),
+        KeyCode::Enter | KeyCode::Esc => {
+            let failed = app.self_test.iter().filter(|item| matches!(item.outcome, Some(Err(_)))).count();
+            app.status = if failed > 0 {
+                format!("{} self-test check(s) failed. Press Shift+S to change settings.", failed)
+            } else {
+                "Setup complete".to_string()
+            };
+            app.go_home();
+        }
         _ => {}
     }
 }
 
+/// Run the setup self-test, each check reporting back as it finishes
+fn start_self_test(app: &mut App, tx: &mpsc::UnboundedSender<AppMessage>) {
+    app.self_test = selftest::pending();
+    app.open(AppMode::SelfTest);
+    for check in app.self_test.iter().map(|item| item.check) {
+        let tx = tx.clone();
+        let rd = app.rd_client.clone();
+        let source = scrapers::all_sources().into_iter().find(|s| app.enabled_sources.contains(*s));
+        let dir = app.download_dir();
+        tokio::spawn(async move {
+            let outcome = match check {
+                selftest::Check::RdToken => selftest::rd_token(rd).await,
+                selftest::Check::Scraper => match source {
+                    Some(source) => selftest::scraper(source).await,
+                    None => Err(anyhow::anyhow!("No sources enabled")),
+                },
+                selftest::Check::Firecrawl => selftest::firecrawl().await,
+                selftest::Check::DownloadDir => selftest::download_dir(&dir),
+            };
+            let _ = tx.send(AppMessage::SelfTest(check, outcome.map_err(|e| format!("{:#}", e))));
+        });
+    }
+}
+
 /// Handle settings screen keys
-fn handle_settings_keys(app: &mut App, code: KeyCode) {
+fn handle_settings_keys(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
     match code {
         KeyCode::Tab | KeyCode::Down => {
             app.next_settings_field();
@@ -480,47 +2129,15 @@ fn handle_settings_keys(app: &mut App, code: KeyCode) {
         KeyCode::BackTab | KeyCode::Up => {
             app.prev_settings_field();
         }
-        KeyCode::Char(c) => {
-            let cursor = app.settings_cursor;
-            app.current_settings_input_mut().insert(cursor, c);
-            app.settings_cursor += 1;
-        }
-        KeyCode::Backspace => {
-            if app.settings_cursor > 0 {
-                app.settings_cursor -= 1;
-                let cursor = app.settings_cursor;
-                app.current_settings_input_mut().remove(cursor);
-            }
-        }
-        KeyCode::Delete => {
-            let len = app.current_settings_input().len();
-            let cursor = app.settings_cursor;
-            if cursor < len {
-                app.current_settings_input_mut().remove(cursor);
-            }
-        }
-        KeyCode::Left => {
-            app.settings_cursor = app.settings_cursor.saturating_sub(1);
-        }
-        KeyCode::Right => {
-            let len = app.current_settings_input().len();
-            if app.settings_cursor < len {
-                app.settings_cursor += 1;
-            }
-        }
-        KeyCode::Home => {
-            app.settings_cursor = 0;
-        }
-        KeyCode::End => {
-            app.settings_cursor = app.current_settings_input().len();
-        }
         KeyCode::Enter => {
             // Save settings
             match app.save_settings() {
                 Ok(_) => {
                     app.reinit_rd_client();
+                    app.apply_ui_settings();
+                    app.settings_banner = None;
                     app.status = "Settings saved!".to_string();
-                    app.mode = AppMode::Search;
+                    app.back();
                 }
                 Err(e) => {
                     app.status = format!("Failed to save: {}", e);
@@ -533,134 +2150,382 @@ fn handle_settings_keys(app: &mut App, code: KeyCode) {
             app.settings_rd_token = std::env::var("RD_API_TOKEN").unwrap_or_default();
             app.settings_firecrawl_key = std::env::var("FIRECRAWL_API_KEY").unwrap_or_default();
             app.settings_download_dir = std::env::var("DOWNLOAD_DIR").unwrap_or_default();
-            app.mode = AppMode::Search;
+            app.settings_vim_mode = app.vim_mode;
+            app.settings_palette = app.palette;
+            app.settings_banner = None;
+            app.back();
+        }
+        _ => {
+            app.edit_settings_input(code, modifiers);
         }
-        _ => {}
     }
 }
 
 async fn handle_search_keys(
     app: &mut App,
     code: KeyCode,
+    modifiers: KeyModifiers,
     tx: mpsc::UnboundedSender<AppMessage>,
 ) {
+    // Ctrl/Alt combos are readline-style editing, never shortcuts
+    if modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+        input::edit(&mut app.search_input, &mut app.cursor_pos, code, modifiers);
+        return;
+    }
+
+    // Shortcuts work when the input is empty, or always in vim normal mode
+    let vim_normal = app.vim_mode && !app.vim_insert;
+    let shortcuts = app.search_input.is_empty() || vim_normal;
+
+    // Search for the next episode of the show downloaded last
+    let code = match code {
+        KeyCode::Char('N') if shortcuts => match app.next_episode.clone() {
+            Some(query) => {
+                app.search_input = query;
+                app.cursor_pos = app.search_input.len();
+                KeyCode::Enter
+            }
+            None => {
+                app.status = "No episodes in the download history".to_string();
+                return;
+            }
+        },
+        // Search the highlighted recent query again, or open the recent download
+        KeyCode::Enter if app.suggestion_index.is_none() => {
+            match app.recent_index.and_then(|i| app.recent_items().into_iter().nth(i)) {
+                Some(RecentItem::Query(query)) => {
+                    app.recent_index = None;
+                    app.search_input = query;
+                    app.cursor_pos = app.search_input.len();
+                }
+                Some(RecentItem::Download(entry)) => {
+                    app.status = match open_in_browser(&entry.path.to_string_lossy()) {
+                        Ok(()) => format!("Opening {}", entry.filename),
+                        Err(e) => format!("Could not open {}: {}", entry.filename, e),
+                    };
+                    return;
+                }
+                None => {}
+            }
+            KeyCode::Enter
+        }
+        code => code,
+    };
+
     match code {
-        // Special shortcuts when input is empty
-        KeyCode::Char('s') if app.search_input.is_empty() => {
+        KeyCode::Char('s') if shortcuts => {
             app.source_cursor = 0;
-            app.mode = AppMode::SourceSelect;
-            return;
+            app.open(AppMode::SourceSelect);
         }
-        KeyCode::Char('S') if app.search_input.is_empty() => {
-            // Open settings (Shift+S)
-            app.settings_field = SettingsField::RdApiToken;
-            app.settings_cursor = app.settings_rd_token.len();
-            app.mode = AppMode::Settings;
-            return;
+        KeyCode::Char('S') if shortcuts => open_settings(app, &tx),
+        KeyCode::Char('d') if shortcuts => open_downloads(app),
+        KeyCode::Char('T') if shortcuts => {
+            app.open(AppMode::Stats);
         }
-        KeyCode::Char('d') if app.search_input.is_empty() => {
-            app.download_cursor = 0;
-            app.mode = AppMode::Downloads;
-            return;
+        KeyCode::Char('U') if shortcuts => {
+            if trakt::enabled() {
+                app.open(AppMode::UpNext);
+            } else {
+                app.status = "Set TRAKT_CLIENT_ID and TRAKT_ACCESS_TOKEN to see what's up next on Trakt".to_string();
+            }
         }
-        KeyCode::Char(c) => {
-            app.search_input.insert(app.cursor_pos, c);
-            app.cursor_pos += 1;
+        KeyCode::Char('L') if shortcuts => {
+            app.open_log();
         }
-        KeyCode::Backspace => {
-            if app.cursor_pos > 0 {
-                app.cursor_pos -= 1;
-                app.search_input.remove(app.cursor_pos);
-            }
+        KeyCode::Char('A') if shortcuts => {
+            // Toggle anime mode
+            app.anime_mode = !app.anime_mode;
+            app.status = if app.anime_mode {
+                "Anime mode on".to_string()
+            } else {
+                "Anime mode off".to_string()
+            };
         }
-        KeyCode::Delete => {
-            if app.cursor_pos < app.search_input.len() {
-                app.search_input.remove(app.cursor_pos);
+        KeyCode::Char('H') if shortcuts => {
+            if blackhole::dir().is_none() {
+                app.status = "Set BLACKHOLE_DIR to send magnets to another client".to_string();
+                return;
             }
+            app.blackhole_mode = !app.blackhole_mode;
+            app.status = if app.blackhole_mode {
+                "Blackhole mode on: picked results are written to BLACKHOLE_DIR".to_string()
+            } else {
+                "Blackhole mode off: picked results go to Real-Debrid".to_string()
+            };
         }
-        KeyCode::Left => {
-            app.cursor_pos = app.cursor_pos.saturating_sub(1);
+        KeyCode::Char('F') if shortcuts => {
+            app.fast_search = !app.fast_search;
+            app.status = if app.fast_search {
+                "Fast search on: magnets are fetched when you pick a result".to_string()
+            } else {
+                "Fast search off".to_string()
+            };
         }
-        KeyCode::Right => {
-            if app.cursor_pos < app.search_input.len() {
-                app.cursor_pos += 1;
-            }
+        KeyCode::Char('P') if shortcuts => {
+            app.deep_search = !app.deep_search;
+            app.status = if app.deep_search {
+                format!(
+                    "Deep search on: reading up to {} pages for {}+ results",
+                    scrapers::deep_search_pages(),
+                    scrapers::deep_search_target()
+                )
+            } else {
+                "Deep search off".to_string()
+            };
         }
-        KeyCode::Home => {
-            app.cursor_pos = 0;
+        KeyCode::Tab => {
+            // Cycle query template
+            app.cycle_template();
+            app.status = match app.current_template() {
+                Some(t) => format!("Template: {}", t),
+                None => "Template: none".to_string(),
+            };
         }
-        KeyCode::End => {
-            app.cursor_pos = app.search_input.len();
+        KeyCode::Down if !app.suggestions.is_empty() => {
+            let last = app.suggestions.len() - 1;
+            app.suggestion_index = Some(app.suggestion_index.map_or(0, |i| (i + 1).min(last)));
+        }
+        KeyCode::Up if app.suggestion_index.is_some() => {
+            app.suggestion_index = app.suggestion_index.and_then(|i| i.checked_sub(1));
+        }
+        KeyCode::Down if app.search_input.is_empty() && !app.recent_items().is_empty() => {
+            let last = app.recent_items().len() - 1;
+            app.recent_index = Some(app.recent_index.map_or(0, |i| (i + 1).min(last)));
+        }
+        KeyCode::Up if app.recent_index.is_some() => {
+            app.recent_index = app.recent_index.and_then(|i| i.checked_sub(1));
         }
         KeyCode::Enter => {
+            // Search for the highlighted suggestion instead of the typed text
+            if let Some(suggestion) = app.suggestion_index.and_then(|i| app.suggestions.get(i)) {
+                app.search_input = suggestion.text.clone();
+                app.cursor_pos = app.search_input.len();
+            }
+            app.suggestions.clear();
+            app.suggestion_index = None;
+
             // Check if input is a magnet link
             if app.search_input.starts_with("magnet:") {
                 let magnet = app.search_input.clone();
-                if let Some(rd_client) = &app.rd_client {
-                    let rd_client = rd_client.clone();
-                    let tx = tx.clone();
-
-                    app.mode = AppMode::Processing;
-                    app.processing_status = "Adding magnet to Real-Debrid...".to_string();
+                start_add_magnet(app, magnet, tx);
+            } else if let Some(torrent_id) = realdebrid::torrent_id(&app.search_input) {
+                // A torrent already on the account: straight to its files
+                app.torrent_category = None;
+                start_reuse_torrent(app, torrent_id, tx);
+            } else if app.search_input.len() >= 2 {
+                let query = app.effective_query();
 
-                    tokio::spawn(async move {
-                        let _ = tx.send(AppMessage::StatusUpdate("Adding magnet...".to_string()));
-                        match rd_client.get_torrent_files(&magnet).await {
-                            Ok((torrent_id, files)) => {
-                                let _ = tx.send(AppMessage::TorrentFiles(torrent_id, files));
-                            }
-                            Err(e) => {
-                                let _ = tx.send(AppMessage::TorrentError(e.to_string()));
-                            }
-                        }
-                    });
-                } else {
-                    app.status = "Real-Debrid not configured".to_string();
+                // Ask before a search that may use many Firecrawl credits
+                let sources: Vec<&str> = scrapers::all_sources()
+                    .into_iter()
+                    .filter(|s| app.enabled_sources.contains(*s))
+                    .collect();
+                let mut cost = scrapers::firecrawl::search_cost(&query, &sources, app.fast_search);
+                if app.deep_search {
+                    cost *= scrapers::deep_search_pages() as u64;
                 }
-            } else if app.search_input.len() >= 2 {
+                if let Some(limit) = scrapers::firecrawl::warn_threshold().filter(|limit| cost > *limit) {
+                    if app.costly_search.as_deref() != Some(query.as_str()) {
+                        app.status = format!(
+                            "This search may use up to {} Firecrawl credits (warning above {}) - press Enter again to search",
+                            cost, limit
+                        );
+                        app.costly_search = Some(query);
+                        return;
+                    }
+                }
+                app.costly_search = None;
+
                 // Start search
-                let query = app.search_input.clone();
+                suggest::record(&mut app.search_history, &app.search_input);
+                app.trakt_search = None;
                 let tx = tx.clone();
                 let enabled_sources = app.enabled_sources.clone();
+                let anime_mode = app.anime_mode;
+                let fast = app.fast_search;
+                let deep = app.deep_search;
 
                 app.page = 1; // Reset page on new search
                 app.status = format!("Searching for '{}'...", query);
-                app.mode = AppMode::Processing;
-                app.processing_status = format!("Searching {} sites...", enabled_sources.len());
+                app.open(AppMode::Processing);
+                app.processing_status = if deep {
+                    format!("Searching {} sites for {}+ results...", enabled_sources.len(), scrapers::deep_search_target())
+                } else {
+                    format!("Searching {} sites...", enabled_sources.len())
+                };
+                let token = app.new_task_token();
 
-                tokio::spawn(async move {
-                    let mut results = scrapers::search_all(&query, 1).await;
-
-                    // Filter by enabled sources
-                    results.retain(|r| enabled_sources.contains(&r.source));
-
-                    // Sort by source priority, then by seeders
-                    results.sort_by(|a, b| {
-                        let a_priority = SOURCE_PRIORITY.iter().position(|&s| s == a.source).unwrap_or(999);
-                        let b_priority = SOURCE_PRIORITY.iter().position(|&s| s == b.source).unwrap_or(999);
-                        match a_priority.cmp(&b_priority) {
-                            std::cmp::Ordering::Equal => b.seeders.cmp(&a.seeders),
-                            other => other,
-                        }
-                    });
+                app.tasks.spawn_cancellable("search", token.clone(), async move {
+                    let (results, statuses, last_page) = tokio::select! {
+                        _ = token.cancelled() => return,
+                        r = run_deep_search(&query, 1, &enabled_sources, anime_mode, fast, deep, &token) => r,
+                    };
 
                     if results.is_empty() {
                         let _ = tx.send(AppMessage::SearchError("No results found".to_string()));
                     } else {
-                        let _ = tx.send(AppMessage::SearchResults(results));
+                        let _ = tx.send(AppMessage::SearchPage(last_page));
+                        let _ = tx.send(AppMessage::SearchResults(results, statuses));
                     }
                 });
             } else {
                 app.status = "Query must be at least 2 characters".to_string();
             }
         }
+        KeyCode::Esc if app.suggestion_index.is_some() => {
+            app.suggestion_index = None;
+        }
+        KeyCode::Esc if app.recent_index.is_some() => {
+            app.recent_index = None;
+        }
+        KeyCode::Esc if app.vim_mode && app.vim_insert => {
+            // Leave insert mode
+            app.vim_insert = false;
+        }
         KeyCode::Esc => {
             app.should_quit = true;
         }
-        _ => {}
+        _ if vim_normal => handle_search_normal_keys(app, code),
+        _ => {
+            input::edit(&mut app.search_input, &mut app.cursor_pos, code, modifiers);
+        }
+    }
+}
+
+/// Refresh suggestions after the search input changed, and look the input up on TMDB
+/// once typing pauses
+fn update_suggestions(app: &mut App, tx: &mpsc::UnboundedSender<AppMessage>) {
+    app.refresh_suggestions();
+
+    let query = app.search_input.trim().to_string();
+    let Some(key) = suggest::tmdb_key() else { return };
+    if query.chars().count() < 3 || query.starts_with("magnet:") || query == app.tmdb_query {
+        return;
+    }
+    app.tmdb_seq += 1;
+    let seq = app.tmdb_seq;
+    let tx = tx.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(suggest::TMDB_DELAY).await;
+        match suggest::tmdb_titles(&key, &query).await {
+            Ok(titles) => {
+                let _ = tx.send(AppMessage::TmdbTitles(seq, query, titles));
+            }
+            Err(e) => scrapers::log_error("tmdb", &e.to_string()),
+        }
+    });
+}
+
+/// Fetch cover art for the selected result: its own cover URL, else a TMDB poster
+/// when TMDB_API_KEY is set
+fn request_cover(app: &mut App, tx: &mpsc::UnboundedSender<AppMessage>) {
+    let Some(result) = app.selected_result_index().and_then(|i| app.results.get(i)) else { return };
+    let key = result.dedup_key();
+    if app.covers.contains_key(&key) {
+        return;
+    }
+    app.covers.insert(key.clone(), Cover::Loading);
+
+    let (name, cover_url) = (result.name.clone(), result.cover.clone());
+    let tx = tx.clone();
+    tokio::spawn(async move {
+        let url = match (cover_url, suggest::tmdb_key()) {
+            (Some(url), _) => Some(url),
+            (None, Some(key)) => {
+                let (title, year) = release::title_and_year(&name);
+                suggest::tmdb_poster(&key, &title, year.as_deref()).await.unwrap_or_else(|e| {
+                    scrapers::log_error("tmdb", &e.to_string());
+                    None
+                })
+            }
+            (None, None) => None,
+        };
+
+        let image = match url {
+            Some(url) => fetch_cover(&url).await,
+            None => None,
+        };
+        let _ = tx.send(AppMessage::Cover(key, image.map(std::sync::Arc::new)));
+    });
+}
+
+async fn fetch_cover(url: &str) -> Option<image::DynamicImage> {
+    let client = http::scraper_client();
+    let bytes = match client.get(url).send().await.and_then(|r| r.error_for_status()) {
+        Ok(response) => response.bytes().await.ok()?,
+        Err(e) => {
+            scrapers::log_error("cover", &format!("{}: {}", url, e));
+            return None;
+        }
+    };
+    tokio::task::spawn_blocking(move || image::load_from_memory(&bytes).ok()).await.ok()?
+}
+
+/// Paint the selected result's cover over the details popup with the graphics
+/// protocol. It stays on screen until cleared, so it's only sent when it changes.
+fn show_cover(terminal: &mut Tui, app: &mut App) -> Result<()> {
+    use std::io::Write;
+
+    let Some(result) = app.selected_result() else { return Ok(()) };
+    let key = result.dedup_key();
+    let Some(Cover::Image(image)) = app.covers.get(&key) else { return Ok(()) };
+    let size = terminal.size()?;
+    if size.width < ui::MIN_WIDTH || size.height < ui::MIN_HEIGHT {
+        return Ok(());
+    }
+    let area = ui::cover_area(ratatui::layout::Rect::new(0, 0, size.width, size.height));
+    if app.shown_cover.as_ref().is_some_and(|(shown_key, shown)| *shown_key == key && *shown == area) {
+        return Ok(());
+    }
+
+    if let Some(sequence) = graphics::encode(app.graphics, image, area.width, area.height) {
+        let backend = terminal.backend_mut();
+        crossterm::queue!(backend, crossterm::cursor::MoveTo(area.x, area.y))?;
+        backend.write_all(sequence.as_bytes())?;
+        backend.flush()?;
+        app.shown_cover = Some((key, area));
     }
+    Ok(())
+}
+
+/// Handle vim normal mode motions and edits in the search input
+fn handle_search_normal_keys(app: &mut App, code: KeyCode) {
+    let text = &mut app.search_input;
+    let cursor = &mut app.cursor_pos;
+    let (code, modifiers) = match code {
+        KeyCode::Char('i') => {
+            app.vim_insert = true;
+            return;
+        }
+        KeyCode::Char('a') => {
+            app.vim_insert = true;
+            (KeyCode::Right, KeyModifiers::NONE)
+        }
+        KeyCode::Char('I') => {
+            app.vim_insert = true;
+            (KeyCode::Home, KeyModifiers::NONE)
+        }
+        KeyCode::Char('h') => (KeyCode::Left, KeyModifiers::NONE),
+        KeyCode::Char('l') => (KeyCode::Right, KeyModifiers::NONE),
+        KeyCode::Char('0') | KeyCode::Char('^') => (KeyCode::Home, KeyModifiers::NONE),
+        KeyCode::Char('$') => (KeyCode::End, KeyModifiers::NONE),
+        KeyCode::Char('w') => (KeyCode::Char('f'), KeyModifiers::ALT),
+        KeyCode::Char('b') => (KeyCode::Char('b'), KeyModifiers::ALT),
+        KeyCode::Char('x') => (KeyCode::Delete, KeyModifiers::NONE),
+        KeyCode::Char('D') => (KeyCode::Char('k'), KeyModifiers::CONTROL),
+        KeyCode::Char('q') => {
+            app.should_quit = true;
+            return;
+        }
+        // Other printable keys do nothing in normal mode
+        KeyCode::Char(_) => return,
+        other => (other, KeyModifiers::NONE),
+    };
+    input::edit(text, cursor, code, modifiers);
 }
 
+// Bounds are checked inside the Up/Down arms so those keys never fall through to later ones
+#[allow(clippy::collapsible_match)]
 async fn handle_results_keys(
     app: &mut App,
     code: KeyCode,
@@ -678,7 +2543,7 @@ async fn handle_results_keys(
             }
         }
         KeyCode::Down | KeyCode::Char('j') => {
-            if app.selected_index < app.results.len().saturating_sub(1) {
+            if app.selected_index < app.result_rows.len().saturating_sub(1) {
                 app.selected_index += 1;
                 if app.selected_index >= app.scroll_offset + visible_height {
                     app.scroll_offset = app.selected_index - visible_height + 1;
@@ -690,7 +2555,7 @@ async fn handle_results_keys(
             app.scroll_offset = app.scroll_offset.saturating_sub(visible_height);
         }
         KeyCode::PageDown => {
-            app.selected_index = (app.selected_index + visible_height).min(app.results.len().saturating_sub(1));
+            app.selected_index = (app.selected_index + visible_height).min(app.result_rows.len().saturating_sub(1));
             if app.selected_index >= app.scroll_offset + visible_height {
                 app.scroll_offset = app.selected_index - visible_height + 1;
             }
@@ -700,37 +2565,86 @@ async fn handle_results_keys(
             app.scroll_offset = 0;
         }
         KeyCode::End => {
-            app.selected_index = app.results.len().saturating_sub(1);
+            app.selected_index = app.result_rows.len().saturating_sub(1);
             if app.selected_index >= visible_height {
                 app.scroll_offset = app.selected_index - visible_height + 1;
             }
         }
-        KeyCode::Enter => {
-            if let Some(result) = app.results.get(app.selected_index) {
-                let magnet = &result.magnet;
-                if !magnet.is_empty() {
-                    if let Some(rd_client) = &app.rd_client {
-                        let magnet = magnet.clone();
-                        let rd_client = rd_client.clone();
-                        let tx = tx.clone();
-
-                        app.mode = AppMode::Processing;
-                        app.processing_status = "Adding magnet to Real-Debrid...".to_string();
-
-                        tokio::spawn(async move {
-                            let _ = tx.send(AppMessage::StatusUpdate("Adding magnet...".to_string()));
-                            match rd_client.get_torrent_files(&magnet).await {
-                                Ok((torrent_id, files)) => {
-                                    let _ = tx.send(AppMessage::TorrentFiles(torrent_id, files));
-                                }
-                                Err(e) => {
-                                    let _ = tx.send(AppMessage::TorrentError(e.to_string()));
-                                }
-                            }
-                        });
-                    } else {
-                        app.status = "Real-Debrid not configured".to_string();
-                    }
+        KeyCode::Enter | KeyCode::Char(' ') if matches!(app.result_rows.get(app.selected_index), Some(ResultRow::Title(_))) => {
+            app.toggle_selected_title();
+        }
+        KeyCode::Char('*') => app.toggle_pin(),
+        KeyCode::Char('A') if !app.pinned.is_empty() => {
+            // Send every pinned result to Real-Debrid in the background
+            let (entries, unresolved): (Vec<_>, Vec<_>) = app.pinned.iter().partition(|p| !p.magnet.is_empty());
+            let entries: Vec<import::Entry> = entries.into_iter().map(|p| import::Entry::Magnet(p.magnet.clone())).collect();
+            let skipped = unresolved.len();
+            if entries.is_empty() {
+                app.status = "Pinned results have no magnet yet - press Enter on them first".to_string();
+            } else {
+                start_import(app, entries, tx);
+                app.pinned.retain(|p| p.magnet.is_empty());
+                app.refresh_rows();
+                if skipped > 0 {
+                    app.status.push_str(&format!(" ({} without a magnet left pinned)", skipped));
+                }
+            }
+        }
+        KeyCode::Char('<') | KeyCode::Char('>') | KeyCode::Char('R') => {
+            // Sort by the previous/next column, or reverse the sort
+            app.sort = match code {
+                KeyCode::Char('R') if app.sort.column != sort::Column::Relevance => {
+                    sort::Sort { descending: !app.sort.descending, ..app.sort }
+                }
+                KeyCode::Char('R') => app.sort,
+                _ => sort::Sort::by(app.sort.column.step(code == KeyCode::Char('<'))),
+            };
+            app.apply_sort();
+            std::env::set_var("RESULT_SORT", app.sort.setting());
+            app.status = match app.save_settings() {
+                Ok(_) if app.sort.column == sort::Column::Relevance => "Sorted by relevance".to_string(),
+                Ok(_) => format!("Sorted by {} {}", app.sort.column.key(), app.sort.arrow()),
+                Err(e) => format!("Sort not saved: {}", e),
+            };
+        }
+        KeyCode::Char('f') => {
+            // Filter by audio language
+            app.cycle_language_filter();
+            app.status = match &app.language_filter {
+                Some(code) => format!("Audio: {} ({} rows)", code, app.result_rows.len()),
+                None => "Audio: all languages".to_string(),
+            };
+        }
+        KeyCode::Char('v') => {
+            // Switch between grouped titles and the flat release list
+            app.group_titles = !app.group_titles;
+            let selected = app.selected_result_index();
+            app.refresh_rows();
+            app.selected_index = 0;
+            app.scroll_offset = 0;
+            if let Some(i) = selected {
+                app.jump_to_result(i + 1);
+            }
+            app.status = if app.group_titles { "Grouped by title" } else { "All releases" }.to_string();
+        }
+        KeyCode::Enter => {
+            if let Some(result) = app.selected_result() {
+                if !result.magnet.is_empty() {
+                    let magnet = result.magnet.clone();
+                    start_add_magnet(app, magnet, tx);
+                } else if result.needs_resolve() {
+                    // Fast search: fetch the magnet (or YTS qualities) now
+                    let result = result.clone();
+                    app.open(AppMode::Processing);
+                    app.processing_status = format!("Fetching {} details...", result.source);
+                    let token = app.new_task_token();
+                    app.tasks.spawn_cancellable("resolve", token.clone(), async move {
+                        let resolved = tokio::select! {
+                            _ = token.cancelled() => return,
+                            r = scrapers::resolve(&result) => r,
+                        };
+                        let _ = tx.send(AppMessage::Resolved(result.dedup_key(), resolved));
+                    });
                 } else {
                     app.status = "No magnet link available".to_string();
                 }
@@ -738,35 +2652,28 @@ async fn handle_results_keys(
         }
         KeyCode::Char('n') => {
             // Next page
-            let query = app.search_input.clone();
+            let query = app.effective_query();
             let tx = tx.clone();
             let next_page = app.page + 1;
             let enabled_sources = app.enabled_sources.clone();
+            let anime_mode = app.anime_mode;
+            let fast = app.fast_search;
 
             app.status = format!("Loading page {}...", next_page);
-            app.mode = AppMode::Processing;
+            app.open(AppMode::Processing);
             app.processing_status = "Searching...".to_string();
+            let token = app.new_task_token();
 
-            tokio::spawn(async move {
-                let mut results = scrapers::search_all(&query, next_page).await;
-
-                // Filter by enabled sources
-                results.retain(|r| enabled_sources.contains(&r.source));
-
-                // Sort by source priority, then by seeders
-                results.sort_by(|a, b| {
-                    let a_priority = SOURCE_PRIORITY.iter().position(|&s| s == a.source).unwrap_or(999);
-                    let b_priority = SOURCE_PRIORITY.iter().position(|&s| s == b.source).unwrap_or(999);
-                    match a_priority.cmp(&b_priority) {
-                        std::cmp::Ordering::Equal => b.seeders.cmp(&a.seeders),
-                        other => other,
-                    }
-                });
+            app.tasks.spawn_cancellable("search", token.clone(), async move {
+                let (results, statuses) = tokio::select! {
+                    _ = token.cancelled() => return,
+                    r = run_search(&query, next_page, &enabled_sources, anime_mode, fast, &token) => r,
+                };
 
                 if results.is_empty() {
                     let _ = tx.send(AppMessage::SearchError("No more results".to_string()));
                 } else {
-                    let _ = tx.send(AppMessage::SearchResults(results));
+                    let _ = tx.send(AppMessage::SearchResults(results, statuses));
                 }
             });
 
@@ -775,54 +2682,81 @@ async fn handle_results_keys(
         KeyCode::Char('p') => {
             // Previous page
             if app.page > 1 {
-                let query = app.search_input.clone();
+                let query = app.effective_query();
                 let tx = tx.clone();
                 let prev_page = app.page - 1;
                 let enabled_sources = app.enabled_sources.clone();
+                let anime_mode = app.anime_mode;
+                let fast = app.fast_search;
 
                 app.status = format!("Loading page {}...", prev_page);
-                app.mode = AppMode::Processing;
+                app.open(AppMode::Processing);
                 app.processing_status = "Searching...".to_string();
+                let token = app.new_task_token();
 
-                tokio::spawn(async move {
-                    let mut results = scrapers::search_all(&query, prev_page).await;
-
-                    // Filter by enabled sources
-                    results.retain(|r| enabled_sources.contains(&r.source));
-
-                    // Sort by source priority, then by seeders
-                    results.sort_by(|a, b| {
-                        let a_priority = SOURCE_PRIORITY.iter().position(|&s| s == a.source).unwrap_or(999);
-                        let b_priority = SOURCE_PRIORITY.iter().position(|&s| s == b.source).unwrap_or(999);
-                        match a_priority.cmp(&b_priority) {
-                            std::cmp::Ordering::Equal => b.seeders.cmp(&a.seeders),
-                            other => other,
-                        }
-                    });
+                app.tasks.spawn_cancellable("search", token.clone(), async move {
+                    let (results, statuses) = tokio::select! {
+                        _ = token.cancelled() => return,
+                        r = run_search(&query, prev_page, &enabled_sources, anime_mode, fast, &token) => r,
+                    };
 
                     if results.is_empty() {
                         let _ = tx.send(AppMessage::SearchError("No results".to_string()));
                     } else {
-                        let _ = tx.send(AppMessage::SearchResults(results));
+                        let _ = tx.send(AppMessage::SearchResults(results, statuses));
                     }
                 });
 
                 app.page = prev_page;
             }
         }
+        KeyCode::Char('r') => {
+            // Re-run only the sources that failed and merge their results
+            let failed = app.failed_sources();
+            if failed.is_empty() {
+                app.status = "No failed sources to retry".to_string();
+            } else {
+                let query = app.effective_query();
+                let page = app.page;
+                let fast = app.fast_search;
+                let tx = tx.clone();
+
+                app.open(AppMode::Processing);
+                app.processing_status = format!("Retrying {}...", failed.join(", "));
+                let token = app.new_task_token();
+
+                app.tasks.spawn_cancellable("search", token.clone(), async move {
+                    let sources: Vec<&str> = failed.iter().map(String::as_str).collect();
+                    let (results, statuses) = tokio::select! {
+                        _ = token.cancelled() => return,
+                        r = scrapers::search_sources(&query, page, &sources, fast, &token) => r,
+                    };
+                    let _ = tx.send(AppMessage::RetryResults(results, statuses));
+                });
+            }
+        }
         KeyCode::Char('s') => {
             // Open source selector
             app.source_cursor = 0;
-            app.mode = AppMode::SourceSelect;
+            app.open(AppMode::SourceSelect);
         }
-        KeyCode::Char('d') => {
-            // Open downloads viewer
-            app.download_cursor = 0;
-            app.mode = AppMode::Downloads;
+        KeyCode::Char('d') => open_downloads(app),
+        KeyCode::Char('t') => {
+            // Open scraper stats
+            app.open(AppMode::Stats);
+        }
+        KeyCode::Char('i') if app.selected_result().is_some() => {
+            // Show details and cover art
+            app.open(AppMode::Details);
+            request_cover(app, &tx);
+        }
+        KeyCode::Char('L') => {
+            // Open the scraper log
+            app.open_log();
         }
         KeyCode::Char('c') => {
             // Copy magnet link to clipboard
-            if let Some(result) = app.results.get(app.selected_index) {
+            if let Some(result) = app.selected_result_index().and_then(|i| app.results.get(i)) {
                 if !result.magnet.is_empty() {
                     match arboard::Clipboard::new() {
                         Ok(mut clipboard) => {
@@ -844,9 +2778,42 @@ async fn handle_results_keys(
                 }
             }
         }
+        KeyCode::Char('e') | KeyCode::Char('E') if !app.results.is_empty() => {
+            // Export the result list (e = JSON, E = CSV) to the download directory
+            let format = if code == KeyCode::Char('E') {
+                export::Format::Csv
+            } else {
+                export::Format::Json
+            };
+            let path = export::default_path(&app.download_dir(), &app.effective_query(), format);
+            let shown = app.shown_results();
+            app.status = match export::write(&shown, &path, format) {
+                Ok(_) => format!("Exported {} results to {}", shown.len(), path.display()),
+                Err(e) => format!("Export failed: {}", e),
+            };
+        }
+        KeyCode::Char('o') => {
+            // Open the source detail page in the browser
+            if let Some(result) = app.selected_result_index().and_then(|i| app.results.get(i)) {
+                match &result.url {
+                    Some(url) => match open_in_browser(url) {
+                        Ok(_) => {
+                            app.status = format!("Opened {}", url);
+                        }
+                        Err(e) => {
+                            app.status = format!("Failed to open browser: {}", e);
+                        }
+                    },
+                    None => {
+                        app.status = format!("No source page for {} results", result.source);
+                    }
+                }
+            }
+        }
         KeyCode::Char('/') | KeyCode::Esc => {
             // Back to search
-            app.mode = AppMode::Search;
+            app.vim_insert = true;
+            app.go_home();
         }
         KeyCode::Char('q') => {
             app.should_quit = true;
@@ -855,6 +2822,7 @@ async fn handle_results_keys(
     }
 }
 
+#[allow(clippy::collapsible_match)]
 async fn handle_file_select_keys(
     app: &mut App,
     code: KeyCode,
@@ -879,6 +2847,16 @@ async fn handle_file_select_keys(
                 }
             }
         }
+        KeyCode::Home => {
+            app.file_cursor = 0;
+            app.file_scroll_offset = 0;
+        }
+        KeyCode::End => {
+            app.file_cursor = app.files.len().saturating_sub(1);
+            if app.file_cursor >= visible_height {
+                app.file_scroll_offset = app.file_cursor - visible_height + 1;
+            }
+        }
         KeyCode::Char(' ') => {
             // Toggle file selection
             if let Some(file) = app.files.get(app.file_cursor) {
@@ -897,37 +2875,21 @@ async fn handle_file_select_keys(
                 app.selected_files = app.files.iter().map(|f| f.id).collect();
             }
         }
+        KeyCode::Char('t') => {
+            // Change the category the downloads are filed under
+            let category = app.torrent_category.unwrap_or(Category::Other).next();
+            app.torrent_category = Some(category);
+            app.status = match category.dir() {
+                Some(dir) => format!("{}: saving to {}", category.label(), dir.display()),
+                None => format!("{}: no CATEGORY_DIRS entry, saving to the download directory", category.label()),
+            };
+        }
         KeyCode::Enter => {
             // Confirm selection and get download links
             if !app.selected_files.is_empty() {
-                if let (Some(rd_client), Some(torrent_id)) = (&app.rd_client, &app.torrent_id) {
-                    let rd_client = rd_client.clone();
-                    let torrent_id = torrent_id.clone();
+                if let Some(torrent_id) = app.torrent_id.clone() {
                     let file_ids: Vec<u32> = app.selected_files.iter().copied().collect();
-                    let tx = tx.clone();
-
-                    app.mode = AppMode::Processing;
-                    app.processing_status = "Getting download links...".to_string();
-
-                    tokio::spawn(async move {
-                        let tx_clone = tx.clone();
-                        let result = rd_client.download_selected_files_with_callback(
-                            &torrent_id,
-                            &file_ids,
-                            |status| {
-                                let _ = tx_clone.send(AppMessage::StatusUpdate(status.to_string()));
-                            }
-                        ).await;
-
-                        match result {
-                            Ok(links) => {
-                                let _ = tx.send(AppMessage::DownloadLinks(links));
-                            }
-                            Err(e) => {
-                                let _ = tx.send(AppMessage::DownloadError(e.to_string()));
-                            }
-                        }
-                    });
+                    start_get_links(app, torrent_id, file_ids, tx);
                 }
             } else {
                 app.status = "No files selected".to_string();
@@ -935,8 +2897,8 @@ async fn handle_file_select_keys(
         }
         KeyCode::Esc | KeyCode::Char('q') => {
             // Cancel and go back to results
-            // Clean up torrent from RD
-            if let (Some(rd_client), Some(torrent_id)) = (&app.rd_client, &app.torrent_id) {
+            // Clean up torrent from RD (unless it was already on the account)
+            if let (Some(rd_client), Some(torrent_id), false) = (&app.rd_client, &app.torrent_id, app.torrent_reused) {
                 let rd_client = rd_client.clone();
                 let torrent_id = torrent_id.clone();
                 tokio::spawn(async move {
@@ -946,7 +2908,7 @@ async fn handle_file_select_keys(
             app.torrent_id = None;
             app.files.clear();
             app.selected_files.clear();
-            app.mode = AppMode::Results;
+            app.back();
         }
         _ => {}
     }
@@ -954,16 +2916,106 @@ async fn handle_file_select_keys(
 
 fn handle_message(app: &mut App, msg: AppMessage) {
     match msg {
-        AppMessage::SearchResults(results) => {
+        AppMessage::TmdbTitles(seq, query, titles) => {
+            // Only the latest lookup counts, and only while still on the search screen
+            if seq == app.tmdb_seq && app.mode == AppMode::Search {
+                app.tmdb_query = query;
+                app.tmdb_titles = titles;
+                let selected = app.suggestion_index;
+                app.refresh_suggestions();
+                app.suggestion_index = selected.filter(|&i| i < app.suggestions.len());
+            }
+        }
+        AppMessage::Resolved(key, resolved) => {
+            app.open(AppMode::Results);
+            // A pinned movie is replaced by its qualities
+            app.pinned.retain(|p| p.dedup_key() != key);
+            let Some(index) = app.results.iter().position(|r| r.needs_resolve() && r.dedup_key() == key) else {
+                return;
+            };
+            match resolved {
+                Some(resolved) if !resolved.is_empty() => {
+                    let single = (resolved.len() == 1).then(|| resolved[0].magnet.clone());
+                    app.results.splice(index..=index, resolved.clone());
+                    app.refresh_rows();
+                    app.jump_to_result(index + 1);
+                    match single {
+                        Some(magnet) => app.pending_magnet = Some(magnet),
+                        None => {
+                            // Ask which quality to send to Real-Debrid
+                            app.quality_choices = resolved;
+                            app.quality_cursor = 0;
+                            app.open(AppMode::QualitySelect);
+                        }
+                    }
+                }
+                _ => app.status = "No magnet link found on the detail page".to_string(),
+            }
+        }
+        AppMessage::UpdateAvailable(release) => {
+            app.status = format!("littlejohn {} is available - Ctrl+R for what's new", release.version);
+            app.update = Some(release);
+        }
+        AppMessage::Suspend => app.suspend_requested = true,
+        AppMessage::Resumed => app.resumed = true,
+        AppMessage::SelfTest(check, outcome) => {
+            if let Some(item) = app.self_test.iter_mut().find(|item| item.check == check) {
+                item.outcome = Some(outcome);
+            }
+        }
+        AppMessage::FirecrawlCredits(credits) => {
+            app.firecrawl_credits = Some(match credits {
+                Ok(left) => format!("{} credits left", left),
+                Err(e) => format!("credits unavailable: {}", e),
+            });
+        }
+        AppMessage::Cover(key, image) => {
+            let cover = match image {
+                Some(image) => Cover::Image(image),
+                None => Cover::Missing,
+            };
+            app.covers.insert(key, cover);
+        }
+        AppMessage::SearchPage(page) => app.page = page,
+        AppMessage::SearchResults(results, statuses) => {
             app.results = results;
+            app.keep_pinned();
+            app.source_statuses = statuses;
             app.selected_index = 0;
             app.scroll_offset = 0;
+            app.sort.apply(&mut app.results);
+            app.refresh_rows();
             app.status = format!("{} results found", app.results.len());
-            app.mode = AppMode::Results;
+            app.open(AppMode::Results);
+        }
+        AppMessage::RetryResults(results, statuses) => {
+            // Merge newly found torrents into the current list
+            let mut seen: std::collections::HashSet<String> = app
+                .results
+                .iter()
+                .map(TorrentResult::dedup_key)
+                .collect();
+            let before = app.results.len();
+            app.results.extend(
+                results
+                    .into_iter()
+                    .filter(|r| seen.insert(r.dedup_key())),
+            );
+            let added = app.results.len() - before;
+
+            let still_failed = statuses.values().filter(|s| **s == SourceStatus::Failed).count();
+            app.source_statuses.extend(statuses);
+            app.apply_sort();
+            app.status = if still_failed > 0 {
+                format!("Retry added {} results, {} source(s) still failing", added, still_failed)
+            } else {
+                format!("Retry added {} results", added)
+            };
+            app.open(AppMode::Results);
         }
         AppMessage::SearchError(e) => {
             app.status = format!("Search error: {}", e);
-            app.mode = AppMode::Error(e);
+            app.open(AppMode::Error(e));
         }
         AppMessage::TorrentFiles(torrent_id, files) => {
             app.torrent_id = Some(torrent_id);
@@ -984,7 +3036,7 @@ fn handle_message(app: &mut App, msg: AppMessage) {
                 // Check if large file (>50MB)
                 let is_large = f.bytes > 50_000_000;
 
-                is_video || is_archive || is_large
+                is_video || is_archive || is_large || f.selected
             }).cloned().collect();
 
             // Use filtered files if any, otherwise use all
@@ -994,9 +3046,19 @@ fn handle_message(app: &mut App, msg: AppMessage) {
                 useful_files
             };
 
+            // A pasted magnet has no result to go by: guess from the largest file
+            if app.torrent_category.is_none() {
+                app.torrent_category = app
+                    .files
+                    .iter()
+                    .max_by_key(|f| f.bytes)
+                    .map(|f| Category::guess(None, &f.path));
+            }
+
             app.file_cursor = 0;
             app.file_scroll_offset = 0;
-            app.selected_files.clear();
+            // Pre-check files already selected on RD (an existing torrent)
+            app.selected_files = app.files.iter().filter(|f| f.selected).map(|f| f.id).collect();
 
             // Auto-select if single file
             if app.files.len() == 1 {
@@ -1004,284 +3066,1558 @@ fn handle_message(app: &mut App, msg: AppMessage) {
             }
 
             app.status = format!("{} files in torrent", app.files.len());
-            app.mode = AppMode::FileSelect;
+            app.open(AppMode::FileSelect);
+        }
+        AppMessage::DuplicateTorrent(magnet, torrent) => {
+            app.status = format!("Already on Real-Debrid: {}", torrent.filename);
+            app.duplicate = Some((magnet, torrent));
+            app.open(AppMode::ConfirmDuplicate);
+        }
+        AppMessage::WaitTimeout(wait, timeout) => {
+            app.status = timeout.to_string();
+            app.timed_out = Some((wait, timeout));
+            app.open(AppMode::ConfirmKeepWaiting);
         }
         AppMessage::TorrentError(e) => {
             app.status = format!("Torrent error: {}", e);
-            app.mode = AppMode::Error(e);
+            app.open(AppMode::Error(e));
         }
         AppMessage::DownloadLinks(links) => {
-            // Add downloads to the download list
-            let downloads_dir = std::env::var("DOWNLOAD_DIR")
-                .map(PathBuf::from)
-                .unwrap_or_else(|_| dirs::download_dir().unwrap_or_else(|| PathBuf::from(".")));
-
-            for (filename, url) in links {
-                let dest_path = downloads_dir.join(&filename);
-                let download = Download {
-                    url: url.clone(),
-                    filename: filename.clone(),
-                    dest_path,
-                    status: DownloadStatus::Pending,
-                    total_bytes: 0,
-                    downloaded_bytes: 0,
-                    speed: 0.0,
-                };
-                app.downloads.push(download);
+            let first = app.downloads.len();
+            app.queue_downloads(links, app.torrent_category);
+            if let Some(item) = app.trakt_search.take() {
+                app.downloads[first..].iter_mut().for_each(|dl| dl.trakt = Some(item.clone()));
             }
-
-            app.status = format!("{} download(s) queued! Press 'd' to view", app.downloads.len());
-
-            // Print links to console (they'll be visible after exit)
-            for dl in &app.downloads {
-                eprintln!("\n{}", dl.filename);
-                eprintln!("{}", dl.url);
+            app.status = format!("{} download(s) queued{}! Press 'd' to view", app.downloads.len(), schedule::note());
+            app.back_to_results();
+        }
+        AppMessage::BackgroundStatus(torrent_id, status) => {
+            if let Some(transfer) = app.background_waits.iter_mut().find(|t| t.key == torrent_id) {
+                transfer.status = status;
             }
-
-            app.mode = AppMode::Results;
+        }
+        AppMessage::BackgroundProgress(torrent_id, progress) => {
+            if let Some(transfer) = app.background_waits.iter_mut().find(|t| t.key == torrent_id) {
+                transfer.status = progress.status.clone();
+                transfer.progress = Some(progress);
+            }
+        }
+        AppMessage::BackgroundLinks(torrent_id, links) => {
+            let category = app.background_waits.iter().find(|t| t.key == torrent_id).and_then(|t| t.category);
+            app.background_waits.retain(|t| t.key != torrent_id);
+            let count = links.len();
+            app.queue_downloads(links, category);
+            app.status = format!("Real-Debrid finished caching: {} download(s) queued{}! Press 'd' to view", count, schedule::note());
+        }
+        AppMessage::BackgroundFailed(torrent_id, e) => {
+            app.background_waits.retain(|t| t.key != torrent_id);
+            app.status = format!("Background Real-Debrid wait failed: {}", e);
         }
         AppMessage::DownloadError(e) => {
             app.status = format!("Download error: {}", e);
-            app.mode = AppMode::Error(e);
+            app.open(AppMode::Error(e));
+        }
+        AppMessage::AuthExpired(e) => {
+            // Drop the dead client and send the user to Settings to enter a new token
+            app.rd_client = None;
+            app.torrent_id = None;
+            app.settings_banner = Some(format!("{} - please enter a new token", e));
+            app.settings_field = SettingsField::RdApiToken;
+            app.settings_cursor = app.settings_rd_token.len();
+            app.status = "Real-Debrid token expired or revoked".to_string();
+            app.open(AppMode::Settings);
         }
         AppMessage::StatusUpdate(s) => {
             app.processing_status = s;
+            app.rd_progress = None;
         }
-        AppMessage::DownloadProgress { index, downloaded, total, speed } => {
-            if let Some(dl) = app.downloads.get_mut(index) {
+        AppMessage::RdProgress(progress) => {
+            // Ignore updates from a wait that was cancelled or sent to the background
+            if app.links_wait.is_some() {
+                app.rd_progress = Some(progress);
+            }
+        }
+        AppMessage::DownloadProgress { id, downloaded, total, speed } => {
+            if let Some(dl) = app.download_mut(id) {
                 dl.downloaded_bytes = downloaded;
                 dl.total_bytes = total;
                 dl.speed = speed;
                 dl.status = DownloadStatus::Downloading;
             }
+            app.measure_speed();
         }
-        AppMessage::DownloadComplete(index) => {
-            if let Some(dl) = app.downloads.get_mut(index) {
+        AppMessage::DownloadComplete(id) => {
+            if let Some(dl) = app.download_index(id).and_then(|i| app.downloads.get_mut(i)) {
                 dl.status = DownloadStatus::Completed;
+                if let Some(bot) = &app.telegram {
+                    bot.notify(format!("Downloaded {}", dl.filename));
+                }
             }
+            mark_collected(app, id);
         }
-        AppMessage::DownloadFailed(index, error) => {
-            if let Some(dl) = app.downloads.get_mut(index) {
+        AppMessage::DownloadFailed(id, error) => {
+            if let Some(dl) = app.download_index(id).and_then(|i| app.downloads.get_mut(i)) {
+                if let Some(bot) = &app.telegram {
+                    bot.notify(format!("Download failed: {}: {}", dl.filename, error));
+                }
+                app.status = format!("{} failed: {}", dl.filename, error);
                 dl.status = DownloadStatus::Failed(error);
             }
         }
-    }
-}
-
-/// Handle source selector keys
-fn handle_source_select_keys(app: &mut App, code: KeyCode) {
-    let num_sources = scrapers::SCRAPERS.len();
-
-    match code {
-        KeyCode::Up | KeyCode::Char('k') => {
-            if app.source_cursor > 0 {
-                app.source_cursor -= 1;
-            }
-        }
-        KeyCode::Down | KeyCode::Char('j') => {
-            if app.source_cursor < num_sources.saturating_sub(1) {
-                app.source_cursor += 1;
+        AppMessage::DownloadSkipped(id) => {
+            if let Some(dl) = app.download_index(id).and_then(|i| app.downloads.get_mut(i)) {
+                dl.status = DownloadStatus::Skipped;
+                app.status = format!("Skipped {} (already exists)", dl.filename);
             }
         }
-        KeyCode::Char(' ') => {
-            // Toggle source
-            let source = scrapers::SCRAPERS[app.source_cursor].to_string();
-            if app.enabled_sources.contains(&source) {
-                app.enabled_sources.remove(&source);
-            } else {
-                app.enabled_sources.insert(source);
+        AppMessage::DownloadPaused(id) => {
+            if let Some(dl) = app.download_mut(id) {
+                dl.status = DownloadStatus::Paused;
+                dl.speed = 0.0;
             }
         }
-        KeyCode::Char('a') => {
-            // Enable all
-            app.enabled_sources = scrapers::SCRAPERS.iter().map(|s| s.to_string()).collect();
+        AppMessage::RemoteGrab(name, links) => {
+            let count = links.len();
+            app.queue_downloads(links, Some(Category::guess(None, &name)));
+            app.autostart_pending = true;
+            app.status = format!("Telegram grab: {} ({} file(s))", name, count);
         }
-        KeyCode::Char('n') => {
-            // Disable all
-            app.enabled_sources.clear();
+        AppMessage::TelegramStatus => {
+            if let Some(bot) = &app.telegram {
+                let mut lines = vec![app.download_summary().unwrap_or_else(|| "No active downloads".to_string())];
+                lines.extend(
+                    app.downloads
+                        .iter()
+                        .filter(|d| d.status.is_active())
+                        .map(|d| format!("{:.0}% {}", d.progress(), d.filename)),
+                );
+                bot.notify(lines.join("\n"));
+            }
         }
-        KeyCode::Enter => {
-            // Confirm and go back
-            if !app.enabled_sources.is_empty() {
-                app.status = format!("{} sources enabled", app.enabled_sources.len());
-                app.mode = AppMode::Search;
-            } else {
-                app.status = "At least one source must be enabled".to_string();
+        AppMessage::DiskSpace(low) => {
+            if low.is_none() && app.disk_low.is_some() {
+                app.status = "Disk space recovered, downloads resumed".to_string();
             }
+            app.disk_low = low;
         }
-        KeyCode::Esc | KeyCode::Char('q') => {
-            // Cancel
-            app.mode = AppMode::Search;
+        AppMessage::UpNext(Ok(items)) => {
+            app.up_next_cursor = app.up_next_cursor.min(items.len().saturating_sub(1));
+            app.up_next = Some(items);
         }
-        _ => {}
-    }
-}
-
-/// Handle downloads viewer keys
-async fn handle_downloads_keys(
-    app: &mut App,
-    code: KeyCode,
-    tx: mpsc::UnboundedSender<AppMessage>,
-) {
-    let num_downloads = app.downloads.len();
-
-    match code {
-        KeyCode::Up | KeyCode::Char('k') => {
-            if app.download_cursor > 0 {
-                app.download_cursor -= 1;
-            }
+        AppMessage::UpNext(Err(e)) => {
+            app.up_next.get_or_insert_with(Vec::new);
+            app.status = format!("Trakt: {}", e);
         }
-        KeyCode::Down | KeyCode::Char('j') => {
-            if app.download_cursor < num_downloads.saturating_sub(1) {
-                app.download_cursor += 1;
-            }
+        AppMessage::TraktGrab(item, name, links) => {
+            let (first, count) = (app.downloads.len(), links.len());
+            app.queue_downloads(links, Some(Category::guess(None, &name)));
+            app.downloads[first..].iter_mut().for_each(|dl| dl.trakt = Some(item.clone()));
+            app.autostart_pending = true;
+            app.status = format!("Grabbed {} for {} ({} file(s))", name, item.query(), count);
         }
-        KeyCode::Char('s') => {
-            // Start selected pending download
-            if let Some(dl) = app.downloads.get_mut(app.download_cursor) {
-                if dl.status == DownloadStatus::Pending {
-                    dl.status = DownloadStatus::Downloading;
-                    let url = dl.url.clone();
-                    let dest_path = dl.dest_path.clone();
-                    let index = app.download_cursor;
-                    let tx = tx.clone();
-
-                    tokio::spawn(async move {
-                        start_download(url, dest_path, index, tx).await;
-                    });
-                }
-            }
+        AppMessage::HistoryRecorded => {
+            let downloaded = history::load();
+            app.next_episode = history::next_episode(&downloaded);
+            app.recent_downloads = history::recent(&downloaded, RECENT_ITEMS);
         }
-        KeyCode::Char('S') => {
-            // Start all pending downloads
-            for (index, dl) in app.downloads.iter_mut().enumerate() {
-                if dl.status == DownloadStatus::Pending {
-                    dl.status = DownloadStatus::Downloading;
-                    let url = dl.url.clone();
-                    let dest_path = dl.dest_path.clone();
-                    let tx = tx.clone();
-
-                    tokio::spawn(async move {
-                        start_download(url, dest_path, index, tx).await;
-                    });
-                }
+        AppMessage::ChecksumRecorded(id, sha1) => {
+            if let Some(dl) = app.download(id) {
+                app.status = format!("{} SHA-1 {}", dl.filename, sha1);
             }
         }
-        KeyCode::Char('c') => {
-            // Cancel selected download
-            if let Some(dl) = app.downloads.get_mut(app.download_cursor) {
-                if dl.status == DownloadStatus::Downloading || dl.status == DownloadStatus::Pending {
-                    dl.status = DownloadStatus::Cancelled;
-                }
-            }
+        AppMessage::VerifyResult(id, result) => {
+            let name = app.download(id).map(|d| d.filename.clone()).unwrap_or_default();
+            app.status = match result {
+                Ok(history::Verification::Match) => format!("{} {}: checksums match", theme::SYM_OK, name),
+                Ok(history::Verification::Mismatch) => format!("{} {}: checksum MISMATCH", theme::SYM_FAIL, name),
+                Ok(history::Verification::NotRecorded) => format!("{}: no checksums recorded (set CHECKSUMS=true)", name),
+                Err(e) => format!("Verify failed: {}", e),
+            };
         }
-        KeyCode::Char('C') => {
-            // Cancel all active downloads
-            for dl in &mut app.downloads {
-                if dl.status == DownloadStatus::Downloading || dl.status == DownloadStatus::Pending {
-                    dl.status = DownloadStatus::Cancelled;
+        AppMessage::DownloadRenamed(id, path) => {
+            if let Some(dl) = app.download_mut(id) {
+                if let Some(name) = path.file_name() {
+                    dl.filename = name.to_string_lossy().into_owned();
                 }
+                dl.dest_path = path;
             }
         }
-        KeyCode::Char('x') => {
-            // Clear completed/failed/cancelled
-            app.downloads.retain(|dl| {
-                matches!(dl.status, DownloadStatus::Downloading | DownloadStatus::Pending)
-            });
-            if app.download_cursor >= app.downloads.len() {
-                app.download_cursor = app.downloads.len().saturating_sub(1);
-            }
-        }
-        KeyCode::Esc | KeyCode::Char('q') => {
-            // Back to search or results
-            if app.results.is_empty() {
-                app.mode = AppMode::Search;
-            } else {
-                app.mode = AppMode::Results;
+        AppMessage::Notice(message) => app.status = message,
+        AppMessage::LinkRenewed(id, url) => {
+            if let Some(dl) = app.download_mut(id) {
+                dl.url = url;
+                dl.status = DownloadStatus::Downloading;
+                app.status = format!("Fresh link for {}, downloading again", dl.filename);
             }
         }
-        _ => {}
     }
 }
 
-/// Start downloading a file in the background
-async fn start_download(
-    url: String,
-    dest_path: PathBuf,
-    index: usize,
-    tx: mpsc::UnboundedSender<AppMessage>,
-) {
-    use futures::StreamExt;
-    use tokio::io::AsyncWriteExt;
+/// Map an RD error to a message, routing auth failures to the token prompt
+fn rd_error_message(e: anyhow::Error, fallback: fn(String) -> AppMessage) -> AppMessage {
+    if realdebrid::is_auth_error(&e) {
+        AppMessage::AuthExpired(e.to_string())
+    } else {
+        fallback(e.to_string())
+    }
+}
+
+/// Add a magnet to Real-Debrid in the background and fetch its file list
+fn start_add_magnet(app: &mut App, magnet: String, tx: mpsc::UnboundedSender<AppMessage>) {
+    if app.blackhole_mode {
+        send_to_blackhole(app, magnet, tx);
+        return;
+    }
+    app.torrent_category = app
+        .results
+        .iter()
+        .find(|r| r.magnet == magnet)
+        .map(|r| Category::guess(r.category.as_deref(), &r.name));
+    add_magnet_to_rd(app, magnet, tx, true);
+}
 
-    let client = reqwest::Client::new();
+/// Hand a magnet to the client watching the blackhole directory
+fn send_to_blackhole(app: &mut App, magnet: String, tx: mpsc::UnboundedSender<AppMessage>) {
+    let name = app.results.iter().find(|r| r.magnet == magnet).map(|r| r.name.clone());
+    app.status = "Writing to the blackhole directory...".to_string();
+    app.tasks.spawn("blackhole", async move {
+        let message = match blackhole::export(&magnet, name.as_deref()).await {
+            Ok(path) => format!("Sent to blackhole: {}", path.display()),
+            Err(e) => format!("Blackhole export failed: {:#}", e),
+        };
+        let _ = tx.send(AppMessage::Notice(message));
+    });
+}
 
-    // Start the download
-    let response = match client.get(&url).send().await {
-        Ok(resp) => resp,
-        Err(e) => {
-            let _ = tx.send(AppMessage::DownloadFailed(index, e.to_string()));
+/// Add a magnet to RD and fetch its file list. With `check_duplicates`, the
+/// account is searched for the same info hash first.
+fn add_magnet_to_rd(
+    app: &mut App,
+    magnet: String,
+    tx: mpsc::UnboundedSender<AppMessage>,
+    check_duplicates: bool,
+) {
+    let rd_client = match &app.rd_client {
+        Some(c) => c.clone(),
+        None => {
+            app.status = "Real-Debrid not configured".to_string();
             return;
         }
     };
 
-    let total_size = response.content_length().unwrap_or(0);
+    app.open(AppMode::Processing);
+    app.processing_status = "Adding magnet to Real-Debrid...".to_string();
+    app.torrent_reused = false;
+    let token = app.new_task_token();
 
-    // Create the file
-    let mut file = match tokio::fs::File::create(&dest_path).await {
-        Ok(f) => f,
-        Err(e) => {
-            let _ = tx.send(AppMessage::DownloadFailed(index, e.to_string()));
-            return;
+    app.tasks.spawn_cancellable("add magnet", token.clone(), async move {
+        if let Some(hash) = check_duplicates.then(|| scrapers::magnet_info_hash(&magnet)).flatten() {
+            let _ = tx.send(AppMessage::StatusUpdate("Checking existing torrents...".to_string()));
+            let existing = tokio::select! {
+                _ = token.cancelled() => return,
+                r = rd_client.find_torrent_by_hash(&hash) => r,
+            };
+            match existing {
+                Ok(Some(torrent)) => {
+                    let _ = tx.send(AppMessage::DuplicateTorrent(magnet, torrent));
+                    return;
+                }
+                Ok(None) => {}
+                Err(e) => scrapers::log_info("rd", &format!("Duplicate check failed: {}", e)),
+            }
         }
-    };
 
-    let mut downloaded: u64 = 0;
-    let mut last_update = std::time::Instant::now();
-    let mut last_downloaded: u64 = 0;
+        let _ = tx.send(AppMessage::StatusUpdate("Adding magnet...".to_string()));
 
-    let mut stream = response.bytes_stream();
-
-    while let Some(chunk_result) = stream.next().await {
-        match chunk_result {
-            Ok(chunk) => {
-                // Write chunk to file
-                if let Err(e) = file.write_all(&chunk).await {
-                    let _ = tx.send(AppMessage::DownloadFailed(index, e.to_string()));
-                    return;
+        let torrent_id = match rd_client.add_magnet(&magnet).await {
+            Ok(id) => id,
+            Err(e) => {
+                if !token.is_cancelled() {
+                    let _ = tx.send(rd_error_message(e, AppMessage::TorrentError));
                 }
+                return;
+            }
+        };
 
-                downloaded += chunk.len() as u64;
+        // Cancelled while the magnet was being added
+        if token.is_cancelled() {
+            let _ = rd_client.delete_torrent(&torrent_id).await;
+            return;
+        }
 
-                // Report progress every 100ms
-                let now = std::time::Instant::now();
-                if now.duration_since(last_update).as_millis() >= 100 {
-                    let elapsed = now.duration_since(last_update).as_secs_f64();
-                    let speed = (downloaded - last_downloaded) as f64 / elapsed;
-
-                    let _ = tx.send(AppMessage::DownloadProgress {
-                        index,
-                        downloaded,
-                        total: total_size,
-                        speed,
-                    });
+        wait_for_files_task(rd_client, torrent_id, tx, token, true).await;
+    });
+}
 
-                    last_update = now;
-                    last_downloaded = downloaded;
-                }
+/// Wait for a torrent's file list and report it. `delete_on_cancel` removes the
+/// torrent from RD if the user cancels (only for torrents we just added).
+async fn wait_for_files_task(
+    rd_client: RealDebridClient,
+    torrent_id: String,
+    tx: mpsc::UnboundedSender<AppMessage>,
+    token: CancellationToken,
+    delete_on_cancel: bool,
+) {
+    let tx_status = tx.clone();
+    tokio::select! {
+        _ = token.cancelled() => {
+            if delete_on_cancel {
+                let _ = rd_client.delete_torrent(&torrent_id).await;
+            }
+        }
+        result = rd_client.wait_for_files_with_callback(&torrent_id, |status| {
+            let _ = tx_status.send(AppMessage::StatusUpdate(status.to_string()));
+        }) => match result {
+            Ok(files) => {
+                let _ = tx.send(AppMessage::TorrentFiles(torrent_id, files));
             }
             Err(e) => {
-                let _ = tx.send(AppMessage::DownloadFailed(index, e.to_string()));
-                return;
+                let _ = tx.send(rd_wait_message(e, RdWait::Files, AppMessage::TorrentError));
             }
-        }
+        },
     }
+}
 
-    // Final sync
-    if let Err(e) = file.sync_all().await {
-        let _ = tx.send(AppMessage::DownloadFailed(index, e.to_string()));
-        return;
-    }
+/// Wait for the torrent's file list in the background (after a timeout)
+fn start_wait_for_files(app: &mut App, torrent_id: String, tx: mpsc::UnboundedSender<AppMessage>) {
+    let rd_client = match &app.rd_client {
+        Some(c) => c.clone(),
+        None => return,
+    };
 
-    let _ = tx.send(AppMessage::DownloadComplete(index));
+    app.open(AppMode::Processing);
+    app.processing_status = "Resolving magnet...".to_string();
+    let token = app.new_task_token();
+    let delete_on_cancel = !app.torrent_reused;
+
+    app.tasks.spawn_cancellable("torrent files", token.clone(), wait_for_files_task(rd_client, torrent_id, tx, token, delete_on_cancel));
+}
+
+/// Select files on RD, wait for it to cache them and fetch the download links
+fn start_get_links(app: &mut App, torrent_id: String, file_ids: Vec<u32>, tx: mpsc::UnboundedSender<AppMessage>) {
+    let rd_client = match &app.rd_client {
+        Some(c) => c.clone(),
+        None => return,
+    };
+
+    app.open(AppMode::Processing);
+    app.processing_status = "Getting download links...".to_string();
+    let token = app.new_task_token();
+    app.links_wait = Some((torrent_id.clone(), file_ids.clone()));
+
+    app.tasks.spawn_cancellable("download links", token.clone(), async move {
+        let tx_clone = tx.clone();
+        let result = tokio::select! {
+            // The key handler removes the torrent (or hands it to a background wait)
+            _ = token.cancelled() => return,
+            r = rd_client.download_selected_files_with_updates(
+                &torrent_id,
+                &file_ids,
+                |update| {
+                    let _ = tx_clone.send(match update {
+                        RdUpdate::Status(status) => AppMessage::StatusUpdate(status.to_string()),
+                        RdUpdate::Progress(progress) => AppMessage::RdProgress(progress.clone()),
+                    });
+                }
+            ) => r,
+        };
+
+        match result {
+            Ok(links) => {
+                let _ = tx.send(AppMessage::DownloadLinks(links));
+            }
+            Err(e) => {
+                let _ = tx.send(rd_wait_message(e, RdWait::Links(file_ids), AppMessage::DownloadError));
+            }
+        }
+    });
+}
+
+/// Poll RD for the torrent without a time limit, queueing the downloads once it's cached
+fn start_background_wait(app: &mut App, torrent_id: String, file_ids: Vec<u32>, tx: mpsc::UnboundedSender<AppMessage>) {
+    let rd_client = match &app.rd_client {
+        Some(c) => c.clone(),
+        None => return,
+    };
+
+    let label = match app.files.iter().find(|f| app.selected_files.contains(&f.id)) {
+        Some(file) => file.name().to_string(),
+        None => format!("Torrent {}", torrent_id),
+    };
+    let mut transfer = RdTransfer::new(torrent_id.clone(), label, "Waiting for Real-Debrid");
+    transfer.category = app.torrent_category;
+    app.background_waits.push(transfer);
+    app.torrent_id = None;
+    app.files.clear();
+    app.selected_files.clear();
+    app.status = "Real-Debrid is caching in the background; downloads will be queued when ready".to_string();
+    app.back_to_results();
+
+    app.tasks.spawn("rd caching", async move {
+        loop {
+            let tx_status = tx.clone();
+            let id = torrent_id.clone();
+            let result = rd_client
+                .download_selected_files_with_updates(&torrent_id, &file_ids, |update| {
+                    let _ = tx_status.send(background_update(&id, update));
+                })
+                .await;
+
+            let message = match result {
+                Ok(links) => AppMessage::BackgroundLinks(torrent_id, links),
+                // No time limit in the background
+                Err(e) if e.is::<WaitTimeout>() => continue,
+                Err(e) => AppMessage::BackgroundFailed(torrent_id, e.to_string()),
+            };
+            let _ = tx.send(message);
+            return;
+        }
+    });
+}
+
+/// Message for an update from a background RD wait
+fn background_update(key: &str, update: RdUpdate) -> AppMessage {
+    match update {
+        RdUpdate::Status(status) => AppMessage::BackgroundStatus(key.to_string(), status.to_string()),
+        RdUpdate::Progress(progress) => AppMessage::BackgroundProgress(key.to_string(), progress.clone()),
+    }
+}
+
+/// Run imported magnets/links through RD one at a time in the background,
+/// queueing the downloads as each one is ready
+fn start_import(app: &mut App, entries: Vec<import::Entry>, tx: mpsc::UnboundedSender<AppMessage>) {
+    let rd_client = match &app.rd_client {
+        Some(c) => c.clone(),
+        None => {
+            app.status = "Real-Debrid not configured".to_string();
+            return;
+        }
+    };
+
+    for entry in &entries {
+        app.background_waits.push(RdTransfer::new(entry.as_str().to_string(), entry.label(), "Queued for import"));
+    }
+    app.status = format!("Importing {} item(s) in the background", entries.len());
+
+    app.tasks.spawn("import", async move {
+        for entry in entries {
+            let key = entry.as_str().to_string();
+            let tx_status = tx.clone();
+            let result = import::resolve(&rd_client, &entry, |update| {
+                let _ = tx_status.send(background_update(&key, update));
+            })
+            .await;
+
+            let _ = tx.send(match result {
+                Ok(links) => AppMessage::BackgroundLinks(key, links),
+                Err(e) => AppMessage::BackgroundFailed(key, e.to_string()),
+            });
+        }
+    });
+}
+
+/// Handle the import path prompt
+fn handle_import_keys(app: &mut App, code: KeyCode, modifiers: KeyModifiers, tx: mpsc::UnboundedSender<AppMessage>) {
+    match code {
+        KeyCode::Enter => {
+            let path = PathBuf::from(app.import_path.trim());
+            match import::read(&path) {
+                Ok(entries) => {
+                    start_import(app, entries, tx);
+                    app.back();
+                }
+                Err(e) => app.status = e.to_string(),
+            }
+        }
+        KeyCode::Esc => {
+            app.status.clear();
+            app.back();
+        }
+        _ => {
+            input::edit(&mut app.import_path, &mut app.import_cursor, code, modifiers);
+        }
+    }
+}
+
+/// Like [`rd_error_message`], but a timeout offers to keep waiting
+fn rd_wait_message(e: anyhow::Error, wait: RdWait, fallback: fn(String) -> AppMessage) -> AppMessage {
+    match e.downcast::<WaitTimeout>() {
+        Ok(timeout) => AppMessage::WaitTimeout(wait, timeout),
+        Err(e) => rd_error_message(e, fallback),
+    }
+}
+
+/// Handle the "RD is taking a while" prompt
+fn handle_keep_waiting_keys(app: &mut App, code: KeyCode, tx: mpsc::UnboundedSender<AppMessage>) {
+    match code {
+        KeyCode::Char('b') => {
+            if let Some((RdWait::Links(file_ids), timeout)) = app.timed_out.take() {
+                start_background_wait(app, timeout.torrent_id, file_ids, tx);
+            }
+        }
+        KeyCode::Char('w') | KeyCode::Enter => match app.timed_out.take() {
+            Some((RdWait::Files, timeout)) => start_wait_for_files(app, timeout.torrent_id, tx),
+            Some((RdWait::Links(file_ids), timeout)) => start_get_links(app, timeout.torrent_id, file_ids, tx),
+            None => {}
+        },
+        KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('q') => {
+            // Give up, removing the torrent unless it was already on the account
+            if let (Some((_, timeout)), Some(rd_client), false) = (app.timed_out.take(), &app.rd_client, app.torrent_reused) {
+                let rd_client = rd_client.clone();
+                tokio::spawn(async move {
+                    let _ = rd_client.delete_torrent(&timeout.torrent_id).await;
+                });
+            }
+            app.torrent_id = None;
+            app.files.clear();
+            app.selected_files.clear();
+            app.status = "Gave up waiting for Real-Debrid".to_string();
+            app.back_to_results();
+        }
+        _ => {}
+    }
+}
+
+/// Open a torrent already on the account instead of adding the magnet again
+fn start_reuse_torrent(app: &mut App, torrent_id: String, tx: mpsc::UnboundedSender<AppMessage>) {
+    let rd_client = match &app.rd_client {
+        Some(c) => c.clone(),
+        None => {
+            app.status = "Real-Debrid not configured".to_string();
+            return;
+        }
+    };
+
+    app.open(AppMode::Processing);
+    app.processing_status = "Loading existing torrent...".to_string();
+    app.torrent_reused = true;
+    let token = app.new_task_token();
+
+    app.tasks.spawn_cancellable("torrent files", token.clone(), wait_for_files_task(rd_client, torrent_id, tx, token, false));
+}
+
+/// Handle the "already on Real-Debrid" prompt
+fn handle_confirm_duplicate_keys(app: &mut App, code: KeyCode, tx: mpsc::UnboundedSender<AppMessage>) {
+    match code {
+        KeyCode::Char('r') | KeyCode::Enter => {
+            if let Some((_, torrent)) = app.duplicate.take() {
+                start_reuse_torrent(app, torrent.id, tx);
+            }
+        }
+        KeyCode::Char('a') => {
+            if let Some((magnet, _)) = app.duplicate.take() {
+                add_magnet_to_rd(app, magnet, tx, false);
+            }
+        }
+        KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('q') => {
+            app.duplicate = None;
+            app.status = "Cancelled".to_string();
+            app.back();
+        }
+        _ => {}
+    }
+}
+
+fn handle_quality_select_keys(app: &mut App, code: KeyCode, tx: mpsc::UnboundedSender<AppMessage>) {
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => app.quality_cursor = app.quality_cursor.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') if app.quality_cursor + 1 < app.quality_choices.len() => {
+            app.quality_cursor += 1;
+        }
+        KeyCode::Enter | KeyCode::Char(' ') => {
+            let Some(choice) = app.quality_choices.get(app.quality_cursor) else { return };
+            let (magnet, name) = (choice.magnet.clone(), choice.name.clone());
+            app.quality_choices.clear();
+            app.back();
+            // Keep the list on the chosen quality
+            if let Some(i) = app.results.iter().position(|r| r.magnet == magnet) {
+                app.jump_to_result(i + 1);
+            }
+            app.status = format!("Adding {}", name);
+            start_add_magnet(app, magnet, tx);
+        }
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.status = format!("{} qualities listed - pick one", app.quality_choices.len());
+            app.quality_choices.clear();
+            app.back();
+        }
+        _ => {}
+    }
+}
+
+/// Open the settings screen, coming back to the current screen when it's closed
+fn open_settings(app: &mut App, tx: &mpsc::UnboundedSender<AppMessage>) {
+    app.settings_field = SettingsField::RdApiToken;
+    app.settings_cursor = app.settings_rd_token.len();
+    app.open(AppMode::Settings);
+    request_firecrawl_credits(app, tx);
+}
+
+/// Open the downloads viewer, coming back to the current screen when it's closed
+fn open_downloads(app: &mut App) {
+    app.download_cursor = 0;
+    app.open(AppMode::Downloads);
+}
+
+/// Look up the Firecrawl credits left, for the settings screen
+fn request_firecrawl_credits(app: &mut App, tx: &mpsc::UnboundedSender<AppMessage>) {
+    if scrapers::firecrawl::api_key().is_none() {
+        app.firecrawl_credits = None;
+        return;
+    }
+    let tx = tx.clone();
+    tokio::spawn(async move {
+        let credits = scrapers::firecrawl::remaining_credits().await.map_err(|e| e.to_string());
+        let _ = tx.send(AppMessage::FirecrawlCredits(credits));
+    });
+}
+
+/// Search enabled sources and sort by source priority, then by seeders.
+/// On the first page, retries with normalized query variants if nothing is found.
+/// `fast` skips detail pages (see [`scrapers::scrape_source`]).
+async fn run_search(
+    query: &str,
+    page: u32,
+    enabled_sources: &std::collections::HashSet<String>,
+    anime_mode: bool,
+    fast: bool,
+    cancel: &CancellationToken,
+) -> (Vec<TorrentResult>, SourceStatuses) {
+    let (results, statuses, _) = run_deep_search(query, page, enabled_sources, anime_mode, fast, false, cancel).await;
+    (results, statuses)
+}
+
+/// [`run_search`], reading further pages when `deep` until there are enough results
+/// (see [`scrapers::deep_search`]). Also returns the last page read.
+async fn run_deep_search(
+    query: &str,
+    page: u32,
+    enabled_sources: &std::collections::HashSet<String>,
+    anime_mode: bool,
+    fast: bool,
+    deep: bool,
+    cancel: &CancellationToken,
+) -> (Vec<TorrentResult>, SourceStatuses, u32) {
+    let sources: Vec<&str> = scrapers::all_sources()
+        .iter()
+        .copied()
+        .filter(|s| enabled_sources.contains(*s))
+        .collect();
+
+    let search = |query: String| {
+        let sources = &sources;
+        async move {
+            if deep {
+                let (target, pages) = (scrapers::deep_search_target(), scrapers::deep_search_pages());
+                scrapers::deep_search(&query, sources, fast, target, pages, cancel).await
+            } else {
+                let (results, statuses) = scrapers::search_sources(&query, page, sources, fast, cancel).await;
+                (results, statuses, page)
+            }
+        }
+    };
+
+    let (mut results, mut statuses, mut last_page) = search(query.to_string()).await;
+
+    if results.is_empty() && page == 1 {
+        for variant in scrapers::query_variants(query) {
+            scrapers::log_info("search", &format!("No results, retrying as '{}'", variant));
+            (results, statuses, last_page) = search(variant).await;
+            if !results.is_empty() {
+                break;
+            }
+        }
+    }
+
+    sort_results(&mut results, anime_mode);
+
+    (results, statuses, last_page)
+}
+
+/// Sort by source priority, then by seeders.
+/// In anime mode, batches are grouped ahead of single episodes.
+fn sort_results(results: &mut [TorrentResult], anime_mode: bool) {
+    let priority: Vec<String> = if anime_mode {
+        ANIME_SOURCE_PRIORITY.iter().map(|s| s.to_string()).collect()
+    } else {
+        source_priority()
+    };
+    results.sort_by(|a, b| {
+        let a_priority = priority.iter().position(|s| *s == a.source).unwrap_or(999);
+        let b_priority = priority.iter().position(|s| *s == b.source).unwrap_or(999);
+        match a_priority.cmp(&b_priority) {
+            std::cmp::Ordering::Equal => b.seeders.cmp(&a.seeders),
+            other => other,
+        }
+    });
+
+    if anime_mode {
+        // Stable sort keeps the priority order within each group
+        results.sort_by_key(|r| !release::is_batch(&r.name));
+    }
+}
+
+/// Open a URL in the default browser without touching the terminal
+fn open_in_browser(url: &str) -> std::io::Result<()> {
+    let mut cmd = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.args(["/C", "start", ""]);
+        cmd
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+
+    cmd.arg(url)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+/// Handle source selector keys
+#[allow(clippy::collapsible_match)]
+fn handle_source_select_keys(app: &mut App, code: KeyCode) {
+    let num_sources = app.source_order.len();
+
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => {
+            if app.source_cursor > 0 {
+                app.source_cursor -= 1;
+            }
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            if app.source_cursor < num_sources.saturating_sub(1) {
+                app.source_cursor += 1;
+            }
+        }
+        KeyCode::Home => {
+            app.source_cursor = 0;
+        }
+        KeyCode::End => {
+            app.source_cursor = num_sources.saturating_sub(1);
+        }
+        KeyCode::Char('K') if app.source_cursor > 0 => {
+            // Raise priority
+            app.source_order.swap(app.source_cursor, app.source_cursor - 1);
+            app.source_cursor -= 1;
+        }
+        KeyCode::Char('J') if app.source_cursor < num_sources.saturating_sub(1) => {
+            // Lower priority
+            app.source_order.swap(app.source_cursor, app.source_cursor + 1);
+            app.source_cursor += 1;
+        }
+        KeyCode::Char(' ') => {
+            // Toggle source
+            let Some(source) = app.source_order.get(app.source_cursor).cloned() else { return };
+            if app.enabled_sources.contains(&source) {
+                app.enabled_sources.remove(&source);
+            } else {
+                app.enabled_sources.insert(source);
+            }
+        }
+        KeyCode::Char('a') => {
+            // Enable all
+            app.enabled_sources = scrapers::all_sources().iter().map(|s| s.to_string()).collect();
+        }
+        KeyCode::Char('n') => {
+            // Disable all
+            app.enabled_sources.clear();
+        }
+        KeyCode::Enter => {
+            // Confirm and go back
+            if !app.enabled_sources.is_empty() {
+                app.status = format!("{} sources enabled", app.enabled_sources.len());
+                save_source_selection(app);
+                app.back();
+            } else {
+                app.status = "At least one source must be enabled".to_string();
+            }
+        }
+        KeyCode::Esc | KeyCode::Char('q') => {
+            // Cancel (toggles stay for this session, a new order is dropped)
+            app.source_order = source_priority();
+            app.back();
+        }
+        _ => {}
+    }
+}
+
+/// Store the enabled sources and priority order in the config file, so they're kept
+/// for the next launch
+fn save_source_selection(app: &mut App) {
+    let disabled: Vec<&str> = scrapers::all_sources()
+        .into_iter()
+        .filter(|s| !app.enabled_sources.contains(*s))
+        .collect();
+    if disabled.is_empty() {
+        std::env::remove_var("DISABLED_SOURCES");
+    } else {
+        std::env::set_var("DISABLED_SOURCES", disabled.join(","));
+    }
+
+    // Only a changed order is saved, so sources added later still slot into the default
+    std::env::remove_var("SOURCE_PRIORITY");
+    if app.source_order != source_priority() {
+        std::env::set_var("SOURCE_PRIORITY", app.source_order.join(","));
+    }
+
+    if let Err(e) = app.save_settings() {
+        app.status = format!("Sources not saved: {}", e);
+    }
+}
+
+/// Handle downloads viewer keys
+#[allow(clippy::collapsible_match)]
+async fn handle_downloads_keys(
+    app: &mut App,
+    code: KeyCode,
+    tx: mpsc::UnboundedSender<AppMessage>,
+) {
+    let num_downloads = app.downloads.len();
+
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => {
+            if app.download_cursor > 0 {
+                app.download_cursor -= 1;
+            }
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            if app.download_cursor < num_downloads.saturating_sub(1) {
+                app.download_cursor += 1;
+            }
+        }
+        KeyCode::Home => {
+            app.download_cursor = 0;
+        }
+        KeyCode::End => {
+            app.download_cursor = num_downloads.saturating_sub(1);
+        }
+        KeyCode::Char('s') => {
+            // Start selected pending download (scheduled ones too, ahead of the window)
+            if let Some(dl) = app.downloads.get_mut(app.download_cursor) {
+                if matches!(dl.status, DownloadStatus::Pending | DownloadStatus::Scheduled) {
+                    dl.status = DownloadStatus::Downloading;
+                    let url = dl.url.clone();
+                    let dest_path = dl.dest_path.clone();
+                    let category = dl.category;
+                    let id = dl.id;
+                    let written = dl.downloaded_bytes;
+                    let tx = tx.clone();
+
+                    dl.task = Some(app.tasks.spawn("download", async move {
+                        start_download(url, dest_path, category, id, written, tx).await;
+                    }));
+                }
+            }
+        }
+        KeyCode::Char('S') => start_pending_downloads(app, &tx),
+        KeyCode::Char('t') => {
+            // Change the category of a download that hasn't started, moving its destination
+            let index = app.download_cursor;
+            if let Some(dl) = app.downloads.get(index).filter(|d| matches!(d.status, DownloadStatus::Pending | DownloadStatus::Scheduled)) {
+                let category = dl.category.next();
+                let dest_path = paths::dest_path(&category_dir(category), &dl.filename);
+                app.status = format!("{}: {}", category.label(), dest_path.display());
+                if let Some(dl) = app.downloads.get_mut(index) {
+                    dl.category = category;
+                    dl.dest_path = dest_path;
+                }
+            }
+        }
+        KeyCode::Char('c') => {
+            // Cancel selected download
+            if let Some(dl) = app.downloads.get_mut(app.download_cursor) {
+                if dl.status.is_active() {
+                    dl.status = DownloadStatus::Cancelled;
+                    if let Some(task) = dl.task {
+                        app.tasks.cancel(task);
+                    }
+                }
+            }
+        }
+        KeyCode::Char('C') => {
+            // Cancel all active downloads
+            for dl in &mut app.downloads {
+                if dl.status.is_active() {
+                    dl.status = DownloadStatus::Cancelled;
+                    if let Some(task) = dl.task {
+                        app.tasks.cancel(task);
+                    }
+                }
+            }
+        }
+        KeyCode::Char('x') => {
+            // Clear completed/failed/cancelled
+            app.downloads.retain(|dl| {
+                dl.status.is_active()
+            });
+            app.index_downloads();
+            if app.download_cursor >= app.downloads.len() {
+                app.download_cursor = app.downloads.len().saturating_sub(1);
+            }
+        }
+        KeyCode::Char('u') => {
+            // Unrestrict a failed download's link again (RD download URLs expire) and restart it
+            let index = app.download_cursor;
+            let Some(dl) = app.downloads.get(index).filter(|d| matches!(d.status, DownloadStatus::Failed(_))) else {
+                return;
+            };
+            let (Some(link), Some(rd_client)) = (dl.rd_link.clone(), app.rd_client.clone()) else {
+                app.status = format!("{} didn't come from Real-Debrid, nothing to renew", dl.filename);
+                return;
+            };
+            let (id, dest_path, category, written) = (dl.id, dl.dest_path.clone(), dl.category, dl.downloaded_bytes);
+            app.status = format!("Getting a fresh link for {}...", dl.filename);
+            let task = app.tasks.spawn("download", async move {
+                match rd_client.unrestrict(&link).await {
+                    Ok((_, url)) => {
+                        let _ = tx.send(AppMessage::LinkRenewed(id, url.clone()));
+                        start_download(url, dest_path, category, id, written, tx).await;
+                    }
+                    Err(e) => {
+                        let _ = tx.send(AppMessage::Notice(format!("Couldn't renew the link: {}", e)));
+                    }
+                }
+            });
+            app.downloads[index].task = Some(task);
+        }
+        KeyCode::Char('v') => {
+            // Recompute the selected file's checksums and compare with the history
+            if let Some(dl) = app.downloads.get(app.download_cursor) {
+                if dl.status == DownloadStatus::Completed {
+                    let id = dl.id;
+                    let path = dl.dest_path.clone();
+                    let tx = tx.clone();
+                    app.status = format!("Verifying {}...", dl.filename);
+
+                    app.tasks.spawn("verify", async move {
+                        let result = tokio::task::spawn_blocking(move || history::verify(&path))
+                            .await
+                            .map_err(|e| e.to_string())
+                            .and_then(|r| r.map_err(|e| e.to_string()));
+                        let _ = tx.send(AppMessage::VerifyResult(id, result));
+                    });
+                } else {
+                    app.status = "Only completed downloads can be verified".to_string();
+                }
+            }
+        }
+        KeyCode::Char('i') => {
+            // Import a magnet list
+            app.import_cursor = app.import_path.len();
+            app.status.clear();
+            app.open(AppMode::ImportPrompt);
+        }
+        KeyCode::Char('+') | KeyCode::Char('=') => throttle::set_limit(throttle::raised(throttle::limit())),
+        KeyCode::Char('-') => {
+            let speed: f64 = app
+                .downloads
+                .iter()
+                .filter(|d| d.status == DownloadStatus::Downloading)
+                .map(|d| d.speed)
+                .sum();
+            throttle::set_limit(throttle::lowered(throttle::limit(), speed as u64));
+        }
+        KeyCode::Char('0') => throttle::set_limit(0),
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.back();
+        }
+        _ => {}
+    }
+}
+
+/// Handle Trakt "Up next" screen keys
+async fn handle_up_next_keys(app: &mut App, code: KeyCode, tx: mpsc::UnboundedSender<AppMessage>) {
+    let count = app.up_next.as_ref().map_or(0, Vec::len);
+    let selected = app.up_next.as_ref().and_then(|items| items.get(app.up_next_cursor)).cloned();
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => app.up_next_cursor = app.up_next_cursor.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.up_next_cursor = (app.up_next_cursor + 1).min(count.saturating_sub(1));
+        }
+        KeyCode::Enter => {
+            // Search for it like a typed query
+            let Some(item) = selected else { return };
+            app.search_input = item.query();
+            app.cursor_pos = app.search_input.len();
+            app.go_home();
+            handle_search_keys(app, KeyCode::Enter, KeyModifiers::NONE, tx).await;
+            if app.mode == AppMode::Processing {
+                app.trakt_search = Some(item);
+            }
+        }
+        KeyCode::Char('g') => {
+            // Grab the best result without looking at the results
+            let Some(item) = selected else { return };
+            app.status = format!("Grabbing the best result for '{}'...", item.query());
+            app.tasks.spawn("trakt grab", trakt_grab(item, tx));
+        }
+        KeyCode::Char('r') => {
+            app.up_next = None;
+            load_up_next(tx);
+        }
+        KeyCode::Esc | KeyCode::Char('q') => app.back(),
+        _ => {}
+    }
+}
+
+/// Handle scraper stats screen keys
+fn handle_stats_keys(app: &mut App, code: KeyCode) {
+    match code {
+        KeyCode::Char('x') => {
+            scrapers::stats::reset();
+            app.status = "Scraper stats reset".to_string();
+        }
+        KeyCode::Char('l') => app.open_log(),
+        KeyCode::Esc | KeyCode::Char('q') => app.back(),
+        _ => {}
+    }
+}
+
+fn handle_log_keys(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+    // Typing into the search filter
+    if app.log_searching {
+        match code {
+            KeyCode::Enter => app.log_searching = false,
+            KeyCode::Esc => {
+                app.log_search.clear();
+                app.log_search_cursor = 0;
+                app.log_searching = false;
+            }
+            _ => {
+                if input::edit(&mut app.log_search, &mut app.log_search_cursor, code, modifiers) {
+                    app.log_scroll = 0;
+                }
+            }
+        }
+        return;
+    }
+
+    let page = log_view_height();
+    let max_scroll = app.filtered_log().len().saturating_sub(page);
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => app.log_scroll = (app.log_scroll + 1).min(max_scroll),
+        KeyCode::Down | KeyCode::Char('j') => app.log_scroll = app.log_scroll.saturating_sub(1),
+        KeyCode::PageUp => app.log_scroll = (app.log_scroll + page).min(max_scroll),
+        KeyCode::PageDown => app.log_scroll = app.log_scroll.saturating_sub(page),
+        KeyCode::Home | KeyCode::Char('g') => app.log_scroll = max_scroll,
+        KeyCode::End | KeyCode::Char('G') => app.log_scroll = 0,
+        KeyCode::Char('/') => app.log_searching = true,
+        KeyCode::Char('f') => {
+            // Cycle the source filter: all, then each source in the log
+            let sources = app.log_sources();
+            let next = match &app.log_source {
+                None => sources.first(),
+                Some(current) => sources.iter().skip_while(|s| *s != current).nth(1),
+            };
+            app.log_source = next.cloned();
+            app.log_scroll = 0;
+        }
+        KeyCode::Char('r') => {
+            // Reload, keeping the filters
+            app.log_lines = scrapers::log::read_recent_logs(LOG_SCREEN_LINES);
+            app.log_scroll = 0;
+        }
+        KeyCode::Char('y') | KeyCode::Char('c') => {
+            // Copy the lines on screen
+            let text = app.log_window(page).join("\n");
+            app.status = if text.is_empty() {
+                "Nothing to copy".to_string()
+            } else {
+                match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text)) {
+                    Ok(_) => format!("Copied {} log lines", app.log_window(page).len()),
+                    Err(e) => format!("Failed to copy: {}", e),
+                }
+            };
+        }
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.log_searching = false;
+            app.back();
+        }
+        _ => {}
+    }
+}
+
+/// Rows available for log lines (terminal height minus header, status bar and borders)
+fn log_view_height() -> usize {
+    crossterm::terminal::size()
+        .map(|(_, rows)| rows.saturating_sub(8) as usize)
+        .unwrap_or(20)
+        .max(1)
+}
+
+/// Start all pending downloads
+fn start_pending_downloads(app: &mut App, tx: &mpsc::UnboundedSender<AppMessage>) {
+    for dl in app.downloads.iter_mut() {
+        if dl.status == DownloadStatus::Pending {
+            dl.status = DownloadStatus::Downloading;
+            let url = dl.url.clone();
+            let dest_path = dl.dest_path.clone();
+            let category = dl.category;
+            let id = dl.id;
+            let written = dl.downloaded_bytes;
+            let tx = tx.clone();
+
+            dl.task = Some(app.tasks.spawn("download", async move {
+                start_download(url, dest_path, category, id, written, tx).await;
+            }));
+        }
+    }
+}
+
+/// Answer Telegram bot commands until the app exits
+async fn run_telegram(bot: telegram::Bot, tx: mpsc::UnboundedSender<AppMessage>) {
+    let mut offset = 0;
+    loop {
+        let commands = match bot.next_commands(&mut offset).await {
+            Ok(commands) => commands,
+            Err(e) => {
+                scrapers::log_error("telegram", &e.to_string());
+                tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                continue;
+            }
+        };
+
+        for command in commands {
+            match command {
+                telegram::Command::Search(query) => {
+                    let bot = bot.clone();
+                    tokio::spawn(async move {
+                        let results = grab_search(&query).await;
+                        let reply = if results.is_empty() {
+                            format!("No results for '{}'", query)
+                        } else {
+                            results
+                                .iter()
+                                .take(5)
+                                .enumerate()
+                                .map(|(i, r)| format!("{}. {} ({}, {} seeders, {})", i + 1, r.name, r.size, r.seeders, r.source))
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        };
+                        bot.notify(reply);
+                    });
+                }
+                telegram::Command::Grab(query) => {
+                    tokio::spawn(telegram_grab(bot.clone(), query, tx.clone()));
+                }
+                telegram::Command::Status => {
+                    let _ = tx.send(AppMessage::TelegramStatus);
+                }
+                telegram::Command::Help => bot.notify(telegram::HELP.to_string()),
+            }
+        }
+    }
+}
+
+/// Search the enabled sources for a one-key grab (Telegram bot, Trakt "Up next"). Results with an excluded codec are dropped;
+/// the rest are ordered by preferred codecs, then seeders.
+async fn grab_search(query: &str) -> Vec<TorrentResult> {
+    let (mut results, _) = run_search(query, 1, &scrapers::default_enabled(), false, false, &CancellationToken::new()).await;
+    let policy = release::CodecPolicy::from_env();
+    results.retain(|r| !r.magnet.is_empty() && policy.allows(&r.name));
+    results.sort_by_key(|r| std::cmp::Reverse((policy.score(&r.name), r.seeders)));
+    results
+}
+
+/// Send the best result for `query` (see [`grab_search`]) through Real-Debrid (all files) and queue its downloads
+async fn telegram_grab(bot: telegram::Bot, query: String, tx: mpsc::UnboundedSender<AppMessage>) {
+    let Some(best) = grab_search(&query).await.into_iter().next() else {
+        bot.notify(format!("No results for '{}'", query));
+        return;
+    };
+    let rd_client = match RealDebridClient::new() {
+        Ok(client) => client,
+        Err(e) => {
+            bot.notify(format!("Real-Debrid not configured: {}", e));
+            return;
+        }
+    };
+
+    bot.notify(format!("Grabbing {} ({} seeders)", best.name, best.seeders));
+    match import::resolve(&rd_client, &import::Entry::Magnet(best.magnet.clone()), |_| {}).await {
+        Ok(links) => {
+            bot.notify(format!("Downloading {} file(s) from {}", links.len(), best.name));
+            let _ = tx.send(AppMessage::RemoteGrab(best.name, links));
+        }
+        Err(e) => bot.notify(format!("Grab failed: {}", e)),
+    }
+}
+
+/// Load the Trakt "Up next" entries in the background
+fn load_up_next(tx: mpsc::UnboundedSender<AppMessage>) {
+    tokio::spawn(async move {
+        let _ = tx.send(AppMessage::UpNext(trakt::up_next().await.map_err(|e| e.to_string())));
+    });
+}
+
+/// Send the best result for a Trakt entry through Real-Debrid (all files) and queue its downloads
+async fn trakt_grab(item: trakt::Item, tx: mpsc::UnboundedSender<AppMessage>) {
+    let query = item.query();
+    let Some(best) = grab_search(&query).await.into_iter().next() else {
+        let _ = tx.send(AppMessage::Notice(format!("No results for '{}'", query)));
+        return;
+    };
+    let result = match RealDebridClient::new() {
+        Ok(rd_client) => import::resolve(&rd_client, &import::Entry::Magnet(best.magnet.clone()), |_| {}).await,
+        Err(e) => Err(e),
+    };
+    let message = match result {
+        Ok(links) => AppMessage::TraktGrab(item, best.name, links),
+        Err(e) => AppMessage::Notice(format!("Grab failed for '{}': {}", query, e)),
+    };
+    let _ = tx.send(message);
+}
+
+/// Mark a download's Trakt entry collected (TRAKT_COLLECT) once none of its files are
+/// still downloading
+fn mark_collected(app: &mut App, id: DownloadId) {
+    let Some(item) = app.download(id).and_then(|dl| dl.trakt.clone()) else { return };
+    let pending = app.downloads.iter().any(|dl| dl.trakt.as_ref() == Some(&item) && dl.status.is_active());
+    if pending || !trakt::collect_enabled() {
+        return;
+    }
+    app.tasks.spawn("trakt collect", async move {
+        match trakt::collect(&item).await {
+            Ok(()) => scrapers::log_info("trakt", &format!("Marked {} collected", item.query())),
+            Err(e) => scrapers::log_error("trakt", &format!("Failed to mark {} collected: {}", item.query(), e)),
+        }
+    });
+}
+
+/// Start downloading a file in the background. `written` is how much of `dest_path` this
+/// download wrote itself on an earlier attempt; that part is resumed rather than put
+/// through ON_FILE_EXISTS.
+async fn start_download(
+    url: String,
+    dest_path: PathBuf,
+    category: Category,
+    id: DownloadId,
+    written: u64,
+    tx: mpsc::UnboundedSender<AppMessage>,
+) {
+    let client = http::transfer_client();
+
+    // Stream to a remote destination instead of local disk
+    let remote_target = remote::from_env().map(|base| {
+        let filename = dest_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        remote::target(&base, &filename)
+    });
+
+    // Apply the collision policy if the destination already exists
+    let mut dest_path = dest_path;
+    let mut existing: u64 = 0;
+    if remote_target.is_some() {
+        // Collision policies only apply to local files
+    } else if let Ok(meta) = tokio::fs::metadata(&dest_path).await {
+        if written > 0 {
+            // Our own partial file: drop anything past what was counted and carry on
+            let kept = written.min(meta.len());
+            let truncated = std::fs::OpenOptions::new().write(true).open(&dest_path).and_then(|f| f.set_len(kept));
+            existing = if truncated.is_ok() { kept } else { 0 };
+        } else {
+            match collision::Policy::from_env() {
+                collision::Policy::Overwrite => {}
+                collision::Policy::Skip => {
+                    let _ = tx.send(AppMessage::DownloadSkipped(id));
+                    return;
+                }
+                collision::Policy::Rename => {
+                    dest_path = collision::unique_path(&dest_path);
+                    let _ = tx.send(AppMessage::DownloadRenamed(id, dest_path.clone()));
+                }
+                collision::Policy::Resume => existing = meta.len(),
+            }
+        }
+    }
+
+    let mut downloaded: u64 = existing;
+    let mut url = url;
+    let slow_threshold = slowlink::threshold();
+    let mut slow_retries = 0;
+    loop {
+        // Hold local downloads while the disk guard has transfers paused
+        if remote_target.is_none() && diskguard::is_paused() {
+            let _ = tx.send(AppMessage::DownloadPaused(id));
+            diskguard::wait_until_resumed().await;
+        }
+
+        // Start the download (or pick it up where a pause left off)
+        let mut request = client.get(&url);
+        if downloaded > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", downloaded));
+        }
+        let response = match request.send().await {
+            Ok(resp) => resp,
+            Err(e) => {
+                let _ = tx.send(AppMessage::DownloadFailed(id, e.to_string()));
+                return;
+            }
+        };
+
+        // Nothing left to fetch: the existing file is already complete
+        if downloaded > 0 && response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+            let _ = tx.send(AppMessage::DownloadProgress { id, downloaded, total: downloaded, speed: 0.0 });
+            let _ = tx.send(AppMessage::DownloadComplete(id));
+            let dest_path = organize_download(id, dest_path, category, &tx).await;
+            record_completion(id, dest_path, tx).await;
+            return;
+        }
+
+        // Unrestricted links stop working after a while
+        let status = response.status();
+        if !status.is_success() {
+            let error = if matches!(status.as_u16(), 403 | 404 | 410) {
+                format!("Link expired (HTTP {}) - press 'u' for a fresh one", status.as_u16())
+            } else {
+                format!("HTTP {}", status)
+            };
+            let _ = tx.send(AppMessage::DownloadFailed(id, error));
+            return;
+        }
+
+        // A server that ignores the range sends the whole file, so start over
+        let resumed = status == reqwest::StatusCode::PARTIAL_CONTENT;
+        if !resumed {
+            downloaded = 0;
+        }
+        let total_size = response.content_length().map(|len| len + downloaded).unwrap_or(0);
+
+        // Create the file (or append to it when resuming), or start the remote upload
+        let sink = match &remote_target {
+            Some(target) => remote::Sink::remote(target),
+            None => remote::Sink::file(&dest_path, resumed).await,
+        };
+        let mut sink = match sink {
+            Ok(s) => s,
+            Err(e) => {
+                let _ = tx.send(AppMessage::DownloadFailed(id, e.to_string()));
+                return;
+            }
+        };
+
+        let mut last_update = std::time::Instant::now();
+        let mut last_downloaded: u64 = downloaded;
+        let mut paused = false;
+        // Speed over the last SLOW_TRANSFER_SECONDS, to spot a server that crawls
+        let mut window_start = last_update;
+        let mut window_downloaded = downloaded;
+        let mut slow = false;
+
+        let mut stream = response.bytes_stream();
+        // Switch servers when one crawls (local files only: they resume on the new link)
+        let watch_slow = slow_threshold.filter(|_| remote_target.is_none() && slow_retries < slowlink::MAX_RETRIES);
+
+        loop {
+            // Wake up when the window ends even if nothing arrives, so a stalled stream counts too
+            let next = match watch_slow {
+                Some(threshold) => {
+                    let deadline = tokio::time::Instant::from_std(window_start + threshold.after);
+                    tokio::time::timeout_at(deadline, stream.next()).await.ok()
+                }
+                None => Some(stream.next().await),
+            };
+
+            match next {
+                None => {}
+                Some(None) => break,
+                Some(Some(Ok(chunk))) => {
+                    // Write chunk to file
+                    if let Err(e) = sink.write_all(&chunk).await {
+                        let _ = tx.send(AppMessage::DownloadFailed(id, e.to_string()));
+                        return;
+                    }
+
+                    downloaded += chunk.len() as u64;
+                    throttle::consume(chunk.len()).await;
+
+                    // Report progress every 100ms
+                    let now = std::time::Instant::now();
+                    if now.duration_since(last_update).as_millis() >= 100 {
+                        let elapsed = now.duration_since(last_update).as_secs_f64();
+                        let speed = (downloaded - last_downloaded) as f64 / elapsed;
+
+                        let _ = tx.send(AppMessage::DownloadProgress {
+                            id,
+                            downloaded,
+                            total: total_size,
+                            speed,
+                        });
+
+                        last_update = now;
+                        last_downloaded = downloaded;
+                    }
+
+                    // Stop before the disk fills up; the next pass resumes with a range request
+                    if remote_target.is_none() && diskguard::is_paused() {
+                        paused = true;
+                        break;
+                    }
+                }
+                Some(Some(Err(e))) => {
+                    let _ = tx.send(AppMessage::DownloadFailed(id, e.to_string()));
+                    return;
+                }
+            }
+
+            if let Some(threshold) = watch_slow {
+                let now = std::time::Instant::now();
+                let elapsed = now.duration_since(window_start);
+                if elapsed >= threshold.after {
+                    let speed = (downloaded - window_downloaded) as f64 / elapsed.as_secs_f64();
+                    if slowlink::is_slow(threshold, speed) {
+                        slow = true;
+                        break;
+                    }
+                    window_start = now;
+                    window_downloaded = downloaded;
+                }
+            }
+        }
+
+        // Final sync (or wait for the remote upload to finish)
+        if let Err(e) = sink.finish().await {
+            let _ = tx.send(AppMessage::DownloadFailed(id, e.to_string()));
+            return;
+        }
+
+        // A slow server: get a fresh link, which may point elsewhere, and resume on it
+        if slow {
+            slow_retries += 1;
+            match slowlink::fresh_url(&url).await {
+                Some(fresh) => {
+                    let _ = tx.send(AppMessage::LinkRenewed(id, fresh.clone()));
+                    let name = dest_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                    let _ = tx.send(AppMessage::Notice(format!("{} was slow, resuming on a fresh Real-Debrid link", name)));
+                    url = fresh;
+                }
+                // Nothing to switch to: carry on with the same link
+                None => slow_retries = slowlink::MAX_RETRIES,
+            }
+            continue;
+        }
+
+        if !paused {
+            break;
+        }
+    }
+
+    let _ = tx.send(AppMessage::DownloadComplete(id));
+    // History and checksums need the file on local disk
+    if remote_target.is_none() {
+        let dest_path = organize_download(id, dest_path, category, &tx).await;
+        record_completion(id, dest_path, tx).await;
+    }
+}
+
+/// Move a completed TV episode into its show and season folder when ORGANIZE_TV is
+/// set (or just say where it would go in dry-run mode). Returns where the file is now.
+async fn organize_download(
+    id: DownloadId,
+    path: PathBuf,
+    category: Category,
+    tx: &mpsc::UnboundedSender<AppMessage>,
+) -> PathBuf {
+    let mode = organize::Mode::from_env();
+    if category != Category::Tv || mode == organize::Mode::Off {
+        return path;
+    }
+    let tv_dir = category_dir(Category::Tv);
+    let Some(target) = organize::target(&tv_dir, &path) else {
+        return path;
+    };
+    let shown = target.strip_prefix(&tv_dir).unwrap_or(&target).display().to_string();
+    if mode == organize::Mode::DryRun {
+        let _ = tx.send(AppMessage::Notice(format!("Would move to {} (ORGANIZE_TV=dry-run)", shown)));
+        return path;
+    }
+
+    let (from, to) = (path.clone(), target.clone());
+    match tokio::task::spawn_blocking(move || organize::move_file(&from, &to)).await {
+        Ok(Ok(())) => {
+            let _ = tx.send(AppMessage::DownloadRenamed(id, target.clone()));
+            let _ = tx.send(AppMessage::Notice(format!("Moved to {}", shown)));
+            target
+        }
+        Ok(Err(e)) => {
+            scrapers::log_error("organize", &format!("Failed to organize {}: {:#}", path.display(), e));
+            path
+        }
+        Err(e) => {
+            scrapers::log_error("organize", &format!("Failed to organize {}: {}", path.display(), e));
+            path
+        }
+    }
+}
+
+/// Directory downloads are saved to (DOWNLOAD_DIR, else the system download directory)
+fn download_dir() -> PathBuf {
+    std::env::var("DOWNLOAD_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| dirs::download_dir().unwrap_or_else(|| PathBuf::from(".")))
+}
+
+/// Where downloads of `category` are saved: its CATEGORY_DIRS entry, or the download directory
+fn category_dir(category: Category) -> PathBuf {
+    category.dir().unwrap_or_else(download_dir)
+}
+
+/// Check free space on the download volume and pause local downloads while it's
+/// below MIN_FREE_SPACE. Resumes once there's 10% headroom above the threshold.
+async fn watch_disk_space(tx: mpsc::UnboundedSender<AppMessage>) {
+    let mut interval = tokio::time::interval(diskguard::CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let threshold = diskguard::threshold();
+        if threshold == 0 || remote::from_env().is_some() {
+            if diskguard::is_paused() {
+                diskguard::set_paused(false);
+                let _ = tx.send(AppMessage::DiskSpace(None));
+            }
+            continue;
+        }
+        let Some(free) = diskguard::free_space(&download_dir()) else {
+            continue;
+        };
+
+        if free < threshold {
+            diskguard::set_paused(true);
+            let _ = tx.send(AppMessage::DiskSpace(Some(free)));
+        } else if diskguard::is_paused() && free >= threshold + threshold / 10 {
+            diskguard::set_paused(false);
+            let _ = tx.send(AppMessage::DiskSpace(None));
+        } else if diskguard::is_paused() {
+            let _ = tx.send(AppMessage::DiskSpace(Some(free)));
+        }
+    }
+}
+
+/// Add a completed download to the history, with checksums if CHECKSUMS is enabled
+async fn record_completion(id: DownloadId, path: PathBuf, tx: mpsc::UnboundedSender<AppMessage>) {
+    let with_checksums = history::checksums_enabled();
+    let result = tokio::task::spawn_blocking(move || {
+        let checksums = if with_checksums {
+            Some(history::compute_checksums(&path)?)
+        } else {
+            None
+        };
+        let sha1 = checksums.as_ref().map(|(sha1, _)| sha1.clone());
+        history::record(&path, checksums)?;
+        anyhow::Ok(sha1)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(sha1)) => {
+            let _ = tx.send(AppMessage::HistoryRecorded);
+            if let Some(sha1) = sha1 {
+                let _ = tx.send(AppMessage::ChecksumRecorded(id, sha1));
+            }
+        }
+        Ok(Err(e)) => scrapers::log_error("history", &format!("Failed to record download: {}", e)),
+        Err(e) => scrapers::log_error("history", &format!("Failed to record download: {}", e)),
+    }
 }
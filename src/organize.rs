@@ -0,0 +1,98 @@
+//! Episode organizer: completed TV downloads are moved into `Show Name/Season 02/`
+//! folders under the TV directory (ORGANIZE_TV), and `littlejohn organize` tidies
+//! episodes already sitting there
+
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+
+use crate::release;
+
+/// What to do with completed TV downloads
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Off,
+    /// Only report where files would go
+    DryRun,
+    Move,
+}
+
+impl Mode {
+    /// From ORGANIZE_TV: true to move episodes, dry-run to only report the moves
+    pub fn from_env() -> Self {
+        match std::env::var("ORGANIZE_TV").unwrap_or_default().trim().to_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Mode::Move,
+            "dry-run" | "dryrun" | "preview" => Mode::DryRun,
+            _ => Mode::Off,
+        }
+    }
+}
+
+/// Where an episode file in `tv_dir` belongs, e.g. `<tv_dir>/Show Name/Season 02/<file>`.
+/// None if the name isn't an episode.
+pub fn target(tv_dir: &Path, file: &Path) -> Option<PathBuf> {
+    let filename = file.file_name()?.to_string_lossy();
+    let (show, season, _) = release::episode(&filename)?;
+    let show = crate::paths::sanitize(&show, cfg!(windows));
+    Some(tv_dir.join(show).join(format!("Season {:02}", season)).join(filename.as_ref()))
+}
+
+/// Move `from` to `to`, creating its folders. Copies and deletes when a rename can't
+/// cross filesystems; never replaces an existing file.
+pub fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if to.exists() {
+        bail!("{} already exists", to.display());
+    }
+    if let Some(dir) = to.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    if std::fs::rename(from, to).is_err() {
+        std::fs::copy(from, to).with_context(|| format!("Failed to copy {} to {}", from.display(), to.display()))?;
+        std::fs::remove_file(from).with_context(|| format!("Failed to remove {}", from.display()))?;
+    }
+    Ok(())
+}
+
+/// Moves for the episode files directly in `tv_dir` (already organized ones are in
+/// subfolders, so they're left alone)
+pub fn plan(tv_dir: &Path) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let entries = std::fs::read_dir(tv_dir).with_context(|| format!("Failed to read {}", tv_dir.display()))?;
+    let mut moves: Vec<(PathBuf, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            let path = entry.path();
+            let to = target(tv_dir, &path)?;
+            Some((path, to))
+        })
+        .collect();
+    moves.sort();
+    Ok(moves)
+}
+
+/// `littlejohn organize [--dry-run]`: sort the episodes in the TV directory into show
+/// and season folders, or with `dry_run` list the moves without making them
+pub fn run(tv_dir: &Path, dry_run: bool) -> Result<()> {
+    let moves = plan(tv_dir)?;
+    if moves.is_empty() {
+        println!("No episodes to organize in {}", tv_dir.display());
+        return Ok(());
+    }
+    let mut failed = 0;
+    for (from, to) in &moves {
+        let name = to.strip_prefix(tv_dir).unwrap_or(to);
+        if dry_run {
+            println!("{} -> {}", from.display(), name.display());
+        } else if let Err(e) = move_file(from, to) {
+            eprintln!("Failed to move {}: {:#}", from.display(), e);
+            failed += 1;
+        } else {
+            println!("Moved to {}", name.display());
+        }
+    }
+    if dry_run {
+        println!("{} episode(s) would be moved (dry run)", moves.len());
+    } else if failed > 0 {
+        bail!("{} of {} episode(s) could not be moved", failed, moves.len());
+    }
+    Ok(())
+}
@@ -0,0 +1,83 @@
+//! Safe destination paths for download filenames from Real-Debrid
+
+use std::path::{Path, PathBuf};
+
+/// Longest filename most filesystems accept, in bytes
+const MAX_NAME_BYTES: usize = 255;
+
+/// Paths this long need the `\\?\` prefix on Windows
+const WINDOWS_MAX_PATH: usize = 260;
+
+/// Device names Windows reserves in every directory, with or without an extension
+const WINDOWS_RESERVED: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+    "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Where to save `filename` in `dir`: the name made safe for this platform, with
+/// Windows long-path support
+pub fn dest_path(dir: &Path, filename: &str) -> PathBuf {
+    long_path(&dir.join(sanitize(filename, cfg!(windows))))
+}
+
+/// Make a filename safe to create: no path separators or control characters, not
+/// `.`/`..`, and at most 255 bytes (keeping the extension). With `windows`, characters
+/// NTFS rejects become `_`, trailing dots and spaces are dropped and reserved device
+/// names get a leading `_`.
+pub fn sanitize(filename: &str, windows: bool) -> String {
+    let mut name: String = filename
+        .chars()
+        .map(|c| {
+            let illegal = c == '/' || c.is_control() || (windows && matches!(c, '<' | '>' | ':' | '"' | '\\' | '|' | '?' | '*'));
+            if illegal { '_' } else { c }
+        })
+        .collect();
+
+    if windows {
+        name = name.trim_end_matches(['.', ' ']).to_string();
+        let stem = name.split('.').next().unwrap_or("").trim_end();
+        if WINDOWS_RESERVED.iter().any(|r| r.eq_ignore_ascii_case(stem)) {
+            name.insert(0, '_');
+        }
+    }
+
+    let name = name.trim();
+    if name.is_empty() || name == "." || name == ".." {
+        return "download".to_string();
+    }
+    truncate(name)
+}
+
+/// Shorten a name to MAX_NAME_BYTES, keeping a short extension
+fn truncate(name: &str) -> String {
+    if name.len() <= MAX_NAME_BYTES {
+        return name.to_string();
+    }
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && ext.len() <= 16 => (stem, format!(".{}", ext)),
+        _ => (name, String::new()),
+    };
+    let mut end = MAX_NAME_BYTES - ext.len();
+    while !stem.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{}", &stem[..end], ext)
+}
+
+/// On Windows, give absolute paths past MAX_PATH the `\\?\` prefix so they can be
+/// created; elsewhere the path is unchanged
+pub fn long_path(path: &Path) -> PathBuf {
+    if !cfg!(windows) {
+        return path.to_path_buf();
+    }
+    let text = path.to_string_lossy();
+    if text.len() < WINDOWS_MAX_PATH || text.starts_with(r"\\?\") || !path.is_absolute() {
+        return path.to_path_buf();
+    }
+    // The prefix turns off path normalization, so separators must be backslashes
+    let text = text.replace('/', "\\");
+    match text.strip_prefix(r"\\") {
+        Some(unc) => PathBuf::from(format!(r"\\?\UNC\{}", unc)),
+        None => PathBuf::from(format!(r"\\?\{}", text)),
+    }
+}
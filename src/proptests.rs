@@ -0,0 +1,179 @@
+//! Property checks for the formatting and parsing helpers: generated inputs, plus edge
+//! values (negative, NaN, huge, multi-byte), must not panic and must keep each helper's
+//! guarantees. A failure is shrunk to a minimal input and saved under
+//! `proptest-regressions/` so it's tried again on later runs.
+
+use crate::{diskguard, format_bytes, format_time, scrapers, throttle, ui};
+use proptest::prelude::*;
+use proptest::sample::select;
+
+/// Characters strings are built from: digits, unit letters, separators and multi-byte ones
+const ALPHABET: &[char] = &[
+    '0', '1', '5', '9', '.', '-', ' ', 'K', 'M', 'G', 'T', 'B', 'i', 'k', 'b', 'x', '=', ':', 'é', 'ß', '€', '日', '😀',
+];
+
+const EDGE_F64: &[f64] = &[
+    0.0,
+    -0.0,
+    -1.0,
+    0.05,
+    59.999,
+    60.0,
+    1023.95,
+    1024.0,
+    3599.999,
+    3600.0,
+    1e15,
+    f64::MIN_POSITIVE,
+    f64::MAX,
+    f64::MIN,
+    f64::NAN,
+    f64::INFINITY,
+    f64::NEG_INFINITY,
+];
+
+/// Any f64: the edge values, or any bit pattern including NaNs, infinities and subnormals
+fn any_f64() -> impl Strategy<Value = f64> {
+    prop_oneof![select(EDGE_F64), proptest::num::f64::ANY]
+}
+
+/// A finite value in [0, max), spread over the orders of magnitude
+fn magnitude(max: f64) -> impl Strategy<Value = f64> {
+    (0.0..max.log10()).prop_map(|exponent| 10f64.powf(exponent) - 1.0)
+}
+
+/// Up to `max_len` characters from ALPHABET
+fn text(max_len: usize) -> impl Strategy<Value = String> {
+    proptest::collection::vec(select(ALPHABET), 0..=max_len).prop_map(String::from_iter)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// RFC 4648 base32 without padding, as used in older magnet links
+fn base32(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    let mut out = String::new();
+    let (mut bits, mut count) = (0u64, 0);
+    for &byte in bytes {
+        bits = (bits << 8) | byte as u64;
+        count += 8;
+        while count >= 5 {
+            count -= 5;
+            out.push(ALPHABET[((bits >> count) & 31) as usize] as char);
+        }
+    }
+    out
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2000))]
+
+    #[test]
+    fn format_bytes_always_has_a_unit(value in any_f64()) {
+        let text = format_bytes(value);
+        prop_assert!(["B", "KB", "MB", "GB", "TB", "PB"].contains(&text.rsplit(' ').next().unwrap()), "{}", text);
+    }
+
+    #[test]
+    fn format_bytes_keeps_the_magnitude(value in magnitude(1e15)) {
+        let text = format_bytes(value);
+        let (number, unit) = text.split_once(' ').unwrap();
+        let scale = 1024f64.powi(["B", "KB", "MB", "GB", "TB"].iter().position(|u| *u == unit).unwrap() as i32);
+        let number: f64 = number.parse().unwrap();
+        prop_assert!(number <= 1024.0, "{}", text);
+        prop_assert!((number * scale - value).abs() <= 0.05 * scale + 1e-6, "{}", text);
+    }
+
+    #[test]
+    fn format_time_takes_any_value(value in any_f64()) {
+        let _ = format_time(value);
+    }
+
+    #[test]
+    fn format_time_splits_into_valid_parts(seconds in magnitude(1e7)) {
+        let text = format_time(seconds);
+        let parts: Vec<u64> = text
+            .split(' ')
+            .map(|part| part.trim_end_matches(['h', 'm', 's']).parse().unwrap())
+            .collect();
+        let whole = seconds as u64;
+        match (text.ends_with('s'), parts.as_slice()) {
+            (true, [s]) => prop_assert_eq!(*s, whole, "{}", text),
+            (true, [m, s]) => {
+                prop_assert!(*s < 60 && *m < 60, "{}", text);
+                prop_assert_eq!(m * 60 + s, whole, "{}", text);
+            }
+            (false, [h, m]) => {
+                prop_assert!(*m < 60, "{}", text);
+                prop_assert_eq!(h * 3600 + m * 60, whole - whole % 60, "{}", text);
+            }
+            _ => prop_assert!(false, "{}", text),
+        }
+    }
+
+    #[test]
+    fn parse_size_takes_any_text(input in text(12)) {
+        let _ = diskguard::parse_size(&input);
+    }
+
+    #[test]
+    fn parse_size_reads_back_every_unit(
+        number in 0u64..1 << 20,
+        (unit, shift) in select(&[("", 0), ("b", 0), ("K", 10), ("kb", 10), ("KiB", 10), ("M", 20), ("MB", 20), ("g", 30), ("GiB", 30), ("T", 40), ("tb", 40)][..]),
+        space in select(&["", " "][..]),
+    ) {
+        let text = format!("{}{}{}", number, space, unit);
+        prop_assert_eq!(diskguard::parse_size(&text), Some(number << shift), "{:?}", text);
+        if number > 0 {
+            prop_assert_eq!(diskguard::parse_size(&format!("-{}", text)), None, "-{:?}", text);
+        }
+    }
+
+    #[test]
+    fn speed_cap_steps_stay_on_the_grid(current in 0u64..1 << 32, speed in 0u64..1 << 32) {
+        let up = throttle::raised(current);
+        let down = throttle::lowered(current, speed);
+        prop_assert!(down >= throttle::STEP && down.is_multiple_of(throttle::STEP), "{}", down);
+        if current == 0 {
+            prop_assert_eq!(up, 0);
+        } else {
+            prop_assert!(up > current && up.is_multiple_of(throttle::STEP), "{}", up);
+            prop_assert!(down < current || current <= throttle::STEP, "{}", down);
+            let floor = (current / throttle::STEP * throttle::STEP).max(throttle::STEP);
+            prop_assert_eq!(throttle::lowered(up, speed), floor);
+        }
+    }
+
+    #[test]
+    fn magnet_info_hash_takes_any_text(hash in text(48), other in text(30)) {
+        let _ = scrapers::magnet_info_hash(&format!("magnet:?xt=urn:btih:{}", hash));
+        let _ = scrapers::magnet_info_hash(&other);
+    }
+
+    #[test]
+    fn magnet_info_hash_normalizes_hex_and_base32(hash in any::<[u8; 20]>(), name in text(20)) {
+        for encoded in [hex(&hash), hex(&hash).to_uppercase(), base32(&hash)] {
+            let magnet = format!("magnet:?xt=urn:btih:{}&dn={}", encoded, name);
+            prop_assert_eq!(scrapers::magnet_info_hash(&magnet), Some(hex(&hash)), "{}", magnet);
+        }
+    }
+
+    #[test]
+    fn truncate_fits_and_keeps_the_start(input in text(40), max_len in 0usize..45) {
+        let short = ui::truncate(&input, max_len);
+        let count = input.chars().count();
+
+        prop_assert!(short.chars().count() <= max_len, "{:?}", short);
+        if count <= max_len {
+            prop_assert_eq!(short, input);
+        } else if max_len > 3 {
+            let kept = short.strip_suffix("...").unwrap();
+            prop_assert_eq!(kept.chars().count(), max_len - 3);
+            prop_assert!(input.starts_with(kept));
+        } else {
+            prop_assert!(input.starts_with(&short));
+        }
+    }
+}
@@ -4,9 +4,39 @@ use anyhow::{anyhow, Result};
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::env;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 const BASE_URL: &str = "https://api.real-debrid.com/rest/1.0";
 
+/// Hoster link each download URL was unrestricted from, by download URL
+static SOURCE_LINKS: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+
+fn source_links() -> &'static Mutex<HashMap<String, String>> {
+    SOURCE_LINKS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// The hoster link a download URL came from, to unrestrict again once the URL expires
+pub fn source_link(download_url: &str) -> Option<String> {
+    source_links().lock().ok()?.get(download_url).cloned()
+}
+
+/// How long a fetched host status list is trusted
+const HOSTS_TTL: Duration = Duration::from_secs(300);
+
+/// Status of each hoster on RD (by domain) and when it was fetched
+static HOST_STATUS: Mutex<Option<(Instant, HashMap<String, HostStatus>)>> = Mutex::new(None);
+
+/// A hoster's entry in `/hosts/status`
+#[derive(Debug, Clone, Deserialize)]
+struct HostStatus {
+    #[serde(default)]
+    name: String,
+    /// "up", "down" or "unsupported"
+    #[serde(default)]
+    status: String,
+}
+
 /// A file in a torrent
 #[derive(Debug, Clone)]
 pub struct TorrentFile {
@@ -57,6 +87,8 @@ struct ApiTorrentFile {
 #[allow(dead_code)]
 struct TorrentInfo {
     id: String,
+    #[serde(default)]
+    filename: String,
     status: String,
     files: Option<Vec<ApiTorrentFile>>,
     links: Option<Vec<String>>,
@@ -65,6 +97,17 @@ struct TorrentInfo {
     seeders: Option<u32>,
 }
 
+/// A torrent already on the account
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExistingTorrent {
+    pub id: String,
+    pub filename: String,
+    pub hash: String,
+    pub status: String,
+    #[serde(default)]
+    pub progress: f64,
+}
+
 /// Real-Debrid unrestrict response
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
@@ -81,6 +124,122 @@ struct ErrorResponse {
     error_code: Option<i32>,
 }
 
+/// Torrents fetched per page when searching the account
+const TORRENTS_PAGE: usize = 1000;
+
+/// Real-Debrid's error code for an invalid or expired token
+const BAD_TOKEN: i32 = 8;
+
+/// Authentication failure (expired or revoked token)
+#[derive(Debug)]
+pub struct AuthError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Real-Debrid rejected the API token (HTTP {}): {}", self.status, self.message)
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// RD did not finish within the configured wait; the torrent is left on the account
+#[derive(Debug)]
+pub struct WaitTimeout {
+    pub torrent_id: String,
+    /// RD status when the wait ran out
+    pub status: String,
+    pub progress: f64,
+}
+
+impl std::fmt::Display for WaitTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Timed out waiting for Real-Debrid ({} {:.0}%)", self.status, self.progress)
+    }
+}
+
+impl std::error::Error for WaitTimeout {}
+
+/// RD-side download progress of an uncached torrent
+#[derive(Debug, Clone)]
+pub struct RdProgress {
+    /// Torrent name on RD
+    pub name: String,
+    pub status: String,
+    /// Percent (0-100)
+    pub progress: f64,
+    /// Bytes per second
+    pub speed: u64,
+    pub seeders: u32,
+    pub elapsed: Duration,
+    /// Time left before the wait times out
+    pub remaining: Duration,
+}
+
+impl std::fmt::Display for RdProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RD {}: {:.0}%", self.status, self.progress)?;
+        if self.speed > 0 {
+            write!(f, " {:.1} MB/s", self.speed as f64 / 1_000_000.0)?;
+        }
+        write!(
+            f,
+            " ({} seeders)\n{} elapsed, {} left",
+            self.seeders,
+            clock(self.elapsed),
+            clock(self.remaining)
+        )
+    }
+}
+
+/// Update reported while waiting for download links
+pub enum RdUpdate<'a> {
+    Status(&'a str),
+    /// RD is still fetching the torrent
+    Progress(&'a RdProgress),
+}
+
+/// Seconds from an env var, falling back to a default
+fn env_secs(key: &str, default: u64) -> Duration {
+    let secs = env::var(key)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|s: &u64| *s > 0)
+        .unwrap_or(default);
+    Duration::from_secs(secs)
+}
+
+/// Format a duration as m:ss
+pub fn clock(d: Duration) -> String {
+    format!("{}:{:02}", d.as_secs() / 60, d.as_secs() % 60)
+}
+
+/// Check whether an error was caused by an invalid or expired token
+pub fn is_auth_error(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<AuthError>().is_some()
+}
+
+/// Torrent ID in a pasted ID or real-debrid.com link (e.g.
+/// `https://real-debrid.com/torrents/info/ABCDEFGHIJ234`): 13 upper-case letters and
+/// digits, as the API and website show them
+pub fn torrent_id(input: &str) -> Option<String> {
+    let input = input.trim();
+    let is_id = |s: &str| s.len() == 13 && s.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+    if is_id(input) {
+        return Some(input.to_string());
+    }
+    let rest = input
+        .strip_prefix("https://")
+        .or_else(|| input.strip_prefix("http://"))?
+        .trim_start_matches("www.");
+    if !rest.starts_with("real-debrid.com/") {
+        return None;
+    }
+    rest.split(['/', '?', '&', '=', '#']).rev().find(|part| is_id(part)).map(String::from)
+}
+
 /// Real-Debrid user info
 #[derive(Debug, Deserialize)]
 pub struct UserInfo {
@@ -90,11 +249,33 @@ pub struct UserInfo {
     pub account_type: String,
 }
 
+/// Convert the API file list, keeping RD's selection state
+fn torrent_files(files: Option<Vec<ApiTorrentFile>>) -> Vec<TorrentFile> {
+    files
+        .unwrap_or_default()
+        .into_iter()
+        .map(|f| TorrentFile {
+            id: f.id,
+            path: f.path,
+            bytes: f.bytes,
+            selected: f.selected.unwrap_or(0) == 1,
+        })
+        .collect()
+}
+
 /// Real-Debrid API client
 #[derive(Debug, Clone)]
 pub struct RealDebridClient {
     api_token: String,
     client: reqwest::Client,
+    /// API root, BASE_URL unless overridden (RD_API_URL, e.g. a local mock server)
+    base_url: String,
+    /// How long to wait for a magnet to resolve (RD_MAGNET_TIMEOUT)
+    magnet_timeout: Duration,
+    /// How long to wait for RD to download the torrent (RD_DOWNLOAD_TIMEOUT)
+    download_timeout: Duration,
+    /// Delay between status polls (RD_POLL_INTERVAL)
+    poll_interval: Duration,
 }
 
 impl RealDebridClient {
@@ -109,7 +290,15 @@ impl RealDebridClient {
 
         Ok(Self {
             api_token,
-            client: reqwest::Client::new(),
+            client: crate::http::transfer_client(),
+            base_url: env::var("RD_API_URL")
+                .ok()
+                .filter(|url| !url.trim().is_empty())
+                .map(|url| url.trim().trim_end_matches('/').to_string())
+                .unwrap_or_else(|| BASE_URL.to_string()),
+            magnet_timeout: env_secs("RD_MAGNET_TIMEOUT", 30),
+            download_timeout: env_secs("RD_DOWNLOAD_TIMEOUT", 300),
+            poll_interval: env_secs("RD_POLL_INTERVAL", 2),
         })
     }
 
@@ -120,7 +309,7 @@ impl RealDebridClient {
         endpoint: &str,
         data: Option<HashMap<&str, &str>>,
     ) -> Result<T> {
-        let url = format!("{}{}", BASE_URL, endpoint);
+        let url = format!("{}{}", self.base_url, endpoint);
 
         let request = match method {
             "GET" => self.client.get(&url),
@@ -150,8 +339,17 @@ impl RealDebridClient {
 
         let text = response.text().await?;
 
+        let error = serde_json::from_str::<ErrorResponse>(&text).ok();
+
+        // 403 is also used for locked accounts and missing permissions, which a new token won't fix
+        let bad_token = error.as_ref().is_some_and(|err| err.error_code == Some(BAD_TOKEN));
+        if status.as_u16() == 401 || bad_token {
+            let message = error.map(|err| err.error).unwrap_or_else(|| status.to_string());
+            return Err(AuthError { status: status.as_u16(), message }.into());
+        }
+
         if !status.is_success() {
-            if let Ok(err) = serde_json::from_str::<ErrorResponse>(&text) {
+            if let Some(err) = error {
                 return Err(anyhow!("Real-Debrid error: {} (code: {:?})", err.error, err.error_code));
             }
             return Err(anyhow!("Real-Debrid error: {} - {}", status, text));
@@ -165,8 +363,8 @@ impl RealDebridClient {
         self.request("GET", "/user", None).await
     }
 
-    /// Add a magnet link
-    async fn add_magnet(&self, magnet: &str) -> Result<String> {
+    /// Add a magnet link, returning the torrent ID
+    pub async fn add_magnet(&self, magnet: &str) -> Result<String> {
         let mut data = HashMap::new();
         data.insert("magnet", magnet);
 
@@ -174,6 +372,27 @@ impl RealDebridClient {
         Ok(response.id)
     }
 
+    /// Find a torrent on the account with the given info hash (hex), a page at a time
+    pub async fn find_torrent_by_hash(&self, hash: &str) -> Result<Option<ExistingTorrent>> {
+        let mut page = 1;
+        loop {
+            let endpoint = format!("/torrents?limit={}&page={}", TORRENTS_PAGE, page);
+            // Past the last page RD answers 204 No Content
+            let torrents: Vec<ExistingTorrent> = match self.request("GET", &endpoint, None).await? {
+                serde_json::Value::Array(list) => serde_json::from_value(serde_json::Value::Array(list))?,
+                _ => Vec::new(),
+            };
+            let last = torrents.len() < TORRENTS_PAGE;
+            if let Some(torrent) = torrents.into_iter().find(|t| t.hash.eq_ignore_ascii_case(hash)) {
+                return Ok(Some(torrent));
+            }
+            if last {
+                return Ok(None);
+            }
+            page += 1;
+        }
+    }
+
     /// Get torrent info
     async fn get_torrent_info(&self, torrent_id: &str) -> Result<TorrentInfo> {
         let endpoint = format!("/torrents/info/{}", torrent_id);
@@ -190,12 +409,50 @@ impl RealDebridClient {
         Ok(())
     }
 
+    /// Status of the hoster serving `link` ("up", "down"...), if RD lists it
+    async fn host_status(&self, link: &str) -> Option<HostStatus> {
+        let host = link.split("://").nth(1)?.split(['/', ':', '?']).next()?.trim_start_matches("www.").to_lowercase();
+        let fresh = |cached: &Option<(Instant, HashMap<String, HostStatus>)>| {
+            cached.as_ref().filter(|(fetched, _)| fetched.elapsed() < HOSTS_TTL).map(|(_, hosts)| hosts.clone())
+        };
+        let cached = HOST_STATUS.lock().ok().and_then(|cached| fresh(&cached));
+        let hosts = match cached {
+            Some(hosts) => hosts,
+            None => {
+                let hosts: HashMap<String, HostStatus> = self.request("GET", "/hosts/status", None).await.ok()?;
+                if let Ok(mut cached) = HOST_STATUS.lock() {
+                    *cached = Some((Instant::now(), hosts.clone()));
+                }
+                hosts
+            }
+        };
+        hosts
+            .into_iter()
+            .find(|(domain, _)| host == *domain || host.ends_with(&format!(".{}", domain)))
+            .map(|(domain, status)| HostStatus { name: if status.name.is_empty() { domain } else { status.name }, ..status })
+    }
+
     /// Unrestrict a link to get direct download URL
     async fn unrestrict_link(&self, link: &str) -> Result<UnrestrictResponse> {
+        // A hoster down for maintenance makes unrestricting fail with an unhelpful error
+        if let Some(host) = self.host_status(link).await.filter(|h| h.status == "down") {
+            return Err(anyhow!("{} is down for maintenance on Real-Debrid, try again later", host.name));
+        }
+
         let mut data = HashMap::new();
         data.insert("link", link);
 
-        self.request("POST", "/unrestrict/link", Some(data)).await
+        let unrestricted: UnrestrictResponse = self.request("POST", "/unrestrict/link", Some(data)).await?;
+        if let Ok(mut links) = source_links().lock() {
+            links.insert(unrestricted.download.clone(), link.to_string());
+        }
+        Ok(unrestricted)
+    }
+
+    /// Unrestrict a hoster link, returning (filename, download URL)
+    pub async fn unrestrict(&self, link: &str) -> Result<(String, String)> {
+        let unrestricted = self.unrestrict_link(link).await?;
+        Ok((unrestricted.filename, unrestricted.download))
     }
 
     /// Delete a torrent
@@ -205,42 +462,66 @@ impl RealDebridClient {
         Ok(())
     }
 
-    /// Add a magnet and get the list of files
+    /// Add a magnet and get the list of files (the torrent is deleted on failure)
     pub async fn get_torrent_files(&self, magnet: &str) -> Result<(String, Vec<TorrentFile>)> {
         let torrent_id = self.add_magnet(magnet).await?;
+        match self.wait_for_files(&torrent_id).await {
+            Ok(files) => Ok((torrent_id, files)),
+            Err(e) => {
+                let _ = self.delete_torrent(&torrent_id).await;
+                Err(e)
+            }
+        }
+    }
 
-        // Wait for files to be available
-        for _ in 0..30 {
-            let info = self.get_torrent_info(&torrent_id).await?;
+    /// Wait for a torrent's file list to become available
+    pub async fn wait_for_files(&self, torrent_id: &str) -> Result<Vec<TorrentFile>> {
+        self.wait_for_files_with_callback(torrent_id, |_| {}).await
+    }
+
+    /// Wait for a torrent's file list with status callback. Works for new torrents
+    /// and for existing ones whose files were already selected.
+    /// The torrent is deleted if the magnet fails to resolve; on timeout it is
+    /// kept so the caller can keep waiting (see [`WaitTimeout`]).
+    pub async fn wait_for_files_with_callback<F>(&self, torrent_id: &str, mut on_status: F) -> Result<Vec<TorrentFile>>
+    where
+        F: FnMut(&str),
+    {
+        let start = Instant::now();
+        let mut status = String::new();
+
+        while start.elapsed() < self.magnet_timeout {
+            let info = self.get_torrent_info(torrent_id).await?;
+            status = info.status.clone();
 
             match info.status.as_str() {
-                "waiting_files_selection" => {
-                    let files = info
-                        .files
-                        .unwrap_or_default()
-                        .into_iter()
-                        .map(|f| TorrentFile {
-                            id: f.id,
-                            path: f.path,
-                            bytes: f.bytes,
-                            selected: f.selected.unwrap_or(0) == 1,
-                        })
-                        .collect();
-
-                    return Ok((torrent_id, files));
-                }
                 "magnet_error" => {
-                    let _ = self.delete_torrent(&torrent_id).await;
+                    let _ = self.delete_torrent(torrent_id).await;
                     return Err(anyhow!("Invalid magnet link"));
                 }
-                _ => {
-                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                "magnet_conversion" => {}
+                status => {
+                    let files = torrent_files(info.files);
+                    if status == "waiting_files_selection" || !files.is_empty() {
+                        return Ok(files);
+                    }
                 }
             }
+
+            on_status(&format!(
+                "Resolving magnet... {} elapsed, {} left",
+                clock(start.elapsed()),
+                clock(self.magnet_timeout.saturating_sub(start.elapsed()))
+            ));
+            tokio::time::sleep(self.poll_interval).await;
         }
 
-        let _ = self.delete_torrent(&torrent_id).await;
-        Err(anyhow!("Timeout waiting for magnet to resolve"))
+        Err(WaitTimeout {
+            torrent_id: torrent_id.to_string(),
+            status,
+            progress: 0.0,
+        }
+        .into())
     }
 
     /// Download specific files from a torrent
@@ -261,6 +542,24 @@ impl RealDebridClient {
     ) -> Result<Vec<(String, String)>>
     where
         F: FnMut(&str),
+    {
+        self.download_selected_files_with_updates(torrent_id, file_ids, |update| match update {
+            RdUpdate::Status(status) => on_status(status),
+            RdUpdate::Progress(progress) => on_status(&progress.to_string()),
+        })
+        .await
+    }
+
+    /// Download specific files from a torrent, reporting status text and, while RD
+    /// is fetching the torrent, structured progress
+    pub async fn download_selected_files_with_updates<F>(
+        &self,
+        torrent_id: &str,
+        file_ids: &[u32],
+        mut on_update: F,
+    ) -> Result<Vec<(String, String)>>
+    where
+        F: FnMut(RdUpdate),
     {
         // Select the specified files
         let files_str = file_ids
@@ -269,20 +568,33 @@ impl RealDebridClient {
             .collect::<Vec<_>>()
             .join(",");
 
-        on_status("Selecting files...");
-        self.select_files(torrent_id, &files_str).await?;
+        // Existing torrents may already have exactly these files selected
+        let info = self.get_torrent_info(torrent_id).await?;
+        let mut current: Vec<u32> = torrent_files(info.files)
+            .iter()
+            .filter(|f| f.selected)
+            .map(|f| f.id)
+            .collect();
+        let mut wanted = file_ids.to_vec();
+        current.sort_unstable();
+        wanted.sort_unstable();
+
+        if info.status == "waiting_files_selection" || current != wanted {
+            on_update(RdUpdate::Status("Selecting files..."));
+            self.select_files(torrent_id, &files_str).await?;
+        }
 
-        // Wait for torrent to be ready (5 minutes max)
-        let wait_timeout = 300;
-        let interval = 2;
-        let mut elapsed = 0;
+        // Wait for torrent to be ready
+        let start = Instant::now();
+        let mut last_status = String::new();
+        let mut last_progress = 0.0;
 
-        while elapsed < wait_timeout {
+        while start.elapsed() < self.download_timeout {
             let info = self.get_torrent_info(torrent_id).await?;
 
             match info.status.as_str() {
                 "downloaded" => {
-                    on_status("Unrestricting links...");
+                    on_update(RdUpdate::Status("Unrestricting links..."));
                     let links = info.links.unwrap_or_default();
                     if links.is_empty() {
                         return Err(anyhow!("No download links available"));
@@ -291,7 +603,7 @@ impl RealDebridClient {
                     // Unrestrict all links
                     let mut downloads = Vec::new();
                     for (i, link) in links.iter().enumerate() {
-                        on_status(&format!("Unrestricting link {}/{}...", i + 1, links.len()));
+                        on_update(RdUpdate::Status(&format!("Unrestricting link {}/{}...", i + 1, links.len())));
                         let unrestricted = self.unrestrict_link(link).await?;
                         downloads.push((unrestricted.filename, unrestricted.download));
                     }
@@ -303,26 +615,28 @@ impl RealDebridClient {
                 }
                 status => {
                     let progress = info.progress.unwrap_or(0.0);
-                    let speed = info.speed.unwrap_or(0);
-                    let seeders = info.seeders.unwrap_or(0);
-
-                    let speed_str = if speed > 0 {
-                        let mb_s = speed as f64 / 1_000_000.0;
-                        format!(" {:.1} MB/s", mb_s)
-                    } else {
-                        String::new()
-                    };
-
-                    on_status(&format!(
-                        "RD {}: {:.0}%{} ({} seeders)",
-                        status, progress, speed_str, seeders
-                    ));
-                    tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
-                    elapsed += interval;
+                    let elapsed = start.elapsed();
+                    on_update(RdUpdate::Progress(&RdProgress {
+                        name: info.filename.clone(),
+                        status: status.to_string(),
+                        progress,
+                        speed: info.speed.unwrap_or(0),
+                        seeders: info.seeders.unwrap_or(0),
+                        elapsed,
+                        remaining: self.download_timeout.saturating_sub(elapsed),
+                    }));
+                    last_status = status.to_string();
+                    last_progress = progress;
+                    tokio::time::sleep(self.poll_interval).await;
                 }
             }
         }
 
-        Err(anyhow!("Timeout waiting for torrent"))
+        Err(WaitTimeout {
+            torrent_id: torrent_id.to_string(),
+            status: last_status,
+            progress: last_progress,
+        }
+        .into())
     }
 }
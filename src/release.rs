@@ -0,0 +1,367 @@
+//! Release name parsing helpers
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Leading fansub group tag, e.g. "[SubsPlease]"
+static FANSUB_GROUP: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*[\[【]([^\]】]+)[\]】]").unwrap());
+
+/// Explicit batch markers
+static BATCH_MARKER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\b(batch|complete(?:\s+series)?)\b").unwrap());
+
+/// Episode ranges like "01-12", "01 ~ 26", "E01-E12", "Ep 1-13"
+static EPISODE_RANGE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(?:^|[\s\[(])(?:e|ep\.?\s*)?(\d{1,4})\s*[-~]\s*(?:e|ep\.?\s*)?(\d{1,4})(?:$|[\s\])v])").unwrap()
+});
+
+/// Extract the leading fansub group tag, e.g. "[SubsPlease] Title - 01"
+pub fn fansub_group(name: &str) -> Option<String> {
+    FANSUB_GROUP
+        .captures(name)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str().trim().to_string())
+        .filter(|g| !g.is_empty())
+}
+
+/// Whether a release name looks like a multi-episode batch
+pub fn is_batch(name: &str) -> bool {
+    if BATCH_MARKER.is_match(name) {
+        return true;
+    }
+
+    let has_range = EPISODE_RANGE.captures_iter(name).any(|c| {
+        let start: u32 = c[1].parse().unwrap_or(0);
+        let end: u32 = c[2].parse().unwrap_or(0);
+        // Ignore things that look like years or resolutions
+        end > start && end < 1900
+    });
+    has_range
+}
+
+/// Title and year from a release name, e.g. "The.Matrix.1999.1080p.BluRay" ->
+/// ("The Matrix", Some("1999")). Without a year, the title ends at the first quality tag.
+pub fn title_and_year(name: &str) -> (String, Option<String>) {
+    let name = name.replace(['.', '_'], " ");
+    let year_re = Regex::new(r"^(.+?)[\s\[(-]+((?:19|20)\d{2})(?:[\s\])-]|$)").ok();
+    if let Some(caps) = year_re.as_ref().and_then(|re| re.captures(&name)) {
+        return (clean_title(&caps[1]), Some(caps[2].to_string()));
+    }
+    let tag_re = Regex::new(r"(?i)[\s\[(-]+(?:\d{3,4}p|4k|uhd|x26[45]|h\.?26[45]|hevc|bluray|web-?dl|webrip|hdtv|dvdrip|s\d{2}e\d{2}|s\d{2})\b").ok();
+    let title = match tag_re.as_ref().and_then(|re| re.find(&name)) {
+        Some(m) => &name[..m.start()],
+        None => &name,
+    };
+    (clean_title(title), None)
+}
+
+fn clean_title(title: &str) -> String {
+    title.trim_matches(|c: char| c.is_whitespace() || "[]()-".contains(c)).split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Show, season and episode of an episode release, e.g. "Show.Name.S02E05.1080p" or
+/// "Show Name 2x05" -> ("Show Name", 2, 5). Fansub releases ("[Group] Show - 05") are season 1.
+pub fn episode(name: &str) -> Option<(String, u32, u32)> {
+    let spaced = name.replace(['.', '_'], " ");
+    let numbered = Regex::new(r"(?i)^(.+?)[\s\[(-]+(?:s(\d{1,2})\s*e(\d{1,3})|(\d{1,2})x(\d{2,3}))\b").ok()?;
+    if let Some(caps) = numbered.captures(&spaced) {
+        let number = |a: usize, b: usize| caps.get(a).or(caps.get(b)).and_then(|m| m.as_str().parse().ok());
+        let show = clean_title(&caps[1]);
+        return Some((show, number(2, 4)?, number(3, 5)?)).filter(|(show, _, _)| !show.is_empty());
+    }
+
+    let absolute = Regex::new(r"^\s*[\[【][^\]】]+[\]】]\s*(.+?)\s+-\s+(\d{1,4})(?:v\d)?\b").ok()?;
+    let caps = absolute.captures(&spaced)?;
+    let show = clean_title(&caps[1]);
+    Some((show, 1, caps[2].parse().ok()?)).filter(|(show, _, _)| !show.is_empty())
+}
+
+/// Resolution of a release, normalized to e.g. "2160p" (4K/UHD count as 2160p)
+pub fn resolution(name: &str) -> Option<String> {
+    let re = Regex::new(r"(?i)\b(?:(\d{3,4})p|(4k|uhd))\b").ok()?;
+    let caps = re.captures(name)?;
+    match caps.get(1) {
+        Some(height) => Some(format!("{}p", height.as_str())),
+        None => Some("2160p".to_string()),
+    }
+}
+
+/// Release count per resolution, highest first, e.g. "2160p ×3 · 1080p ×7 · 720p ×2"
+pub fn quality_summary<'a>(names: impl Iterator<Item = &'a str>) -> String {
+    let mut counts: Vec<(u32, String, usize)> = Vec::new();
+    for name in names {
+        let label = resolution(name).unwrap_or_else(|| "other".to_string());
+        let height = label.trim_end_matches('p').parse().unwrap_or(0);
+        match counts.iter_mut().find(|(_, l, _)| *l == label) {
+            Some((_, _, count)) => *count += 1,
+            None => counts.push((height, label, 1)),
+        }
+    }
+    counts.sort_by_key(|(height, _, _)| std::cmp::Reverse(*height));
+    counts
+        .iter()
+        .map(|(_, label, count)| format!("{} ×{}", label, count))
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+/// Audio and subtitle languages tagged in a release name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Languages {
+    pub audio: Vec<String>,
+    pub subtitles: Vec<String>,
+}
+
+/// Language code for a language tag (ITA, VF, ...)
+fn language_code(token: &str) -> Option<&'static str> {
+    Some(match token {
+        "ITA" => "IT",
+        "ENG" => "EN",
+        "FRE" | "FRA" | "TRUEFRENCH" | "VF" | "VFF" | "VFQ" | "VFI" => "FR",
+        "SPA" | "ESP" | "CASTELLANO" | "LATINO" => "ES",
+        "GER" | "DEU" => "DE",
+        "JAP" | "JPN" => "JA",
+        "KOR" => "KO",
+        "RUS" => "RU",
+        "POR" => "PT",
+        _ => return None,
+    })
+}
+
+/// Language code for a spelled-out language, which only counts as a tag after the
+/// title ("The Italian Job 2003 ITALIAN")
+fn language_name_code(token: &str) -> Option<&'static str> {
+    Some(match token {
+        "ITALIAN" => "IT",
+        "ENGLISH" => "EN",
+        "FRENCH" => "FR",
+        "SPANISH" => "ES",
+        "GERMAN" => "DE",
+        "JAPANESE" => "JA",
+        "KOREAN" => "KO",
+        "RUSSIAN" => "RU",
+        "PORTUGUESE" | "BRAZILIAN" => "PT",
+        _ => return None,
+    })
+}
+
+/// Whether a token ends the title part of a release name: a year, resolution or episode
+fn is_title_end(token: &str) -> bool {
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    (token.len() == 4 && digits(token) && (token.starts_with("19") || token.starts_with("20")))
+        || token.strip_suffix('P').is_some_and(digits)
+        || (token.starts_with('S') && token[1..].split_once('E').is_some_and(|(s, e)| digits(s) && digits(e)))
+}
+
+/// Audio languages (ITA, ENG, MULTI, DUAL, ...) and subtitles (SUB ITA, SUBITA,
+/// VOSTFR, ESUBS, ...) from the tags in a release name
+pub fn languages(name: &str) -> Languages {
+    let tokens: Vec<String> = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_uppercase)
+        .collect();
+
+    let title_end = tokens.iter().position(|t| is_title_end(t)).unwrap_or(tokens.len());
+    let code_at = |i: usize| {
+        let token = tokens.get(i)?;
+        language_code(token).or_else(|| (i >= title_end).then(|| language_name_code(token)).flatten())
+    };
+
+    let mut found = Languages::default();
+    let add = |list: &mut Vec<String>, code: &str| {
+        if !list.iter().any(|c| c == code) {
+            list.push(code.to_string());
+        }
+    };
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i].as_str();
+        match token {
+            "MULTI" => add(&mut found.audio, "MULTI"),
+            "DUAL" => add(&mut found.audio, "DUAL"),
+            "VOSTFR" | "VOST" | "SUBFRENCH" => add(&mut found.subtitles, "FR"),
+            "ESUB" | "ESUBS" => add(&mut found.subtitles, "EN"),
+            "SUB" | "SUBS" | "SUBBED" | "SUBTITLES" | "SUBTITLED" => {
+                // "SUB ITA", or "ENG SUBS" where the language came first
+                if let Some(code) = code_at(i + 1) {
+                    add(&mut found.subtitles, code);
+                    i += 1;
+                } else if let Some(code) = i.checked_sub(1).and_then(code_at) {
+                    found.audio.retain(|c| c != code);
+                    add(&mut found.subtitles, code);
+                } else {
+                    add(&mut found.subtitles, "SUB");
+                }
+            }
+            _ => {
+                // SUBITA, SUBSENG
+                let joined = token.strip_prefix("SUBS").or_else(|| token.strip_prefix("SUB"));
+                if let Some(code) = joined.and_then(language_code) {
+                    add(&mut found.subtitles, code);
+                } else if let Some(code) = code_at(i) {
+                    add(&mut found.audio, code);
+                }
+            }
+        }
+        i += 1;
+    }
+    found
+}
+
+/// Video/audio format badges for a release name: codec (H264, HEVC, AV1), HDR format
+/// (HDR, HDR10+, DV) and Atmos
+pub fn badges(name: &str) -> Vec<&'static str> {
+    let tokens: Vec<String> = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_uppercase)
+        .collect();
+    let has = |wanted: &[&str]| tokens.iter().any(|t| wanted.contains(&t.as_str()));
+    // "H.265" / "H 264" split into two tokens
+    let pair = |second: &str| tokens.windows(2).any(|w| w[0] == "H" && w[1] == second);
+
+    let mut badges = Vec::new();
+    if has(&["X265", "H265", "HEVC"]) || pair("265") {
+        badges.push("HEVC");
+    } else if has(&["AV1"]) {
+        badges.push("AV1");
+    } else if has(&["X264", "H264", "AVC"]) || pair("264") {
+        badges.push("H264");
+    }
+    let upper = name.to_uppercase();
+    if upper.contains("HDR10+") || has(&["HDR10PLUS"]) {
+        badges.push("HDR10+");
+    } else if has(&["HDR", "HDR10", "HDR10P"]) {
+        badges.push("HDR");
+    }
+    if has(&["DV", "DOVI"]) || upper.contains("DOLBY VISION") || upper.contains("DOLBY.VISION") {
+        badges.push("DV");
+    }
+    if has(&["ATMOS"]) {
+        badges.push("Atmos");
+    }
+    badges
+}
+
+/// Badge preferences for automatic result picks, from PREFER_CODECS and
+/// EXCLUDE_CODECS (comma-separated badges, e.g. `HEVC,HDR` and `AV1`)
+#[derive(Debug, Clone, Default)]
+pub struct CodecPolicy {
+    pub prefer: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl CodecPolicy {
+    pub fn from_env() -> Self {
+        let list = |key: &str| -> Vec<String> {
+            std::env::var(key)
+                .unwrap_or_default()
+                .split(',')
+                .map(|b| b.trim().to_uppercase())
+                .filter(|b| !b.is_empty())
+                .collect()
+        };
+        Self {
+            prefer: list("PREFER_CODECS"),
+            exclude: list("EXCLUDE_CODECS"),
+        }
+    }
+
+    /// Whether a release has none of the excluded badges
+    pub fn allows(&self, name: &str) -> bool {
+        !badges(name).iter().any(|b| self.exclude.contains(&b.to_uppercase()))
+    }
+
+    /// Number of preferred badges a release has
+    pub fn score(&self, name: &str) -> usize {
+        badges(name).iter().filter(|b| self.prefer.contains(&b.to_uppercase())).count()
+    }
+}
+
+/// Kind of media in a download, each with its own destination directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Movie,
+    Tv,
+    Music,
+    Other,
+}
+
+impl Category {
+    pub const ALL: [Category; 4] = [Category::Movie, Category::Tv, Category::Music, Category::Other];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Category::Movie => "Movie",
+            Category::Tv => "TV",
+            Category::Music => "Music",
+            Category::Other => "Other",
+        }
+    }
+
+    /// Name in CATEGORY_DIRS
+    pub fn key(self) -> &'static str {
+        match self {
+            Category::Movie => "movie",
+            Category::Tv => "tv",
+            Category::Music => "music",
+            Category::Other => "other",
+        }
+    }
+
+    /// The next category, for cycling through them
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|c| *c == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Guess from the site's category (e.g. "Movies > HD", "TV") if it says, else
+    /// from the release or file name
+    pub fn guess(site_category: Option<&str>, name: &str) -> Self {
+        let words = |text: &str| -> Vec<String> {
+            text.split(|c: char| !c.is_alphanumeric())
+                .filter(|w| !w.is_empty())
+                .map(str::to_lowercase)
+                .collect()
+        };
+        if let Some(site) = site_category {
+            let site = words(site);
+            let has = |prefixes: &[&str]| site.iter().any(|w| prefixes.iter().any(|p| w.starts_with(p)));
+            if has(&["tv", "serie", "show", "episode", "anime"]) {
+                return Category::Tv;
+            }
+            if has(&["movie", "film"]) {
+                return Category::Movie;
+            }
+            if has(&["music", "audio", "album", "lossless", "flac", "mp3"]) {
+                return Category::Music;
+            }
+        }
+
+        let tokens = words(name);
+        let has = |wanted: &[&str]| tokens.iter().any(|t| wanted.contains(&t.as_str()));
+        let episode = Regex::new(r"(?i)\bs\d{1,2}(?:e\d{1,3})?\b|\b\d{1,2}x\d{2}\b").ok();
+        if episode.is_some_and(|re| re.is_match(name)) || is_batch(name) || fansub_group(name).is_some() {
+            Category::Tv
+        } else if has(&["flac", "mp3", "aac", "ogg", "m4a", "320kbps", "discography", "album"]) {
+            Category::Music
+        } else if resolution(name).is_some() || has(&["mkv", "mp4", "avi", "bluray", "webrip", "dvdrip", "remux"]) {
+            Category::Movie
+        } else {
+            Category::Other
+        }
+    }
+
+    /// Destination directory from CATEGORY_DIRS (`movie:/media/movies,tv:/media/tv`),
+    /// if this category has one
+    pub fn dir(self) -> Option<std::path::PathBuf> {
+        std::env::var("CATEGORY_DIRS")
+            .ok()?
+            .split(',')
+            .filter_map(|entry| entry.split_once(':'))
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case(self.key()))
+            .map(|(_, dir)| std::path::PathBuf::from(dir.trim()))
+            .filter(|dir| !dir.as_os_str().is_empty())
+    }
+}
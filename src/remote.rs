@@ -0,0 +1,110 @@
+//! Remote download destinations (rclone remotes and SFTP), streamed through `rclone rcat`
+
+use anyhow::{anyhow, Context, Result};
+use std::path::Path;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::{Child, ChildStdin, Command};
+
+/// Remote destination from DOWNLOAD_REMOTE, as an rclone path.
+/// Accepts an rclone remote (`nas:media/downloads`) or an SFTP URL
+/// (`sftp://user@host:port/path`, authenticated with the SSH agent).
+pub fn from_env() -> Option<String> {
+    let value = std::env::var("DOWNLOAD_REMOTE").ok()?;
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    Some(match value.strip_prefix("sftp://") {
+        Some(rest) => sftp_to_rclone(rest),
+        None => value.to_string(),
+    })
+}
+
+/// Convert `user@host:port/path` to an rclone on-the-fly SFTP remote
+fn sftp_to_rclone(rest: &str) -> String {
+    let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let (user, host_port) = match authority.split_once('@') {
+        Some((user, host)) => (Some(user), host),
+        None => (None, authority),
+    };
+    let (host, port) = match host_port.split_once(':') {
+        Some((host, port)) => (host, Some(port)),
+        None => (host_port, None),
+    };
+
+    let mut remote = format!(":sftp,host={}", host);
+    if let Some(user) = user {
+        remote.push_str(&format!(",user={}", user));
+    }
+    if let Some(port) = port {
+        remote.push_str(&format!(",port={}", port));
+    }
+    format!("{}:{}", remote, path)
+}
+
+/// Full rclone path of a file in the remote destination
+pub fn target(base: &str, filename: &str) -> String {
+    if base.ends_with(':') || base.ends_with('/') {
+        format!("{}{}", base, filename)
+    } else {
+        format!("{}/{}", base, filename)
+    }
+}
+
+/// Where downloaded bytes go: a local file or an `rclone rcat` process
+pub enum Sink {
+    File(tokio::fs::File),
+    Remote { child: Child, stdin: ChildStdin },
+}
+
+impl Sink {
+    /// Start streaming to `target` with `rclone rcat`
+    pub fn remote(target: &str) -> Result<Self> {
+        let mut child = Command::new("rclone")
+            .arg("rcat")
+            .arg(target)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to start rclone (is it installed?)")?;
+        let stdin = child.stdin.take().context("rclone stdin unavailable")?;
+        Ok(Sink::Remote { child, stdin })
+    }
+
+    /// Create (or, with `append`, continue) a local file
+    pub async fn file(path: &Path, append: bool) -> Result<Self> {
+        let file = if append {
+            tokio::fs::OpenOptions::new().append(true).open(path).await?
+        } else {
+            tokio::fs::File::create(path).await?
+        };
+        Ok(Sink::File(file))
+    }
+
+    pub async fn write_all(&mut self, data: &[u8]) -> Result<()> {
+        match self {
+            Sink::File(file) => file.write_all(data).await?,
+            Sink::Remote { stdin, .. } => stdin.write_all(data).await?,
+        }
+        Ok(())
+    }
+
+    /// Flush to disk, or close the stream and wait for rclone to finish the upload
+    pub async fn finish(self) -> Result<()> {
+        match self {
+            Sink::File(file) => file.sync_all().await?,
+            Sink::Remote { child, stdin } => {
+                drop(stdin);
+                let output = child.wait_with_output().await?;
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    return Err(anyhow!("rclone failed: {}", stderr.trim()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
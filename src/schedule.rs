@@ -0,0 +1,46 @@
+//! Off-peak download window: downloads queued outside it wait for it to open,
+//! while Real-Debrid keeps caching in the meantime
+
+use chrono::{Local, NaiveTime};
+
+/// Start and end of the window from DOWNLOAD_WINDOW, e.g. "02:00-07:00" (may span
+/// midnight). None if unset or not understood.
+pub fn window() -> Option<(NaiveTime, NaiveTime)> {
+    let value = std::env::var("DOWNLOAD_WINDOW").ok()?;
+    let (start, end) = value.trim().split_once('-')?;
+    let time = |s: &str| NaiveTime::parse_from_str(s.trim(), "%H:%M").ok();
+    Some((time(start)?, time(end)?))
+}
+
+fn contains((start, end): (NaiveTime, NaiveTime), time: NaiveTime) -> bool {
+    if start <= end {
+        start <= time && time < end
+    } else {
+        time >= start || time < end
+    }
+}
+
+/// Whether downloads may start now (always, without a window)
+pub fn is_open() -> bool {
+    window().is_none_or(|w| contains(w, Local::now().time()))
+}
+
+/// Wait until downloads may start
+pub async fn wait_until_open() {
+    while !is_open() {
+        tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+    }
+}
+
+/// When the window next opens, e.g. "02:00"
+pub fn opens_at() -> Option<String> {
+    window().map(|(start, _)| start.format("%H:%M").to_string())
+}
+
+/// " for 02:00" while the window is closed, to follow "queued" in status messages
+pub fn note() -> String {
+    match opens_at() {
+        Some(start) if !is_open() => format!(" for {}", start),
+        _ => String::new(),
+    }
+}
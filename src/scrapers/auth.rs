@@ -0,0 +1,192 @@
+//! Sessions for sources that need a login (private and semi-private trackers), from
+//! the `[auth.<source>]` sections of the config file
+//!
+//! Session cookies from logins are kept in `<config dir>/littlejohn/sessions.json`
+//! (owner-only) and renewed when they expire or a search with them fails.
+
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::header::SET_COOKIE;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tokio::sync::Mutex;
+
+use super::{log_error, log_info};
+use crate::config::{self, SourceAuth};
+
+/// Sessions expiring sooner than this are renewed before use
+const EXPIRY_MARGIN_SECS: i64 = 60;
+
+static SESSIONS: OnceLock<Mutex<HashMap<String, Session>>> = OnceLock::new();
+
+/// Cookies from a login
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Session {
+    cookie: String,
+    /// Unix time the first cookie expires, if the site said
+    expires: Option<i64>,
+}
+
+impl Session {
+    fn expired(&self) -> bool {
+        self.expires
+            .is_some_and(|t| t <= chrono::Utc::now().timestamp() + EXPIRY_MARGIN_SECS)
+    }
+}
+
+fn sessions_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("littlejohn").join("sessions.json"))
+}
+
+fn sessions() -> &'static Mutex<HashMap<String, Session>> {
+    SESSIONS.get_or_init(|| {
+        let saved = sessions_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Mutex::new(saved)
+    })
+}
+
+fn save(sessions: &HashMap<String, Session>) {
+    let Some(path) = sessions_path() else { return };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(sessions) {
+        if let Err(e) = config::write_private(&path, &json) {
+            log_error("auth", &format!("Failed to save sessions: {}", e));
+        }
+    }
+}
+
+/// Login form of a built-in source that needs an account
+pub struct LoginForm {
+    pub url: &'static str,
+    pub username_field: &'static str,
+    pub password_field: &'static str,
+    /// Other fields the form needs
+    pub extra: &'static [(&'static str, &'static str)],
+    /// Cookie that proves the login worked
+    pub session_cookie: &'static str,
+}
+
+fn builtin_form(source: &str) -> Option<&'static LoginForm> {
+    match source {
+        "rutracker" => Some(&super::rutracker::LOGIN_FORM),
+        _ => None,
+    }
+}
+
+/// Whether `source` has credentials to log in with
+pub fn has_login(source: &str) -> bool {
+    config::source_auth(source).is_some_and(|auth| logs_in(source, auth))
+}
+
+fn logs_in(source: &str, auth: &SourceAuth) -> bool {
+    auth.username.is_some() && (auth.login_url.is_some() || builtin_form(source).is_some())
+}
+
+/// Cookie header to send to `source`, logging in first if its session is missing or
+/// expired (or `renew` is set). None if the source has no credentials or login failed.
+pub async fn cookie(source: &str, renew: bool) -> Option<String> {
+    let auth = config::source_auth(source)?;
+    let session = if logs_in(source, auth) {
+        match session(source, auth, renew).await {
+            Ok(session) => Some(session.cookie),
+            Err(e) => {
+                log_error(source, &format!("Login failed: {:#}", e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let cookies: Vec<&str> = auth.cookie.as_deref().into_iter().chain(session.as_deref()).collect();
+    if cookies.is_empty() {
+        return None;
+    }
+    Some(cookies.join("; "))
+}
+
+/// Saved session for `source`, or a new one
+async fn session(source: &str, auth: &SourceAuth, renew: bool) -> Result<Session> {
+    let mut sessions = sessions().lock().await;
+    if let Some(session) = sessions.get(source).filter(|s| !renew && !s.expired()) {
+        return Ok(session.clone());
+    }
+    let session = login(source, auth).await?;
+    sessions.insert(source.to_string(), session.clone());
+    save(&sessions);
+    Ok(session)
+}
+
+/// POST the login form and collect the cookies it sets
+async fn login(source: &str, auth: &SourceAuth) -> Result<Session> {
+    let builtin = builtin_form(source);
+    let url = auth.login_url.as_deref().or(builtin.map(|f| f.url)).context("no login_url")?;
+    let (Some(username), Some(password)) = (&auth.username, &auth.password) else {
+        bail!("username or password is missing");
+    };
+    let field = |configured: &Option<String>, builtin: Option<&'static str>, default: &'static str| {
+        configured.clone().unwrap_or_else(|| builtin.unwrap_or(default).to_string())
+    };
+    let mut form = vec![
+        (field(&auth.username_field, builtin.map(|f| f.username_field), "username"), username.clone()),
+        (field(&auth.password_field, builtin.map(|f| f.password_field), "password"), password.clone()),
+    ];
+    form.extend(builtin.into_iter().flat_map(|f| f.extra).map(|(k, v)| (k.to_string(), v.to_string())));
+    form.extend(auth.form.iter().map(|(k, v)| (k.clone(), v.clone())));
+    let session_cookie = auth.session_cookie.as_deref().or(builtin.map(|f| f.session_cookie));
+    log_info(source, &format!("Logging in at {}", url));
+
+    let mut request = crate::http::login_client().post(url).form(&form);
+    if let Some(cookie) = &auth.cookie {
+        request = request.header(reqwest::header::COOKIE, cookie);
+    }
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() && !status.is_redirection() {
+        return Err(anyhow!("login page returned {}", status));
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let mut cookies = Vec::new();
+    let mut expires: Option<i64> = None;
+    for header in response.headers().get_all(SET_COOKIE) {
+        let Ok(header) = header.to_str() else { continue };
+        let mut parts = header.split(';').map(str::trim);
+        let Some(pair) = parts.next().filter(|p| p.contains('=')) else { continue };
+        let mut cookie_expiry = None;
+        for attribute in parts {
+            let Some((name, value)) = attribute.split_once('=') else { continue };
+            if name.eq_ignore_ascii_case("max-age") {
+                cookie_expiry = value.trim().parse::<i64>().ok().map(|secs| now + secs);
+            } else if name.eq_ignore_ascii_case("expires") && cookie_expiry.is_none() {
+                cookie_expiry = chrono::DateTime::parse_from_rfc2822(&value.trim().replace('-', " "))
+                    .ok()
+                    .map(|t| t.timestamp());
+            }
+        }
+        // Sites clear stale cookies with an expiry in the past
+        if cookie_expiry.is_some_and(|t| t <= now) {
+            continue;
+        }
+        if let Some(t) = cookie_expiry {
+            expires = Some(expires.map_or(t, |e| e.min(t)));
+        }
+        cookies.push(pair.to_string());
+    }
+
+    let has_session = match session_cookie {
+        Some(name) => cookies.iter().any(|c| c.split_once('=').is_some_and(|(n, _)| n == name)),
+        None => !cookies.is_empty(),
+    };
+    if !has_session {
+        bail!("no session cookie in the login response (wrong username or password?)");
+    }
+    log_info(source, "Logged in");
+    Ok(Session { cookie: cookies.join("; "), expires })
+}
@@ -1,62 +1,16 @@
 //! BitSearch scraper
 
-use super::{clean_text, log_error, log_info, TorrentResult};
+use super::{clean_magnet, clean_text, firecrawl, headless, log_error, log_info, normalize, pause, selectors, source_limit, Scraper, TorrentResult};
+use futures::future::BoxFuture;
 use regex::Regex;
 use reqwest::Client;
 use scraper::{Html, Selector};
-use serde::Serialize;
-
-/// Firecrawl scrape request
-#[derive(Serialize)]
-struct FirecrawlRequest {
-    url: String,
-    formats: Vec<String>,
-}
-
-/// Fetch URL using Firecrawl API (for bypassing Cloudflare)
-async fn fetch_with_firecrawl(client: &Client, url: &str) -> Option<String> {
-    let api_key = match std::env::var("FIRECRAWL_API_KEY") {
-        Ok(key) if !key.is_empty() => key,
-        _ => return None,
-    };
-
-    let request = FirecrawlRequest {
-        url: url.to_string(),
-        formats: vec!["html".to_string()],
-    };
-
-    let response = match client
-        .post("https://api.firecrawl.dev/v1/scrape")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .json(&request)
-        .send()
-        .await
-    {
-        Ok(r) => r,
-        Err(e) => {
-            log_error("bitsearch", &format!("Firecrawl request failed: {}", e));
-            return None;
-        }
-    };
-
-    let data: serde_json::Value = match response.json().await {
-        Ok(d) => d,
-        Err(e) => {
-            log_error("bitsearch", &format!("Firecrawl response parse error: {}", e));
-            return None;
-        }
-    };
-
-    data.get("data")
-        .and_then(|d| d.get("html"))
-        .and_then(|h| h.as_str())
-        .map(String::from)
-}
+use tokio_util::sync::CancellationToken;
 
 /// Fetch URL with Firecrawl fallback to direct fetch
 async fn fetch_with_fallback(client: &Client, url: &str) -> Option<String> {
     // Try Firecrawl first (needed for Cloudflare bypass)
-    if let Some(html) = fetch_with_firecrawl(client, url).await {
+    if let Some(html) = firecrawl::scrape("bitsearch", url).await {
         if !html.is_empty() && (html.contains("search-result") || html.contains("card")) {
             log_info("bitsearch", "Using Firecrawl");
             return Some(html);
@@ -65,17 +19,25 @@ async fn fetch_with_fallback(client: &Client, url: &str) -> Option<String> {
 
     // Fall back to direct fetch
     log_info("bitsearch", "Trying direct fetch");
+    pause("bitsearch").await;
     match client.get(url).send().await {
         Ok(resp) => {
             let status = resp.status();
             if !status.is_success() {
                 log_error("bitsearch", &format!("HTTP {} for {}", status, url));
-                return None;
+                // Cloudflare answers its challenge with 403 or 503
+                return match status.as_u16() {
+                    403 | 503 => headless::fetch("bitsearch", url).await,
+                    _ => None,
+                };
             }
             match resp.text().await {
                 Ok(text) => {
                     // Check for Cloudflare challenge
-                    if text.contains("Just a moment") || text.contains("Enable JavaScript") {
+                    if headless::is_challenge(&text) {
+                        if headless::available() {
+                            return headless::fetch("bitsearch", url).await;
+                        }
                         log_error("bitsearch", "Cloudflare challenge detected - set FIRECRAWL_API_KEY to bypass");
                         return None;
                     }
@@ -107,14 +69,12 @@ pub async fn scrape_bitsearch(client: &Client, query: &str, page: u32) -> Option
     let document = Html::parse_document(&html);
 
     // Find all magnet links first (like the working Python version)
-    let magnet_sel = Selector::parse("a[href^='magnet:']").ok()?;
-    let title_selectors = [
-        "h5 a",
-        "a.text-sky-600",
-        "a[href*='/torrent/']",
-    ];
-    let green_sel = Selector::parse("span.text-green-600, span.text-emerald-600").ok();
-    let red_sel = Selector::parse("span.text-red-600, span.text-rose-600").ok();
+    let magnet_sel = selectors::selector("bitsearch", "magnet", "a[href^='magnet:']")?;
+    // Tried in order; a pack override is a comma-separated list
+    let title_selectors = selectors::get("bitsearch", "title", "h5 a, a.text-sky-600, a[href*='/torrent/']");
+    let title_selectors: Vec<&str> = title_selectors.split(',').map(str::trim).collect();
+    let green_sel = selectors::selector("bitsearch", "seeders", "span.text-green-600, span.text-emerald-600");
+    let red_sel = selectors::selector("bitsearch", "leechers", "span.text-red-600, span.text-rose-600");
     let size_re = Regex::new(r"([\d.]+\s*(?:GB|MB|KB|TB|GiB|MiB))").ok()?;
 
     let mut results = Vec::new();
@@ -127,11 +87,7 @@ pub async fn scrape_bitsearch(client: &Client, query: &str, page: u32) -> Option
             continue;
         }
 
-        // Decode HTML entities in magnet
-        let magnet = magnet_raw
-            .replace("&amp;", "&")
-            .replace("&#x3D;", "=")
-            .replace("&#x3A;", ":");
+        let magnet = clean_magnet(magnet_raw);
 
         // Skip duplicates
         if seen_magnets.contains(&magnet) {
@@ -197,23 +153,17 @@ pub async fn scrape_bitsearch(client: &Client, query: &str, page: u32) -> Option
             .unwrap_or_default();
 
         // Extract seeders (green text)
-        let seeders: i64 = green_sel
+        let seeders = green_sel
             .as_ref()
             .and_then(|sel| card.select(sel).next())
-            .map(|el| {
-                let text = el.text().collect::<String>();
-                text.trim().replace(",", "").parse().unwrap_or(0)
-            })
+            .map(|el| normalize::count(&el.text().collect::<String>()))
             .unwrap_or(0);
 
         // Extract leechers (red text)
-        let leechers: i64 = red_sel
+        let leechers = red_sel
             .as_ref()
             .and_then(|sel| card.select(sel).next())
-            .map(|el| {
-                let text = el.text().collect::<String>();
-                text.trim().replace(",", "").parse().unwrap_or(0)
-            })
+            .map(|el| normalize::count(&el.text().collect::<String>()))
             .unwrap_or(0);
 
         results.push(TorrentResult {
@@ -225,9 +175,17 @@ pub async fn scrape_bitsearch(client: &Client, query: &str, page: u32) -> Option
             source: "bitsearch".to_string(),
             url: if detail_url.is_empty() { None } else { Some(detail_url) },
             category: None,
+            cover: None,
+            audio: Vec::new(),
+            subtitles: Vec::new(),
         });
     }
 
+    if let Some(limit) = source_limit("bitsearch") {
+        results.sort_by_key(|r| std::cmp::Reverse(r.seeders));
+        results.truncate(limit);
+    }
+
     if results.is_empty() {
         log_info("bitsearch", "No results found");
     } else {
@@ -236,3 +194,23 @@ pub async fn scrape_bitsearch(client: &Client, query: &str, page: u32) -> Option
 
     Some(results)
 }
+
+/// bitsearch in the scraper registry
+pub struct Bitsearch;
+
+impl Scraper for Bitsearch {
+    fn name(&self) -> &'static str {
+        "bitsearch"
+    }
+
+    fn search<'a>(
+        &'a self,
+        client: &'a Client,
+        query: &'a str,
+        page: u32,
+        _fast: bool,
+        _cancel: &'a CancellationToken,
+    ) -> BoxFuture<'a, Option<Vec<TorrentResult>>> {
+        Box::pin(scrape_bitsearch(client, query, page))
+    }
+}
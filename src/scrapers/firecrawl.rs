@@ -0,0 +1,187 @@
+//! Firecrawl API, used by scrapers to get past anti-bot protection
+//!
+//! Calls from all scrapers share a limit on concurrent requests (FIRECRAWL_CONCURRENCY,
+//! default 2) and a per-minute budget (FIRECRAWL_PER_MINUTE, default 10); calls past
+//! either wait their turn. Rate-limited calls are retried after a backoff.
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, Semaphore};
+
+use super::{log_error, log_info};
+
+const SCRAPE_URL: &str = "https://api.firecrawl.dev/v1/scrape";
+const CREDIT_USAGE_URL: &str = "https://api.firecrawl.dev/v1/team/credit-usage";
+
+/// Firecrawl renders pages in a browser, so scrapes take longer than plain fetches
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Retries after a 429, waiting Retry-After or 5s, 10s...
+const MAX_RETRIES: u32 = 2;
+const BACKOFF: Duration = Duration::from_secs(5);
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+static SLOTS: OnceLock<Semaphore> = OnceLock::new();
+static STARTS: Mutex<VecDeque<Instant>> = Mutex::const_new(VecDeque::new());
+static CREDITS: AtomicU64 = AtomicU64::new(0);
+
+/// Firecrawl scrape request
+#[derive(Serialize)]
+struct FirecrawlRequest<'a> {
+    url: &'a str,
+    formats: [&'a str; 1],
+}
+
+/// API key, if Firecrawl is configured
+pub fn api_key() -> Option<String> {
+    std::env::var("FIRECRAWL_API_KEY").ok().filter(|k| !k.trim().is_empty())
+}
+
+/// Successful scrapes this session (one credit each)
+pub fn credits_used() -> u64 {
+    CREDITS.load(Ordering::Relaxed)
+}
+
+/// Credits left on the account's plan
+pub async fn remaining_credits() -> Result<i64> {
+    let api_key = api_key().ok_or_else(|| anyhow!("FIRECRAWL_API_KEY not set"))?;
+    let response = crate::http::scraper_client()
+        .get(CREDIT_USAGE_URL)
+        .bearer_auth(api_key)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("Firecrawl returned {}", response.status()));
+    }
+    let data: serde_json::Value = response.json().await?;
+    data.pointer("/data/remaining_credits")
+        .and_then(serde_json::Value::as_i64)
+        .ok_or_else(|| anyhow!("no remaining_credits in the response"))
+}
+
+/// Most credits a search for `query` (comma-separated alternatives) on `sources` can
+/// use: each source's search page, plus its detail pages up to the source limit unless
+/// it's a `fast` search. Zero without an API key, since nothing goes through Firecrawl then.
+pub fn search_cost(query: &str, sources: &[&str], fast: bool) -> u64 {
+    if api_key().is_none() {
+        return 0;
+    }
+    let details = |source: &str, default: usize| {
+        if fast {
+            0
+        } else {
+            super::source_limit(source).unwrap_or(default) as u64
+        }
+    };
+    let per_query: u64 = sources
+        .iter()
+        .map(|source| match *source {
+            "1337x" => 1 + details("1337x", super::x1337::DETAIL_LIMIT),
+            "yts" if fast => 1,
+            "yts" => 1 + super::yts::expand_limit().min(super::source_limit("yts").unwrap_or(usize::MAX)) as u64,
+            "ilcorsaronero" => 1 + details("ilcorsaronero", super::ilcorsaronero::DETAIL_LIMIT),
+            "bitsearch" => 1,
+            _ => 0,
+        })
+        .sum();
+    let queries = query.split(',').filter(|q| !q.trim().is_empty()).count() as u64;
+    per_query * queries
+}
+
+/// Searches estimated to cost more credits than this ask first (FIRECRAWL_WARN_CREDITS)
+pub fn warn_threshold() -> Option<u64> {
+    std::env::var("FIRECRAWL_WARN_CREDITS").ok()?.trim().parse().ok()
+}
+
+fn env_limit(key: &str, default: usize) -> usize {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(default)
+}
+
+/// Wait until a request fits in the per-minute budget, then count it
+async fn wait_for_budget(source: &str) {
+    let per_minute = env_limit("FIRECRAWL_PER_MINUTE", 10);
+    loop {
+        let wait = {
+            let mut starts = STARTS.lock().await;
+            while starts.front().is_some_and(|t| t.elapsed() >= WINDOW) {
+                starts.pop_front();
+            }
+            if starts.len() < per_minute {
+                starts.push_back(Instant::now());
+                return;
+            }
+            WINDOW.saturating_sub(starts.front().map_or(Duration::ZERO, Instant::elapsed))
+        };
+        log_info(source, &format!("Firecrawl budget used up, waiting {}s", wait.as_secs().max(1)));
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Page HTML through Firecrawl. None if no API key is set or the scrape failed;
+/// `source` is used for logging.
+pub async fn scrape(source: &str, url: &str) -> Option<String> {
+    let api_key = api_key()?;
+    let slots = SLOTS.get_or_init(|| Semaphore::new(env_limit("FIRECRAWL_CONCURRENCY", 2)));
+    let _slot = slots.acquire().await.ok()?;
+    // Not the caller's client: that may carry a source's session cookie
+    let client = crate::http::scraper_client();
+
+    for attempt in 0..=MAX_RETRIES {
+        wait_for_budget(source).await;
+        let response = match client
+            .post(SCRAPE_URL)
+            .timeout(TIMEOUT)
+            .bearer_auth(&api_key)
+            .json(&FirecrawlRequest { url, formats: ["html"] })
+            .send()
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                log_error(source, &format!("Firecrawl request failed: {}", e));
+                return None;
+            }
+        };
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS && attempt < MAX_RETRIES {
+            let wait = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok()?.trim().parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(BACKOFF * 2u32.pow(attempt));
+            log_info(source, &format!("Firecrawl rate limited, retrying in {}s", wait.as_secs()));
+            tokio::time::sleep(wait).await;
+            continue;
+        }
+        if !response.status().is_success() {
+            log_error(source, &format!("Firecrawl returned {}", response.status()));
+            return None;
+        }
+
+        let data: serde_json::Value = match response.json().await {
+            Ok(d) => d,
+            Err(e) => {
+                log_error(source, &format!("Firecrawl response parse error: {}", e));
+                return None;
+            }
+        };
+        CREDITS.fetch_add(1, Ordering::Relaxed);
+        // Structure is { data: { html: "..." } }
+        return data
+            .get("data")
+            .and_then(|d| d.get("html"))
+            .and_then(|h| h.as_str())
+            .map(String::from);
+    }
+    None
+}
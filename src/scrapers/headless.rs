@@ -0,0 +1,92 @@
+//! Headless Chromium fetch backend, built with `--features headless`. The last resort for
+//! pages behind a Cloudflare challenge when Firecrawl isn't configured: a real browser
+//! runs the challenge's JavaScript, then the cleared page is read. Chrome or Chromium
+//! must be installed (CHROME_PATH if it isn't found on its own).
+
+use super::{firecrawl, log_error, log_info};
+
+/// Whether a page is Cloudflare's challenge rather than the site
+pub fn is_challenge(html: &str) -> bool {
+    html.contains("Just a moment") || html.contains("Enable JavaScript")
+}
+
+/// Whether headless fetches can be made: built with the feature and no Firecrawl key
+pub fn available() -> bool {
+    cfg!(feature = "headless") && firecrawl::api_key().is_none()
+}
+
+/// The page at `url` once the browser got past the challenge; None without the feature,
+/// with Firecrawl configured, or if the challenge didn't clear in time
+pub async fn fetch(source: &str, url: &str) -> Option<String> {
+    if !available() {
+        return None;
+    }
+    log_info(source, "Cloudflare challenge: trying headless Chromium");
+    match browser::fetch(url).await {
+        Ok(Some(html)) => {
+            log_info(source, "Headless Chromium cleared the challenge");
+            Some(html)
+        }
+        Ok(None) => {
+            log_error(source, "Headless Chromium: challenge didn't clear in time");
+            None
+        }
+        Err(e) => {
+            log_error(source, &format!("Headless Chromium failed: {:#}", e));
+            None
+        }
+    }
+}
+
+#[cfg(feature = "headless")]
+mod browser {
+    use anyhow::{anyhow, Result};
+    use chromiumoxide::browser::{Browser, BrowserConfig};
+    use futures::StreamExt;
+    use std::time::{Duration, Instant};
+    use tokio::sync::Mutex;
+
+    /// One browser at a time: each launch is a full Chromium process
+    static LOCK: Mutex<()> = Mutex::const_new(());
+
+    pub async fn fetch(url: &str) -> Result<Option<String>> {
+        let _running = LOCK.lock().await;
+        let mut config = BrowserConfig::builder()
+            .new_headless_mode()
+            .user_data_dir(std::env::temp_dir().join("littlejohn-chromium"));
+        if let Ok(path) = std::env::var("CHROME_PATH") {
+            config = config.chrome_executable(path);
+        }
+        let (mut browser, mut handler) = Browser::launch(config.build().map_err(|e| anyhow!(e))?).await?;
+        let events = tokio::spawn(async move { while handler.next().await.is_some() {} });
+
+        let html = read_cleared(&browser, url).await;
+        let _ = browser.close().await;
+        let _ = browser.wait().await;
+        events.abort();
+        html
+    }
+
+    /// Poll the page until the challenge is gone or the scraper timeout runs out
+    async fn read_cleared(browser: &Browser, url: &str) -> Result<Option<String>> {
+        let page = browser.new_page(url).await?;
+        let deadline = Instant::now() + crate::http::request_timeout() * 2;
+        loop {
+            let html = page.content().await?;
+            if !super::is_challenge(&html) {
+                return Ok(Some(html));
+            }
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+}
+
+#[cfg(not(feature = "headless"))]
+mod browser {
+    pub async fn fetch(_url: &str) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+}
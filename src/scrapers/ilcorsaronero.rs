@@ -1,75 +1,16 @@
 //! ilCorsaroNero scraper - Italian torrent site, requires Firecrawl
 
-use super::{clean_text, log_error, log_info, TorrentResult};
+use super::{clean_magnet, clean_text, firecrawl, log_error, log_info, normalize, selectors, source_limit, Scraper, TorrentResult};
+use futures::future::BoxFuture;
 use regex::Regex;
 use reqwest::Client;
-use scraper::{Html, Selector};
-use serde::Serialize;
+use scraper::Html;
+use tokio_util::sync::CancellationToken;
 
 const BASE_URL: &str = "https://ilcorsaronero.link";
 
-/// Firecrawl scrape request
-#[derive(Serialize)]
-struct FirecrawlRequest {
-    url: String,
-    formats: Vec<String>,
-}
-
-/// Fetch URL using Firecrawl API
-async fn fetch_with_firecrawl(url: &str) -> Option<String> {
-    let api_key = match std::env::var("FIRECRAWL_API_KEY") {
-        Ok(key) if !key.is_empty() => key,
-        _ => {
-            log_error("ilcorsaronero", "FIRECRAWL_API_KEY not set - this source requires Firecrawl");
-            return None;
-        }
-    };
-
-    // Create client with longer timeout for Firecrawl
-    let client = match Client::builder()
-        .timeout(std::time::Duration::from_secs(60))
-        .build()
-    {
-        Ok(c) => c,
-        Err(e) => {
-            log_error("ilcorsaronero", &format!("Failed to create client: {}", e));
-            return None;
-        }
-    };
-
-    let request = FirecrawlRequest {
-        url: url.to_string(),
-        formats: vec!["html".to_string()],
-    };
-
-    let response = match client
-        .post("https://api.firecrawl.dev/v1/scrape")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .json(&request)
-        .send()
-        .await
-    {
-        Ok(r) => r,
-        Err(e) => {
-            log_error("ilcorsaronero", &format!("Firecrawl request failed: {}", e));
-            return None;
-        }
-    };
-
-    let data: serde_json::Value = match response.json().await {
-        Ok(d) => d,
-        Err(e) => {
-            log_error("ilcorsaronero", &format!("Firecrawl response parse error: {}", e));
-            return None;
-        }
-    };
-
-    // Extract HTML from response - structure is { data: { html: "..." } }
-    data.get("data")
-        .and_then(|d| d.get("html"))
-        .and_then(|h| h.as_str())
-        .map(String::from)
-}
+/// Detail pages fetched per search without a SOURCE_LIMITS entry
+pub const DETAIL_LIMIT: usize = 10;
 
 /// Extract magnet link from detail page HTML
 fn extract_magnet(html: &str) -> Option<String> {
@@ -81,19 +22,18 @@ fn extract_magnet(html: &str) -> Option<String> {
     // Look for magnet link directly in HTML with regex
     let re = Regex::new(r#"(magnet:\?xt=urn:btih:[a-fA-F0-9]{40}[^"<>\s]*)"#).ok()?;
     if let Some(cap) = re.captures(html) {
-        let magnet = cap.get(1)?.as_str().replace("&amp;", "&");
-        return Some(magnet);
+        return Some(clean_magnet(cap.get(1)?.as_str()));
     }
 
     // Fallback: parse with scraper
     let document = Html::parse_document(html);
-    let magnet_sel = Selector::parse(r#"a[href^="magnet:"]"#).ok()?;
+    let magnet_sel = selectors::selector("ilcorsaronero", "magnet", r#"a[href^="magnet:"]"#)?;
 
     document
         .select(&magnet_sel)
         .next()
         .and_then(|el| el.value().attr("href"))
-        .map(String::from)
+        .map(clean_magnet)
 }
 
 /// Parse search results from ilcorsaronero HTML
@@ -102,13 +42,13 @@ fn parse_search_results(html: &str) -> Vec<(String, String, String, String, Stri
     let document = Html::parse_document(html);
     let mut results = Vec::new();
 
-    let row_sel = match Selector::parse("tbody tr") {
-        Ok(s) => s,
-        Err(_) => return results,
+    let row_sel = match selectors::selector("ilcorsaronero", "row", "tbody tr") {
+        Some(s) => s,
+        None => return results,
     };
 
-    let title_sel = Selector::parse("th a").ok();
-    let cell_sel = Selector::parse("td, th").ok();
+    let title_sel = selectors::selector("ilcorsaronero", "title", "th a");
+    let cell_sel = selectors::selector("ilcorsaronero", "cell", "td, th");
 
     for row in document.select(&row_sel) {
         // Get title and URL
@@ -161,12 +101,24 @@ fn parse_search_results(html: &str) -> Vec<(String, String, String, String, Stri
     results
 }
 
-/// Scrape ilcorsaronero.link for torrents
+/// Magnet link from a detail page
+pub async fn fetch_magnet(url: &str) -> Option<String> {
+    extract_magnet(&firecrawl::scrape("ilcorsaronero", url).await?)
+}
+
+/// Scrape ilcorsaronero.link for torrents. With `fast`, detail pages are skipped and
+/// results come without magnets.
 pub async fn scrape_ilcorsaronero(
     _client: &Client,
     query: &str,
     page: u32,
+    fast: bool,
 ) -> Option<Vec<TorrentResult>> {
+    if firecrawl::api_key().is_none() {
+        log_error("ilcorsaronero", "FIRECRAWL_API_KEY not set - this source requires Firecrawl");
+        return None;
+    }
+
     // Build search URL
     let encoded = urlencoding::encode(query);
     let url = if page > 1 {
@@ -178,7 +130,7 @@ pub async fn scrape_ilcorsaronero(
     log_info("ilcorsaronero", &format!("Fetching: {}", url));
 
     // Fetch search page with Firecrawl
-    let html = match fetch_with_firecrawl(&url).await {
+    let html = match firecrawl::scrape("ilcorsaronero", &url).await {
         Some(h) => h,
         None => {
             log_error("ilcorsaronero", "Failed to fetch search page");
@@ -186,25 +138,50 @@ pub async fn scrape_ilcorsaronero(
         }
     };
 
-    // Parse search results
-    let items = parse_search_results(&html);
+    // Parse search results, best-seeded first
+    let mut items = parse_search_results(&html);
+    items.sort_by_key(|item| std::cmp::Reverse(item.2.parse::<i64>().unwrap_or(0)));
 
     if items.is_empty() {
         log_info("ilcorsaronero", "No items found in search results");
         return Some(Vec::new());
     }
 
+    let limit = source_limit("ilcorsaronero").or((!fast).then_some(DETAIL_LIMIT)).unwrap_or(usize::MAX);
+    if fast {
+        log_info("ilcorsaronero", &format!("Found {} items (fast search, magnets on demand)", items.len()));
+        return Some(
+            items
+                .into_iter()
+                .take(limit)
+                .map(|(name, detail_url, seeders, leechers, size)| TorrentResult {
+                    name,
+                    size,
+                    seeders: normalize::count(&seeders),
+                    leechers: normalize::count(&leechers),
+                    magnet: String::new(),
+                    source: "ilcorsaronero".to_string(),
+                    url: Some(detail_url),
+                    category: None,
+                    cover: None,
+                    audio: Vec::new(),
+                    subtitles: Vec::new(),
+                })
+                .collect(),
+        );
+    }
+
     log_info("ilcorsaronero", &format!("Found {} items, fetching details...", items.len()));
 
-    // Fetch magnet links from detail pages (limit to 10)
+    // Fetch magnet links from detail pages (limit to 10 by default)
     let mut results = Vec::new();
     let mut magnet_failures = 0;
 
-    for (name, detail_url, seeders, leechers, size) in items.into_iter().take(10) {
-        if let Some(detail_html) = fetch_with_firecrawl(&detail_url).await {
+    for (name, detail_url, seeders, leechers, size) in items.into_iter().take(limit) {
+        if let Some(detail_html) = firecrawl::scrape("ilcorsaronero", &detail_url).await {
             if let Some(magnet) = extract_magnet(&detail_html) {
-                let seeders_num = seeders.parse::<i64>().unwrap_or(0);
-                let leechers_num = leechers.parse::<i64>().unwrap_or(0);
+                let seeders_num = normalize::count(&seeders);
+                let leechers_num = normalize::count(&leechers);
 
                 results.push(TorrentResult {
                     name,
@@ -215,6 +192,9 @@ pub async fn scrape_ilcorsaronero(
                     source: "ilcorsaronero".to_string(),
                     url: Some(detail_url),
                     category: None,
+                    cover: None,
+                    audio: Vec::new(),
+                    subtitles: Vec::new(),
                 });
             } else {
                 magnet_failures += 1;
@@ -232,3 +212,30 @@ pub async fn scrape_ilcorsaronero(
 
     Some(results)
 }
+
+/// ilcorsaronero in the scraper registry
+pub struct IlCorsaroNero;
+
+impl Scraper for IlCorsaroNero {
+    fn name(&self) -> &'static str {
+        "ilcorsaronero"
+    }
+
+    fn search<'a>(
+        &'a self,
+        client: &'a Client,
+        query: &'a str,
+        page: u32,
+        fast: bool,
+        _cancel: &'a CancellationToken,
+    ) -> BoxFuture<'a, Option<Vec<TorrentResult>>> {
+        Box::pin(scrape_ilcorsaronero(client, query, page, fast))
+    }
+
+    fn resolve<'a>(&'a self, _client: &'a Client, result: &'a TorrentResult) -> BoxFuture<'a, Option<Vec<TorrentResult>>> {
+        Box::pin(async move {
+            let magnet = fetch_magnet(result.url.as_deref()?).await?;
+            Some(vec![TorrentResult { magnet, ..result.clone() }])
+        })
+    }
+}
@@ -3,10 +3,17 @@
 use std::fs::{File, OpenOptions};
 use std::io::Write;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 use chrono::Local;
 
 static LOG_FILE: Mutex<Option<PathBuf>> = Mutex::new(None);
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Stop echoing errors to stderr (the log file still gets them)
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
 
 /// Initialize the log file path
 pub fn init_log() -> Option<PathBuf> {
@@ -32,7 +39,9 @@ pub fn log_error(source: &str, message: &str) {
     let log_line = format!("[{}] [{}] ERROR: {}", timestamp, source, message);
 
     // Also print to stderr for debugging
-    eprintln!("{}", log_line);
+    if !QUIET.load(Ordering::Relaxed) {
+        eprintln!("{}", log_line);
+    }
 
     if let Ok(guard) = LOG_FILE.lock() {
         if let Some(ref path) = *guard {
@@ -83,3 +92,9 @@ pub fn read_recent_logs(n: usize) -> Vec<String> {
         Err(_) => vec!["Could not read log file".to_string()],
     }
 }
+
+/// Source tag of a log line (`[12:00:00] [tpb] ERROR: ...` gives `tpb`)
+pub fn line_source(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix('[')?.split_once("] [")?.1;
+    Some(rest.split_once(']')?.0)
+}
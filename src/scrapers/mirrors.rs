@@ -0,0 +1,128 @@
+//! Mirror health tracking for sources with several domains (TPB proxies, YTS)
+//!
+//! The last mirror that worked is tried first, and the rest are ordered by
+//! recent health. State is persisted to `mirrors.json` in the config directory.
+
+use super::log_info;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static STATE: Mutex<Option<HashMap<String, MirrorGroup>>> = Mutex::new(None);
+
+/// How often background probes run
+pub const PROBE_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Health of one mirror
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MirrorHealth {
+    pub successes: u64,
+    pub failures: u64,
+    /// Failures since the last success
+    pub consecutive_failures: u64,
+    /// Latency of the last successful request in milliseconds
+    pub last_latency_ms: Option<u64>,
+}
+
+/// Mirrors of one source
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MirrorGroup {
+    /// Mirror that most recently worked
+    pub last_good: Option<String>,
+    pub health: HashMap<String, MirrorHealth>,
+}
+
+/// Path of the persisted state file
+fn state_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("littlejohn").join("mirrors.json"))
+}
+
+fn load() -> HashMap<String, MirrorGroup> {
+    state_path()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save(state: &HashMap<String, MirrorGroup>) {
+    if let (Some(path), Ok(json)) = (state_path(), serde_json::to_string_pretty(state)) {
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let _ = std::fs::write(path, json);
+    }
+}
+
+/// Mirrors in the order they should be tried: the last good one first, then
+/// healthy mirrors before failing ones (ties keep the configured order)
+pub fn ordered(source: &str, mirrors: &[&str]) -> Vec<String> {
+    let group = match STATE.lock() {
+        Ok(mut guard) => guard.get_or_insert_with(load).get(source).cloned().unwrap_or_default(),
+        Err(_) => MirrorGroup::default(),
+    };
+
+    let mut ordered: Vec<String> = mirrors.iter().map(|m| m.to_string()).collect();
+    ordered.sort_by_key(|m| {
+        let is_last_good = group.last_good.as_deref() == Some(m.as_str());
+        let failures = group.health.get(m).map(|h| h.consecutive_failures).unwrap_or(0);
+        (!is_last_good, failures)
+    });
+    ordered
+}
+
+/// Update a mirror's health. `promote` makes a success the new last good mirror.
+fn update(source: &str, mirror: &str, ok: bool, latency: Duration, promote: bool) {
+    if let Ok(mut guard) = STATE.lock() {
+        let state = guard.get_or_insert_with(load);
+        let group = state.entry(source.to_string()).or_default();
+        let health = group.health.entry(mirror.to_string()).or_default();
+        if ok {
+            health.successes += 1;
+            health.consecutive_failures = 0;
+            health.last_latency_ms = Some(latency.as_millis() as u64);
+            if promote {
+                group.last_good = Some(mirror.to_string());
+            }
+        } else {
+            health.failures += 1;
+            health.consecutive_failures += 1;
+            if group.last_good.as_deref() == Some(mirror) {
+                group.last_good = None;
+            }
+        }
+        save(state);
+    }
+}
+
+/// Record the outcome of a real request to a mirror
+pub fn record(source: &str, mirror: &str, ok: bool, latency: Duration) {
+    update(source, mirror, ok, latency, true);
+}
+
+/// Snapshot of the mirror state
+pub fn snapshot() -> HashMap<String, MirrorGroup> {
+    match STATE.lock() {
+        Ok(mut guard) => guard.get_or_insert_with(load).clone(),
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// Probe every mirror of a source with a lightweight request and record the results.
+/// The last good mirror is left alone; it is exercised by real searches.
+pub async fn probe(client: &reqwest::Client, source: &str, mirrors: &[&str]) {
+    let last_good = snapshot().get(source).and_then(|g| g.last_good.clone());
+
+    for mirror in mirrors.iter().filter(|m| last_good.as_deref() != Some(**m)) {
+        let start = Instant::now();
+        let ok = match client.get(format!("https://{}/", mirror)).send().await {
+            Ok(resp) => resp.status().is_success(),
+            Err(_) => false,
+        };
+        log_info(source, &format!("Probe {}: {}", mirror, if ok { "ok" } else { "failed" }));
+
+        // A probe success only updates health; it doesn't displace the last good mirror
+        update(source, mirror, ok, start.elapsed(), false);
+    }
+}
@@ -5,22 +5,32 @@ pub mod tpb;
 pub mod bitsearch;
 pub mod yts;
 pub mod ilcorsaronero;
+pub mod rutracker;
+pub mod nyaa;
+pub mod auth;
+pub mod firecrawl;
+pub mod headless;
 pub mod log;
+pub mod mirrors;
+pub mod normalize;
+pub mod plugins;
+pub mod query;
+pub mod selectors;
+pub mod stats;
 
 use anyhow::Result;
+use futures::future::BoxFuture;
 use reqwest::Client;
-use std::time::Duration;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
 
 pub use log::{init_log, log_error, log_info};
-
-pub use x1337::scrape_1337x;
-pub use tpb::scrape_tpb;
-pub use bitsearch::scrape_bitsearch;
-pub use yts::scrape_yts;
-pub use ilcorsaronero::scrape_ilcorsaronero;
+pub use normalize::decode_entities;
+pub use query::query_variants;
 
 /// Torrent search result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct TorrentResult {
     pub name: String,
     pub size: String,
@@ -30,6 +40,22 @@ pub struct TorrentResult {
     pub source: String,
     pub url: Option<String>,
     pub category: Option<String>,
+    /// Cover art image URL
+    pub cover: Option<String>,
+    /// Audio languages from the release name (e.g. "IT", "EN", "MULTI")
+    pub audio: Vec<String>,
+    /// Subtitle languages from the release name ("SUB" when unspecified)
+    pub subtitles: Vec<String>,
+}
+
+/// Swarm health of a torrent, based on seeders and leechers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Health {
+    Good,
+    Fair,
+    Poor,
+    /// Source does not report swarm numbers (YTS)
+    Unknown,
 }
 
 impl TorrentResult {
@@ -37,6 +63,52 @@ impl TorrentResult {
         self.seeders.to_string()
     }
 
+    pub fn leechers_str(&self) -> String {
+        self.leechers.to_string()
+    }
+
+    /// Whether the source reports seeders/leechers
+    fn has_swarm_info(&self) -> bool {
+        self.source != "yts"
+    }
+
+    /// Seeders per leecher (leechers clamped to at least 1)
+    pub fn health_ratio(&self) -> f64 {
+        self.seeders.max(0) as f64 / self.leechers.max(1) as f64
+    }
+
+    pub fn health_ratio_str(&self) -> String {
+        if self.has_swarm_info() {
+            format!("{:.1}", self.health_ratio())
+        } else {
+            "-".to_string()
+        }
+    }
+
+    /// Swarm health: plenty of seeders or more seeders than leechers is good,
+    /// a handful of seeders is fair, no seeders is dead
+    pub fn health(&self) -> Health {
+        if !self.has_swarm_info() {
+            Health::Unknown
+        } else if self.seeders >= 50 || (self.seeders >= 10 && self.health_ratio() >= 1.0) {
+            Health::Good
+        } else if self.seeders >= 3 {
+            Health::Fair
+        } else {
+            Health::Poor
+        }
+    }
+
+    /// Audio and subtitle languages for display, e.g. "IT+EN sub IT"
+    pub fn languages_str(&self) -> String {
+        let audio = self.audio.join("+");
+        match (audio.is_empty(), self.subtitles.is_empty()) {
+            (_, true) => audio,
+            (true, false) => format!("sub {}", self.subtitles.join("+")),
+            (false, false) => format!("{} sub {}", audio, self.subtitles.join("+")),
+        }
+    }
+
     pub fn size_str(&self) -> String {
         self.size.clone()
     }
@@ -44,15 +116,65 @@ impl TorrentResult {
     pub fn source_str(&self) -> String {
         self.source.clone()
     }
+
+    /// Info hash from the magnet link (lowercased), if present
+    pub fn info_hash(&self) -> Option<String> {
+        magnet_info_hash(&self.magnet)
+    }
+
+    /// Identity for dropping duplicates: the info hash, else the magnet, else (for
+    /// fast-search results without a magnet yet) the detail page
+    pub fn dedup_key(&self) -> String {
+        self.info_hash().unwrap_or_else(|| match (&self.url, self.magnet.is_empty()) {
+            (Some(url), true) => url.clone(),
+            _ => self.magnet.clone(),
+        })
+    }
+
+    /// Whether the magnet is still to be fetched from the detail page (fast search)
+    pub fn needs_resolve(&self) -> bool {
+        self.magnet.is_empty() && self.url.is_some()
+    }
 }
 
-/// HTTP client with standard headers
+/// Info hash of a magnet link as lowercase hex (base32 hashes are converted)
+pub fn magnet_info_hash(magnet: &str) -> Option<String> {
+    let start = magnet.find("xt=urn:btih:")? + "xt=urn:btih:".len();
+    let hash: String = magnet[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect();
+    match hash.len() {
+        0 => None,
+        32 => base32_to_hex(&hash).or(Some(hash.to_lowercase())),
+        _ => Some(hash.to_lowercase()),
+    }
+}
+
+/// Decode an RFC 4648 base32 string into lowercase hex
+fn base32_to_hex(s: &str) -> Option<String> {
+    let mut bits: u64 = 0;
+    let mut bit_count = 0;
+    let mut hex = String::new();
+    for c in s.chars() {
+        let value = match c.to_ascii_uppercase() {
+            c @ 'A'..='Z' => c as u64 - 'A' as u64,
+            c @ '2'..='7' => c as u64 - '2' as u64 + 26,
+            _ => return None,
+        };
+        bits = (bits << 5) | value;
+        bit_count += 5;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            hex.push_str(&format!("{:02x}", (bits >> bit_count) & 0xff));
+        }
+    }
+    Some(hex)
+}
+
+/// HTTP client with standard headers (shared, so connections are reused)
 pub fn create_client() -> Result<Client> {
-    Client::builder()
-        .timeout(Duration::from_secs(15))
-        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
-        .build()
-        .map_err(Into::into)
+    Ok(crate::http::scraper_client())
 }
 
 /// Fetch URL and return HTML
@@ -67,87 +189,394 @@ pub async fn fetch(client: &Client, url: &str) -> Option<String> {
         .ok()
 }
 
-/// Clean and trim text
+/// A source's value in a per-source setting like `1337x:8,tpb:20,*:30`, where `*`
+/// applies to all other sources; entries failing `valid` are skipped
+fn source_setting<T: std::str::FromStr>(var: &str, source: &str, valid: impl Fn(&T) -> bool) -> Option<T> {
+    let settings = std::env::var(var).ok()?;
+    let mut fallback = None;
+
+    for entry in settings.split(',') {
+        let (name, value) = match entry.split_once(':') {
+            Some((n, v)) => (n.trim(), v.trim()),
+            None => continue,
+        };
+        let value: T = match value.parse() {
+            Ok(v) if valid(&v) => v,
+            _ => continue,
+        };
+        if name.eq_ignore_ascii_case(source) {
+            return Some(value);
+        }
+        if name == "*" {
+            fallback = Some(value);
+        }
+    }
+
+    fallback
+}
+
+/// Maximum number of results to keep for a source, from SOURCE_LIMITS
+/// (e.g. `SOURCE_LIMITS=1337x:8,tpb:20,*:30`; `*` applies to all other sources)
+pub fn source_limit(source: &str) -> Option<usize> {
+    source_setting("SOURCE_LIMITS", source, |&limit: &usize| limit > 0)
+}
+
+/// Longest random delay before each request to a source, from REQUEST_JITTER in
+/// milliseconds (e.g. `REQUEST_JITTER=1337x:2000,*:500`; default 300)
+pub fn request_jitter(source: &str) -> Duration {
+    Duration::from_millis(source_setting("REQUEST_JITTER", source, |_: &u64| true).unwrap_or(300))
+}
+
+/// Wait a random part of the source's [`request_jitter`], so requests don't go out in
+/// a machine-regular rhythm
+pub async fn pause(source: &str) {
+    let max = request_jitter(source).as_millis() as u64;
+    if max > 0 {
+        tokio::time::sleep(Duration::from_millis(crate::http::random_below(max + 1))).await;
+    }
+}
+
+/// Clean and trim text, decoding HTML entities
 pub fn clean_text(text: &str) -> String {
-    text.trim().to_string()
+    decode_entities(text).trim().to_string()
 }
 
-/// Available scrapers
-pub const SCRAPERS: &[&str] = &["1337x", "tpb", "bitsearch", "yts", "ilcorsaronero"];
+/// Magnet link from an href or raw page source, with entities like `&amp;` decoded
+pub fn clean_magnet(href: &str) -> String {
+    decode_entities(href.trim())
+}
 
-/// Search all sites in parallel
-pub async fn search_all(query: &str, page: u32) -> Vec<TorrentResult> {
-    let client = match create_client() {
-        Ok(c) => c,
-        Err(e) => {
-            log_error("client", &format!("Failed to create HTTP client: {}", e));
-            return Vec::new();
-        }
-    };
+/// Query a health check searches for: any working site has results for it
+pub const HEALTH_QUERY: &str = "ubuntu";
 
-    log_info("search", &format!("Searching for '{}' (page {})", query, page));
+/// A torrent site littlejohn can search. Built-in sites and WASM plugins implement it,
+/// and searches find them by name in the [`registry`].
+pub trait Scraper: Send + Sync {
+    /// Source name, as shown in the UI and used in settings like SOURCE_LIMITS
+    fn name(&self) -> &'static str;
 
-    // Run all scrapers in parallel
-    let (r1337x, rtpb, rbitsearch, ryts, rilcorsaronero) = tokio::join!(
-        scrape_1337x(&client, query, page),
-        scrape_tpb(&client, query, page),
-        scrape_bitsearch(&client, query, page),
-        scrape_yts(&client, query, page),
-        scrape_ilcorsaronero(&client, query, page),
-    );
+    /// One page of results for `query`, None on a network or parse failure. With `fast`,
+    /// sites that keep magnets on detail pages skip them (see [`Scraper::resolve`]).
+    fn search<'a>(
+        &'a self,
+        client: &'a Client,
+        query: &'a str,
+        page: u32,
+        fast: bool,
+        cancel: &'a CancellationToken,
+    ) -> BoxFuture<'a, Option<Vec<TorrentResult>>>;
 
-    let mut results = Vec::new();
+    /// What a fast search skipped for `result`; None for sites that never skip anything
+    fn resolve<'a>(&'a self, _client: &'a Client, _result: &'a TorrentResult) -> BoxFuture<'a, Option<Vec<TorrentResult>>> {
+        Box::pin(async { None })
+    }
 
-    // Collect results with logging
-    match r1337x {
-        Some(ref r) if !r.is_empty() => {
-            log_info("1337x", &format!("Found {} results", r.len()));
-            results.extend(r.clone());
-        }
-        Some(_) => log_info("1337x", "No results found"),
-        None => log_error("1337x", "Scraper failed (returned None)"),
+    /// Results the site has for [`HEALTH_QUERY`], None if it can't be searched
+    fn health_check<'a>(&'a self, client: &'a Client) -> BoxFuture<'a, Option<usize>> {
+        Box::pin(async move {
+            let cancel = CancellationToken::new();
+            self.search(client, HEALTH_QUERY, 1, true, &cancel).await.map(|results| results.len())
+        })
     }
+}
 
-    match rtpb {
-        Some(ref r) if !r.is_empty() => {
-            log_info("tpb", &format!("Found {} results", r.len()));
-            results.extend(r.clone());
+/// Built-in scrapers, in the order they're listed
+pub static SCRAPERS: &[&dyn Scraper] = &[
+    &x1337::X1337,
+    &tpb::Tpb,
+    &bitsearch::Bitsearch,
+    &yts::Yts,
+    &ilcorsaronero::IlCorsaroNero,
+    &rutracker::Rutracker,
+    &nyaa::Nyaa,
+];
+
+/// Built-in scrapers followed by the loaded WASM plugins
+pub fn registry() -> Vec<&'static dyn Scraper> {
+    let mut scrapers = SCRAPERS.to_vec();
+    scrapers.extend(plugins::scrapers());
+    scrapers
+}
+
+/// The scraper for a source name
+pub fn find(source: &str) -> Option<&'static dyn Scraper> {
+    SCRAPERS.iter().copied().chain(plugins::scrapers()).find(|s| s.name() == source)
+}
+
+/// Names of every source, built-in ones first
+pub fn all_sources() -> Vec<&'static str> {
+    registry().iter().map(|s| s.name()).collect()
+}
+
+/// Client for requests to `source`, with its session cookie if it has credentials
+pub async fn session_client(source: &str) -> Client {
+    match auth::cookie(source, false).await {
+        Some(cookie) => crate::http::scraper_client_with_cookie(&cookie),
+        None => crate::http::source_client(source),
+    }
+}
+
+/// Sources searched unless the user turns them off: all but those in DISABLED_SOURCES
+pub fn default_enabled() -> std::collections::HashSet<String> {
+    let disabled = std::env::var("DISABLED_SOURCES").unwrap_or_default();
+    let disabled: Vec<&str> = disabled.split(',').map(str::trim).collect();
+    all_sources()
+        .into_iter()
+        .filter(|s| !disabled.iter().any(|d| d.eq_ignore_ascii_case(s)))
+        .map(String::from)
+        .collect()
+}
+
+/// Outcome of a scraper run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SourceStatus {
+    /// Returned this many results
+    Ok(usize),
+    /// Ran successfully but found nothing
+    Empty,
+    /// Network or parse failure
+    Failed,
+}
+
+impl SourceStatus {
+    /// Combine the outcomes of several queries against the same source
+    fn merge(self, other: SourceStatus) -> SourceStatus {
+        match (self, other) {
+            (SourceStatus::Ok(a), SourceStatus::Ok(b)) => SourceStatus::Ok(a + b),
+            (SourceStatus::Ok(a), _) | (_, SourceStatus::Ok(a)) => SourceStatus::Ok(a),
+            (SourceStatus::Empty, _) | (_, SourceStatus::Empty) => SourceStatus::Empty,
+            _ => SourceStatus::Failed,
         }
-        Some(_) => log_info("tpb", "No results found"),
-        None => log_error("tpb", "Scraper failed (returned None)"),
     }
+}
+
+/// Per-source outcomes of a search
+pub type SourceStatuses = HashMap<String, SourceStatus>;
 
-    match rbitsearch {
-        Some(ref r) if !r.is_empty() => {
-            log_info("bitsearch", &format!("Found {} results", r.len()));
-            results.extend(r.clone());
+/// Run a single scraper by name, with the source's session cookie if it has credentials.
+/// With `fast`, sources that keep magnets on detail pages skip them (see [`resolve`]).
+/// When `cancel` fires, requests in flight are dropped and None is returned.
+pub async fn scrape_source(
+    client: &Client,
+    source: &str,
+    query: &str,
+    page: u32,
+    fast: bool,
+    cancel: &CancellationToken,
+) -> Option<Vec<TorrentResult>> {
+    let scrape = async {
+        match auth::cookie(source, false).await {
+            None => scrape_with(client, source, query, page, fast, cancel).await,
+            Some(cookie) => {
+                let client = crate::http::scraper_client_with_cookie(&cookie);
+                let outcome = scrape_with(&client, source, query, page, fast, cancel).await;
+                // The site may have ended the session early: log in again and retry once
+                if outcome.is_none() && auth::has_login(source) {
+                    log_info(source, "Search failed, renewing the session");
+                    let cookie = auth::cookie(source, true).await?;
+                    scrape_with(&crate::http::scraper_client_with_cookie(&cookie), source, query, page, fast, cancel).await
+                } else {
+                    outcome
+                }
+            }
+        }
+    };
+    let results = tokio::select! {
+        _ = cancel.cancelled() => {
+            log_info(source, "Search cancelled");
+            return None;
         }
-        Some(_) => log_info("bitsearch", "No results found"),
-        None => log_error("bitsearch", "Scraper failed (returned None)"),
+        results = scrape => results,
+    }?;
+    Some(with_languages(results))
+}
+
+/// Fill in the languages detected from release names
+fn with_languages(mut results: Vec<TorrentResult>) -> Vec<TorrentResult> {
+    for result in &mut results {
+        let languages = crate::release::languages(&result.name);
+        result.audio = languages.audio;
+        result.subtitles = languages.subtitles;
     }
+    results
+}
 
-    match ryts {
-        Some(ref r) if !r.is_empty() => {
-            log_info("yts", &format!("Found {} results", r.len()));
-            results.extend(r.clone());
+async fn scrape_with(
+    client: &Client,
+    source: &str,
+    query: &str,
+    page: u32,
+    fast: bool,
+    cancel: &CancellationToken,
+) -> Option<Vec<TorrentResult>> {
+    match find(source) {
+        Some(scraper) => scraper.search(client, query, page, fast, cancel).await,
+        None => {
+            log_error(source, "Unknown source");
+            None
         }
-        Some(_) => log_info("yts", "No results found"),
-        None => log_error("yts", "Scraper failed (returned None)"),
     }
+}
+
+/// Fetch what a fast search skipped for a result: its magnet from the detail page, or
+/// for YTS the movie's torrents (one per quality). None if the page couldn't be read.
+pub async fn resolve(result: &TorrentResult) -> Option<Vec<TorrentResult>> {
+    let client = session_client(&result.source).await;
+    let resolved = find(&result.source)?.resolve(&client, result).await?;
+    Some(with_languages(resolved))
+}
+
+/// Probe the alternate mirrors of multi-domain sources (TPB, YTS) in the background
+pub async fn probe_mirrors() {
+    let client = crate::http::scraper_client();
+    mirrors::probe(&client, "tpb", tpb::TPB_PROXIES).await;
+    mirrors::probe(&client, "yts", yts::YTS_DOMAINS).await;
+}
 
-    match rilcorsaronero {
-        Some(ref r) if !r.is_empty() => {
-            log_info("ilcorsaronero", &format!("Found {} results", r.len()));
-            results.extend(r.clone());
+/// Search the given sources for one or more comma-separated queries concurrently,
+/// merging the results, dropping duplicate torrents and reporting per-source status.
+/// `fast` skips detail pages (see [`scrape_source`]). Once `cancel` fires every scraper
+/// stops and nothing is returned.
+pub async fn search_sources(
+    query: &str,
+    page: u32,
+    sources: &[&str],
+    fast: bool,
+    cancel: &CancellationToken,
+) -> (Vec<TorrentResult>, SourceStatuses) {
+    let queries: Vec<&str> = query
+        .split(',')
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .collect();
+
+    log_info("search", &format!("Searching for '{}' (page {}) on {}", queries.join(" | "), page, sources.join(", ")));
+
+    // Run every (query, source) pair in parallel
+    let jobs: Vec<(&str, &str)> = queries
+        .iter()
+        .flat_map(|q| sources.iter().map(move |s| (*q, *s)))
+        .collect();
+    let outcomes = futures::future::join_all(jobs.iter().map(|(q, s)| {
+        // Each source gets its own pick of user agent
+        let client = crate::http::source_client(s);
+        async move {
+            let start = Instant::now();
+            let outcome = scrape_source(&client, s, q, page, fast, cancel).await;
+            // A search cut short says nothing about the source's health
+            if !cancel.is_cancelled() {
+                stats::record(s, start.elapsed(), outcome.as_ref().map(Vec::len));
+            }
+            outcome
         }
-        Some(_) => log_info("ilcorsaronero", "No results found"),
-        None => log_error("ilcorsaronero", "Scraper failed (returned None)"),
+    }))
+    .await;
+
+    if cancel.is_cancelled() {
+        log_info("search", "Search cancelled");
+        return (Vec::new(), SourceStatuses::new());
+    }
+
+    let mut results = Vec::new();
+    let mut statuses = SourceStatuses::new();
+    let mut seen = HashSet::new();
+
+    // Collect results with logging
+    for ((_, source), outcome) in jobs.iter().zip(outcomes) {
+        let status = match outcome {
+            Some(r) if !r.is_empty() => {
+                log_info(source, &format!("Found {} results", r.len()));
+                let count = r.len();
+                results.extend(
+                    r.into_iter()
+                        .map(normalize::result)
+                        .filter(|t| seen.insert(t.dedup_key())),
+                );
+                SourceStatus::Ok(count)
+            }
+            Some(_) => {
+                log_info(source, "No results found");
+                SourceStatus::Empty
+            }
+            None => {
+                log_error(source, "Scraper failed (returned None)");
+                SourceStatus::Failed
+            }
+        };
+        let merged = match statuses.get(*source) {
+            Some(prev) => prev.merge(status),
+            None => status,
+        };
+        statuses.insert(source.to_string(), merged);
     }
 
-    log_info("search", &format!("Total: {} results from all sources", results.len()));
+    log_info("search", &format!("Total: {} unique results", results.len()));
 
     // Sort by seeders (descending)
-    results.sort_by(|a, b| b.seeders.cmp(&a.seeders));
+    results.sort_by_key(|r| std::cmp::Reverse(r.seeders));
 
-    results
+    (results, statuses)
+}
+
+/// Results a deep search stops at, from `DEEP_SEARCH_RESULTS` (default 50)
+pub fn deep_search_target() -> usize {
+    std::env::var("DEEP_SEARCH_RESULTS").ok().and_then(|v| v.trim().parse().ok()).unwrap_or(50)
+}
+
+/// Pages a deep search reads at most, from `DEEP_SEARCH_PAGES` (default 5)
+pub fn deep_search_pages() -> u32 {
+    std::env::var("DEEP_SEARCH_PAGES")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|&pages| pages > 0)
+        .unwrap_or(5)
+}
+
+/// Search page after page until there are `target` results or `max_pages` pages were
+/// read. A source is dropped once a page brings nothing new from it (it ran out, or
+/// ignores paging). Returns the last page read along with the merged results.
+pub async fn deep_search(
+    query: &str,
+    sources: &[&str],
+    fast: bool,
+    target: usize,
+    max_pages: u32,
+    cancel: &CancellationToken,
+) -> (Vec<TorrentResult>, SourceStatuses, u32) {
+    let mut results: Vec<TorrentResult> = Vec::new();
+    let mut statuses = SourceStatuses::new();
+    let mut seen = HashSet::new();
+    let mut active: Vec<&str> = sources.to_vec();
+    let mut page = 1;
+
+    loop {
+        let (found, page_statuses) = search_sources(query, page, &active, fast, cancel).await;
+        let before = results.len();
+        results.extend(found.into_iter().filter(|t| seen.insert(t.dedup_key())));
+        let new = &results[before..];
+        active.retain(|source| new.iter().any(|t| t.source == *source));
+
+        for (source, status) in page_statuses {
+            // Later pages only add results; a failure there doesn't undo an earlier page
+            let merged = match statuses.remove(&source) {
+                Some(prev) => prev.merge(status),
+                None => status,
+            };
+            statuses.insert(source, merged);
+        }
+
+        if results.len() >= target || active.is_empty() || page >= max_pages || cancel.is_cancelled() {
+            break;
+        }
+        page += 1;
+        log_info("search", &format!("Deep search: {} results so far, reading page {}", results.len(), page));
+    }
+
+    results.sort_by_key(|r| std::cmp::Reverse(r.seeders));
+    (results, statuses, page)
+}
+
+/// Search all sites in parallel
+pub async fn search_all(query: &str, page: u32) -> Vec<TorrentResult> {
+    search_sources(query, page, &all_sources(), false, &CancellationToken::new()).await.0
 }
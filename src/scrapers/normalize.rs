@@ -0,0 +1,148 @@
+//! Normalization of scraped values: sources differ in how they write sizes ("1.4 GiB",
+//! "1,4 GB"), counts ("1.2k", "1,234") and names (HTML entities, stray whitespace)
+
+use super::TorrentResult;
+
+/// Named entities seen in scraped pages (the numeric forms are decoded too)
+const ENTITIES: &[(&str, &str)] = &[
+    ("amp", "&"),
+    ("lt", "<"),
+    ("gt", ">"),
+    ("quot", "\""),
+    ("apos", "'"),
+    ("nbsp", " "),
+    ("ndash", "–"),
+    ("mdash", "—"),
+    ("hellip", "…"),
+    ("agrave", "à"),
+    ("egrave", "è"),
+    ("eacute", "é"),
+    ("igrave", "ì"),
+    ("ograve", "ò"),
+    ("ugrave", "ù"),
+];
+
+/// Replace HTML entities (`&amp;`, `&#39;`, `&#x3D;`...) with the characters they stand
+/// for; anything that isn't a known entity is kept as is
+pub fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest[1..].find(';').filter(|&end| end <= 10).and_then(|end| {
+            let name = &rest[1..end + 1];
+            let ch = match name.strip_prefix('#') {
+                Some(code) => match code.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32).map(String::from),
+                    None => code.parse().ok().and_then(char::from_u32).map(String::from),
+                },
+                None => ENTITIES.iter().find(|(n, _)| *n == name).map(|(_, c)| c.to_string()),
+            }?;
+            Some((ch, end + 2))
+        });
+        match decoded {
+            Some((ch, len)) => {
+                out.push_str(&ch);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Decoded text with runs of whitespace collapsed to single spaces
+pub fn text(raw: &str) -> String {
+    decode_entities(raw).split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Parse a number written with either separator. A lone separator followed by exactly
+/// three digits, or one that repeats, groups thousands ("1,234", "1.234.567"); otherwise
+/// it's decimal ("1,4", "1.46"). With both kinds, the last one is the decimal separator.
+fn number(text: &str) -> Option<f64> {
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let normalized = match text.rfind(['.', ',']) {
+        None => text,
+        Some(i) => {
+            let (int, frac) = (&text[..i], &text[i + 1..]);
+            let last = &text[i..i + 1];
+            let grouping = if int.contains(['.', ',']) {
+                // "1,234,567" groups; "1.234,5" has a decimal comma
+                !int.contains(if last == "." { ',' } else { '.' })
+            } else {
+                frac.len() == 3 && !int.is_empty()
+            };
+            if grouping {
+                text.replace(last, "")
+            } else {
+                format!("{}.{}", int.replace(['.', ','], ""), frac)
+            }
+        }
+    };
+    normalized.parse().ok().filter(|n: &f64| n.is_finite())
+}
+
+/// Size in bytes from text like "1.4 GiB", "1,4 GB" or "700MB" (units are powers of 1024,
+/// as most sites mean them whatever they're called)
+pub fn size_bytes(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ',' || c.is_whitespace()))
+        .unwrap_or(text.len());
+    let value = number(&text[..split])?;
+    let unit = text[split..].trim().to_uppercase();
+    let power = match unit.trim_end_matches("BYTES").trim_end_matches("IB").trim_end_matches('B') {
+        "" => 0,
+        "K" => 1,
+        "M" => 2,
+        "G" => 3,
+        "T" => 4,
+        _ => return None,
+    };
+    (value >= 0.0).then(|| (value * 1024f64.powi(power)) as u64)
+}
+
+/// Size in one format for every source, e.g. "1.4 GB"; text that isn't a size is only tidied
+pub fn size(raw: &str) -> String {
+    let Some(bytes) = size_bytes(raw) else {
+        return text(raw);
+    };
+    let mut value = bytes as f64;
+    for unit in ["B", "KB", "MB", "GB"] {
+        if value < 1024.0 {
+            return format!("{:.1} {}", value, unit);
+        }
+        value /= 1024.0;
+    }
+    format!("{:.1} TB", value)
+}
+
+/// Seeder/leecher count from text like "1234", "1,234" or "1.2k"; 0 when unreadable
+pub fn count(text: &str) -> i64 {
+    let text = text.trim().to_lowercase();
+    let (digits, multiplier) = match text.char_indices().last() {
+        Some((i, 'k')) => (&text[..i], 1_000.0),
+        Some((i, 'm')) => (&text[..i], 1_000_000.0),
+        _ => (text.as_str(), 1.0),
+    };
+    // In "1.2k" the separator is always a decimal one
+    let value = if multiplier > 1.0 {
+        digits.trim().replace(',', ".").parse().ok()
+    } else {
+        number(digits)
+    };
+    value.map(|n| (n * multiplier).round().max(0.0) as i64).unwrap_or(0)
+}
+
+/// A result with its name, size and magnet normalized
+pub fn result(mut result: TorrentResult) -> TorrentResult {
+    result.name = text(&result.name);
+    result.magnet = decode_entities(result.magnet.trim());
+    result.size = size(&result.size);
+    result
+}
@@ -0,0 +1,138 @@
+//! Nyaa scraper - anime torrents, with magnets and Nyaa's category on the search page
+
+use super::{clean_magnet, clean_text, log_error, log_info, normalize, pause, selectors, source_limit, Scraper, TorrentResult};
+use futures::future::BoxFuture;
+use reqwest::Client;
+use scraper::Html;
+use tokio_util::sync::CancellationToken;
+
+const BASE_URL: &str = "https://nyaa.si";
+
+/// Site root, BASE_URL unless overridden (NYAA_URL, e.g. a mirror or a local mock server)
+fn base_url() -> String {
+    std::env::var("NYAA_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .unwrap_or_else(|| BASE_URL.to_string())
+}
+
+/// Nyaa category searched: all of Anime (English-translated, raw, music videos...)
+const CATEGORY: &str = "1_0";
+
+/// Search page URL, best-seeded first
+fn search_url(query: &str, page: u32) -> String {
+    format!(
+        "{}/?f=0&c={}&q={}&s=seeders&o=desc&p={}",
+        base_url(),
+        CATEGORY,
+        urlencoding::encode(query),
+        page.max(1)
+    )
+}
+
+/// Parse the torrent list of a search page
+fn parse_search_results(html: &str) -> Option<Vec<TorrentResult>> {
+    let document = Html::parse_document(html);
+    let row_sel = selectors::selector("nyaa", "row", "table.torrent-list tbody tr")?;
+    let category_sel = selectors::selector("nyaa", "category", "td:first-child a")?;
+    let title_sel = selectors::selector("nyaa", "title", "td[colspan='2'] a:not(.comments)")?;
+    let magnet_sel = selectors::selector("nyaa", "magnet", "a[href^='magnet:']")?;
+    let cell_sel = selectors::selector("nyaa", "cell", "td.text-center")?;
+
+    let base = base_url();
+    let mut results = Vec::new();
+    for row in document.select(&row_sel) {
+        let Some(link) = row.select(&title_sel).last() else { continue };
+        let name = clean_text(link.value().attr("title").unwrap_or(&link.text().collect::<String>()));
+        let Some(magnet) = row.select(&magnet_sel).next().and_then(|el| el.value().attr("href")).map(clean_magnet) else {
+            continue;
+        };
+        if name.is_empty() {
+            continue;
+        }
+        let url = link.value().attr("href").map(|href| format!("{}{}", base, href));
+        // e.g. "Anime - English-translated"
+        let category = row
+            .select(&category_sel)
+            .next()
+            .and_then(|el| el.value().attr("title"))
+            .map(clean_text)
+            .filter(|c| !c.is_empty());
+
+        // Centered cells: links, size, date, seeders, leechers, completed downloads
+        let cells: Vec<String> = row.select(&cell_sel).map(|el| clean_text(&el.text().collect::<String>())).collect();
+        let cell = |i: usize| cells.get(i).cloned().unwrap_or_default();
+
+        results.push(TorrentResult {
+            name,
+            size: cell(1),
+            seeders: normalize::count(&cell(3)),
+            leechers: normalize::count(&cell(4)),
+            magnet,
+            source: "nyaa".to_string(),
+            url,
+            category,
+            cover: None,
+            audio: Vec::new(),
+            subtitles: Vec::new(),
+        });
+    }
+    Some(results)
+}
+
+/// Fetch and parse a Nyaa search page
+pub async fn fetch_results(client: &Client, url: &str) -> Option<Vec<TorrentResult>> {
+    pause("nyaa").await;
+    let html = match client.get(url).send().await {
+        Ok(resp) if resp.status().is_success() => resp.text().await.ok()?,
+        Ok(resp) => {
+            log_error("nyaa", &format!("HTTP {} for {}", resp.status(), url));
+            return None;
+        }
+        Err(e) => {
+            log_error("nyaa", &format!("Request failed: {}", e));
+            return None;
+        }
+    };
+
+    let mut results = match parse_search_results(&html) {
+        Some(results) => results,
+        None => {
+            log_error("nyaa", "Failed to parse selectors");
+            return None;
+        }
+    };
+    if let Some(limit) = source_limit("nyaa") {
+        results.truncate(limit);
+    }
+    log_info("nyaa", &format!("Returning {} results", results.len()));
+    Some(results)
+}
+
+/// Scrape nyaa.si for anime torrents
+pub async fn scrape_nyaa(client: &Client, query: &str, page: u32) -> Option<Vec<TorrentResult>> {
+    let url = search_url(query, page);
+    log_info("nyaa", &format!("Fetching: {}", url));
+    fetch_results(client, &url).await
+}
+
+/// nyaa in the scraper registry
+pub struct Nyaa;
+
+impl Scraper for Nyaa {
+    fn name(&self) -> &'static str {
+        "nyaa"
+    }
+
+    fn search<'a>(
+        &'a self,
+        client: &'a Client,
+        query: &'a str,
+        page: u32,
+        _fast: bool,
+        _cancel: &'a CancellationToken,
+    ) -> BoxFuture<'a, Option<Vec<TorrentResult>>> {
+        Box::pin(scrape_nyaa(client, query, page))
+    }
+}
@@ -0,0 +1,261 @@
+//! WASM scraper plugins loaded from `<config dir>/littlejohn/plugins/*.wasm`
+//!
+//! A plugin is named after its file and exports:
+//! - `memory`
+//! - `alloc(len: i32) -> i32`: reserve `len` bytes for data passed in by the host
+//! - `search(query_ptr: i32, query_len: i32, page: i32) -> i64`: results as a JSON array
+//!   of `{name, size, seeders, leechers, magnet, url?, category?, cover?}`, returned as
+//!   `(ptr << 32) | len`
+//!
+//! Host functions (module `littlejohn`):
+//! - `fetch(url_ptr: i32, url_len: i32) -> i64`: GET a URL (with the source's session
+//!   cookie, if it has credentials); the body is written to memory from `alloc` and
+//!   returned as `(ptr << 32) | len`, or -1 on failure
+//! - `log(ptr: i32, len: i32)`: write a line to the scraper log
+
+use anyhow::{anyhow, Context, Result};
+use futures::future::BoxFuture;
+use reqwest::Client;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tokio_util::sync::CancellationToken;
+use wasmi::{Caller, Config, Engine, Extern, Linker, Module, Store};
+
+use super::{log_error, log_info, Scraper, TorrentResult};
+
+/// Instruction budget for one search, so a stuck plugin can't hang a search
+const FUEL: u64 = 5_000_000_000;
+
+static PLUGINS: OnceLock<Vec<Plugin>> = OnceLock::new();
+
+/// A compiled plugin, searched through the scraper registry like a built-in site
+pub struct Plugin {
+    name: &'static str,
+    engine: Engine,
+    module: Module,
+}
+
+/// A result as returned by a plugin
+#[derive(Debug, Deserialize)]
+struct PluginResult {
+    name: String,
+    #[serde(default)]
+    size: String,
+    #[serde(default)]
+    seeders: i64,
+    #[serde(default)]
+    leechers: i64,
+    magnet: String,
+    url: Option<String>,
+    category: Option<String>,
+    cover: Option<String>,
+}
+
+/// Directory plugins are loaded from
+pub fn plugin_dir() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("littlejohn").join("plugins"))
+}
+
+/// Compile every plugin in the plugin directory (once); broken plugins are logged and skipped
+fn plugins() -> &'static [Plugin] {
+    PLUGINS.get_or_init(|| {
+        let Some(entries) = plugin_dir().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "wasm"))
+            .collect();
+        paths.sort();
+
+        let mut plugins: Vec<Plugin> = Vec::new();
+        for path in paths {
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else { continue };
+            if super::SCRAPERS.iter().any(|s| s.name() == name) || plugins.iter().any(|p| p.name == name) {
+                log_error(name, "Plugin name clashes with another source, skipping");
+                continue;
+            }
+            match compile(&path) {
+                Ok((engine, module)) => {
+                    log_info(name, "Loaded WASM plugin");
+                    plugins.push(Plugin { name: Box::leak(name.to_string().into_boxed_str()), engine, module });
+                }
+                Err(e) => log_error(name, &format!("Failed to load plugin: {}", e)),
+            }
+        }
+        plugins
+    })
+}
+
+fn compile(path: &std::path::Path) -> Result<(Engine, Module)> {
+    let bytes = std::fs::read(path)?;
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config);
+    let module = Module::new(&engine, &bytes).map_err(|e| anyhow!("{}", e))?;
+    Ok((engine, module))
+}
+
+/// The loaded plugins as scrapers
+pub fn scrapers() -> impl Iterator<Item = &'static dyn Scraper> {
+    plugins().iter().map(|p| p as &dyn Scraper)
+}
+
+impl Scraper for Plugin {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn search<'a>(
+        &'a self,
+        client: &'a Client,
+        query: &'a str,
+        page: u32,
+        _fast: bool,
+        cancel: &'a CancellationToken,
+    ) -> BoxFuture<'a, Option<Vec<TorrentResult>>> {
+        Box::pin(scrape(client, self.name, query, page, cancel))
+    }
+}
+
+/// Run a plugin's search. Returns None if there's no such plugin or it failed. Once
+/// `cancel` fires, the plugin's fetches fail without reaching the network.
+pub async fn scrape(client: &Client, source: &str, query: &str, page: u32, cancel: &CancellationToken) -> Option<Vec<TorrentResult>> {
+    let plugin = plugins().iter().find(|p| p.name == source)?;
+    let host = Host {
+        name: plugin.name,
+        runtime: tokio::runtime::Handle::current(),
+        client: client.clone(),
+        cancel: cancel.clone(),
+    };
+    let query = query.to_string();
+
+    let outcome = tokio::task::spawn_blocking(move || run(plugin, host, &query, page)).await;
+    match outcome {
+        Ok(Ok(results)) => Some(results),
+        Ok(Err(e)) => {
+            log_error(plugin.name, &format!("Plugin failed: {:#}", e));
+            None
+        }
+        Err(e) => {
+            log_error(plugin.name, &format!("Plugin panicked: {}", e));
+            None
+        }
+    }
+}
+
+/// Host state: the plugin name (for logging), and a runtime handle, client and
+/// cancellation token for fetches
+struct Host {
+    name: &'static str,
+    runtime: tokio::runtime::Handle,
+    client: Client,
+    cancel: CancellationToken,
+}
+
+/// Instantiate the plugin and call its `search` export (runs on a blocking thread)
+fn run(plugin: &Plugin, host: Host, query: &str, page: u32) -> Result<Vec<TorrentResult>> {
+    let mut store = Store::new(&plugin.engine, host);
+    store.set_fuel(FUEL).map_err(|e| anyhow!("{}", e))?;
+
+    let mut linker = <Linker<Host>>::new(&plugin.engine);
+    linker
+        .func_wrap("littlejohn", "log", |caller: Caller<'_, Host>, ptr: i32, len: i32| {
+            if let Some(bytes) = read_guest(&caller, ptr, len) {
+                log_info(caller.data().name, &String::from_utf8_lossy(&bytes));
+            }
+        })
+        .map_err(|e| anyhow!("{}", e))?;
+    linker
+        .func_wrap("littlejohn", "fetch", |mut caller: Caller<'_, Host>, ptr: i32, len: i32| -> i64 {
+            let Some(url) = read_guest(&caller, ptr, len).and_then(|b| String::from_utf8(b).ok()) else {
+                return -1;
+            };
+            let host = caller.data();
+            let body = host.runtime.block_on(async {
+                tokio::select! {
+                    _ = host.cancel.cancelled() => None,
+                    body = super::fetch(&host.client, &url) => body,
+                }
+            });
+            match body {
+                Some(body) => write_guest(&mut caller, body.as_bytes()).unwrap_or(-1),
+                None => -1,
+            }
+        })
+        .map_err(|e| anyhow!("{}", e))?;
+
+    let instance = linker
+        .instantiate(&mut store, &plugin.module)
+        .and_then(|pre| pre.start(&mut store))
+        .map_err(|e| anyhow!("{}", e))?;
+    let search = instance
+        .get_typed_func::<(i32, i32, i32), i64>(&store, "search")
+        .map_err(|e| anyhow!("missing search export: {}", e))?;
+
+    // Pass the query in through the plugin's allocator
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&store, "alloc")
+        .map_err(|e| anyhow!("missing alloc export: {}", e))?;
+    let memory = instance.get_memory(&store, "memory").context("missing memory export")?;
+    let query_ptr = alloc.call(&mut store, query.len() as i32).map_err(|e| anyhow!("{}", e))?;
+    memory
+        .write(&mut store, query_ptr as usize, query.as_bytes())
+        .map_err(|e| anyhow!("{}", e))?;
+
+    let packed = search
+        .call(&mut store, (query_ptr, query.len() as i32, page as i32))
+        .map_err(|e| anyhow!("{}", e))?;
+    let (ptr, len) = unpack(packed).context("search returned no results buffer")?;
+    let mut json = vec![0u8; len];
+    memory.read(&store, ptr, &mut json).map_err(|e| anyhow!("{}", e))?;
+
+    let results: Vec<PluginResult> = serde_json::from_slice(&json).context("invalid results JSON")?;
+    Ok(results
+        .into_iter()
+        .filter(|r| !r.magnet.is_empty())
+        .map(|r| TorrentResult {
+            name: r.name,
+            size: r.size,
+            seeders: r.seeders,
+            leechers: r.leechers,
+            magnet: r.magnet,
+            source: plugin.name.to_string(),
+            url: r.url,
+            category: r.category,
+            cover: r.cover,
+            audio: Vec::new(),
+            subtitles: Vec::new(),
+        })
+        .collect())
+}
+
+/// Split a `(ptr << 32) | len` return value
+fn unpack(packed: i64) -> Option<(usize, usize)> {
+    if packed < 0 {
+        return None;
+    }
+    Some(((packed as u64 >> 32) as usize, (packed as u64 & 0xffff_ffff) as usize))
+}
+
+fn read_guest(caller: &Caller<'_, Host>, ptr: i32, len: i32) -> Option<Vec<u8>> {
+    let memory = caller.get_export("memory").and_then(Extern::into_memory)?;
+    let mut buf = vec![0u8; usize::try_from(len).ok()?];
+    memory.read(caller, usize::try_from(ptr).ok()?, &mut buf).ok()?;
+    Some(buf)
+}
+
+/// Copy `data` into memory from the plugin's `alloc` and return it packed
+fn write_guest(caller: &mut Caller<'_, Host>, data: &[u8]) -> Option<i64> {
+    let memory = caller.get_export("memory").and_then(Extern::into_memory)?;
+    let alloc = caller
+        .get_export("alloc")
+        .and_then(Extern::into_func)?
+        .typed::<i32, i32>(&*caller)
+        .ok()?;
+    let len = i32::try_from(data.len()).ok()?;
+    let ptr = alloc.call(&mut *caller, len).ok()?;
+    memory.write(&mut *caller, usize::try_from(ptr).ok()?, data).ok()?;
+    Some(((ptr as u32 as i64) << 32) | data.len() as i64)
+}
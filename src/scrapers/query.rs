@@ -0,0 +1,119 @@
+//! Query normalization for fallback searches
+
+/// Replace accented Latin characters with their ASCII equivalents
+fn transliterate(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        let replacement = match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' => "a",
+            'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' => "A",
+            'æ' => "ae",
+            'Æ' => "AE",
+            'ç' | 'ć' | 'č' => "c",
+            'Ç' | 'Ć' | 'Č' => "C",
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ę' | 'ě' => "e",
+            'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ę' | 'Ě' => "E",
+            'ì' | 'í' | 'î' | 'ï' | 'ī' => "i",
+            'Ì' | 'Í' | 'Î' | 'Ï' | 'Ī' => "I",
+            'ñ' | 'ń' | 'ň' => "n",
+            'Ñ' | 'Ń' | 'Ň' => "N",
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' => "o",
+            'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' => "O",
+            'œ' => "oe",
+            'Œ' => "OE",
+            'ś' | 'š' => "s",
+            'Ś' | 'Š' => "S",
+            'ß' => "ss",
+            'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' => "u",
+            'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ū' | 'Ů' => "U",
+            'ý' | 'ÿ' => "y",
+            'Ý' | 'Ÿ' => "Y",
+            'ź' | 'ż' | 'ž' => "z",
+            'Ź' | 'Ż' | 'Ž' => "Z",
+            'ł' => "l",
+            'Ł' => "L",
+            _ => {
+                out.push(c);
+                continue;
+            }
+        };
+        out.push_str(replacement);
+    }
+    out
+}
+
+/// Swap "&" for "and" (or "and" for "&" if there is no ampersand)
+fn swap_ampersand(text: &str) -> String {
+    if text.contains('&') {
+        text.replace('&', " and ")
+    } else {
+        text.split_whitespace()
+            .map(|w| if w.eq_ignore_ascii_case("and") { "&" } else { w })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Replace punctuation with spaces
+fn strip_punctuation(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_alphanumeric() || c.is_whitespace() { c } else { ' ' })
+        .collect()
+}
+
+/// Remove release years (1900-2099) from the query
+fn strip_year(text: &str) -> String {
+    text.split_whitespace()
+        .filter(|w| {
+            let w = w.trim_matches(|c: char| !c.is_ascii_digit());
+            !(w.len() == 4 && (w.starts_with("19") || w.starts_with("20")) && w.chars().all(|c| c.is_ascii_digit()))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Collapse runs of whitespace
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Normalized variants of a single query, most conservative first
+fn single_variants(query: &str) -> Vec<String> {
+    let ascii = transliterate(query);
+    vec![
+        collapse(&ascii),
+        collapse(&swap_ampersand(&ascii)),
+        collapse(&strip_punctuation(&ascii)),
+        collapse(&strip_year(&strip_punctuation(&ascii))),
+        collapse(&strip_year(&strip_punctuation(&swap_ampersand(&ascii)))),
+    ]
+}
+
+/// Build fallback variants for a (possibly comma-separated) query.
+/// The original query and duplicates are omitted.
+pub fn query_variants(query: &str) -> Vec<String> {
+    let parts: Vec<&str> = query
+        .split(',')
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .collect();
+
+    let per_part: Vec<Vec<String>> = parts.iter().map(|p| single_variants(p)).collect();
+    let count = per_part.first().map(Vec::len).unwrap_or(0);
+
+    let original = collapse(&parts.join(", ")).to_lowercase();
+    let mut seen = std::collections::HashSet::new();
+    seen.insert(original);
+
+    (0..count)
+        .map(|i| {
+            per_part
+                .iter()
+                .map(|v| v[i].as_str())
+                .filter(|v| !v.is_empty())
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .filter(|v| !v.is_empty() && seen.insert(v.to_lowercase()))
+        .collect()
+}
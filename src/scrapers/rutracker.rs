@@ -0,0 +1,187 @@
+//! RuTracker scraper - Russian forum tracker, searching needs an account
+//! (`[auth.rutracker]` username and password in the config file)
+
+use super::auth::LoginForm;
+use super::{clean_magnet, clean_text, log_error, log_info, normalize, pause, selectors, source_limit, Scraper, TorrentResult};
+use futures::future::BoxFuture;
+use reqwest::Client;
+use scraper::Html;
+use tokio_util::sync::CancellationToken;
+
+const BASE_URL: &str = "https://rutracker.org/forum";
+
+/// Results per search page
+const PAGE_SIZE: u32 = 50;
+
+/// Forum login; "вход" is the label of the submit button the form posts
+pub const LOGIN_FORM: LoginForm = LoginForm {
+    url: "https://rutracker.org/forum/login.php",
+    username_field: "login_username",
+    password_field: "login_password",
+    extra: &[("login", "вход")],
+    session_cookie: "bb_session",
+};
+
+/// Search result row: name, topic URL, seeders, leechers, size, forum
+type Item = (String, String, i64, i64, String, Option<String>);
+
+async fn fetch_page(client: &Client, url: &str) -> Option<String> {
+    pause("rutracker").await;
+    match client.get(url).send().await {
+        Ok(resp) if resp.status().is_success() => resp.text().await.ok(),
+        Ok(resp) => {
+            log_error("rutracker", &format!("HTTP {} for {}", resp.status(), url));
+            None
+        }
+        Err(e) => {
+            log_error("rutracker", &format!("Request failed: {}", e));
+            None
+        }
+    }
+}
+
+/// Whether the page is the login form rather than the content asked for
+fn is_login_page(html: &str) -> bool {
+    html.contains("name=\"login_username\"")
+}
+
+/// Parse the tracker search table
+fn parse_search_results(html: &str) -> Option<Vec<Item>> {
+    let document = Html::parse_document(html);
+    let row_sel = selectors::selector("rutracker", "row", "#tor-tbl tbody tr")?;
+    let title_sel = selectors::selector("rutracker", "title", "a.tLink")?;
+    let seeds_sel = selectors::selector("rutracker", "seeds", "b.seedmed")?;
+    let leeches_sel = selectors::selector("rutracker", "leeches", "td.leechmed")?;
+    let size_sel = selectors::selector("rutracker", "size", "td.tor-size")?;
+    let forum_sel = selectors::selector("rutracker", "forum", "td.f-name-col a")?;
+
+    let number = |text: String| normalize::count(&text);
+    let mut items = Vec::new();
+    for row in document.select(&row_sel) {
+        let Some(link) = row.select(&title_sel).next() else { continue };
+        let name = clean_text(&link.text().collect::<String>());
+        let href = link.value().attr("href").unwrap_or("");
+        if name.is_empty() || href.is_empty() {
+            continue;
+        }
+        let url = if href.starts_with("http") {
+            href.to_string()
+        } else {
+            format!("{}/{}", BASE_URL, href.trim_start_matches("./"))
+        };
+
+        let seeders = row.select(&seeds_sel).next().map(|e| number(e.text().collect())).unwrap_or(0);
+        let leechers = row.select(&leeches_sel).next().map(|e| number(e.text().collect())).unwrap_or(0);
+        // Size cell: "1.46 GB ↓" (the arrow links to the .torrent)
+        let size = row
+            .select(&size_sel)
+            .next()
+            .map(|e| clean_text(&e.text().collect::<String>().replace(['\u{a0}', '↓'], " ")))
+            .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "))
+            .unwrap_or_default();
+        let forum = row
+            .select(&forum_sel)
+            .next()
+            .map(|e| clean_text(&e.text().collect::<String>()))
+            .filter(|f| !f.is_empty());
+
+        items.push((name, url, seeders, leechers, size, forum));
+    }
+    Some(items)
+}
+
+/// Magnet link from a topic page
+fn extract_magnet(html: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let magnet_sel = selectors::selector("rutracker", "magnet", "a.magnet-link, a[href^='magnet:']")?;
+    document
+        .select(&magnet_sel)
+        .next()
+        .and_then(|el| el.value().attr("href"))
+        .map(clean_magnet)
+}
+
+/// Scrape rutracker.org for torrents (the client carries the session cookie)
+pub async fn scrape_rutracker(client: &Client, query: &str, page: u32) -> Option<Vec<TorrentResult>> {
+    if !super::auth::has_login("rutracker") {
+        log_error("rutracker", "No credentials - set username and password under [auth.rutracker] in config.toml");
+        return None;
+    }
+
+    let url = format!(
+        "{}/tracker.php?nm={}&start={}",
+        BASE_URL,
+        urlencoding::encode(query),
+        page.saturating_sub(1) * PAGE_SIZE
+    );
+    log_info("rutracker", &format!("Fetching: {}", url));
+    let html = fetch_page(client, &url).await?;
+    if is_login_page(&html) {
+        log_error("rutracker", "Not logged in (session expired?)");
+        return None;
+    }
+
+    let mut items = match parse_search_results(&html) {
+        Some(items) => items,
+        None => {
+            log_error("rutracker", "Failed to parse selectors");
+            return None;
+        }
+    };
+    if items.is_empty() {
+        log_info("rutracker", "No items found in search results");
+        return Some(Vec::new());
+    }
+
+    // Magnets are only on topic pages: fetch the best-seeded ones
+    items.sort_by_key(|item| std::cmp::Reverse(item.2));
+    let limit = source_limit("rutracker").unwrap_or(10);
+    log_info("rutracker", &format!("Found {} items, fetching details...", items.len()));
+
+    let mut results = Vec::new();
+    let mut magnet_failures = 0;
+    for (name, url, seeders, leechers, size, forum) in items.into_iter().take(limit) {
+        match fetch_page(client, &url).await.as_deref().and_then(extract_magnet) {
+            Some(magnet) => results.push(TorrentResult {
+                name,
+                size,
+                seeders,
+                leechers,
+                magnet,
+                source: "rutracker".to_string(),
+                url: Some(url),
+                category: forum,
+                cover: None,
+                audio: Vec::new(),
+                subtitles: Vec::new(),
+            }),
+            None => magnet_failures += 1,
+        }
+    }
+
+    if magnet_failures > 0 {
+        log_info("rutracker", &format!("{} magnet fetches failed", magnet_failures));
+    }
+    log_info("rutracker", &format!("Returning {} results", results.len()));
+    Some(results)
+}
+
+/// rutracker in the scraper registry
+pub struct Rutracker;
+
+impl Scraper for Rutracker {
+    fn name(&self) -> &'static str {
+        "rutracker"
+    }
+
+    fn search<'a>(
+        &'a self,
+        client: &'a Client,
+        query: &'a str,
+        page: u32,
+        _fast: bool,
+        _cancel: &'a CancellationToken,
+    ) -> BoxFuture<'a, Option<Vec<TorrentResult>>> {
+        Box::pin(scrape_rutracker(client, query, page))
+    }
+}
@@ -0,0 +1,135 @@
+//! Remote selector packs: CSS selector overrides fetched at startup, so parsing
+//! fixes for a changed site don't need a new release.
+//!
+//! A pack is JSON mapping source to selector key to selector:
+//! `{"version": 3, "sources": {"1337x": {"row": "table.table-list tbody tr"}}}`.
+//! It is only used when its Ed25519 signature (hex, at `<url>.sig`) verifies
+//! against SELECTOR_PACK_KEY. The last good pack is cached for offline starts.
+
+use anyhow::{anyhow, Context, Result};
+use ring::signature::{UnparsedPublicKey, ED25519};
+use scraper::Selector;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use super::{log_error, log_info};
+
+static OVERRIDES: RwLock<Option<HashMap<String, HashMap<String, String>>>> = RwLock::new(None);
+
+/// Selector pack file contents
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Pack {
+    #[serde(default)]
+    pub version: u64,
+    /// source -> selector key -> CSS selector
+    #[serde(default)]
+    pub sources: HashMap<String, HashMap<String, String>>,
+}
+
+/// Selector string for `source`/`key`: the pack override if there is one, else `default`
+pub fn get(source: &str, key: &str, default: &str) -> String {
+    OVERRIDES
+        .read()
+        .ok()
+        .and_then(|guard| guard.as_ref()?.get(source)?.get(key).cloned())
+        .unwrap_or_else(|| default.to_string())
+}
+
+/// Parsed selector for `source`/`key` (see [`get`])
+pub fn selector(source: &str, key: &str, default: &str) -> Option<Selector> {
+    Selector::parse(&get(source, key, default)).ok()
+}
+
+/// Cached copy of the last verified pack
+fn cache_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("littlejohn").join("selectors.json"))
+}
+
+/// Fetch and apply the pack from SELECTOR_PACK_URL, falling back to the cached pack
+/// when the download or signature check fails. Does nothing if no URL is set.
+pub async fn load_pack() {
+    let url = match std::env::var("SELECTOR_PACK_URL") {
+        Ok(url) if !url.trim().is_empty() => url.trim().to_string(),
+        _ => return,
+    };
+
+    match fetch_verified(&url).await {
+        Ok(bytes) => {
+            if let Some(path) = cache_path() {
+                if let Some(dir) = path.parent() {
+                    let _ = std::fs::create_dir_all(dir);
+                }
+                let _ = std::fs::write(path, &bytes);
+            }
+            apply(&bytes);
+        }
+        Err(e) => {
+            log_error("selectors", &format!("Selector pack not updated: {}", e));
+            if let Some(bytes) = cache_path().and_then(|p| std::fs::read(p).ok()) {
+                apply(&bytes);
+            }
+        }
+    }
+}
+
+/// Download the pack and its signature, and verify it against SELECTOR_PACK_KEY
+async fn fetch_verified(url: &str) -> Result<Vec<u8>> {
+    let key = std::env::var("SELECTOR_PACK_KEY").context("SELECTOR_PACK_KEY not set")?;
+    let key = decode_hex(key.trim()).context("SELECTOR_PACK_KEY is not hex")?;
+
+    let client = crate::http::scraper_client();
+    let pack = client.get(url).send().await?.error_for_status()?.bytes().await?;
+    let signature = client
+        .get(format!("{}.sig", url))
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let signature = decode_hex(signature.trim()).context("Signature is not hex")?;
+
+    UnparsedPublicKey::new(&ED25519, &key)
+        .verify(&pack, &signature)
+        .map_err(|_| anyhow!("bad signature"))?;
+    Ok(pack.to_vec())
+}
+
+/// Install the selectors of a verified pack, skipping any that don't parse
+fn apply(bytes: &[u8]) {
+    let pack: Pack = match serde_json::from_slice(bytes) {
+        Ok(pack) => pack,
+        Err(e) => {
+            log_error("selectors", &format!("Invalid selector pack: {}", e));
+            return;
+        }
+    };
+
+    let mut sources = pack.sources;
+    for (source, selectors) in sources.iter_mut() {
+        selectors.retain(|key, value| {
+            let ok = Selector::parse(value).is_ok();
+            if !ok {
+                log_error("selectors", &format!("Ignoring invalid selector {}.{}: {}", source, key, value));
+            }
+            ok
+        });
+    }
+
+    let count: usize = sources.values().map(HashMap::len).sum();
+    log_info("selectors", &format!("Selector pack v{}: {} override(s)", pack.version, count));
+    if let Ok(mut guard) = OVERRIDES.write() {
+        *guard = Some(sources);
+    }
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,121 @@
+//! Per-scraper session statistics
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+static STATS: Mutex<Option<HashMap<String, SourceStats>>> = Mutex::new(None);
+
+/// Accumulated statistics for one source
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SourceStats {
+    /// Number of scraper runs
+    pub runs: u64,
+    /// Runs that returned results
+    pub successes: u64,
+    /// Runs that completed but found nothing
+    pub empties: u64,
+    /// Runs that failed (network/parse error)
+    pub failures: u64,
+    /// Sum of run latencies in milliseconds
+    pub total_latency_ms: u64,
+    /// Sum of results returned
+    pub total_results: u64,
+}
+
+impl SourceStats {
+    /// Percentage of runs that did not fail
+    pub fn success_rate(&self) -> f64 {
+        if self.runs == 0 {
+            0.0
+        } else {
+            (self.successes + self.empties) as f64 / self.runs as f64 * 100.0
+        }
+    }
+
+    /// Average latency per run in milliseconds
+    pub fn avg_latency_ms(&self) -> u64 {
+        self.total_latency_ms.checked_div(self.runs).unwrap_or(0)
+    }
+
+    /// Average number of results per run
+    pub fn avg_results(&self) -> f64 {
+        if self.runs == 0 {
+            0.0
+        } else {
+            self.total_results as f64 / self.runs as f64
+        }
+    }
+}
+
+/// Whether stats should be persisted across sessions (PERSIST_STATS=true)
+fn persist_enabled() -> bool {
+    matches!(
+        std::env::var("PERSIST_STATS").map(|v| v.to_lowercase()).as_deref(),
+        Ok("1") | Ok("true") | Ok("yes")
+    )
+}
+
+/// Path of the persisted stats file
+fn stats_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("littlejohn").join("scraper_stats.json"))
+}
+
+/// Load persisted stats (if enabled), otherwise start empty
+fn load() -> HashMap<String, SourceStats> {
+    if !persist_enabled() {
+        return HashMap::new();
+    }
+    stats_path()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Write stats to disk (if enabled)
+fn save(stats: &HashMap<String, SourceStats>) {
+    if !persist_enabled() {
+        return;
+    }
+    if let (Some(path), Ok(json)) = (stats_path(), serde_json::to_string_pretty(stats)) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+/// Record the outcome of one scraper run (`None` results = failure)
+pub fn record(source: &str, latency: Duration, results: Option<usize>) {
+    if let Ok(mut guard) = STATS.lock() {
+        let stats = guard.get_or_insert_with(load);
+        let entry = stats.entry(source.to_string()).or_default();
+        entry.runs += 1;
+        entry.total_latency_ms += latency.as_millis() as u64;
+        match results {
+            Some(0) => entry.empties += 1,
+            Some(n) => {
+                entry.successes += 1;
+                entry.total_results += n as u64;
+            }
+            None => entry.failures += 1,
+        }
+        save(stats);
+    }
+}
+
+/// Snapshot of the current stats
+pub fn snapshot() -> HashMap<String, SourceStats> {
+    match STATS.lock() {
+        Ok(mut guard) => guard.get_or_insert_with(load).clone(),
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// Reset all stats (and the persisted file)
+pub fn reset() {
+    if let Ok(mut guard) = STATS.lock() {
+        let stats = HashMap::new();
+        save(&stats);
+        *guard = Some(stats);
+    }
+}
@@ -1,11 +1,14 @@
 //! The Pirate Bay scraper - uses proxy sites
 
-use super::{clean_text, log_error, log_info, TorrentResult};
+use super::{clean_magnet, clean_text, log_error, log_info, mirrors, normalize, pause, selectors, source_limit, Scraper, TorrentResult};
+use futures::future::BoxFuture;
 use reqwest::Client;
-use scraper::{Html, Selector};
+use scraper::Html;
+use std::time::Instant;
+use tokio_util::sync::CancellationToken;
 
 /// List of TPB proxy domains to try
-const TPB_PROXIES: &[&str] = &[
+pub const TPB_PROXIES: &[&str] = &[
     "thepiratebay11.com",
     "thepiratebay10.org",
     "piratebay.live",
@@ -13,33 +16,40 @@ const TPB_PROXIES: &[&str] = &[
     "tpb.party",
 ];
 
-/// Try fetching from TPB proxies until one works
+/// Try fetching from TPB proxies until one works (last working proxy first)
 async fn try_fetch_tpb(client: &Client, path: &str) -> Option<(String, String)> {
-    for domain in TPB_PROXIES {
+    for domain in mirrors::ordered("tpb", TPB_PROXIES) {
         let url = format!("https://{}{}", domain, path);
+        pause("tpb").await;
+        let start = Instant::now();
         match client.get(&url).send().await {
             Ok(resp) => {
                 let status = resp.status();
                 if !status.is_success() {
                     log_info("tpb", &format!("Proxy {} returned HTTP {}", domain, status));
+                    mirrors::record("tpb", &domain, false, start.elapsed());
                     continue;
                 }
                 match resp.text().await {
                     Ok(html) => {
                         if html.contains("searchResult") {
                             log_info("tpb", &format!("Using proxy: {}", domain));
-                            return Some((html, domain.to_string()));
+                            mirrors::record("tpb", &domain, true, start.elapsed());
+                            return Some((html, domain));
                         } else {
                             log_info("tpb", &format!("Proxy {} returned HTML without searchResult", domain));
+                            mirrors::record("tpb", &domain, false, start.elapsed());
                         }
                     }
                     Err(e) => {
                         log_info("tpb", &format!("Proxy {} body read failed: {}", domain, e));
+                        mirrors::record("tpb", &domain, false, start.elapsed());
                     }
                 }
             }
             Err(e) => {
                 log_info("tpb", &format!("Proxy {} connection failed: {}", domain, e));
+                mirrors::record("tpb", &domain, false, start.elapsed());
             }
         }
     }
@@ -52,16 +62,16 @@ fn parse_search_results(html: &str) -> Vec<TorrentResult> {
     let document = Html::parse_document(html);
     let mut results = Vec::new();
 
-    let table_sel = match Selector::parse("table#searchResult") {
-        Ok(s) => s,
-        Err(_) => return results,
+    let (Some(table_sel), Some(row_sel), Some(cell_sel), Some(link_sel), Some(magnet_sel)) = (
+        selectors::selector("tpb", "table", "table#searchResult"),
+        selectors::selector("tpb", "row", "tr"),
+        selectors::selector("tpb", "cell", "td"),
+        selectors::selector("tpb", "link", "a"),
+        selectors::selector("tpb", "magnet", "a[href^='magnet:']"),
+    ) else {
+        return results;
     };
 
-    let row_sel = Selector::parse("tr").unwrap();
-    let cell_sel = Selector::parse("td").unwrap();
-    let link_sel = Selector::parse("a").unwrap();
-    let magnet_sel = Selector::parse("a[href^='magnet:']").unwrap();
-
     let table = match document.select(&table_sel).next() {
         Some(t) => t,
         None => return results,
@@ -88,7 +98,7 @@ fn parse_search_results(html: &str) -> Vec<TorrentResult> {
             .select(&magnet_sel)
             .next()
             .and_then(|l| l.value().attr("href"))
-            .map(String::from)
+            .map(clean_magnet)
             .unwrap_or_default();
 
         if magnet.is_empty() {
@@ -102,15 +112,15 @@ fn parse_search_results(html: &str) -> Vec<TorrentResult> {
             .unwrap_or_default();
 
         // Seeders (cell 5)
-        let seeders: i64 = cells
+        let seeders = cells
             .get(5)
-            .and_then(|c| c.text().collect::<String>().trim().parse().ok())
+            .map(|c| normalize::count(&c.text().collect::<String>()))
             .unwrap_or(0);
 
         // Leechers (cell 6)
-        let leechers: i64 = cells
+        let leechers = cells
             .get(6)
-            .and_then(|c| c.text().collect::<String>().trim().parse().ok())
+            .map(|c| normalize::count(&c.text().collect::<String>()))
             .unwrap_or(0);
 
         results.push(TorrentResult {
@@ -122,6 +132,9 @@ fn parse_search_results(html: &str) -> Vec<TorrentResult> {
             source: "tpb".to_string(),
             url: None,
             category: None,
+            cover: None,
+            audio: Vec::new(),
+            subtitles: Vec::new(),
         });
     }
 
@@ -136,7 +149,32 @@ pub async fn scrape_tpb(client: &Client, query: &str, page: u32) -> Option<Vec<T
     let path = format!("/search/{}/{}/99/0", encoded, tpb_page);
 
     let (html, _domain) = try_fetch_tpb(client, &path).await?;
-    let results = parse_search_results(&html);
+    let mut results = parse_search_results(&html);
+
+    if let Some(limit) = source_limit("tpb") {
+        results.sort_by_key(|r| std::cmp::Reverse(r.seeders));
+        results.truncate(limit);
+    }
 
     Some(results)
 }
+
+/// tpb in the scraper registry
+pub struct Tpb;
+
+impl Scraper for Tpb {
+    fn name(&self) -> &'static str {
+        "tpb"
+    }
+
+    fn search<'a>(
+        &'a self,
+        client: &'a Client,
+        query: &'a str,
+        page: u32,
+        _fast: bool,
+        _cancel: &'a CancellationToken,
+    ) -> BoxFuture<'a, Option<Vec<TorrentResult>>> {
+        Box::pin(scrape_tpb(client, query, page))
+    }
+}
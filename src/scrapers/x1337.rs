@@ -1,63 +1,20 @@
 //! 1337x scraper
 
-use super::{clean_text, log_error, log_info, TorrentResult};
+use super::{clean_magnet, clean_text, firecrawl, headless, log_error, log_info, normalize, pause, selectors, source_limit, Scraper, TorrentResult};
+use futures::future::BoxFuture;
 use reqwest::Client;
-use scraper::{Html, Selector};
-use serde::Serialize;
+use scraper::Html;
+use tokio_util::sync::CancellationToken;
 
 const BASE_URL: &str = "https://www.1337xx.to";
 
-/// Firecrawl scrape request
-#[derive(Serialize)]
-struct FirecrawlRequest {
-    url: String,
-    formats: Vec<String>,
-}
-
-/// Fetch URL using Firecrawl API (for bypassing Cloudflare)
-async fn fetch_with_firecrawl(client: &Client, url: &str) -> Option<String> {
-    let api_key = match std::env::var("FIRECRAWL_API_KEY") {
-        Ok(key) if !key.is_empty() => key,
-        _ => return None,
-    };
-
-    let request = FirecrawlRequest {
-        url: url.to_string(),
-        formats: vec!["html".to_string()],
-    };
-
-    let response = match client
-        .post("https://api.firecrawl.dev/v1/scrape")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .json(&request)
-        .send()
-        .await
-    {
-        Ok(r) => r,
-        Err(e) => {
-            log_error("1337x", &format!("Firecrawl request failed: {}", e));
-            return None;
-        }
-    };
-
-    let data: serde_json::Value = match response.json().await {
-        Ok(d) => d,
-        Err(e) => {
-            log_error("1337x", &format!("Firecrawl response parse error: {}", e));
-            return None;
-        }
-    };
-
-    data.get("data")
-        .and_then(|d| d.get("html"))
-        .and_then(|h| h.as_str())
-        .map(String::from)
-}
+/// Detail pages fetched per search without a SOURCE_LIMITS entry (each is a Firecrawl call)
+pub const DETAIL_LIMIT: usize = 8;
 
 /// Fetch URL with Firecrawl fallback to direct fetch
 async fn fetch_with_fallback(client: &Client, url: &str, context: &str) -> Option<String> {
     // Try Firecrawl first (needed for Cloudflare bypass)
-    if let Some(html) = fetch_with_firecrawl(client, url).await {
+    if let Some(html) = firecrawl::scrape("1337x", url).await {
         // Basic validation - check we got actual HTML content
         if !html.is_empty() && (html.contains("1337x") || html.contains("magnet:") || html.contains("torrent")) {
             log_info("1337x", &format!("{}: Using Firecrawl", context));
@@ -67,17 +24,25 @@ async fn fetch_with_fallback(client: &Client, url: &str, context: &str) -> Optio
 
     // Fall back to direct fetch
     log_info("1337x", &format!("{}: Trying direct fetch", context));
+    pause("1337x").await;
     match client.get(url).send().await {
         Ok(resp) => {
             let status = resp.status();
             if !status.is_success() {
                 log_error("1337x", &format!("{}: HTTP {} for {}", context, status, url));
-                return None;
+                // Cloudflare answers its challenge with 403 or 503
+                return match status.as_u16() {
+                    403 | 503 => headless::fetch("1337x", url).await,
+                    _ => None,
+                };
             }
             match resp.text().await {
                 Ok(text) => {
                     // Check for Cloudflare challenge
-                    if text.contains("Just a moment") || text.contains("Enable JavaScript") {
+                    if headless::is_challenge(&text) {
+                        if headless::available() {
+                            return headless::fetch("1337x", url).await;
+                        }
                         log_error("1337x", &format!("{}: Cloudflare challenge detected - set FIRECRAWL_API_KEY to bypass", context));
                         return None;
                     }
@@ -97,16 +62,16 @@ async fn fetch_with_fallback(client: &Client, url: &str, context: &str) -> Optio
 }
 
 /// Fetch magnet link from detail page
-async fn fetch_detail(client: &Client, url: &str) -> Option<String> {
+pub async fn fetch_detail(client: &Client, url: &str) -> Option<String> {
     let html = fetch_with_fallback(client, url, "detail page").await?;
     let document = Html::parse_document(&html);
 
-    let magnet_sel = Selector::parse("a[href^='magnet:']").ok()?;
+    let magnet_sel = selectors::selector("1337x", "magnet", "a[href^='magnet:']")?;
     let magnet = document
         .select(&magnet_sel)
         .next()
         .and_then(|el| el.value().attr("href"))
-        .map(String::from);
+        .map(clean_magnet);
 
     if magnet.is_none() {
         log_error("1337x", &format!("No magnet link found on detail page: {}", url));
@@ -115,8 +80,9 @@ async fn fetch_detail(client: &Client, url: &str) -> Option<String> {
     magnet
 }
 
-/// Scrape 1337x for torrents
-pub async fn scrape_1337x(client: &Client, query: &str, page: u32) -> Option<Vec<TorrentResult>> {
+/// Scrape 1337x for torrents. With `fast`, detail pages are skipped and results come
+/// without magnets.
+pub async fn scrape_1337x(client: &Client, query: &str, page: u32, fast: bool) -> Option<Vec<TorrentResult>> {
     let encoded = urlencoding::encode(query);
     let url = format!("{}/search/{}/{}/", BASE_URL, encoded, page);
 
@@ -127,17 +93,17 @@ pub async fn scrape_1337x(client: &Client, query: &str, page: u32) -> Option<Vec
     let items = {
         let document = Html::parse_document(&html);
 
-        let row_sel = match Selector::parse("table.table-list tbody tr") {
-            Ok(s) => s,
-            Err(e) => {
-                log_error("1337x", &format!("Failed to parse row selector: {:?}", e));
+        let row_sel = match selectors::selector("1337x", "row", "table.table-list tbody tr") {
+            Some(s) => s,
+            None => {
+                log_error("1337x", "Failed to parse row selector");
                 return None;
             }
         };
-        let name_sel = Selector::parse("td.name a:nth-of-type(2)").ok()?;
-        let seeds_sel = Selector::parse("td.seeds").ok()?;
-        let leech_sel = Selector::parse("td.leeches").ok()?;
-        let size_sel = Selector::parse("td.size").ok()?;
+        let name_sel = selectors::selector("1337x", "name", "td.name a:nth-of-type(2)")?;
+        let seeds_sel = selectors::selector("1337x", "seeds", "td.seeds")?;
+        let leech_sel = selectors::selector("1337x", "leeches", "td.leeches")?;
+        let size_sel = selectors::selector("1337x", "size", "td.size")?;
 
         let mut items = Vec::new();
         let mut row_count = 0;
@@ -159,14 +125,12 @@ pub async fn scrape_1337x(client: &Client, query: &str, page: u32) -> Option<Vec
                     format!("{}{}", BASE_URL, href)
                 };
 
-                let seeders: i64 = seeds_el
-                    .map(|e| e.text().collect::<String>())
-                    .and_then(|s| s.trim().parse().ok())
+                let seeders = seeds_el
+                    .map(|e| normalize::count(&e.text().collect::<String>()))
                     .unwrap_or(0);
 
-                let leechers: i64 = leech_el
-                    .map(|e| e.text().collect::<String>())
-                    .and_then(|s| s.trim().parse().ok())
+                let leechers = leech_el
+                    .map(|e| normalize::count(&e.text().collect::<String>()))
                     .unwrap_or(0);
 
                 let size = size_el
@@ -198,11 +162,30 @@ pub async fn scrape_1337x(client: &Client, query: &str, page: u32) -> Option<Vec
         return Some(Vec::new());
     }
 
+    // Keep the best-seeded items; default to 8 to avoid Firecrawl rate limits
+    let mut items = items;
+    items.sort_by_key(|item| std::cmp::Reverse(item.2));
+    let limit = source_limit("1337x").or((!fast).then_some(DETAIL_LIMIT)).unwrap_or(usize::MAX);
+    let items: Vec<_> = items.into_iter().take(limit).collect();
+
+    if fast {
+        log_info("1337x", &format!("Found {} items (fast search, magnets on demand)", items.len()));
+        return Some(items.into_iter().map(|(name, url, seeders, leechers, size)| TorrentResult {
+            name,
+            size,
+            seeders,
+            leechers,
+            magnet: String::new(),
+            source: "1337x".to_string(),
+            url: Some(url),
+            category: None,
+            cover: None,
+            audio: Vec::new(),
+            subtitles: Vec::new(),
+        }).collect());
+    }
     log_info("1337x", &format!("Found {} items, fetching magnet links...", items.len()));
 
-    // Limit to 8 to avoid Firecrawl rate limits
-    let items: Vec<_> = items.into_iter().take(8).collect();
-
     // Fetch magnets sequentially to avoid Send issues
     let mut results = Vec::new();
     let mut magnet_failures = 0;
@@ -218,6 +201,9 @@ pub async fn scrape_1337x(client: &Client, query: &str, page: u32) -> Option<Vec
                     source: "1337x".to_string(),
                     url: Some(url),
                     category: None,
+                    cover: None,
+                    audio: Vec::new(),
+                    subtitles: Vec::new(),
                 });
             } else {
                 magnet_failures += 1;
@@ -233,3 +219,30 @@ pub async fn scrape_1337x(client: &Client, query: &str, page: u32) -> Option<Vec
 
     Some(results)
 }
+
+/// 1337x in the scraper registry
+pub struct X1337;
+
+impl Scraper for X1337 {
+    fn name(&self) -> &'static str {
+        "1337x"
+    }
+
+    fn search<'a>(
+        &'a self,
+        client: &'a Client,
+        query: &'a str,
+        page: u32,
+        fast: bool,
+        _cancel: &'a CancellationToken,
+    ) -> BoxFuture<'a, Option<Vec<TorrentResult>>> {
+        Box::pin(scrape_1337x(client, query, page, fast))
+    }
+
+    fn resolve<'a>(&'a self, client: &'a Client, result: &'a TorrentResult) -> BoxFuture<'a, Option<Vec<TorrentResult>>> {
+        Box::pin(async move {
+            let magnet = fetch_detail(client, result.url.as_deref()?).await?;
+            Some(vec![TorrentResult { magnet, ..result.clone() }])
+        })
+    }
+}
@@ -1,13 +1,26 @@
 //! YTS scraper with Firecrawl support
 
-use super::{clean_text, log_error, log_info, TorrentResult};
+use super::{clean_text, firecrawl, headless, log_error, log_info, mirrors, pause, selectors, source_limit, Scraper, TorrentResult};
+use futures::future::BoxFuture;
 use regex::Regex;
 use reqwest::Client;
-use scraper::{Html, Selector};
-use serde::Serialize;
+use scraper::Html;
+use tokio_util::sync::CancellationToken;
 
 /// YTS domains to try (yts.lt works better with Firecrawl)
-const YTS_DOMAINS: &[&str] = &["yts.lt", "yts.mx"];
+pub const YTS_DOMAINS: &[&str] = &["yts.lt", "yts.mx"];
+
+/// Movie pages fetched per search without a YTS_EXPAND setting
+pub const DETAIL_LIMIT: usize = 10;
+
+/// How many movies of a search are expanded into their qualities up front, from
+/// YTS_EXPAND (default 10); the rest are listed to be expanded when picked
+pub fn expand_limit() -> usize {
+    std::env::var("YTS_EXPAND")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DETAIL_LIMIT)
+}
 
 /// Standard trackers for YTS magnets
 const YTS_TRACKERS: &[&str] = &[
@@ -21,13 +34,6 @@ const YTS_TRACKERS: &[&str] = &[
     "udp://tracker.leechers-paradise.org:6969",
 ];
 
-/// Firecrawl scrape request
-#[derive(Serialize)]
-struct FirecrawlRequest {
-    url: String,
-    formats: Vec<String>,
-}
-
 /// Convert info hash to magnet link
 fn hash_to_magnet(info_hash: &str, name: &str) -> String {
     let hash = info_hash.to_uppercase();
@@ -48,54 +54,10 @@ fn extract_hash_from_url(url: &str) -> Option<String> {
         .map(|m| m.as_str().to_string())
 }
 
-/// Fetch URL using Firecrawl API (for bypassing anti-bot)
-async fn fetch_with_firecrawl(client: &Client, url: &str) -> Option<String> {
-    let api_key = match std::env::var("FIRECRAWL_API_KEY") {
-        Ok(key) if !key.is_empty() => key,
-        _ => {
-            log_info("yts", "FIRECRAWL_API_KEY not set - Firecrawl unavailable");
-            return None;
-        }
-    };
-
-    let request = FirecrawlRequest {
-        url: url.to_string(),
-        formats: vec!["html".to_string()],
-    };
-
-    let response = match client
-        .post("https://api.firecrawl.dev/v1/scrape")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .json(&request)
-        .send()
-        .await
-    {
-        Ok(r) => r,
-        Err(e) => {
-            log_error("yts", &format!("Firecrawl request failed: {}", e));
-            return None;
-        }
-    };
-
-    let data: serde_json::Value = match response.json().await {
-        Ok(d) => d,
-        Err(e) => {
-            log_error("yts", &format!("Firecrawl response parse error: {}", e));
-            return None;
-        }
-    };
-
-    // Extract HTML from response - structure is { data: { html: "..." } }
-    data.get("data")
-        .and_then(|d| d.get("html"))
-        .and_then(|h| h.as_str())
-        .map(String::from)
-}
-
 /// Fetch URL - tries Firecrawl first, falls back to regular fetch
 async fn fetch_with_fallback(client: &Client, url: &str) -> Option<String> {
     // Try Firecrawl first (better for YTS anti-bot)
-    if let Some(html) = fetch_with_firecrawl(client, url).await {
+    if let Some(html) = firecrawl::scrape("yts", url).await {
         if !html.is_empty() {
             log_info("yts", "Using Firecrawl fetch");
             return Some(html);
@@ -104,12 +66,17 @@ async fn fetch_with_fallback(client: &Client, url: &str) -> Option<String> {
 
     // Fall back to regular fetch
     log_info("yts", "Falling back to direct fetch");
+    pause("yts").await;
     match client.get(url).send().await {
         Ok(resp) => {
             let status = resp.status();
             if !status.is_success() {
                 log_error("yts", &format!("HTTP {} for {}", status, url));
-                return None;
+                // Cloudflare answers its challenge with 403 or 503
+                return match status.as_u16() {
+                    403 | 503 => headless::fetch("yts", url).await,
+                    _ => None,
+                };
             }
             match resp.text().await {
                 Ok(text) => Some(text),
@@ -127,13 +94,13 @@ async fn fetch_with_fallback(client: &Client, url: &str) -> Option<String> {
 }
 
 /// Parse movie page and extract torrent info
-fn parse_movie_page(html: &str, movie_name: &str) -> Vec<TorrentResult> {
+fn parse_movie_page(html: &str, movie_name: &str, cover: Option<&str>) -> Vec<TorrentResult> {
     let document = Html::parse_document(html);
     let mut results = Vec::new();
 
-    let torrent_sel = match Selector::parse("a[href*='/torrent/download/']") {
-        Ok(s) => s,
-        Err(_) => return results,
+    let torrent_sel = match selectors::selector("yts", "torrent", "a[href*='/torrent/download/']") {
+        Some(s) => s,
+        None => return results,
     };
 
     let quality_re = match Regex::new(r"(\d+p(?:\.\w+)*)") {
@@ -198,20 +165,31 @@ fn parse_movie_page(html: &str, movie_name: &str) -> Vec<TorrentResult> {
             source: "yts".to_string(),
             url: None,
             category: Some("Movies".to_string()),
+            cover: cover.map(str::to_string),
+            audio: Vec::new(),
+            subtitles: Vec::new(),
         });
     }
 
     results
 }
 
-/// Scrape YTS for movies
-pub async fn scrape_yts(client: &Client, query: &str, page: u32) -> Option<Vec<TorrentResult>> {
+/// Torrents (one per quality) from a movie page
+pub async fn fetch_movie(client: &Client, url: &str, movie_name: &str, cover: Option<&str>) -> Option<Vec<TorrentResult>> {
+    let html = fetch_with_fallback(client, url).await?;
+    Some(parse_movie_page(&html, movie_name, cover))
+}
+
+/// Scrape YTS for movies. The first [`expand_limit`] movies (none with `fast`) are
+/// expanded into one result per quality from their movie pages; every other movie is
+/// one result without a magnet, to be expanded into its qualities on demand.
+pub async fn scrape_yts(client: &Client, query: &str, page: u32, fast: bool) -> Option<Vec<TorrentResult>> {
     let encoded = urlencoding::encode(query);
 
     let mut html = None;
 
-    // Try each domain with Firecrawl
-    for domain in YTS_DOMAINS {
+    // Try each domain with Firecrawl (last working domain first)
+    for domain in mirrors::ordered("yts", YTS_DOMAINS) {
         let url = if page > 1 {
             format!(
                 "https://{}/browse-movies/{}/all/all/0/latest/0/all?page={}",
@@ -224,7 +202,10 @@ pub async fn scrape_yts(client: &Client, query: &str, page: u32) -> Option<Vec<T
             )
         };
 
-        if let Some(h) = fetch_with_fallback(client, &url).await {
+        let start = std::time::Instant::now();
+        let fetched = fetch_with_fallback(client, &url).await;
+        mirrors::record("yts", &domain, fetched.is_some(), start.elapsed());
+        if let Some(h) = fetched {
             if h.contains("browse-movie-wrap") {
                 html = Some(h);
                 break;
@@ -238,10 +219,11 @@ pub async fn scrape_yts(client: &Client, query: &str, page: u32) -> Option<Vec<T
     let movies = {
         let document = Html::parse_document(&html);
 
-        let movie_sel = Selector::parse("div.browse-movie-wrap").ok()?;
-        let link_sel = Selector::parse("a.browse-movie-link").ok()?;
-        let title_sel = Selector::parse("a.browse-movie-title").ok()?;
-        let year_sel = Selector::parse("div.browse-movie-year").ok()?;
+        let movie_sel = selectors::selector("yts", "movie", "div.browse-movie-wrap")?;
+        let link_sel = selectors::selector("yts", "link", "a.browse-movie-link")?;
+        let title_sel = selectors::selector("yts", "title", "a.browse-movie-title")?;
+        let year_sel = selectors::selector("yts", "year", "div.browse-movie-year")?;
+        let poster_sel = selectors::selector("yts", "poster", "img.img-responsive")?;
 
         let mut movies = Vec::new();
 
@@ -249,6 +231,12 @@ pub async fn scrape_yts(client: &Client, query: &str, page: u32) -> Option<Vec<T
             let link = movie.select(&link_sel).next();
             let title = movie.select(&title_sel).next();
             let year = movie.select(&year_sel).next();
+            let poster = movie
+                .select(&poster_sel)
+                .next()
+                .and_then(|img| img.value().attr("src"))
+                .filter(|src| src.starts_with("http"))
+                .map(str::to_string);
 
             if let (Some(link), Some(title)) = (link, title) {
                 let movie_url = link.value().attr("href").unwrap_or("").to_string();
@@ -263,7 +251,7 @@ pub async fn scrape_yts(client: &Client, query: &str, page: u32) -> Option<Vec<T
                     } else {
                         format!("{} ({})", name, year_str)
                     };
-                    movies.push((movie_url, movie_name));
+                    movies.push((movie_url, movie_name, poster));
                 }
             }
         }
@@ -271,16 +259,55 @@ pub async fn scrape_yts(client: &Client, query: &str, page: u32) -> Option<Vec<T
         movies
     }; // document dropped here
 
-    // Fetch details for each movie (limit to 10)
-    let movies: Vec<_> = movies.into_iter().take(10).collect();
+    let limit = source_limit("yts").unwrap_or(usize::MAX);
+    let expand = if fast { 0 } else { expand_limit() };
     let mut results = Vec::new();
 
-    for (url, name) in movies {
-        if let Some(html) = fetch_with_fallback(client, &url).await {
-            let movie_results = parse_movie_page(&html, &name);
-            results.extend(movie_results);
+    for (i, (url, name, poster)) in movies.into_iter().take(limit).enumerate() {
+        if i < expand {
+            if let Some(html) = fetch_with_fallback(client, &url).await {
+                results.extend(parse_movie_page(&html, &name, poster.as_deref()));
+            }
+            continue;
         }
+        results.push(TorrentResult {
+            name,
+            size: String::new(),
+            seeders: 0,
+            leechers: 0,
+            magnet: String::new(),
+            source: "yts".to_string(),
+            url: Some(url),
+            category: Some("Movies".to_string()),
+            cover: poster,
+            audio: Vec::new(),
+            subtitles: Vec::new(),
+        });
     }
 
     Some(results)
 }
+
+/// yts in the scraper registry
+pub struct Yts;
+
+impl Scraper for Yts {
+    fn name(&self) -> &'static str {
+        "yts"
+    }
+
+    fn search<'a>(
+        &'a self,
+        client: &'a Client,
+        query: &'a str,
+        page: u32,
+        fast: bool,
+        _cancel: &'a CancellationToken,
+    ) -> BoxFuture<'a, Option<Vec<TorrentResult>>> {
+        Box::pin(scrape_yts(client, query, page, fast))
+    }
+
+    fn resolve<'a>(&'a self, client: &'a Client, result: &'a TorrentResult) -> BoxFuture<'a, Option<Vec<TorrentResult>>> {
+        Box::pin(async move { fetch_movie(client, result.url.as_deref()?, &result.name, result.cover.as_deref()).await })
+    }
+}
@@ -0,0 +1,79 @@
+//! Connectivity self-test, the last step of first-run setup: the RD token, one scraper,
+//! Firecrawl (when a key is set) and the download directory are each checked, and
+//! reported as passed or failed before entering the app.
+
+use crate::realdebrid::RealDebridClient;
+use crate::scrapers::{self, HEALTH_QUERY};
+use anyhow::{anyhow, Context, Result};
+use std::path::Path;
+
+/// One thing the self-test checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    RdToken,
+    Scraper,
+    Firecrawl,
+    DownloadDir,
+}
+
+impl Check {
+    pub fn label(self) -> &'static str {
+        match self {
+            Check::RdToken => "Real-Debrid token",
+            Check::Scraper => "Torrent site",
+            Check::Firecrawl => "Firecrawl",
+            Check::DownloadDir => "Download directory",
+        }
+    }
+}
+
+/// A line of the report; `outcome` is None while the check runs
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub check: Check,
+    pub outcome: Option<Result<String, String>>,
+}
+
+/// The checks to run, all pending: Firecrawl only when a key is configured
+pub fn pending() -> Vec<Item> {
+    let mut checks = vec![Check::RdToken, Check::Scraper];
+    if scrapers::firecrawl::api_key().is_some() {
+        checks.push(Check::Firecrawl);
+    }
+    checks.push(Check::DownloadDir);
+    checks.into_iter().map(|check| Item { check, outcome: None }).collect()
+}
+
+/// The token is accepted: who it belongs to and the account type
+pub async fn rd_token(client: Option<RealDebridClient>) -> Result<String> {
+    let client = client.context("No token set")?;
+    let user = client.get_user().await?;
+    let until = user.expiration.get(..10).unwrap_or(&user.expiration);
+    Ok(format!("{} ({} until {})", user.username, user.account_type, until))
+}
+
+/// `source` answers a search with results
+pub async fn scraper(source: &str) -> Result<String> {
+    let scraper = scrapers::find(source).with_context(|| format!("Unknown source {}", source))?;
+    let client = scrapers::session_client(source).await;
+    match scraper.health_check(&client).await {
+        Some(0) => Err(anyhow!("{} answered but found nothing for '{}'", source, HEALTH_QUERY)),
+        Some(count) => Ok(format!("{}: {} results for '{}'", source, count, HEALTH_QUERY)),
+        None => Err(anyhow!("{} could not be reached (see the scraper log)", source)),
+    }
+}
+
+/// The Firecrawl key is accepted: the credits left on it
+pub async fn firecrawl() -> Result<String> {
+    let credits = scrapers::firecrawl::remaining_credits().await?;
+    Ok(format!("{} credits left", credits))
+}
+
+/// Files can be created in `dir` (created if missing)
+pub fn download_dir(dir: &Path) -> Result<String> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Cannot create {}", dir.display()))?;
+    let probe = dir.join(".littlejohn-write-test");
+    std::fs::write(&probe, b"").with_context(|| format!("Cannot write to {}", dir.display()))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(dir.display().to_string())
+}
@@ -0,0 +1,54 @@
+//! Slow-transfer retry: Real-Debrid download servers occasionally crawl at dial-up
+//! speeds. When a download stays below SLOW_TRANSFER_SPEED for SLOW_TRANSFER_SECONDS,
+//! its hoster link is unrestricted again (which can land on another server) and the
+//! transfer resumes from where it was.
+
+use crate::realdebrid::{self, RealDebridClient};
+use std::time::Duration;
+
+/// Fresh links tried per download before it's left to run at whatever speed it gets
+pub const MAX_RETRIES: u32 = 3;
+
+/// When a transfer counts as slow
+#[derive(Debug, Clone, Copy)]
+pub struct Threshold {
+    /// Bytes per second a transfer has to stay under
+    pub speed: u64,
+    /// For this long
+    pub after: Duration,
+}
+
+/// From SLOW_TRANSFER_SPEED (bytes per second or with a K/M/G suffix, default 50K;
+/// 0 turns retries off) and SLOW_TRANSFER_SECONDS (default 30)
+pub fn threshold() -> Option<Threshold> {
+    let speed = std::env::var("SLOW_TRANSFER_SPEED")
+        .ok()
+        .and_then(|v| crate::diskguard::parse_size(&v))
+        .unwrap_or(50 * 1024);
+    let seconds = std::env::var("SLOW_TRANSFER_SECONDS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|&s: &u64| s > 0)
+        .unwrap_or(30);
+    (speed > 0).then_some(Threshold { speed, after: Duration::from_secs(seconds) })
+}
+
+/// Whether a rate is slow by `threshold`. With a speed cap set, a transfer may be as
+/// fast as its share of the cap allows, so nothing counts as slow.
+pub fn is_slow(threshold: Threshold, bytes_per_sec: f64) -> bool {
+    bytes_per_sec < threshold.speed as f64 && crate::throttle::limit() == 0
+}
+
+/// A new download URL for the Real-Debrid link `url` came from; None if it didn't
+/// come from Real-Debrid or unrestricting failed
+pub async fn fresh_url(url: &str) -> Option<String> {
+    let link = realdebrid::source_link(url)?;
+    let client = RealDebridClient::new().ok()?;
+    match client.unrestrict(&link).await {
+        Ok((_, url)) => Some(url),
+        Err(e) => {
+            crate::scrapers::log_error("download", &format!("Couldn't renew a slow link: {}", e));
+            None
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Sorting the results table by one of its columns. The sort picked last is the
+//! default for the next searches (RESULT_SORT, e.g. `seeds` or `size:asc`).
+
+use crate::scrapers::{normalize, TorrentResult};
+use std::cmp::Ordering;
+
+/// Column the results are sorted by; `Relevance` is the source-priority order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Relevance,
+    Name,
+    Size,
+    Seeds,
+    Leech,
+    Ratio,
+    Source,
+}
+
+/// Columns in the order `<`/`>` step through them (and the table shows them)
+const COLUMNS: [Column; 7] = [
+    Column::Relevance,
+    Column::Name,
+    Column::Size,
+    Column::Seeds,
+    Column::Leech,
+    Column::Ratio,
+    Column::Source,
+];
+
+impl Column {
+    /// Name in RESULT_SORT and in status messages
+    pub fn key(self) -> &'static str {
+        match self {
+            Column::Relevance => "relevance",
+            Column::Name => "name",
+            Column::Size => "size",
+            Column::Seeds => "seeds",
+            Column::Leech => "leech",
+            Column::Ratio => "ratio",
+            Column::Source => "source",
+        }
+    }
+
+    /// Results table header the sort arrow goes on
+    pub fn header(self) -> Option<&'static str> {
+        match self {
+            Column::Relevance => None,
+            Column::Name => Some("Name"),
+            Column::Size => Some("Size"),
+            Column::Seeds => Some("Seeds"),
+            Column::Leech => Some("Leech"),
+            Column::Ratio => Some("Ratio"),
+            Column::Source => Some("Source"),
+        }
+    }
+
+    fn parse(key: &str) -> Option<Column> {
+        COLUMNS.into_iter().find(|c| c.key().eq_ignore_ascii_case(key.trim()))
+    }
+
+    /// The next column to the right (or left with `back`), wrapping around
+    pub fn step(self, back: bool) -> Column {
+        let i = COLUMNS.iter().position(|c| *c == self).unwrap_or(0);
+        let n = COLUMNS.len();
+        COLUMNS[if back { (i + n - 1) % n } else { (i + 1) % n }]
+    }
+
+    /// Numbers sort biggest first, text A-Z
+    fn descending_by_default(self) -> bool {
+        !matches!(self, Column::Name | Column::Source)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sort {
+    pub column: Column,
+    pub descending: bool,
+}
+
+impl Default for Sort {
+    fn default() -> Self {
+        Sort::by(Column::Relevance)
+    }
+}
+
+impl Sort {
+    /// Sort by `column` in its natural direction
+    pub fn by(column: Column) -> Sort {
+        Sort { column, descending: column.descending_by_default() }
+    }
+
+    /// From RESULT_SORT (`column` or `column:asc|desc`); relevance when unset or unknown
+    pub fn from_env() -> Sort {
+        let Ok(setting) = std::env::var("RESULT_SORT") else {
+            return Sort::default();
+        };
+        let (column, direction) = match setting.split_once(':') {
+            Some((column, direction)) => (column, Some(direction.trim().to_lowercase())),
+            None => (setting.as_str(), None),
+        };
+        let Some(column) = Column::parse(column) else {
+            return Sort::default();
+        };
+        match direction.as_deref() {
+            Some("asc") => Sort { column, descending: false },
+            Some("desc") => Sort { column, descending: true },
+            _ => Sort::by(column),
+        }
+    }
+
+    /// Value for RESULT_SORT
+    pub fn setting(self) -> String {
+        match self.column {
+            Column::Relevance => self.column.key().to_string(),
+            _ => format!("{}:{}", self.column.key(), if self.descending { "desc" } else { "asc" }),
+        }
+    }
+
+    /// Arrow shown next to the sorted column's header
+    pub fn arrow(self) -> &'static str {
+        if self.descending {
+            "▼"
+        } else {
+            "▲"
+        }
+    }
+
+    /// Header text for `header`, with the arrow if it's the sorted column
+    pub fn header(self, header: &str) -> String {
+        if self.column.header() == Some(header) {
+            format!("{} {}", header, self.arrow())
+        } else {
+            header.to_string()
+        }
+    }
+
+    /// Sort `results` by the column; stable, so ties keep their relevance order.
+    /// Relevance leaves them as they are.
+    pub fn apply(self, results: &mut [TorrentResult]) {
+        let compare: fn(&TorrentResult, &TorrentResult) -> Ordering = match self.column {
+            Column::Relevance => return,
+            Column::Name => |a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            Column::Size => |a, b| normalize::size_bytes(&a.size).cmp(&normalize::size_bytes(&b.size)),
+            Column::Seeds => |a, b| a.seeders.cmp(&b.seeders),
+            Column::Leech => |a, b| a.leechers.cmp(&b.leechers),
+            Column::Ratio => |a, b| a.health_ratio().total_cmp(&b.health_ratio()),
+            Column::Source => |a, b| a.source.cmp(&b.source),
+        };
+        if self.descending {
+            results.sort_by(|a, b| compare(b, a));
+        } else {
+            results.sort_by(compare);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Search suggestions: past queries and, with TMDB_API_KEY, TMDB title completions
+//! (TMDB also supplies cover art for the result details)
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// Past queries kept on disk
+const MAX_HISTORY: usize = 200;
+
+/// Suggestions shown from each source
+pub const MAX_PER_SOURCE: usize = 5;
+
+/// Typing pause before asking TMDB
+pub const TMDB_DELAY: std::time::Duration = std::time::Duration::from_millis(300);
+
+const TMDB_SEARCH_URL: &str = "https://api.themoviedb.org/3/search/multi";
+
+/// Poster URL prefix (w342 wide)
+const TMDB_IMAGE_URL: &str = "https://image.tmdb.org/t/p/w342";
+
+/// An entry in the suggestion dropdown
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub text: String,
+    /// From TMDB rather than the query history
+    pub tmdb: bool,
+}
+
+fn history_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("littlejohn").join("search_history.json"))
+}
+
+/// Past queries, most recent first
+pub fn load_history() -> Vec<String> {
+    history_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Move `query` to the front of the history and save it
+pub fn record(history: &mut Vec<String>, query: &str) {
+    let query = query.trim();
+    if query.is_empty() {
+        return;
+    }
+    history.retain(|q| !q.eq_ignore_ascii_case(query));
+    history.insert(0, query.to_string());
+    history.truncate(MAX_HISTORY);
+
+    if let Some(path) = history_path() {
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(history) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+}
+
+/// Past queries containing `input` (case-insensitive), prefix matches first
+pub fn from_history(history: &[String], input: &str) -> Vec<String> {
+    let needle = input.trim().to_lowercase();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    let mut matches: Vec<&String> = history
+        .iter()
+        .filter(|q| {
+            let q = q.to_lowercase();
+            q != needle && q.contains(&needle)
+        })
+        .collect();
+    matches.sort_by_key(|q| !q.to_lowercase().starts_with(&needle));
+    matches.into_iter().take(MAX_PER_SOURCE).cloned().collect()
+}
+
+/// TMDB API key, if title completions are enabled
+pub fn tmdb_key() -> Option<String> {
+    std::env::var("TMDB_API_KEY").ok().filter(|k| !k.trim().is_empty())
+}
+
+#[derive(Debug, Deserialize)]
+struct TmdbResponse {
+    #[serde(default)]
+    results: Vec<TmdbResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TmdbResult {
+    #[serde(default)]
+    media_type: String,
+    /// Movies
+    title: Option<String>,
+    release_date: Option<String>,
+    /// TV shows
+    name: Option<String>,
+    first_air_date: Option<String>,
+    poster_path: Option<String>,
+}
+
+impl TmdbResult {
+    /// Title and release date, for movies and TV shows only
+    fn title_and_date(&self) -> Option<(&str, Option<&str>)> {
+        let (title, date) = match self.media_type.as_str() {
+            "movie" => (self.title.as_deref(), self.release_date.as_deref()),
+            "tv" => (self.name.as_deref(), self.first_air_date.as_deref()),
+            _ => return None,
+        };
+        Some((title.filter(|t| !t.is_empty())?, date))
+    }
+}
+
+async fn tmdb_search(key: &str, query: &str) -> Result<Vec<TmdbResult>> {
+    let client = crate::http::scraper_client();
+    let response = client
+        .get(TMDB_SEARCH_URL)
+        .query(&[("api_key", key), ("query", query), ("include_adult", "false")])
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("TMDB returned {}", response.status()));
+    }
+    let body: TmdbResponse = response.json().await?;
+    Ok(body.results)
+}
+
+/// Movie and TV titles matching `query`, as "Title Year"
+pub async fn tmdb_titles(key: &str, query: &str) -> Result<Vec<String>> {
+    let mut titles: Vec<String> = Vec::new();
+    for result in tmdb_search(key, query).await? {
+        let Some((title, date)) = result.title_and_date() else { continue };
+        let text = match date.and_then(|d| d.get(..4)) {
+            Some(year) => format!("{} {}", title, year),
+            None => title.to_string(),
+        };
+        if !titles.contains(&text) {
+            titles.push(text);
+        }
+        if titles.len() == MAX_PER_SOURCE {
+            break;
+        }
+    }
+    Ok(titles)
+}
+
+/// Poster URL of the best TMDB match for a title, preferring one from `year`
+pub async fn tmdb_poster(key: &str, title: &str, year: Option<&str>) -> Result<Option<String>> {
+    let results = tmdb_search(key, title).await?;
+    let with_poster = || results.iter().filter(|r| r.poster_path.is_some() && r.title_and_date().is_some());
+    let best = with_poster()
+        .find(|r| year.is_some() && r.title_and_date().and_then(|(_, d)| d?.get(..4)) == year)
+        .or_else(|| with_poster().next());
+    Ok(best.and_then(|r| r.poster_path.as_ref()).map(|path| format!("{}{}", TMDB_IMAGE_URL, path)))
+}
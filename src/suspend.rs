@@ -0,0 +1,51 @@
+//! Job control for the TUI (Unix): Ctrl+Z and SIGTSTP leave raw mode and the alternate
+//! screen before stopping, and the TUI is set up again when the shell resumes it with `fg`.
+
+use anyhow::Result;
+use crossterm::execute;
+use crossterm::terminal::{enable_raw_mode, EnterAlternateScreen};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::mpsc;
+
+use crate::{AppMessage, Tui};
+
+/// Give the terminal back to the shell and stop like any other job; returns once
+/// resumed, with the TUI restored
+pub fn suspend(terminal: &mut Tui) -> Result<()> {
+    ratatui::restore();
+    // SIGSTOP rather than SIGTSTP, which `watch` catches. Raising a signal at our own
+    // process has no memory-safety requirements.
+    unsafe {
+        libc::raise(libc::SIGSTOP);
+    }
+    resume(terminal)
+}
+
+/// Back to raw mode on the alternate screen, with the next frame drawn from scratch
+pub fn resume(terminal: &mut Tui) -> Result<()> {
+    enable_raw_mode()?;
+    execute!(std::io::stdout(), EnterAlternateScreen)?;
+    terminal.clear()?;
+    Ok(())
+}
+
+/// Turn SIGTSTP from outside (e.g. `kill -TSTP`) into a clean suspend, and SIGCONT after
+/// any stop into a redraw
+pub async fn watch(tx: mpsc::UnboundedSender<AppMessage>) {
+    let (Ok(mut stop), Ok(mut resumed)) = (
+        signal(SignalKind::from_raw(libc::SIGTSTP)),
+        signal(SignalKind::from_raw(libc::SIGCONT)),
+    ) else {
+        return;
+    };
+    loop {
+        let message = tokio::select! {
+            Some(()) = stop.recv() => AppMessage::Suspend,
+            Some(()) = resumed.recv() => AppMessage::Resumed,
+            else => return,
+        };
+        if tx.send(message).is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Background task manager: searches, Real-Debrid waits and downloads are spawned
+//! through it, so they can be listed, cancelled and shut down together on quit
+
+use std::future::Future;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+pub type TaskId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskState {
+    Running,
+    /// Cancelled but still cleaning up
+    Cancelling,
+    Finished,
+}
+
+/// A spawned background task
+pub struct Task {
+    pub id: TaskId,
+    /// What the task does, e.g. "search" or "download"
+    pub name: String,
+    token: CancellationToken,
+    handle: JoinHandle<()>,
+}
+
+impl Task {
+    pub fn state(&self) -> TaskState {
+        if self.handle.is_finished() {
+            TaskState::Finished
+        } else if self.token.is_cancelled() {
+            TaskState::Cancelling
+        } else {
+            TaskState::Running
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct TaskManager {
+    tasks: Vec<Task>,
+    next_id: TaskId,
+}
+
+impl TaskManager {
+    /// Spawn `future` as a named task, dropped where it stands when cancelled
+    pub fn spawn<F>(&mut self, name: &str, future: F) -> TaskId
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let token = CancellationToken::new();
+        let task_token = token.clone();
+        let handle = tokio::spawn(async move {
+            tokio::select! {
+                _ = task_token.cancelled() => {}
+                _ = future => {}
+            }
+        });
+        self.track(name, token, handle)
+    }
+
+    /// Spawn `future` as a named task that watches `token` itself, so it can clean up
+    /// (e.g. delete a half-added torrent) when cancelled
+    pub fn spawn_cancellable<F>(&mut self, name: &str, token: CancellationToken, future: F) -> TaskId
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let handle = tokio::spawn(future);
+        self.track(name, token, handle)
+    }
+
+    fn track(&mut self, name: &str, token: CancellationToken, handle: JoinHandle<()>) -> TaskId {
+        self.reap();
+        self.next_id += 1;
+        self.tasks.push(Task {
+            id: self.next_id,
+            name: name.to_string(),
+            token,
+            handle,
+        });
+        self.next_id
+    }
+
+    /// Cancel a task; false if it isn't running
+    pub fn cancel(&mut self, id: TaskId) -> bool {
+        match self.tasks.iter().find(|t| t.id == id && t.state() != TaskState::Finished) {
+            Some(task) => {
+                task.token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forget tasks that have finished
+    pub fn reap(&mut self) {
+        self.tasks.retain(|t| t.state() != TaskState::Finished);
+    }
+
+    /// Tasks that haven't finished yet, oldest first
+    pub fn active(&self) -> impl Iterator<Item = &Task> {
+        self.tasks.iter().filter(|t| t.state() != TaskState::Finished)
+    }
+
+    /// Cancel every task and wait up to `grace` for them to stop
+    pub async fn shutdown(&mut self, grace: Duration) {
+        for task in &self.tasks {
+            task.token.cancel();
+        }
+        let handles: Vec<JoinHandle<()>> = self.tasks.drain(..).map(|t| t.handle).collect();
+        let _ = tokio::time::timeout(grace, async {
+            for handle in handles {
+                let _ = handle.await;
+            }
+        })
+        .await;
+    }
+}
@@ -0,0 +1,140 @@
+//! Telegram bot: completion notifications and simple remote commands
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::time::Duration;
+
+const API_URL: &str = "https://api.telegram.org";
+
+/// Long-poll timeout for getUpdates
+const POLL_TIMEOUT_SECS: u64 = 30;
+
+/// Bot configured from TELEGRAM_BOT_TOKEN and TELEGRAM_CHAT_ID
+#[derive(Debug, Clone)]
+pub struct Bot {
+    token: String,
+    /// The only chat the bot talks to and takes commands from
+    chat_id: i64,
+    client: reqwest::Client,
+}
+
+/// A command sent to the bot
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Reply with the top results for a query
+    Search(String),
+    /// Send the best-seeded result for a query to Real-Debrid and download it
+    Grab(String),
+    /// Reply with the download status
+    Status,
+    Help,
+}
+
+impl Command {
+    /// Parse `/search q`, `/grab q`, `/status` or `/help` (a `@botname` suffix is allowed)
+    pub fn parse(text: &str) -> Option<Self> {
+        let (name, arg) = text.trim().split_once(' ').unwrap_or((text.trim(), ""));
+        let name = name.strip_prefix('/')?.split('@').next()?;
+        let arg = arg.trim().to_string();
+        match name {
+            "search" | "s" if !arg.is_empty() => Some(Command::Search(arg)),
+            "grab" | "g" if !arg.is_empty() => Some(Command::Grab(arg)),
+            "status" => Some(Command::Status),
+            _ => Some(Command::Help),
+        }
+    }
+}
+
+pub const HELP: &str = "/search <query> - top results\n/grab <query> - download the best-seeded result\n/status - download progress";
+
+#[derive(Debug, Deserialize)]
+struct ApiResponse<T> {
+    ok: bool,
+    result: Option<T>,
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Update {
+    update_id: i64,
+    message: Option<Message>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Message {
+    chat: Chat,
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Chat {
+    id: i64,
+}
+
+impl Bot {
+    /// Bot from the environment, if both the token and chat id are set
+    pub fn from_env() -> Option<Self> {
+        let token = std::env::var("TELEGRAM_BOT_TOKEN").ok()?.trim().to_string();
+        let chat_id = std::env::var("TELEGRAM_CHAT_ID").ok()?.trim().parse().ok()?;
+        if token.is_empty() {
+            return None;
+        }
+        Some(Self {
+            token,
+            chat_id,
+            client: crate::http::transfer_client(),
+        })
+    }
+
+    /// Send a message to the configured chat
+    pub async fn send(&self, text: &str) -> Result<()> {
+        let url = format!("{}/bot{}/sendMessage", API_URL, self.token);
+        let body = serde_json::json!({ "chat_id": self.chat_id, "text": text });
+        let response: ApiResponse<serde_json::Value> = self.client.post(&url).json(&body).send().await?.json().await?;
+        if !response.ok {
+            return Err(anyhow!("Telegram: {}", response.description.unwrap_or_default()));
+        }
+        Ok(())
+    }
+
+    /// Send a message without waiting for it; failures go to the scraper log
+    pub fn notify(&self, text: String) {
+        let bot = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = bot.send(&text).await {
+                crate::scrapers::log_error("telegram", &e.to_string());
+            }
+        });
+    }
+
+    /// Wait for the next batch of commands from the configured chat. `offset` tracks
+    /// which updates have been seen; messages from other chats are ignored.
+    pub async fn next_commands(&self, offset: &mut i64) -> Result<Vec<Command>> {
+        let url = format!("{}/bot{}/getUpdates", API_URL, self.token);
+        let response: ApiResponse<Vec<Update>> = self
+            .client
+            .get(&url)
+            .query(&[("offset", offset.to_string()), ("timeout", POLL_TIMEOUT_SECS.to_string())])
+            .timeout(Duration::from_secs(POLL_TIMEOUT_SECS + 10))
+            .send()
+            .await?
+            .json()
+            .await?;
+        if !response.ok {
+            return Err(anyhow!("Telegram: {}", response.description.unwrap_or_default()));
+        }
+
+        let mut commands = Vec::new();
+        for update in response.result.unwrap_or_default() {
+            *offset = update.update_id + 1;
+            let Some(message) = update.message else { continue };
+            if message.chat.id != self.chat_id {
+                continue;
+            }
+            if let Some(command) = message.text.as_deref().and_then(Command::parse) {
+                commands.push(command);
+            }
+        }
+        Ok(commands)
+    }
+}
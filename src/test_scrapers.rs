@@ -24,16 +24,16 @@ async fn main() {
     }
 
     println!("\nResults by source:");
-    for source in scrapers::SCRAPERS {
-        let count = by_source.get(*source).copied().unwrap_or(0);
+    for source in scrapers::SCRAPERS.iter().map(|s| s.name()) {
+        let count = by_source.get(source).copied().unwrap_or(0);
         let status = if count > 0 { "OK" } else { "FAILED" };
         println!("  {:15} {:>3} results [{}]", source, count, status);
     }
 
     // Show first few results from each source
     println!("\nSample results:");
-    for source in scrapers::SCRAPERS {
-        let source_results: Vec<_> = results.iter().filter(|r| r.source == *source).take(2).collect();
+    for source in scrapers::SCRAPERS.iter().map(|s| s.name()) {
+        let source_results: Vec<_> = results.iter().filter(|r| r.source == source).take(2).collect();
         if source_results.is_empty() {
             println!("\n[{}] - No results", source);
         } else {
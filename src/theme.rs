@@ -0,0 +1,75 @@
+//! Status color palettes and symbols
+
+use ratatui::style::Color;
+
+/// Colors used to convey state (success, warning, failure...)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    /// Name used in PALETTE
+    pub name: &'static str,
+    pub ok: Color,
+    pub warn: Color,
+    pub error: Color,
+    /// Cancelled/stopped
+    pub stopped: Color,
+    pub muted: Color,
+}
+
+/// Classic green/yellow/red
+pub const DEFAULT: Palette = Palette {
+    name: "default",
+    ok: Color::Green,
+    warn: Color::Yellow,
+    error: Color::Red,
+    stopped: Color::Magenta,
+    muted: Color::Gray,
+};
+
+/// Okabe-Ito colors, distinguishable with red-green color blindness
+pub const COLORBLIND: Palette = Palette {
+    name: "colorblind",
+    ok: Color::Rgb(86, 180, 233),
+    warn: Color::Rgb(230, 159, 0),
+    error: Color::Rgb(213, 94, 0),
+    stopped: Color::Rgb(204, 121, 167),
+    muted: Color::Gray,
+};
+
+/// Bright colors for low-contrast terminals
+pub const HIGH_CONTRAST: Palette = Palette {
+    name: "high-contrast",
+    ok: Color::LightGreen,
+    warn: Color::LightYellow,
+    error: Color::LightRed,
+    stopped: Color::LightMagenta,
+    muted: Color::White,
+};
+
+pub const PALETTES: &[Palette] = &[DEFAULT, COLORBLIND, HIGH_CONTRAST];
+
+impl Palette {
+    /// Palette named by PALETTE, falling back to the default
+    pub fn from_env() -> Self {
+        let name = std::env::var("PALETTE").unwrap_or_default().to_lowercase();
+        PALETTES
+            .iter()
+            .copied()
+            .find(|p| p.name == name)
+            .unwrap_or(DEFAULT)
+    }
+
+    /// The next palette in the list (wraps around)
+    pub fn next(&self) -> Self {
+        let i = PALETTES.iter().position(|p| p == self).unwrap_or(0);
+        PALETTES[(i + 1) % PALETTES.len()]
+    }
+}
+
+/// Symbols shown next to colored states so they don't rely on color alone
+pub const SYM_OK: &str = "✓";
+pub const SYM_FAIL: &str = "✗";
+pub const SYM_WARN: &str = "~";
+pub const SYM_PENDING: &str = "…";
+pub const SYM_ACTIVE: &str = "↓";
+pub const SYM_STOPPED: &str = "⊘";
+pub const SYM_EMPTY: &str = "–";
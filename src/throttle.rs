@@ -0,0 +1,75 @@
+//! Global download speed cap shared by every transfer. It starts from MAX_DOWNLOAD_SPEED
+//! and can be changed live with +/- on the Downloads screen.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// How much one +/- press moves the cap
+pub const STEP: u64 = 500 * 1024;
+
+/// Cap in bytes per second; 0 means unlimited
+static LIMIT: AtomicU64 = AtomicU64::new(0);
+
+/// Byte budget all transfers draw from: bytes still available and when it was last refilled
+static BUCKET: Mutex<Option<(f64, Instant)>> = Mutex::new(None);
+
+static INIT: OnceLock<()> = OnceLock::new();
+
+/// Current cap in bytes per second, 0 when unlimited
+pub fn limit() -> u64 {
+    INIT.get_or_init(|| {
+        let initial = std::env::var("MAX_DOWNLOAD_SPEED")
+            .ok()
+            .and_then(|v| crate::diskguard::parse_size(&v))
+            .unwrap_or(0);
+        LIMIT.store(initial, Ordering::Relaxed);
+    });
+    LIMIT.load(Ordering::Relaxed)
+}
+
+pub fn set_limit(bytes_per_sec: u64) {
+    limit();
+    LIMIT.store(bytes_per_sec, Ordering::Relaxed);
+}
+
+/// The cap one step higher; raising past nothing stays unlimited
+pub fn raised(current: u64) -> u64 {
+    if current == 0 {
+        0
+    } else {
+        (current / STEP + 1) * STEP
+    }
+}
+
+/// The cap one step lower, never below one step. From unlimited it starts one step
+/// under `speed`, the rate downloads are running at now.
+pub fn lowered(current: u64, speed: u64) -> u64 {
+    let from = if current == 0 { speed } else { current };
+    (from.div_ceil(STEP).saturating_sub(1) * STEP).max(STEP)
+}
+
+/// Wait as long as the cap requires after `bytes` were received
+pub async fn consume(bytes: usize) {
+    let limit = limit();
+    if limit == 0 {
+        return;
+    }
+    let wait = {
+        let mut bucket = BUCKET.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let (available, last) = bucket.get_or_insert((limit as f64, now));
+        // Refill for the time passed, allowing at most a second's worth of burst
+        *available = (*available + now.duration_since(*last).as_secs_f64() * limit as f64).min(limit as f64);
+        *last = now;
+        *available -= bytes as f64;
+        if *available < 0.0 {
+            Duration::from_secs_f64(-*available / limit as f64)
+        } else {
+            Duration::ZERO
+        }
+    };
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+}
@@ -0,0 +1,175 @@
+//! Trakt.tv: the next episodes of shows being watched and the watchlist, for the
+//! "Up next" screen, and marking grabbed titles as collected (TRAKT_COLLECT)
+//!
+//! Needs the client ID of a Trakt API app (TRAKT_CLIENT_ID) and an OAuth access token
+//! for the account (TRAKT_ACCESS_TOKEN).
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+const API_URL: &str = "https://api.trakt.tv";
+
+/// Recently watched shows checked for a next episode
+const MAX_SHOWS: usize = 10;
+
+/// What an "Up next" entry is
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    Movie,
+    /// A show on the watchlist, nothing watched yet
+    Show,
+    Episode { season: u32, number: u32 },
+}
+
+/// An "Up next" entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    pub title: String,
+    pub year: Option<u32>,
+    pub kind: Kind,
+    /// Trakt IDs of the movie or show
+    ids: Value,
+}
+
+impl Item {
+    /// Search query for the entry, e.g. "Show Name S01E06" or "Movie 1999"
+    pub fn query(&self) -> String {
+        match (&self.kind, self.year) {
+            (Kind::Episode { season, number }, _) => format!("{} S{:02}E{:02}", self.title, season, number),
+            (Kind::Movie, Some(year)) => format!("{} {}", self.title, year),
+            _ => self.title.clone(),
+        }
+    }
+
+    /// Where the entry came from, for the list
+    pub fn label(&self) -> &'static str {
+        match self.kind {
+            Kind::Movie => "Watchlist movie",
+            Kind::Show => "Watchlist show",
+            Kind::Episode { .. } => "Next episode",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Media {
+    title: Option<String>,
+    year: Option<u32>,
+    #[serde(default)]
+    ids: Value,
+}
+
+#[derive(Deserialize)]
+struct WatchedShow {
+    #[serde(default)]
+    last_watched_at: String,
+    show: Media,
+}
+
+#[derive(Deserialize)]
+struct WatchlistEntry {
+    movie: Option<Media>,
+    show: Option<Media>,
+}
+
+#[derive(Deserialize)]
+struct Progress {
+    next_episode: Option<NextEpisode>,
+}
+
+#[derive(Deserialize)]
+struct NextEpisode {
+    season: u32,
+    number: u32,
+}
+
+/// Client ID and access token, if Trakt is configured
+fn credentials() -> Option<(String, String)> {
+    let var = |key: &str| std::env::var(key).ok().filter(|v| !v.trim().is_empty());
+    Some((var("TRAKT_CLIENT_ID")?, var("TRAKT_ACCESS_TOKEN")?))
+}
+
+/// Whether Trakt is configured
+pub fn enabled() -> bool {
+    credentials().is_some()
+}
+
+/// Whether downloaded "Up next" entries are marked as collected (TRAKT_COLLECT=true)
+pub fn collect_enabled() -> bool {
+    matches!(
+        std::env::var("TRAKT_COLLECT").map(|v| v.to_lowercase()).as_deref(),
+        Ok("1") | Ok("true") | Ok("yes")
+    )
+}
+
+fn request(method: reqwest::Method, path: &str) -> Result<reqwest::RequestBuilder> {
+    let (client_id, token) = credentials().ok_or_else(|| anyhow!("TRAKT_CLIENT_ID and TRAKT_ACCESS_TOKEN not set"))?;
+    Ok(crate::http::scraper_client()
+        .request(method, format!("{}{}", API_URL, path))
+        .header("trakt-api-version", "2")
+        .header("trakt-api-key", client_id)
+        .bearer_auth(token))
+}
+
+async fn get<T: serde::de::DeserializeOwned>(path: &str) -> Result<T> {
+    let response = request(reqwest::Method::GET, path)?.send().await?;
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(anyhow!("Trakt rejected the access token (expired?)"));
+    }
+    if !response.status().is_success() {
+        return Err(anyhow!("Trakt returned {}", response.status()));
+    }
+    Ok(response.json().await?)
+}
+
+/// The next episode of each recently watched show, then the watchlist
+pub async fn up_next() -> Result<Vec<Item>> {
+    let mut watched: Vec<WatchedShow> = get("/sync/watched/shows?extended=noseasons").await?;
+    watched.sort_by(|a, b| b.last_watched_at.cmp(&a.last_watched_at));
+
+    let mut items = Vec::new();
+    for entry in watched.into_iter().take(MAX_SHOWS) {
+        let Some(id) = entry.show.ids.get("trakt").and_then(Value::as_u64) else { continue };
+        let progress: Progress = match get(&format!("/shows/{}/progress/watched", id)).await {
+            Ok(progress) => progress,
+            Err(_) => continue,
+        };
+        let (Some(next), Some(title)) = (progress.next_episode, entry.show.title) else { continue };
+        items.push(Item {
+            title,
+            year: entry.show.year,
+            kind: Kind::Episode { season: next.season, number: next.number },
+            ids: entry.show.ids,
+        });
+    }
+
+    let watchlist: Vec<WatchlistEntry> = get("/sync/watchlist").await?;
+    for entry in watchlist {
+        let (media, kind) = match (entry.movie, entry.show) {
+            (Some(movie), _) => (movie, Kind::Movie),
+            (None, Some(show)) => (show, Kind::Show),
+            _ => continue,
+        };
+        let Some(title) = media.title else { continue };
+        items.push(Item { title, year: media.year, kind, ids: media.ids });
+    }
+    Ok(items)
+}
+
+/// Add a movie or episode to the Trakt collection (watchlist shows are left alone, as
+/// that would collect every episode)
+pub async fn collect(item: &Item) -> Result<()> {
+    let body = match item.kind {
+        Kind::Movie => json!({ "movies": [{ "ids": item.ids }] }),
+        Kind::Episode { season, number } => json!({
+            "shows": [{ "ids": item.ids, "seasons": [{ "number": season, "episodes": [{ "number": number }] }] }]
+        }),
+        Kind::Show => return Ok(()),
+    };
+    let response = request(reqwest::Method::POST, "/sync/collection")?.json(&body).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("Trakt returned {}", response.status()));
+    }
+    Ok(())
+}
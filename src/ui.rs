@@ -2,15 +2,46 @@
 
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Row, Table, Wrap},
+    widgets::{Block, Borders, Cell, Clear, Gauge, List, ListItem, Paragraph, Row, Table, Wrap},
 };
 
-use crate::{App, AppMode, DownloadStatus, SettingsField, format_bytes, scrapers};
+use crate::{App, AppMode, Cover, DownloadStatus, RdWait, SettingsField, format_bytes, input, realdebrid, release, remote, schedule, scrapers, theme, throttle};
+use crate::scrapers::{Health, SourceStatus};
+
+/// Smallest terminal the screens are laid out for; below it only a notice is shown
+pub const MIN_WIDTH: u16 = 60;
+pub const MIN_HEIGHT: u16 = 16;
+
+/// A `width` x `height` popup centered in `area`, shrunk to fit (with a margin) on small
+/// terminals so it never reaches outside `area`
+pub fn centered_popup(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width.saturating_sub(4));
+    let height = height.min(area.height.saturating_sub(2));
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
 
 /// Main draw function
 pub fn draw(frame: &mut Frame, app: &App) {
     let area = frame.area();
 
+    // Too small to lay the screens out: say so until the terminal is resized
+    if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+        let notice = Paragraph::new(format!(
+            "Terminal too small\n{}x{} (need {}x{})",
+            area.width, area.height, MIN_WIDTH, MIN_HEIGHT
+        ))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::Yellow))
+        .wrap(Wrap { trim: true });
+        frame.render_widget(notice, centered_popup(area, area.width, 2));
+        return;
+    }
+
     // Create main layout: header, content, footer
     let layout = Layout::default()
         .direction(Direction::Vertical)
@@ -22,27 +53,101 @@ pub fn draw(frame: &mut Frame, app: &App) {
         .split(area);
 
     draw_header(frame, app, layout[0]);
+    draw_screen(frame, app, &app.mode, layout[1]);
+    draw_status_bar(frame, app, layout[2]);
+}
 
-    match &app.mode {
-        AppMode::Setup => draw_setup(frame, app, layout[1]),
-        AppMode::Settings => draw_settings(frame, app, layout[1]),
-        AppMode::Search => draw_search(frame, app, layout[1]),
-        AppMode::Results => draw_results(frame, app, layout[1]),
-        AppMode::FileSelect => draw_file_select(frame, app, layout[1]),
-        AppMode::SourceSelect => draw_source_select(frame, app, layout[1]),
-        AppMode::Downloads => draw_downloads(frame, app, layout[1]),
-        AppMode::Processing => draw_processing(frame, app, layout[1]),
-        AppMode::Error(msg) => draw_error(frame, msg, layout[1]),
+fn draw_screen(frame: &mut Frame, app: &App, mode: &AppMode, area: Rect) {
+    match mode {
+        AppMode::Setup => draw_setup(frame, app, area),
+        AppMode::SelfTest => draw_self_test(frame, app, area),
+        AppMode::Settings => draw_settings(frame, app, area),
+        AppMode::Search => draw_search(frame, app, area),
+        AppMode::Results => draw_results(frame, app, area),
+        AppMode::FileSelect => draw_file_select(frame, app, area),
+        AppMode::SourceSelect => draw_source_select(frame, app, area),
+        AppMode::Downloads => draw_downloads(frame, app, area),
+        AppMode::Stats => draw_stats(frame, app, area),
+        AppMode::UpNext => draw_up_next(frame, app, area),
+        AppMode::Log => draw_log(frame, app, area),
+        AppMode::Processing => draw_processing(frame, app, area),
+        AppMode::ConfirmQuit => draw_confirm_quit(frame, app, area),
+        AppMode::ConfirmDuplicate => draw_confirm_duplicate(frame, app, area),
+        AppMode::ConfirmKeepWaiting => draw_keep_waiting(frame, app, area),
+        AppMode::ImportPrompt => draw_import_prompt(frame, app, area),
+        AppMode::Details => {
+            draw_results(frame, app, area);
+            draw_details(frame, app, area);
+        }
+        AppMode::QualitySelect => {
+            draw_results(frame, app, area);
+            draw_quality_select(frame, app, area);
+        }
+        AppMode::Messages => {
+            // Over the screen it was opened from
+            if let Some(previous) = app.nav.last() {
+                draw_screen(frame, app, previous, area);
+            }
+            draw_messages(frame, app, area);
+        }
+        AppMode::Changelog => {
+            if let Some(previous) = app.nav.last() {
+                draw_screen(frame, app, previous, area);
+            }
+            draw_changelog(frame, app, area);
+        }
+        AppMode::Error(msg) => draw_error(frame, app, msg, area),
     }
-
-    draw_status_bar(frame, app, layout[2]);
 }
 
-fn draw_header(frame: &mut Frame, _app: &App, area: Rect) {
-    let title = Paragraph::new("LITTLEJOHN - Torrent Search with Real-Debrid")
-        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
-        .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::DarkGray)));
+fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
+    let mut block = Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::DarkGray));
+
+    // Where Esc leads back through
+    if !app.nav.is_empty() {
+        block = block.title_bottom(Line::from(Span::styled(
+            format!(" {} ", app.breadcrumb()),
+            Style::default().fg(Color::Gray),
+        )));
+    }
+
+    // Aggregate download progress, visible from every screen
+    if let Some(summary) = app.download_summary() {
+        block = block.title_bottom(
+            Line::from(Span::styled(format!(" {} ", summary), Style::default().fg(Color::Yellow)))
+                .right_aligned(),
+        );
+    }
+
+    // Background tasks in flight
+    if let Some(summary) = app.task_summary() {
+        block = block.title(
+            Line::from(Span::styled(format!(" {} ", summary), Style::default().fg(Color::DarkGray)))
+                .right_aligned(),
+        );
+    }
+
+    // A newer release, announced without getting in the way
+    if let Some(release) = &app.update {
+        block = block.title(Line::from(Span::styled(
+            format!(" v{} available - [^R] what's new ", release.version),
+            Style::default().fg(Color::Green),
+        )));
+    }
+
+    // Low-disk guard banner replaces the title while downloads are paused
+    let title = match app.disk_low {
+        Some(free) => Paragraph::new(format!(
+            "LOW DISK SPACE: {} free - downloads paused until space is freed",
+            format_bytes(free as f64)
+        ))
+        .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+        .block(block.border_style(Style::default().fg(Color::Red))),
+        None => Paragraph::new("LITTLEJOHN - Torrent Search with Real-Debrid")
+            .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+            .block(block),
+    }
+    .alignment(Alignment::Center);
 
     frame.render_widget(title, area);
 }
@@ -51,6 +156,43 @@ fn draw_setup(frame: &mut Frame, app: &App, area: Rect) {
     draw_settings_form(frame, app, area, true);
 }
 
+/// Pass/fail of each setup self-test check, with what was found or what went wrong
+fn draw_self_test(frame: &mut Frame, app: &App, area: Rect) {
+    let spinner = ["|", "/", "-", "\\"][(app.tick % 4) as usize];
+    let mut lines = vec![
+        Line::from(Span::styled("Checking that everything is reachable...", Style::default().fg(Color::Yellow))),
+        Line::from(""),
+    ];
+    for item in &app.self_test {
+        let (symbol, detail, color) = match &item.outcome {
+            None => (spinner, "checking...".to_string(), Color::Gray),
+            Some(Ok(found)) => (theme::SYM_OK, found.clone(), app.palette.ok),
+            Some(Err(e)) => (theme::SYM_FAIL, e.clone(), app.palette.error),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {} {:<20}", symbol, item.check.label()), Style::default().fg(color)),
+            Span::styled(detail, Style::default().fg(Color::Gray)),
+        ]));
+    }
+    if app.self_test.iter().all(|item| item.outcome.is_some()) {
+        let failed = app.self_test.iter().any(|item| matches!(item.outcome, Some(Err(_))));
+        lines.push(Line::from(""));
+        lines.push(Line::from(if failed {
+            "Some checks failed: fix them in settings (Shift+S) or press r to run them again."
+        } else {
+            "All checks passed. Press Enter to start searching."
+        }));
+    }
+
+    let report = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .title("Self-test")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+    frame.render_widget(report, area);
+}
+
 fn draw_settings(frame: &mut Frame, app: &App, area: Rect) {
     draw_settings_form(frame, app, area, false);
 }
@@ -63,6 +205,8 @@ fn draw_settings_form(frame: &mut Frame, app: &App, area: Rect, is_setup: bool)
             Constraint::Length(3),  // RD Token field
             Constraint::Length(3),  // Firecrawl field
             Constraint::Length(3),  // Download Dir field
+            Constraint::Length(3),  // Vim mode toggle
+            Constraint::Length(3),  // Palette choice
             Constraint::Min(0),     // Help text
         ])
         .margin(1)
@@ -74,9 +218,15 @@ fn draw_settings_form(frame: &mut Frame, app: &App, area: Rect, is_setup: bool)
     } else {
         "Settings - Edit your configuration"
     };
-    let title_widget = Paragraph::new(title)
-        .style(Style::default().fg(Color::Yellow))
-        .alignment(Alignment::Center);
+    let title_widget = match &app.settings_banner {
+        Some(banner) => Paragraph::new(banner.as_str())
+            .style(Style::default().fg(app.palette.error).add_modifier(Modifier::BOLD))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        None => Paragraph::new(title)
+            .style(Style::default().fg(Color::Yellow))
+            .alignment(Alignment::Center),
+    };
     frame.render_widget(title_widget, layout[0]);
 
     // Helper to draw a field
@@ -117,7 +267,7 @@ fn draw_settings_form(frame: &mut Frame, app: &App, area: Rect, is_setup: bool)
         // Draw cursor if active
         if is_active {
             frame.set_cursor_position((
-                area.x + 1 + cursor_pos as u16,
+                area.x + 1 + input::cursor_column(value, cursor_pos),
                 area.y + 1,
             ));
         }
@@ -135,12 +285,20 @@ fn draw_settings_form(frame: &mut Frame, app: &App, area: Rect, is_setup: bool)
         if is_rd_active { app.settings_cursor } else { 0 },
     );
 
-    // Firecrawl field
+    // Firecrawl field, with the credits left and used this session
     let is_fc_active = app.settings_field == SettingsField::FirecrawlApiKey;
+    let fc_label = match &app.firecrawl_credits {
+        Some(credits) => format!(
+            "Firecrawl API Key (optional) - {}, {} used this session",
+            credits,
+            scrapers::firecrawl::credits_used()
+        ),
+        None => "Firecrawl API Key (optional)".to_string(),
+    };
     draw_field(
         frame,
         layout[2],
-        "Firecrawl API Key (optional)",
+        &fc_label,
         &app.settings_firecrawl_key,
         is_fc_active,
         true,
@@ -159,6 +317,39 @@ fn draw_settings_form(frame: &mut Frame, app: &App, area: Rect, is_setup: bool)
         if is_dd_active { app.settings_cursor } else { 0 },
     );
 
+    // Vim mode toggle
+    let is_vim_active = app.settings_field == SettingsField::VimMode;
+    let vim_value = if app.settings_vim_mode { "[x] on" } else { "[ ] off" };
+    let vim_field = Paragraph::new(vim_value)
+        .style(Style::default().fg(if is_vim_active { Color::White } else { Color::Gray }))
+        .block(
+            Block::default()
+                .title("Vim-style navigation (Space to toggle)")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(if is_vim_active { Color::Yellow } else { Color::DarkGray }))
+        );
+    frame.render_widget(vim_field, layout[4]);
+
+    // Palette choice
+    let is_palette_active = app.settings_field == SettingsField::Palette;
+    let palette = app.settings_palette;
+    let palette_value = Line::from(vec![
+        Span::raw(format!("{}  ", palette.name)),
+        Span::styled(format!("{} ok ", theme::SYM_OK), Style::default().fg(palette.ok)),
+        Span::styled(format!("{} warn ", theme::SYM_WARN), Style::default().fg(palette.warn)),
+        Span::styled(format!("{} error ", theme::SYM_FAIL), Style::default().fg(palette.error)),
+        Span::styled(format!("{} stopped", theme::SYM_STOPPED), Style::default().fg(palette.stopped)),
+    ]);
+    let palette_field = Paragraph::new(palette_value)
+        .style(Style::default().fg(if is_palette_active { Color::White } else { Color::Gray }))
+        .block(
+            Block::default()
+                .title("Status colors (Space to change)")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(if is_palette_active { Color::Yellow } else { Color::DarkGray }))
+        );
+    frame.render_widget(palette_field, layout[5]);
+
     // Help text
     let help = if is_setup {
         vec![
@@ -181,7 +372,7 @@ fn draw_settings_form(frame: &mut Frame, app: &App, area: Rect, is_setup: bool)
     let help_widget = Paragraph::new(help_text)
         .style(Style::default().fg(Color::DarkGray))
         .alignment(Alignment::Center);
-    frame.render_widget(help_widget, layout[4]);
+    frame.render_widget(help_widget, layout[6]);
 }
 
 fn draw_search(frame: &mut Frame, app: &App, area: Rect) {
@@ -195,11 +386,18 @@ fn draw_search(frame: &mut Frame, app: &App, area: Rect) {
         .split(area);
 
     // Search input
+    let mut input_title = match app.current_template() {
+        Some(template) => format!("Search (or paste magnet link) - template: {}", template),
+        None => "Search (or paste magnet link)".to_string(),
+    };
+    if app.vim_mode {
+        input_title.push_str(if app.vim_insert { " -- INSERT --" } else { " -- NORMAL --" });
+    }
     let input = Paragraph::new(app.search_input.as_str())
         .style(Style::default().fg(Color::White))
         .block(
             Block::default()
-                .title("Search (or paste magnet link)")
+                .title(input_title)
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(Color::Yellow))
         );
@@ -208,64 +406,228 @@ fn draw_search(frame: &mut Frame, app: &App, area: Rect) {
 
     // Set cursor position
     frame.set_cursor_position((
-        layout[0].x + 1 + app.cursor_pos as u16,
+        layout[0].x + 1 + input::cursor_column(&app.search_input, app.cursor_pos),
         layout[0].y + 1,
     ));
 
     // Build sources list
     let enabled_count = app.enabled_sources.len();
-    let total_count = scrapers::SCRAPERS.len();
-    let sources_str: Vec<&str> = scrapers::SCRAPERS
+    let total_count = scrapers::all_sources().len();
+    let sources_str: Vec<&str> = scrapers::all_sources()
         .iter()
-        .filter(|s| app.enabled_sources.contains(&s.to_string()))
+        .filter(|s| app.enabled_sources.contains(**s))
         .copied()
         .collect();
 
     // Show downloads indicator
     let active_downloads = app.downloads.iter()
-        .filter(|d| matches!(d.status, DownloadStatus::Downloading | DownloadStatus::Pending))
+        .filter(|d| d.status.is_active())
         .count();
     let downloads_line = if active_downloads > 0 {
         format!("\n  {} download(s) in progress - press 'd' to view", active_downloads)
     } else {
         String::new()
     };
+    let mut next_episode_line = match &app.next_episode {
+        Some(query) => format!("\n  Next episode: {} - press 'N' to search", query),
+        None => String::new(),
+    };
+    if let Some(items) = app.up_next.as_ref().filter(|items| !items.is_empty()) {
+        next_episode_line.push_str(&format!("\n  Up next on Trakt: {} - press 'U' to view", items.len()));
+    }
+
+    // The Recent panel takes the place of the introduction once there's something in it
+    let recent = app.recent_items();
+    let (help_area, intro) = if recent.is_empty() {
+        let intro = "\nEnter a search query to find torrents across multiple sites.\n\
+            Separate alternate titles with commas to search them together.\n\
+            You can also paste a magnet link, or the ID of a torrent on Real-Debrid.\n";
+        (layout[1], intro)
+    } else {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(RECENT_WIDTH)])
+            .split(layout[1]);
+        draw_recent(frame, app, &recent, columns[1]);
+        (columns[0], "")
+    };
 
     // Instructions
     let instructions = format!(
-        r#"
-Enter a search query to find torrents across multiple sites.
-You can also paste a magnet link directly.
-
+        r#"{}
 Enabled sources ({}/{}): {}
-{}
+Anime mode: {}
+Fast search: {}
+Deep search: {}{}{}
+{}{}
 Controls:
   [Enter]     Search / Process magnet
+  [↑/↓]       Pick a suggestion or recent item
+  [Tab]       Cycle query template
   [s]         Select sources
+  [A]         Toggle anime mode
+  [F]         Toggle fast search
+  [P]         Toggle deep search
+  [H]         Toggle blackhole mode
+  [N]         Search next episode
+  [U]         Up next on Trakt
   [d]         View downloads
+  [T]         Scraper stats
   [Esc]       Quit
 "#,
+        intro,
         enabled_count,
         total_count,
         sources_str.join(", "),
+        if app.anime_mode { "on (Nyaa first, batches grouped)" } else { "off" },
+        if app.fast_search { "on (detail pages skipped, magnets fetched on Enter)" } else { "off" },
+        if app.deep_search {
+            format!("on (up to {} pages for {}+ results)", scrapers::deep_search_pages(), scrapers::deep_search_target())
+        } else {
+            "off".to_string()
+        },
+        match crate::blackhole::dir() {
+            Some(dir) if app.blackhole_mode => format!("\nBlackhole mode: on (magnets written to {})", dir.display()),
+            Some(_) => "\nBlackhole mode: off".to_string(),
+            None => String::new(),
+        },
+        match scrapers::firecrawl::api_key() {
+            Some(_) => format!("\nFirecrawl credits used this session: {}", scrapers::firecrawl::credits_used()),
+            None => String::new(),
+        },
         downloads_line,
+        next_episode_line,
     );
 
     let help = Paragraph::new(instructions)
         .style(Style::default().fg(Color::Gray))
         .block(Block::default().borders(Borders::NONE));
 
-    frame.render_widget(help, layout[1]);
+    frame.render_widget(help, help_area);
+
+    draw_suggestions(frame, app, layout[0], layout[1]);
+}
+
+/// Width of the search screen's Recent panel
+const RECENT_WIDTH: u16 = 48;
+
+/// Recent queries and completed downloads beside the search instructions
+fn draw_recent(frame: &mut Frame, app: &App, recent: &[crate::RecentItem], area: Rect) {
+    let area = Rect { height: (recent.len() as u16 + 2).min(area.height), ..area };
+    let width = area.width.saturating_sub(9) as usize;
+    let items: Vec<ListItem> = recent
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let (kind, text) = match item {
+                crate::RecentItem::Query(query) => ("search ", query.as_str()),
+                crate::RecentItem::Download(entry) => ("file   ", entry.filename.as_str()),
+            };
+            let style = if app.recent_index == Some(i) {
+                Style::default().fg(Color::Black).bg(Color::Yellow)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(kind, Style::default().fg(Color::DarkGray)),
+                Span::styled(truncate(text, width), style),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title("Recent [↓] pick [Enter] open")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray)),
+    );
+    frame.render_widget(list, area);
+}
+
+/// Suggestion dropdown over the instructions, below the search input
+fn draw_suggestions(frame: &mut Frame, app: &App, input_area: Rect, below: Rect) {
+    if app.suggestions.is_empty() || below.height < 3 {
+        return;
+    }
+    let height = (app.suggestions.len() as u16 + 2).min(below.height);
+    let area = Rect::new(input_area.x, below.y, input_area.width, height);
+
+    let items: Vec<ListItem> = app
+        .suggestions
+        .iter()
+        .enumerate()
+        .map(|(i, suggestion)| {
+            let style = if app.suggestion_index == Some(i) {
+                Style::default().fg(Color::Black).bg(Color::Yellow)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let mut spans = vec![Span::styled(suggestion.text.clone(), style)];
+            if suggestion.tmdb {
+                spans.push(Span::styled("  TMDB", Style::default().fg(Color::DarkGray)));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title("Suggestions [↑/↓] pick [Enter] search")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray)),
+    );
+    frame.render_widget(Clear, area);
+    frame.render_widget(list, area);
+}
+
+/// Aggregated row for several releases of one title, with per-resolution counts
+fn title_row<'a>(app: &App, group: &crate::TitleGroup, is_selected: bool) -> Row<'a> {
+    let releases: Vec<&scrapers::TorrentResult> = group.releases.iter().map(|&i| &app.results[i]).collect();
+    let chips = release::quality_summary(releases.iter().map(|r| r.name.as_str()));
+    let best_seeders = releases.iter().map(|r| r.seeders).max().unwrap_or(0);
+    let mut sources: Vec<&str> = releases.iter().map(|r| r.source.as_str()).collect();
+    sources.sort_unstable();
+    sources.dedup();
+    let mut audio: Vec<&str> = releases.iter().flat_map(|r| r.audio.iter().map(String::as_str)).collect();
+    audio.sort_unstable();
+    audio.dedup();
+
+    let style = if is_selected {
+        Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    };
+    let prefix = if is_selected { "> " } else { "  " };
+    let arrow = if group.expanded { "▾" } else { "▸" };
+    let name = Line::from(vec![
+        Span::styled(format!("{} ", truncate(&group.title, 40)), Style::default().fg(Color::White)),
+        Span::styled(chips, Style::default().fg(Color::Cyan)),
+    ]);
+
+    Row::new(vec![
+        Cell::from(format!("{}{}", prefix, arrow)),
+        Cell::from(name),
+        Cell::from(truncate(&audio.join("+"), 12)),
+        Cell::from(""),
+        Cell::from(format!("{} rel.", releases.len())),
+        Cell::from(best_seeders.to_string()),
+        Cell::from(""),
+        Cell::from(""),
+        Cell::from(truncate(&format!("{} source(s)", sources.len()), 12)),
+    ])
+    .style(style)
 }
 
 fn draw_results(frame: &mut Frame, app: &App, area: Rect) {
     // Check for active downloads
     let active_downloads = app.downloads.iter()
-        .filter(|d| matches!(d.status, DownloadStatus::Downloading | DownloadStatus::Pending))
+        .filter(|d| d.status.is_active())
         .count();
 
     // Adjust visible height if showing downloads indicator
     let has_downloads = active_downloads > 0;
+    // Estimated download times, once there's a speed to estimate them with
+    let show_eta = app.transfer_speed().is_some();
     let visible_height = if has_downloads {
         area.height.saturating_sub(6) as usize
     } else {
@@ -274,73 +636,147 @@ fn draw_results(frame: &mut Frame, app: &App, area: Rect) {
 
     // Create table rows
     let rows: Vec<Row> = app
-        .results
+        .result_rows
         .iter()
         .skip(app.scroll_offset)
         .take(visible_height)
         .enumerate()
-        .map(|(i, result)| {
-            let actual_idx = app.scroll_offset + i;
-            let is_selected = actual_idx == app.selected_index;
+        .map(|(i, row)| {
+            let is_selected = app.scroll_offset + i == app.selected_index;
+            let (actual_idx, indent) = match row {
+                crate::ResultRow::Release(idx) => (*idx, ""),
+                crate::ResultRow::Member(idx) => (*idx, "  "),
+                crate::ResultRow::Title(group) => return title_row(app, group, is_selected),
+            };
+            let result = &app.results[actual_idx];
 
-            let name = truncate(&result.name, 50);
+            let name = format!("{}{}", indent, truncate(&result.name, 50));
             let size = truncate(&result.size_str(), 10);
             let seeds = result.seeders_str();
+            let leeches = result.leechers_str();
+            let ratio = match result.health() {
+                Health::Good => format!("{} {}", theme::SYM_OK, result.health_ratio_str()),
+                Health::Fair => format!("{} {}", theme::SYM_WARN, result.health_ratio_str()),
+                Health::Poor => format!("{} {}", theme::SYM_FAIL, result.health_ratio_str()),
+                Health::Unknown => result.health_ratio_str(),
+            };
             let source = truncate(&result.source_str(), 12);
 
+            // Color rows by swarm health so dead torrents stand out
+            let health_color = match result.health() {
+                Health::Good => app.palette.ok,
+                Health::Fair => app.palette.warn,
+                Health::Poor => app.palette.error,
+                Health::Unknown => Color::White,
+            };
             let style = if is_selected {
-                Style::default().bg(Color::DarkGray).fg(Color::White).add_modifier(Modifier::BOLD)
+                Style::default().bg(Color::DarkGray).fg(health_color).add_modifier(Modifier::BOLD)
             } else {
-                Style::default().fg(Color::White)
+                Style::default().fg(health_color)
             };
 
-            let prefix = if is_selected { "> " } else { "  " };
+            let prefix = match (is_selected, app.is_pinned(result)) {
+                (true, true) => ">*",
+                (true, false) => "> ",
+                (false, true) => " *",
+                (false, false) => "  ",
+            };
 
-            Row::new(vec![
+            let mut cells = vec![
                 format!("{}{:3}", prefix, actual_idx + 1),
                 name,
+                truncate(&result.languages_str(), 12),
+                release::badges(&result.name).join(" "),
                 size,
                 seeds,
+                leeches,
+                ratio,
                 source,
-            ])
-            .style(style)
+            ];
+            if show_eta {
+                cells.insert(5, app.download_eta(&result.size).unwrap_or_else(|| "-".to_string()));
+            }
+
+            // Anime mode: show fansub group, whether the release is a batch and the site's category
+            if app.anime_mode {
+                let group = release::fansub_group(&result.name).unwrap_or_default();
+                let kind = if release::is_batch(&result.name) { "Batch" } else { "Single" };
+                let category = result.category.as_deref().map(anime_category).unwrap_or("-");
+                cells.insert(2, truncate(&group, 12));
+                cells.insert(3, kind.to_string());
+                cells.insert(4, truncate(category, 11));
+            }
+
+            Row::new(cells).style(style)
         })
         .collect();
 
-    let header = Row::new(vec!["  #", "Name", "Size", "Seeds", "Source"])
+    let mut header_cells: Vec<String> = ["  #", "Name", "Lang", "Format", "Size", "Seeds", "Leech", "Ratio", "Source"]
+        .iter()
+        .map(|header| app.sort.header(header))
+        .collect();
+    let mut widths = vec![
+        Constraint::Length(5),
+        Constraint::Min(30),
+        Constraint::Length(12),
+        Constraint::Length(18),
+        Constraint::Length(12),
+        Constraint::Length(7),
+        Constraint::Length(7),
+        Constraint::Length(8),
+        Constraint::Length(14),
+    ];
+    if show_eta {
+        header_cells.insert(5, "ETA".to_string());
+        widths.insert(5, Constraint::Length(9));
+    }
+    if app.anime_mode {
+        header_cells.insert(2, "Group".to_string());
+        header_cells.insert(3, "Type".to_string());
+        header_cells.insert(4, "Category".to_string());
+        widths.insert(2, Constraint::Length(12));
+        widths.insert(3, Constraint::Length(7));
+        widths.insert(4, Constraint::Length(11));
+    }
+
+    let header = Row::new(header_cells)
         .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
         .bottom_margin(1);
 
     // Build title with downloads indicator
+    let mut mode_tag = if app.anime_mode { " [anime]" } else { "" }.to_string();
+    if let Some(code) = &app.language_filter {
+        mode_tag.push_str(&format!(" [audio {}]", code));
+    }
+    if app.results.iter().any(|r| r.needs_resolve()) {
+        mode_tag.push_str(" [Enter fetches missing magnets]");
+    }
+    let sources = app.sources_summary().map(|s| format!(" | {}", s)).unwrap_or_default();
     let title = if has_downloads {
         format!(
-            "Results - Page {} ({} total) | {} downloads active",
+            "Results{} - Page {} ({} total){} | {} downloads active",
+            mode_tag,
             app.page,
             app.results.len(),
+            sources,
             active_downloads
         )
     } else {
         format!(
-            "Results - Page {} ({} total)",
+            "Results{} - Page {} ({} total){}",
+            mode_tag,
             app.page,
-            app.results.len()
+            app.results.len(),
+            sources
         )
     };
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(5),
-            Constraint::Min(30),
-            Constraint::Length(12),
-            Constraint::Length(7),
-            Constraint::Length(14),
-        ],
-    )
+    let table = Table::new(rows, widths)
     .header(header)
     .block(
         Block::default()
             .title(title)
+            .title_bottom(source_status_line(app))
             .borders(Borders::ALL)
             .border_style(Style::default().fg(Color::Green)),
     );
@@ -348,6 +784,34 @@ fn draw_results(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(table, area);
 }
 
+/// Nyaa's anime categories, shortened for the results table
+/// ("Anime - English-translated" becomes "English")
+fn anime_category(category: &str) -> &str {
+    match category.trim_start_matches("Anime - ") {
+        "Anime Music Video" => "AMV",
+        short => short.trim_end_matches("-translated"),
+    }
+}
+
+/// Per-source status of the last search (ok/empty/failed)
+fn source_status_line(app: &App) -> Line<'static> {
+    let mut spans = vec![Span::raw(" ")];
+    for source in scrapers::all_sources().iter().filter(|s| app.enabled_sources.contains(**s)) {
+        let (text, color) = match app.source_statuses.get(*source) {
+            Some(SourceStatus::Ok(n)) => (format!("{} {} ok({})", theme::SYM_OK, source, n), app.palette.ok),
+            Some(SourceStatus::Empty) => (format!("{} {} empty", theme::SYM_EMPTY, source), app.palette.muted),
+            Some(SourceStatus::Failed) => (format!("{} {} failed", theme::SYM_FAIL, source), app.palette.error),
+            None => continue,
+        };
+        spans.push(Span::styled(text, Style::default().fg(color)));
+        spans.push(Span::raw(" "));
+    }
+    if !app.failed_sources().is_empty() {
+        spans.push(Span::styled("- [r] retry failed ", Style::default().fg(app.palette.warn)));
+    }
+    Line::from(spans)
+}
+
 fn draw_file_select(frame: &mut Frame, app: &App, area: Rect) {
     let visible_height = area.height.saturating_sub(6) as usize;
 
@@ -377,7 +841,7 @@ fn draw_file_select(frame: &mut Frame, app: &App, area: Rect) {
             let style = if is_cursor {
                 Style::default().bg(Color::DarkGray).fg(Color::White)
             } else if is_selected {
-                Style::default().fg(Color::Green)
+                Style::default().fg(app.palette.ok)
             } else {
                 Style::default().fg(Color::White)
             };
@@ -386,10 +850,11 @@ fn draw_file_select(frame: &mut Frame, app: &App, area: Rect) {
         })
         .collect();
 
-    let title = if let Some(result) = app.results.get(app.selected_index) {
-        format!("Select Files - {} ({} files)", truncate(&result.name, 40), app.files.len())
+    let category = app.torrent_category.map(|c| format!(" → {}", c.label())).unwrap_or_default();
+    let title = if let Some(result) = app.selected_result() {
+        format!("Select Files - {} ({} files){}", truncate(&result.name, 40), app.files.len(), category)
     } else {
-        format!("Select Files ({} files)", app.files.len())
+        format!("Select Files ({} files){}", app.files.len(), category)
     };
 
     let list = List::new(items)
@@ -404,23 +869,17 @@ fn draw_file_select(frame: &mut Frame, app: &App, area: Rect) {
 }
 
 fn draw_processing(frame: &mut Frame, app: &App, area: Rect) {
-    let popup_width = 60.min(area.width.saturating_sub(4));
-    let popup_height = 7.min(area.height.saturating_sub(4));
-
-    let popup_area = Rect::new(
-        (area.width - popup_width) / 2,
-        (area.height - popup_height) / 2,
-        popup_width,
-        popup_height,
-    );
+    if let Some(progress) = &app.rd_progress {
+        draw_rd_progress(frame, progress, area);
+        return;
+    }
+
+    let popup_area = centered_popup(area, 60, 7);
 
     frame.render_widget(Clear, popup_area);
 
     let spinner_frames = ["[    ]", "[=   ]", "[==  ]", "[=== ]", "[ ===]", "[  ==]", "[   =]", "[    ]"];
-    let frame_idx = (std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_millis() / 100) as usize % spinner_frames.len();
+    let frame_idx = (app.tick % spinner_frames.len() as u64) as usize;
 
     let text = format!(
         "\n{}\n\n{}",
@@ -441,16 +900,356 @@ fn draw_processing(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(processing, popup_area);
 }
 
-fn draw_error(frame: &mut Frame, message: &str, area: Rect) {
-    let popup_width = 60.min(area.width.saturating_sub(4));
-    let popup_height = 9.min(area.height.saturating_sub(4));
+/// Width of the cover art column in the details popup
+const COVER_COLS: u16 = 24;
+
+/// Details popup in the content area: (popup, cover art area, text area)
+fn details_layout(area: Rect) -> (Rect, Rect, Rect) {
+    let popup = centered_popup(area, 84, 22);
+    let inner = Block::default().borders(Borders::ALL).inner(popup);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(COVER_COLS.min(inner.width / 2)), Constraint::Min(0)])
+        .spacing(2)
+        .horizontal_margin(1)
+        .split(inner);
+    (popup, columns[0], columns[1])
+}
+
+/// Where the details popup shows cover art, for a frame of `frame_area`
+pub fn cover_area(frame_area: Rect) -> Rect {
+    let content = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0), Constraint::Length(3)])
+        .split(frame_area)[1];
+    details_layout(content).1
+}
+
+/// Details of the selected result, with cover art. The image itself is painted after
+/// the frame when the terminal supports a graphics protocol; otherwise it's drawn
+/// here as ASCII art.
+fn draw_details(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(result) = app.selected_result() else { return };
+    let (popup, cover_area, text_area) = details_layout(area);
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Block::default()
+            .title("Details [Esc] Close")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow)),
+        popup,
+    );
+
+    let placeholder = |text: &str| {
+        Paragraph::new(format!("\n\n\n{}", text))
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::DarkGray))
+            .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::DarkGray)))
+    };
+    match app.covers.get(&result.dedup_key()) {
+        Some(Cover::Image(image)) if app.graphics == crate::graphics::Protocol::None => {
+            let art = crate::graphics::ascii_art(image, cover_area.width, cover_area.height).join("\n");
+            frame.render_widget(Paragraph::new(art).style(Style::default().fg(Color::Gray)), cover_area);
+        }
+        Some(Cover::Image(_)) => {}
+        Some(Cover::Loading) | None => frame.render_widget(placeholder("Loading cover..."), cover_area),
+        Some(Cover::Missing) => frame.render_widget(placeholder("No cover"), cover_area),
+    }
+
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::DarkGray));
+    let value = |text: String| Span::styled(text, Style::default().fg(Color::White));
+    let mut lines = vec![
+        Line::from(Span::styled(result.name.clone(), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from(vec![label("Size:     "), value(result.size.clone())]),
+        Line::from(vec![
+            label("Peers:    "),
+            value(format!("{} seeders, {} leechers", result.seeders, result.leechers)),
+        ]),
+        Line::from(vec![label("Source:   "), value(result.source.clone())]),
+    ];
+    if let Some(category) = &result.category {
+        lines.push(Line::from(vec![label("Category: "), value(category.clone())]));
+    }
+    if !result.audio.is_empty() {
+        lines.push(Line::from(vec![label("Audio:    "), value(result.audio.join(", "))]));
+    }
+    if !result.subtitles.is_empty() {
+        lines.push(Line::from(vec![label("Subs:     "), value(result.subtitles.join(", "))]));
+    }
+    let badges = release::badges(&result.name);
+    if !badges.is_empty() {
+        lines.push(Line::from(vec![label("Format:   "), value(badges.join(" "))]));
+    }
+    if let Some(url) = &result.url {
+        lines.push(Line::from(vec![label("Page:     "), value(url.clone())]));
+    }
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), text_area);
+}
+
+/// Progress view while RD fetches an uncached torrent
+fn draw_rd_progress(frame: &mut Frame, progress: &realdebrid::RdProgress, area: Rect) {
+    let popup_area = centered_popup(area, 60, 11);
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title("Real-Debrid is downloading")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(4),
+        ])
+        .split(inner);
+
+    let gauge = Gauge::default()
+        .gauge_style(Style::default().fg(Color::Cyan).bg(Color::DarkGray))
+        .ratio((progress.progress / 100.0).clamp(0.0, 1.0))
+        .label(format!("{:.1}%", progress.progress));
+    frame.render_widget(gauge, rows[0]);
+
+    let speed = if progress.speed > 0 {
+        format!("{}/s", format_bytes(progress.speed as f64))
+    } else {
+        theme::SYM_EMPTY.to_string()
+    };
+    let details = format!(
+        "Status:  {}\nSpeed:   {}\nSeeders: {}\nElapsed: {} ({} left)",
+        progress.status,
+        speed,
+        progress.seeders,
+        realdebrid::clock(progress.elapsed),
+        realdebrid::clock(progress.remaining)
+    );
+    frame.render_widget(Paragraph::new(details), rows[3]);
+}
+
+fn draw_messages(frame: &mut Frame, app: &App, area: Rect) {
+    let popup = centered_popup(area, 100, 20);
+
+    // Newest first
+    let lines: Vec<Line> = app
+        .status_history
+        .iter()
+        .rev()
+        .skip(app.messages_scroll)
+        .map(|(time, message)| {
+            Line::from(vec![
+                Span::styled(format!("{} ", time.format("%H:%M:%S")), Style::default().fg(Color::DarkGray)),
+                Span::raw(message.as_str()),
+            ])
+        })
+        .collect();
+    let text = if lines.is_empty() {
+        Text::styled("No messages yet", Style::default().fg(Color::DarkGray))
+    } else {
+        Text::from(lines)
+    };
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(text).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .title(format!("Messages ({})", app.status_history.len()))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow)),
+        ),
+        popup,
+    );
+}
+
+/// Release notes of the newer version, over the screen it was opened from
+fn draw_changelog(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(release) = &app.update else {
+        return;
+    };
+    let popup = centered_popup(area, 100, 20);
 
-    let popup_area = Rect::new(
-        (area.width - popup_width) / 2,
-        (area.height - popup_height) / 2,
-        popup_width,
-        popup_height,
+    let notes = if release.notes.trim().is_empty() {
+        Text::styled("No release notes", Style::default().fg(Color::DarkGray))
+    } else {
+        Text::raw(release.notes.replace("\r\n", "\n"))
+    };
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(notes).wrap(Wrap { trim: false }).scroll((app.changelog_scroll, 0)).block(
+            Block::default()
+                .title(format!("What's new in {} (running {})", release.version, env!("CARGO_PKG_VERSION")))
+                .title_bottom(Line::from(Span::styled(format!(" {} ", release.url), Style::default().fg(Color::DarkGray))))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow)),
+        ),
+        popup,
     );
+}
+
+fn draw_confirm_quit(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_popup(area, 60, 9);
+
+    frame.render_widget(Clear, popup_area);
+
+    let text = format!(
+        "\n{} download(s) still active.\n\n[d] Continue in background and quit\n[q] Quit and stop downloads\n[Esc] Stay",
+        app.active_downloads().len()
+    );
+
+    let confirm = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::Yellow))
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .title("Quit")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow)),
+        );
+
+    frame.render_widget(confirm, popup_area);
+}
+
+fn draw_confirm_duplicate(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_popup(area, 60, 10);
+
+    frame.render_widget(Clear, popup_area);
+
+    let (name, status) = match &app.duplicate {
+        Some((_, torrent)) => (
+            truncate(&torrent.filename, popup_area.width.saturating_sub(4) as usize),
+            format!("{} ({:.0}%)", torrent.status, torrent.progress),
+        ),
+        None => (String::new(), String::new()),
+    };
+    let text = format!(
+        "\nThis torrent is already on Real-Debrid:\n{}\n{}\n\n[r] Reuse it  [a] Add a new copy  [Esc] Back",
+        name, status
+    );
+
+    let confirm = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::Yellow))
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .title("Duplicate")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow)),
+        );
+
+    frame.render_widget(confirm, popup_area);
+}
+
+fn draw_quality_select(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_popup(area, 70, app.quality_choices.len() as u16 + 2);
+
+    frame.render_widget(Clear, popup_area);
+
+    let name_width = popup_area.width.saturating_sub(16) as usize;
+    let items: Vec<ListItem> = app
+        .quality_choices
+        .iter()
+        .enumerate()
+        .map(|(i, choice)| {
+            let is_cursor = i == app.quality_cursor;
+            let prefix = if is_cursor { "> " } else { "  " };
+            let text = format!("{}{:<w$} {:>10}", prefix, truncate(&choice.name, name_width), choice.size, w = name_width);
+            let style = if is_cursor {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default()
+            };
+            ListItem::new(text).style(style)
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title("Which quality?")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+
+    frame.render_widget(list, popup_area);
+}
+
+fn draw_keep_waiting(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_popup(area, 60, 9);
+
+    frame.render_widget(Clear, popup_area);
+
+    let progress = match &app.timed_out {
+        Some((_, timeout)) if !timeout.status.is_empty() => {
+            format!("RD status: {} ({:.0}%)", timeout.status, timeout.progress)
+        }
+        _ => String::new(),
+    };
+    let keys = match &app.timed_out {
+        Some((RdWait::Links(_), _)) => "[w] Keep waiting  [b] Background  [Esc] Give up",
+        _ => "[w] Keep waiting  [Esc] Give up",
+    };
+    let text = format!(
+        "\nReal-Debrid is taking longer than expected.\n{}\n\n{}",
+        progress, keys
+    );
+
+    let prompt = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::Yellow))
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .title("Still waiting")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow)),
+        );
+
+    frame.render_widget(prompt, popup_area);
+}
+
+fn draw_import_prompt(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_popup(area, 70, 7);
+
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title("Import magnets")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Length(3)])
+        .split(inner);
+
+    let hint = Paragraph::new("Text file with one magnet or link per line:")
+        .style(Style::default().fg(Color::Gray));
+    frame.render_widget(hint, rows[0]);
+
+    let path = Paragraph::new(app.import_path.as_str())
+        .style(Style::default().fg(Color::Yellow))
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::DarkGray)));
+    frame.render_widget(path, rows[1]);
+
+    frame.set_cursor_position((
+        rows[1].x + 1 + input::cursor_column(&app.import_path, app.import_cursor),
+        rows[1].y + 1,
+    ));
+}
+
+fn draw_error(frame: &mut Frame, app: &App, message: &str, area: Rect) {
+    let popup_area = centered_popup(area, 60, 9);
 
     frame.render_widget(Clear, popup_area);
 
@@ -458,13 +1257,13 @@ fn draw_error(frame: &mut Frame, message: &str, area: Rect) {
 
     let error = Paragraph::new(text)
         .alignment(Alignment::Center)
-        .style(Style::default().fg(Color::Red))
+        .style(Style::default().fg(app.palette.error))
         .wrap(Wrap { trim: true })
         .block(
             Block::default()
                 .title("Error")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Red)),
+                .border_style(Style::default().fg(app.palette.error)),
         );
 
     frame.render_widget(error, popup_area);
@@ -472,22 +1271,23 @@ fn draw_error(frame: &mut Frame, message: &str, area: Rect) {
 
 fn draw_source_select(frame: &mut Frame, app: &App, area: Rect) {
     // Create list items for each source
-    let items: Vec<ListItem> = scrapers::SCRAPERS
+    let items: Vec<ListItem> = app
+        .source_order
         .iter()
         .enumerate()
         .map(|(i, source)| {
             let is_cursor = i == app.source_cursor;
-            let is_enabled = app.enabled_sources.contains(&source.to_string());
+            let is_enabled = app.enabled_sources.contains(source);
 
             let checkbox = if is_enabled { "[x]" } else { "[ ]" };
             let prefix = if is_cursor { "> " } else { "  " };
 
-            let text = format!("{}{} {}", prefix, checkbox, source);
+            let text = format!("{}{} {}. {}", prefix, checkbox, i + 1, source);
 
             let style = if is_cursor {
                 Style::default().bg(Color::DarkGray).fg(Color::White)
             } else if is_enabled {
-                Style::default().fg(Color::Green)
+                Style::default().fg(app.palette.ok)
             } else {
                 Style::default().fg(Color::Gray)
             };
@@ -497,9 +1297,9 @@ fn draw_source_select(frame: &mut Frame, app: &App, area: Rect) {
         .collect();
 
     let title = format!(
-        "Select Sources ({}/{} enabled)",
+        "Select Sources ({}/{} enabled, in priority order)",
         app.enabled_sources.len(),
-        scrapers::SCRAPERS.len()
+        scrapers::all_sources().len()
     );
 
     let list = List::new(items)
@@ -513,15 +1313,73 @@ fn draw_source_select(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(list, area);
 }
 
+fn draw_up_next(frame: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default()
+        .title(match &app.up_next {
+            Some(items) => format!("Up next on Trakt ({})", items.len()),
+            None => "Up next on Trakt".to_string(),
+        })
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Magenta));
+
+    let items = match &app.up_next {
+        None => {
+            frame.render_widget(Paragraph::new("\nLoading from Trakt...").alignment(Alignment::Center).block(block), area);
+            return;
+        }
+        Some(items) if items.is_empty() => {
+            let text = "\nNothing up next: no unwatched episodes of shows in progress, and the watchlist is empty.";
+            frame.render_widget(Paragraph::new(text).alignment(Alignment::Center).block(block), area);
+            return;
+        }
+        Some(items) => items,
+    };
+
+    // Keep the cursor in view
+    let visible = area.height.saturating_sub(2).max(1) as usize;
+    let first = app.up_next_cursor.saturating_sub(visible - 1);
+    let rows: Vec<ListItem> = items
+        .iter()
+        .enumerate()
+        .skip(first)
+        .map(|(i, item)| {
+            let is_cursor = i == app.up_next_cursor;
+            let prefix = if is_cursor { "> " } else { "  " };
+            let style = if is_cursor {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{}{}", prefix, item.query()), style),
+                Span::styled(format!("  {}", item.label()), Style::default().fg(Color::DarkGray)),
+            ]))
+        })
+        .collect();
+
+    frame.render_widget(List::new(rows).block(block), area);
+}
+
+/// The global speed cap for the Downloads title, None when unlimited
+fn speed_cap() -> Option<String> {
+    match throttle::limit() {
+        0 => None,
+        limit => Some(format!("capped at {}/s", format_bytes(limit as f64))),
+    }
+}
+
 fn draw_downloads(frame: &mut Frame, app: &App, area: Rect) {
-    if app.downloads.is_empty() {
+    if app.downloads.is_empty() && app.background_waits.is_empty() {
         // Show empty state
         let text = Paragraph::new("\n\nNo downloads yet.\n\nStart by searching and selecting a torrent.")
             .alignment(Alignment::Center)
             .style(Style::default().fg(Color::Gray))
             .block(
                 Block::default()
-                    .title("Downloads")
+                    .title(match speed_cap() {
+                        Some(cap) => format!("Downloads ({})", cap),
+                        None => "Downloads".to_string(),
+                    })
                     .borders(Borders::ALL)
                     .border_style(Style::default().fg(Color::Blue)),
             );
@@ -532,21 +1390,43 @@ fn draw_downloads(frame: &mut Frame, app: &App, area: Rect) {
 
     let visible_height = area.height.saturating_sub(4) as usize;
 
-    // Create table rows
-    let rows: Vec<Row> = app
+    // Torrents still on Real-Debrid's side come first; they can't be selected
+    let rd_rows = app.background_waits.iter().map(|transfer| {
+        let (progress, speed, status) = match &transfer.progress {
+            Some(p) => (
+                format!("{:.1}%", p.progress),
+                if p.speed > 0 { format!("{}/s", format_bytes(p.speed as f64)) } else { "-".to_string() },
+                format!("{}, {} seeders", p.status, p.seeders),
+            ),
+            None => ("-".to_string(), "-".to_string(), transfer.status.clone()),
+        };
+        Row::new(vec![
+            "  RD".to_string(),
+            format!("{} Cach", theme::SYM_PENDING),
+            transfer.category.map(|c| c.label()).unwrap_or("-").to_string(),
+            format!("{} [{}]", truncate(transfer.name(), 40), status),
+            progress,
+            speed,
+        ])
+        .style(Style::default().fg(app.palette.muted))
+    });
+
+    let download_rows = app
         .downloads
         .iter()
-        .take(visible_height)
         .enumerate()
         .map(|(i, dl)| {
             let is_selected = i == app.download_cursor;
 
             let (status_str, status_style) = match &dl.status {
-                DownloadStatus::Pending => ("Wait", Style::default().fg(Color::Gray)),
-                DownloadStatus::Downloading => ("Down", Style::default().fg(Color::Yellow)),
-                DownloadStatus::Completed => ("Done", Style::default().fg(Color::Green)),
-                DownloadStatus::Failed(_) => ("Fail", Style::default().fg(Color::Red)),
-                DownloadStatus::Cancelled => ("Stop", Style::default().fg(Color::Magenta)),
+                DownloadStatus::Pending => (format!("{} Wait", theme::SYM_PENDING), Style::default().fg(app.palette.muted)),
+                DownloadStatus::Downloading => (format!("{} Down", theme::SYM_ACTIVE), Style::default().fg(app.palette.warn)),
+                DownloadStatus::Completed => (format!("{} Done", theme::SYM_OK), Style::default().fg(app.palette.ok)),
+                DownloadStatus::Failed(_) => (format!("{} Fail", theme::SYM_FAIL), Style::default().fg(app.palette.error)),
+                DownloadStatus::Cancelled => (format!("{} Stop", theme::SYM_STOPPED), Style::default().fg(app.palette.stopped)),
+                DownloadStatus::Skipped => (format!("{} Skip", theme::SYM_EMPTY), Style::default().fg(app.palette.muted)),
+                DownloadStatus::Paused => (format!("{} Hold", theme::SYM_STOPPED), Style::default().fg(app.palette.error)),
+                DownloadStatus::Scheduled => (format!("{} Later", theme::SYM_PENDING), Style::default().fg(app.palette.muted)),
             };
 
             let progress = if dl.total_bytes > 0 {
@@ -571,28 +1451,39 @@ fn draw_downloads(frame: &mut Frame, app: &App, area: Rect) {
 
             Row::new(vec![
                 format!("{}{:2}", prefix, i + 1),
-                status_str.to_string(),
+                status_str,
+                dl.category.label().to_string(),
                 truncate(&dl.filename, 40),
                 progress,
                 speed,
             ])
             .style(style)
-        })
-        .collect();
+        });
+    let rows: Vec<Row> = rd_rows.chain(download_rows).take(visible_height).collect();
 
-    let header = Row::new(vec!["  #", "Status", "Name", "Progress", "Speed"])
+    let header = Row::new(vec!["  #", "Status", "Type", "Name", "Progress", "Speed"])
         .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
         .bottom_margin(1);
 
     let active = app.downloads.iter()
-        .filter(|d| matches!(d.status, DownloadStatus::Downloading | DownloadStatus::Pending))
+        .filter(|d| d.status.is_active())
         .count();
+    let caching = match app.background_waits.len() {
+        0 => String::new(),
+        n => format!(", {} on Real-Debrid", n),
+    };
+    let scheduled = match (app.downloads.iter().any(|d| d.status == DownloadStatus::Scheduled), schedule::opens_at()) {
+        (true, Some(start)) => format!(", scheduled for {}", start),
+        _ => String::new(),
+    };
+    let cap = speed_cap().map(|cap| format!(", {}", cap)).unwrap_or_default();
 
     let table = Table::new(
         rows,
         [
             Constraint::Length(4),
-            Constraint::Length(6),
+            Constraint::Length(7),
+            Constraint::Length(5),
             Constraint::Min(20),
             Constraint::Length(12),
             Constraint::Length(12),
@@ -601,7 +1492,10 @@ fn draw_downloads(frame: &mut Frame, app: &App, area: Rect) {
     .header(header)
     .block(
         Block::default()
-            .title(format!("Downloads ({} active)", active))
+            .title(match remote::from_env() {
+                Some(target) => format!("Downloads ({} active{}{}{}) → {}", active, caching, scheduled, cap, target),
+                None => format!("Downloads ({} active{}{}{})", active, caching, scheduled, cap),
+            })
             .borders(Borders::ALL)
             .border_style(Style::default().fg(Color::Blue)),
     );
@@ -609,20 +1503,179 @@ fn draw_downloads(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(table, area);
 }
 
+fn draw_stats(frame: &mut Frame, app: &App, area: Rect) {
+    let stats = scrapers::stats::snapshot();
+
+    let rows: Vec<Row> = scrapers::all_sources()
+        .iter()
+        .map(|source| {
+            let enabled = app.enabled_sources.contains(*source);
+            let style = if enabled {
+                Style::default().fg(Color::White)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+
+            match stats.get(*source) {
+                Some(s) if s.runs > 0 => {
+                    let rate = s.success_rate();
+                    let (rate_symbol, rate_style) = if rate >= 80.0 {
+                        (theme::SYM_OK, Style::default().fg(app.palette.ok))
+                    } else if rate >= 50.0 {
+                        (theme::SYM_WARN, Style::default().fg(app.palette.warn))
+                    } else {
+                        (theme::SYM_FAIL, Style::default().fg(app.palette.error))
+                    };
+                    Row::new(vec![
+                        Cell::from(source.to_string()),
+                        Cell::from(s.runs.to_string()),
+                        Cell::from(format!("{} {:.0}%", rate_symbol, rate)).style(rate_style),
+                        Cell::from(s.failures.to_string()),
+                        Cell::from(format!("{} ms", s.avg_latency_ms())),
+                        Cell::from(format!("{:.1}", s.avg_results())),
+                        Cell::from(s.total_results.to_string()),
+                    ])
+                    .style(style)
+                }
+                _ => Row::new(vec![
+                    source.to_string(),
+                    "0".to_string(),
+                    "-".to_string(),
+                    "-".to_string(),
+                    "-".to_string(),
+                    "-".to_string(),
+                    "-".to_string(),
+                ])
+                .style(style),
+            }
+        })
+        .collect();
+
+    let header = Row::new(vec!["Source", "Runs", "Success", "Failed", "Avg latency", "Avg results", "Total"])
+        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        .bottom_margin(1);
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(15),
+            Constraint::Length(6),
+            Constraint::Length(9),
+            Constraint::Length(8),
+            Constraint::Length(13),
+            Constraint::Length(13),
+            Constraint::Length(8),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::default()
+            .title(match scrapers::firecrawl::api_key() {
+                Some(_) => format!("Scraper Stats (Firecrawl: {} credits used this session)", scrapers::firecrawl::credits_used()),
+                None => "Scraper Stats".to_string(),
+            })
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan)),
+    );
+
+    frame.render_widget(table, area);
+}
+
+fn draw_log(frame: &mut Frame, app: &App, area: Rect) {
+    let height = area.height.saturating_sub(2) as usize;
+    let search = app.log_search.to_lowercase();
+
+    let lines: Vec<Line> = app
+        .log_window(height)
+        .into_iter()
+        .map(|line| {
+            let style = if line.contains("] ERROR: ") {
+                Style::default().fg(app.palette.error)
+            } else {
+                Style::default().fg(Color::Gray)
+            };
+            highlight(line, &search, style)
+        })
+        .collect();
+
+    let mut title = format!("Scraper Log - {}", app.log_source.as_deref().unwrap_or("all sources"));
+    if !app.log_search.is_empty() {
+        title.push_str(&format!(" - \"{}\"", app.log_search));
+    }
+    let count = app.filtered_log().len();
+    let position = if app.log_scroll > 0 {
+        format!(" {} lines, {} up ", count, app.log_scroll)
+    } else {
+        format!(" {} lines ", count)
+    };
+
+    let log = Paragraph::new(lines).block(
+        Block::default()
+            .title(title)
+            .title_bottom(Line::from(position).right_aligned())
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan)),
+    );
+
+    frame.render_widget(log, area);
+}
+
+/// A line with case-insensitive matches of `search` (already lowercase) highlighted
+fn highlight<'a>(line: &'a str, search: &str, style: Style) -> Line<'a> {
+    // ASCII lowercasing keeps byte offsets valid in the original line
+    let lower = line.to_ascii_lowercase();
+    if search.is_empty() {
+        return Line::from(Span::styled(line, style));
+    }
+
+    let mut spans = Vec::new();
+    let mut pos = 0;
+    for (start, matched) in lower.match_indices(search) {
+        spans.push(Span::styled(&line[pos..start], style));
+        spans.push(Span::styled(
+            &line[start..start + matched.len()],
+            Style::default().fg(Color::Black).bg(Color::Yellow),
+        ));
+        pos = start + matched.len();
+    }
+    spans.push(Span::styled(&line[pos..], style));
+    Line::from(spans)
+}
+
 fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     let help_text = match app.mode {
         AppMode::Setup => "[Tab] Next  [Enter] Save  [Esc] Skip",
+        AppMode::SelfTest => "[r] Run again  [Enter] Continue",
         AppMode::Settings => "[Tab] Next  [Enter] Save  [Esc] Cancel",
-        AppMode::Search => "[Enter] Search  [s] Sources  [S] Settings  [d] Downloads  [Esc] Quit",
-        AppMode::Results => "[j/k] Nav  [Enter] Select  [c] Copy  [s] Sources  [d] Downloads  [n/p] Page  [/] Search  [q] Quit",
-        AppMode::FileSelect => "[j/k] Navigate  [Space] Toggle  [a] All  [Enter] Confirm  [Esc] Back",
-        AppMode::SourceSelect => "[j/k] Navigate  [Space] Toggle  [a] All  [n] None  [Enter] Confirm  [Esc] Back",
-        AppMode::Downloads => "[j/k] Nav  [s] Start  [S] Start All  [c] Cancel  [C] Cancel All  [x] Clear  [Esc] Back",
+        AppMode::Search if app.vim_mode && !app.vim_insert => "[i/a] Insert  [h/l/w/b] Move  [x/D] Delete  [Enter] Search  [s] Sources  [S] Settings  [d] Downloads  [q] Quit",
+        AppMode::Search if app.vim_mode => "[Enter] Search  [Tab] Template  [Esc] Normal mode",
+        AppMode::Search => "[Enter] Search  [Tab] Template  [s] Sources  [S] Settings  [d] Downloads  [T] Stats  [L] Log  [Esc] Quit",
+        AppMode::Results => "[j/k] Nav  [:N] Go to  [Enter] Select  [v] Group  [f] Language  [</>] Sort  [R] Reverse  [*] Pin  [A] Add pinned  [i] Info  [c] Copy  [o] Open  [e/E] Export  [r] Retry failed  [s] Sources  [d] Downloads  [^S] Settings  [t] Stats  [n/p] Page  [/] Search  [q] Quit",
+        AppMode::FileSelect => "[j/k] Navigate  [Space] Toggle  [a] All  [t] Category  [Enter] Confirm  [^S] Settings  [^D] Downloads  [Esc] Back",
+        AppMode::SourceSelect => "[j/k] Navigate  [J/K] Move  [Space] Toggle  [a] All  [n] None  [Enter] Save  [Esc] Back",
+        AppMode::Downloads => "[j/k] Nav  [s] Start  [S] Start All  [c] Cancel  [C] Cancel All  [x] Clear  [u] Renew link  [t] Category  [v] Verify  [i] Import  [+/-] Speed cap  [^S] Settings  [Esc] Back",
+        AppMode::Stats => "[x] Reset  [l] Log  [Esc] Back",
+        AppMode::Log => "[j/k] Scroll  [g/G] Top/Bottom  [f] Source  [/] Search  [y] Copy  [r] Reload  [Esc] Back",
+        AppMode::Processing if app.links_wait.is_some() => "[b] Wait in background  [Esc] Cancel",
         AppMode::Processing => "[Esc] Cancel",
+        AppMode::ConfirmQuit => "[d] Detach  [q] Quit anyway  [Esc] Stay",
+        AppMode::ConfirmDuplicate => "[r] Reuse  [a] Add anyway  [Esc] Back",
+        AppMode::ConfirmKeepWaiting if matches!(app.timed_out, Some((RdWait::Links(_), _))) => "[w] Keep waiting  [b] Background  [Esc] Give up",
+        AppMode::ConfirmKeepWaiting => "[w] Keep waiting  [Esc] Give up",
+        AppMode::ImportPrompt => "[Enter] Import  [Esc] Cancel",
+        AppMode::Details => "[Esc] Close",
+        AppMode::QualitySelect => "[j/k] Navigate  [Enter] Add to Real-Debrid  [Esc] Back",
+        AppMode::Messages => "[j/k] Scroll  [g] Newest  [Esc] Close",
+        AppMode::Changelog => "[j/k] Scroll  [o] Open release page  [Esc] Close",
+        AppMode::UpNext => "[j/k] Navigate  [Enter] Search  [g] Grab best  [r] Refresh  [Esc] Back",
         AppMode::Error(_) => "Press any key...",
     };
 
-    let status_text = if app.status.is_empty() {
+    let status_text = if let (AppMode::Results, Some(input)) = (&app.mode, &app.jump_input) {
+        format!("Go to result: {}_  [Enter] Jump  [Esc] Cancel", input)
+    } else if app.mode == AppMode::Log && app.log_searching {
+        format!("Search log: {}_  [Enter] Done  [Esc] Clear", app.log_search)
+    } else if app.status.is_empty() {
         help_text.to_string()
     } else {
         format!("{} | {}", app.status, help_text)
@@ -640,7 +1693,7 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
 }
 
 /// Truncate string with ellipsis (UTF-8 safe)
-fn truncate(s: &str, max_len: usize) -> String {
+pub fn truncate(s: &str, max_len: usize) -> String {
     let char_count = s.chars().count();
     if char_count <= max_len {
         s.to_string()
@@ -651,3 +1704,6 @@ fn truncate(s: &str, max_len: usize) -> String {
         s.chars().take(max_len).collect()
     }
 }
+
+#[cfg(test)]
+mod tests;
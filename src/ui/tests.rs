@@ -0,0 +1,351 @@
+//! Rendering snapshots of each screen on a fixed-size test terminal. After an
+//! intended layout change, review and accept the new snapshots with `cargo insta review`.
+
+use super::*;
+use crate::{Download, RdTransfer};
+use ratatui::backend::TestBackend;
+use release::Category;
+use scrapers::TorrentResult;
+use std::path::PathBuf;
+
+const WIDTH: u16 = 130;
+const HEIGHT: u16 = 30;
+
+/// An app in its initial state, independent of the machine's config and history
+fn app() -> App {
+    let _env = crate::apptests::ENV.blocking_lock();
+    std::env::set_var("XDG_CONFIG_HOME", std::env::temp_dir().join("littlejohn-ui-tests"));
+    let mut app = App::new();
+    app.rd_client = None;
+    app.telegram = None;
+    app.search_history.clear();
+    app.recent_downloads.clear();
+    app.next_episode = None;
+    app.up_next = None;
+    app.enabled_sources = scrapers::SCRAPERS.iter().map(|s| s.name().to_string()).collect();
+    app.settings_rd_token.clear();
+    app.settings_firecrawl_key.clear();
+    app.settings_download_dir.clear();
+    app.firecrawl_credits = None;
+    app.sort = crate::sort::Sort::default();
+    app.configured_speed = None;
+    app
+}
+
+fn render(app: &App) -> Terminal<TestBackend> {
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap();
+    terminal.draw(|frame| draw(frame, app)).unwrap();
+    terminal
+}
+
+fn result(name: &str, source: &str, seeders: i64, size: &str) -> TorrentResult {
+    TorrentResult {
+        name: name.to_string(),
+        size: size.to_string(),
+        seeders,
+        leechers: seeders / 3,
+        magnet: format!("magnet:?xt=urn:btih:{:040}", seeders),
+        source: source.to_string(),
+        url: None,
+        category: None,
+        cover: None,
+        audio: Vec::new(),
+        subtitles: Vec::new(),
+    }
+}
+
+fn download(filename: &str, status: DownloadStatus, downloaded: u64, total: u64, speed: f64) -> Download {
+    Download {
+        id: uuid::Uuid::nil(),
+        url: format!("https://download.example/{}", filename),
+        filename: filename.to_string(),
+        dest_path: PathBuf::from("downloads").join(filename),
+        category: Category::Movie,
+        trakt: None,
+        rd_link: None,
+        task: None,
+        status,
+        total_bytes: total,
+        downloaded_bytes: downloaded,
+        speed,
+    }
+}
+
+#[test]
+fn search() {
+    let mut app = app();
+    app.search_input = "the matrix 1999".to_string();
+    app.cursor_pos = app.search_input.len();
+    let terminal = render(&app);
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[test]
+fn search_with_recent() {
+    let mut app = app();
+    app.search_history = vec!["the matrix 1999".to_string(), "ubuntu 24.04".to_string()];
+    app.recent_downloads = vec![crate::history::Entry {
+        filename: "The.Matrix.1999.1080p.BluRay.x264.mkv".to_string(),
+        path: PathBuf::from("downloads/The.Matrix.1999.1080p.BluRay.x264.mkv"),
+        size: 2_100_000_000,
+        completed_at: "2024-05-01T20:00:00Z".to_string(),
+        sha1: None,
+        md5: None,
+    }];
+    app.recent_index = Some(2);
+    let terminal = render(&app);
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[test]
+fn results_with_long_names() {
+    let mut app = app();
+    app.results = vec![
+        result(
+            "The.Lord.of.the.Rings.The.Fellowship.of.the.Ring.2001.EXTENDED.REMASTERED.2160p.UHD.BluRay.x265.10bit.HDR.TrueHD.7.1.Atmos-SWTYBLZ",
+            "tpb",
+            1520,
+            "68.2 GB",
+        ),
+        result("Ubuntu 24.04 LTS Desktop amd64", "1337x", 48, "5.7 GB"),
+        result("Il Signore degli Anelli - La Compagnia dell'Anello (2001) [versione estesa] 1080p ITA ENG", "ilcorsaronero", 3, "14.1 GB"),
+        result("The Matrix (1999) [1080p]", "yts", 0, "2.1 GB"),
+    ];
+    app.source_statuses = [
+        ("tpb", SourceStatus::Ok(1)),
+        ("1337x", SourceStatus::Ok(1)),
+        ("ilcorsaronero", SourceStatus::Ok(1)),
+        ("yts", SourceStatus::Ok(1)),
+        ("bitsearch", SourceStatus::Failed),
+        ("rutracker", SourceStatus::Empty),
+    ]
+    .into_iter()
+    .map(|(source, status)| (source.to_string(), status))
+    .collect();
+    app.refresh_rows();
+    app.open(AppMode::Results);
+    let terminal = render(&app);
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[test]
+fn results_sorted_by_size() {
+    let mut app = app();
+    app.group_titles = false;
+    app.results = vec![
+        result("Medium.Release.1080p", "tpb", 50, "4.2 GB"),
+        result("Small.Release.720p", "1337x", 200, "900 MB"),
+        result("Big.Release.2160p", "bitsearch", 10, "18 GB"),
+    ];
+    app.sort = crate::sort::Sort::by(crate::sort::Column::Size);
+    app.apply_sort();
+    app.open(AppMode::Results);
+    let names: Vec<&str> = app.results.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, ["Big.Release.2160p", "Medium.Release.1080p", "Small.Release.720p"]);
+    let terminal = render(&app);
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[test]
+fn results_with_download_eta() {
+    let mut app = app();
+    app.configured_speed = Some(10 * 1024 * 1024);
+    app.results = vec![
+        result("Big.Release.2160p", "bitsearch", 10, "18.0 GB"),
+        result("Small.Release.720p", "1337x", 200, "900.0 MB"),
+        result("Unknown.Size.Release", "tpb", 5, "N/A"),
+    ];
+    app.refresh_rows();
+    app.open(AppMode::Results);
+    assert_eq!(app.download_eta("18.0 GB").as_deref(), Some("30m 43s"));
+    let terminal = render(&app);
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[test]
+fn anime_results_with_category() {
+    let mut app = app();
+    app.anime_mode = true;
+    let mut batch = result("[SubsPlease] Frieren (01-28) (1080p) [Batch]", "nyaa", 900, "38.2 GB");
+    batch.category = Some("Anime - English-translated".to_string());
+    let mut raw = result("[Ohys-Raws] Frieren - 28 (BS11 1280x720 x264 AAC).mp4", "nyaa", 40, "420.0 MB");
+    raw.category = Some("Anime - Raw".to_string());
+    app.results = vec![raw, batch, result("Frieren.S01E28.1080p.WEB.x264", "tpb", 120, "1.4 GB")];
+    app.apply_sort();
+    app.open(AppMode::Results);
+    // Wide enough for the extra anime columns, so none of them is squeezed
+    let mut terminal = Terminal::new(TestBackend::new(160, HEIGHT)).unwrap();
+    terminal.draw(|frame| draw(frame, &app)).unwrap();
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[test]
+fn pinned_results_stay_on_top() {
+    let mut app = app();
+    app.results = vec![
+        result("Popular.Release.1080p", "tpb", 900, "4.2 GB"),
+        result("Rare.Release.720p", "1337x", 2, "900 MB"),
+    ];
+    app.refresh_rows();
+    app.open(AppMode::Results);
+    app.jump_to_result(2);
+    app.toggle_pin();
+    assert_eq!(app.result_rows[0], crate::ResultRow::Release(1));
+
+    app.sort = crate::sort::Sort::by(crate::sort::Column::Seeds);
+    app.apply_sort();
+    assert_eq!(app.results[app.selected_result_index().unwrap()].name, "Rare.Release.720p");
+    assert_eq!(app.result_rows.len(), 2);
+    let pinned = match &app.result_rows[0] {
+        crate::ResultRow::Release(i) => &app.results[*i],
+        row => panic!("{:?}", row),
+    };
+    assert_eq!(pinned.name, "Rare.Release.720p");
+    let terminal = render(&app);
+    assert!(format!("{:?}", terminal.backend()).contains(">*"));
+}
+
+#[test]
+fn downloads_with_every_status() {
+    let mut app = app();
+    let gb = 1_000_000_000;
+    app.downloads = vec![
+        download("queued.mkv", DownloadStatus::Pending, 0, 0, 0.0),
+        download("transferring.mkv", DownloadStatus::Downloading, 700_000_000, 2 * gb, 12_500_000.0),
+        download("finished.mkv", DownloadStatus::Completed, 2 * gb, 2 * gb, 0.0),
+        download("broken.mkv", DownloadStatus::Failed("Link expired (HTTP 403)".to_string()), 0, 0, 0.0),
+        download("stopped.mkv", DownloadStatus::Cancelled, 300_000_000, gb, 0.0),
+        download("existing.mkv", DownloadStatus::Skipped, 0, 0, 0.0),
+        download("low-disk.mkv", DownloadStatus::Paused, 100_000_000, gb, 0.0),
+        download("tonight.mkv", DownloadStatus::Scheduled, 0, 0, 0.0),
+    ];
+    app.background_waits.push(RdTransfer::new("TORRENT1".to_string(), "Uncached.Torrent.2024".to_string(), "Waiting for Real-Debrid"));
+    app.open(AppMode::Downloads);
+    let terminal = render(&app);
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[test]
+fn quality_popup_over_results() {
+    let mut app = app();
+    app.results = vec![
+        result("The Matrix (1999) [720p]", "yts", 0, "1.0 GB"),
+        result("The Matrix (1999) [1080p]", "yts", 0, "2.1 GB"),
+        result("The Matrix (1999) [2160p]", "yts", 0, "6.4 GB"),
+    ];
+    app.refresh_rows();
+    app.open(AppMode::Results);
+    app.quality_choices = app.results.clone();
+    app.quality_cursor = 1;
+    app.open(AppMode::QualitySelect);
+    let terminal = render(&app);
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[test]
+fn settings_cursor_follows_active_field() {
+    let mut app = app();
+    app.settings_rd_token = "ABCDEFGHIJKLMNOP".to_string();
+    app.settings_download_dir = "/media/movies".to_string();
+    app.open(AppMode::Settings);
+
+    app.settings_field = SettingsField::RdApiToken;
+    app.settings_cursor = 4;
+    let mut terminal_token = render(&app);
+    insta::assert_snapshot!("settings_token", terminal_token.backend());
+    let token_cursor = terminal_token.get_cursor_position().unwrap();
+
+    app.settings_field = SettingsField::DownloadDir;
+    app.settings_cursor = app.settings_download_dir.len();
+    let mut terminal_dir = render(&app);
+    insta::assert_snapshot!("settings_download_dir", terminal_dir.backend());
+    let dir_cursor = terminal_dir.get_cursor_position().unwrap();
+
+    // Each field is a bordered box of 3 rows; the cursor sits inside the active one
+    assert_eq!(dir_cursor.y, token_cursor.y + 6);
+    assert_eq!(token_cursor.x + 9, dir_cursor.x);
+}
+
+#[test]
+fn changelog_of_newer_release() {
+    let mut app = app();
+    app.update = Some(crate::update::Release {
+        version: "9.0.0".to_string(),
+        url: "https://github.com/mat-lo/littlejohn/releases/tag/v9.0.0".to_string(),
+        notes: "## Added\r\n- Nyaa scraper\r\n- Estimated download times\r\n\r\n## Fixed\r\n- Sorting by size".to_string(),
+    });
+    assert!(crate::update::is_newer("v9.0.0", env!("CARGO_PKG_VERSION")));
+    assert!(!crate::update::is_newer("v0.1", env!("CARGO_PKG_VERSION")));
+    app.open(AppMode::Changelog);
+    let terminal = render(&app);
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[test]
+fn self_test_report() {
+    use crate::selftest::{Check, Item};
+    let mut app = app();
+    app.self_test = vec![
+        Item { check: Check::RdToken, outcome: Some(Ok("alice (premium until 2025-06-01)".to_string())) },
+        Item { check: Check::Scraper, outcome: Some(Err("tpb could not be reached (see the scraper log)".to_string())) },
+        Item { check: Check::DownloadDir, outcome: Some(Ok("/media/movies".to_string())) },
+    ];
+    app.open(AppMode::SelfTest);
+    let terminal = render(&app);
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[test]
+fn tiny_terminals_never_panic() {
+    let mut app = app();
+    app.processing_status = "Adding magnet to Real-Debrid...".to_string();
+    app.results = vec![result("The Matrix (1999) [1080p]", "yts", 10, "2.1 GB")];
+    app.quality_choices = app.results.clone();
+    app.refresh_rows();
+    let modes = [
+        AppMode::Search,
+        AppMode::Results,
+        AppMode::Processing,
+        AppMode::ConfirmQuit,
+        AppMode::QualitySelect,
+        AppMode::Details,
+        AppMode::Messages,
+        AppMode::Error("Real-Debrid rejected the API token".to_string()),
+    ];
+    for (width, height) in [(0, 0), (1, 1), (20, 5), (MIN_WIDTH, MIN_HEIGHT), (MIN_WIDTH + 1, 40)] {
+        for mode in &modes {
+            app.go_home();
+            app.open(mode.clone());
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            terminal.draw(|frame| draw(frame, &app)).unwrap();
+        }
+    }
+
+    let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+    terminal.draw(|frame| draw(frame, &app)).unwrap();
+    insta::assert_snapshot!(terminal.backend());
+}
+
+#[test]
+fn remaining_screens() {
+    let mut app = app();
+    app.tick = 3;
+    app.processing_status = "Adding magnet to Real-Debrid...".to_string();
+    for (name, mode) in [
+        ("setup", AppMode::Setup),
+        ("source_select", AppMode::SourceSelect),
+        ("file_select", AppMode::FileSelect),
+        ("up_next", AppMode::UpNext),
+        ("log", AppMode::Log),
+        ("processing", AppMode::Processing),
+        ("confirm_quit", AppMode::ConfirmQuit),
+        ("import_prompt", AppMode::ImportPrompt),
+        ("messages", AppMode::Messages),
+        ("error", AppMode::Error("Real-Debrid rejected the API token (HTTP 401): bad_token".to_string())),
+    ] {
+        app.go_home();
+        app.open(mode);
+        let terminal = render(&app);
+        insta::assert_snapshot!(name, terminal.backend());
+    }
+}
@@ -0,0 +1,71 @@
+//! Opt-in update check (UPDATE_CHECK=true): on startup the latest GitHub release is
+//! looked up, and a newer one is announced in the header with its changelog a key away.
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+
+/// Latest published release (drafts and pre-releases are skipped by GitHub)
+const LATEST_URL: &str = "https://api.github.com/repos/mat-lo/littlejohn/releases/latest";
+
+/// A release newer than the running build
+#[derive(Debug, Clone)]
+pub struct Release {
+    /// Version without the leading `v`, e.g. "0.7.0"
+    pub version: String,
+    pub url: String,
+    /// Release notes as written on GitHub (Markdown)
+    pub notes: String,
+}
+
+#[derive(Deserialize)]
+struct ApiRelease {
+    tag_name: String,
+    html_url: String,
+    body: Option<String>,
+}
+
+/// Whether the check is turned on (UPDATE_CHECK)
+pub fn enabled() -> bool {
+    matches!(
+        std::env::var("UPDATE_CHECK").map(|v| v.to_lowercase()).as_deref(),
+        Ok("1") | Ok("true") | Ok("yes")
+    )
+}
+
+/// Numeric parts of a version like "v0.7.0" or "0.7.0-rc1" (stopping at the first non-number)
+fn parts(version: &str) -> Vec<u64> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split(['.', '-'])
+        .map_while(|part| part.parse().ok())
+        .collect()
+}
+
+/// Whether `latest` is a later version than `current`
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    parts(latest) > parts(current)
+}
+
+/// The latest release if it's newer than this build
+pub async fn check() -> Result<Option<Release>> {
+    let response = crate::http::transfer_client()
+        .get(LATEST_URL)
+        .header("User-Agent", concat!("littlejohn/", env!("CARGO_PKG_VERSION")))
+        .header("Accept", "application/vnd.github+json")
+        .timeout(crate::http::request_timeout())
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("GitHub returned HTTP {}", response.status().as_u16()));
+    }
+    let release: ApiRelease = response.json().await?;
+    if !is_newer(&release.tag_name, env!("CARGO_PKG_VERSION")) {
+        return Ok(None);
+    }
+    Ok(Some(Release {
+        version: release.tag_name.trim_start_matches('v').to_string(),
+        url: release.html_url,
+        notes: release.body.unwrap_or_default(),
+    }))
+}
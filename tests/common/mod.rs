@@ -0,0 +1,50 @@
+//! wiremock helpers for the integration tests, standing in for Real-Debrid and the
+//! scraped sites
+
+#![allow(dead_code)]
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn reply(status: u16, body: &str) -> ResponseTemplate {
+    ResponseTemplate::new(status).set_body_string(body)
+}
+
+/// Answer `verb endpoint` with `status` and `body`
+pub async fn route(server: &MockServer, verb: &str, endpoint: &str, status: u16, body: &str) {
+    Mock::given(method(verb)).and(path(endpoint)).respond_with(reply(status, body)).mount(server).await;
+}
+
+/// Answer successive `verb endpoint` requests with `responses`, repeating the last
+pub async fn sequence(server: &MockServer, verb: &str, endpoint: &str, responses: &[(u16, &str)]) {
+    let Some(((status, body), first)) = responses.split_last() else { return };
+    // Earlier mocks win while they still have uses left
+    for (status, body) in first {
+        Mock::given(method(verb))
+            .and(path(endpoint))
+            .respond_with(reply(*status, body))
+            .up_to_n_times(1)
+            .mount(server)
+            .await;
+    }
+    route(server, verb, endpoint, *status, body).await;
+}
+
+/// Requests received so far, oldest first
+pub async fn requests(server: &MockServer) -> Vec<wiremock::Request> {
+    server.received_requests().await.unwrap_or_default()
+}
+
+/// Whether a `verb endpoint` request was received
+pub async fn received(server: &MockServer, verb: &str, endpoint: &str) -> bool {
+    requests(server).await.iter().any(|r| r.method.as_str() == verb && r.url.path() == endpoint)
+}
+
+/// Body of the first `verb endpoint` request
+pub async fn body(server: &MockServer, verb: &str, endpoint: &str) -> Option<String> {
+    requests(server)
+        .await
+        .into_iter()
+        .find(|r| r.method.as_str() == verb && r.url.path() == endpoint)
+        .map(|r| String::from_utf8_lossy(&r.body).into_owned())
+}
@@ -0,0 +1,178 @@
+//! End-to-end Real-Debrid and scraper flows against a local mock server
+
+mod common;
+
+use common::{received, route, sequence};
+use littlejohn::realdebrid::{self, RealDebridClient};
+use littlejohn::scrapers;
+use wiremock::MockServer;
+
+const API: &str = "/rest/1.0";
+const MAGNET: &str = "magnet:?xt=urn:btih:0123456789abcdef0123456789abcdef01234567&dn=Test.Movie.2024.1080p";
+
+/// RD_API_URL and RD_API_TOKEN are read when a client is created; tests create theirs one at a time
+static ENV: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+fn client(server: &MockServer) -> RealDebridClient {
+    let _env = ENV.lock().unwrap_or_else(|e| e.into_inner());
+    std::env::set_var("RD_API_TOKEN", "mock-token");
+    std::env::set_var("RD_API_URL", format!("{}{}", server.uri(), API));
+    std::env::set_var("RD_POLL_INTERVAL", "1");
+    RealDebridClient::new().expect("client")
+}
+
+fn path(endpoint: &str) -> String {
+    format!("{}{}", API, endpoint)
+}
+
+const FILES: &str = r#"[
+    {"id": 1, "path": "/Test.Movie.2024.1080p/movie.mkv", "bytes": 2000000000, "selected": 0},
+    {"id": 2, "path": "/Test.Movie.2024.1080p/sample.mkv", "bytes": 20000000, "selected": 0}
+]"#;
+
+const FILES_SELECTED: &str = r#"[
+    {"id": 1, "path": "/Test.Movie.2024.1080p/movie.mkv", "bytes": 2000000000, "selected": 1},
+    {"id": 2, "path": "/Test.Movie.2024.1080p/sample.mkv", "bytes": 20000000, "selected": 0}
+]"#;
+
+/// Hoster statuses stay cached for five minutes and the cache is shared by every test in
+/// this binary, so every test serves the same ones
+const HOSTS: &str = r#"{
+    "hoster.example": {"id": "hoster.example", "name": "Hoster", "status": "up"},
+    "down.example": {"id": "down.example", "name": "DownHost", "status": "down"}
+}"#;
+
+#[tokio::test]
+async fn magnet_to_download_links() {
+    let waiting = format!(r#"{{"id": "TORRENT1", "status": "waiting_files_selection", "files": {}}}"#, FILES);
+    let downloaded = format!(
+        r#"{{"id": "TORRENT1", "status": "downloaded", "progress": 100, "files": {}, "links": ["https://hoster.example/abc"]}}"#,
+        FILES_SELECTED
+    );
+    let server = MockServer::start().await;
+    route(&server, "POST", &path("/torrents/addMagnet"), 201, r#"{"id": "TORRENT1", "uri": "https://api/torrents/info/TORRENT1"}"#).await;
+    sequence(&server, "GET", &path("/torrents/info/TORRENT1"), &[(200, &waiting), (200, &waiting), (200, &downloaded)]).await;
+    route(&server, "POST", &path("/torrents/selectFiles/TORRENT1"), 204, "").await;
+    route(&server, "GET", &path("/hosts/status"), 200, HOSTS).await;
+    route(
+        &server,
+        "POST",
+        &path("/unrestrict/link"),
+        200,
+        r#"{"filename": "movie.mkv", "download": "https://download.example/d/movie.mkv", "filesize": 2000000000}"#,
+    )
+    .await;
+    let rd = client(&server);
+
+    let (torrent_id, files) = rd.get_torrent_files(MAGNET).await.expect("file list");
+    assert_eq!(torrent_id, "TORRENT1");
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[0].name(), "movie.mkv");
+
+    let links = rd.download_selected_files(&torrent_id, &[1]).await.expect("links");
+    assert_eq!(links, vec![("movie.mkv".to_string(), "https://download.example/d/movie.mkv".to_string())]);
+    assert_eq!(
+        realdebrid::source_link("https://download.example/d/movie.mkv").as_deref(),
+        Some("https://hoster.example/abc")
+    );
+
+    let add = common::body(&server, "POST", &path("/torrents/addMagnet")).await.expect("addMagnet");
+    assert!(add.starts_with("magnet=magnet%3A"));
+    let select = common::body(&server, "POST", &path("/torrents/selectFiles/TORRENT1")).await;
+    assert_eq!(select.as_deref(), Some("files=1"));
+}
+
+#[tokio::test]
+async fn bad_magnet_removes_torrent() {
+    let server = MockServer::start().await;
+    route(&server, "POST", &path("/torrents/addMagnet"), 201, r#"{"id": "TORRENT2", "uri": ""}"#).await;
+    route(&server, "GET", &path("/torrents/info/TORRENT2"), 200, r#"{"id": "TORRENT2", "status": "magnet_error"}"#).await;
+    route(&server, "DELETE", &path("/torrents/delete/TORRENT2"), 204, "").await;
+    let rd = client(&server);
+
+    let err = rd.get_torrent_files(MAGNET).await.expect_err("magnet_error");
+    assert!(err.to_string().contains("Invalid magnet"));
+    assert!(received(&server, "DELETE", &path("/torrents/delete/TORRENT2")).await);
+}
+
+#[tokio::test]
+async fn rejected_token_is_an_auth_error() {
+    let server = MockServer::start().await;
+    route(&server, "GET", &path("/user"), 401, r#"{"error": "bad_token", "error_code": 8}"#).await;
+    let rd = client(&server);
+
+    let err = rd.get_user().await.expect_err("401");
+    assert!(realdebrid::is_auth_error(&err));
+}
+
+#[tokio::test]
+async fn duplicate_found_past_the_first_page() {
+    let torrent = |i: usize| format!(r#"{{"id": "T{}", "filename": "other", "hash": "{:040x}", "status": "downloaded"}}"#, i, i + 1);
+    let first: Vec<String> = (0..1000).map(torrent).collect();
+    let second = r#"[{"id": "DUP", "filename": "Test.Movie", "hash": "0123456789ABCDEF0123456789ABCDEF01234567", "status": "downloaded"}]"#;
+    let server = MockServer::start().await;
+    sequence(&server, "GET", &path("/torrents"), &[(200, &format!("[{}]", first.join(","))), (200, second)]).await;
+    let rd = client(&server);
+
+    let found = rd.find_torrent_by_hash("0123456789abcdef0123456789abcdef01234567").await.expect("torrents");
+    assert_eq!(found.map(|t| t.id).as_deref(), Some("DUP"));
+    assert_eq!(common::requests(&server).await.len(), 2);
+}
+
+#[tokio::test]
+async fn host_down_for_maintenance() {
+    let server = MockServer::start().await;
+    route(&server, "GET", &path("/hosts/status"), 200, HOSTS).await;
+    let rd = client(&server);
+
+    let err = rd.unrestrict("https://down.example/file/123").await.expect_err("host down");
+    assert!(err.to_string().contains("DownHost is down for maintenance"));
+    assert!(!received(&server, "POST", &path("/unrestrict/link")).await);
+}
+
+#[tokio::test]
+async fn scraped_detail_page_magnet() {
+    let page = format!(
+        r#"<html><body><h1>Test Movie</h1><ul><li><a href="{}">Magnet Download</a></li></ul></body></html>"#,
+        MAGNET.replace('&', "&amp;")
+    );
+    let server = MockServer::start().await;
+    route(&server, "GET", "/torrent/1/test-movie/", 200, &page).await;
+
+    let magnet = scrapers::x1337::fetch_detail(&reqwest::Client::new(), &format!("{}/torrent/1/test-movie/", server.uri())).await;
+    assert_eq!(magnet.as_deref(), Some(MAGNET));
+}
+
+#[tokio::test]
+async fn nyaa_search_page() {
+    let page = format!(
+        r#"<html><body><table class="table torrent-list"><tbody>
+        <tr class="success">
+            <td><a href="/?c=1_2" title="Anime - English-translated"><img alt="Anime - English-translated"></a></td>
+            <td colspan="2">
+                <a href="/view/1800000#comments" class="comments" title="4 comments">4</a>
+                <a href="/view/1800000" title="[SubsPlease] Test Show - 01 (1080p) [ABCD1234].mkv">[SubsPlease] Test Show - 01 (1080p) [ABCD1234].mkv</a>
+            </td>
+            <td class="text-center"><a href="/download/1800000.torrent">t</a> <a href="{}">m</a></td>
+            <td class="text-center">1.4 GiB</td>
+            <td class="text-center" data-timestamp="1700000000">2023-11-14 22:13</td>
+            <td class="text-center">1,234</td>
+            <td class="text-center">56</td>
+            <td class="text-center">7890</td>
+        </tr>
+        </tbody></table></body></html>"#,
+        MAGNET.replace('&', "&amp;")
+    );
+    let server = MockServer::start().await;
+    route(&server, "GET", "/", 200, &page).await;
+
+    let results = scrapers::nyaa::fetch_results(&reqwest::Client::new(), &format!("{}/?q=test", server.uri())).await.expect("results");
+    assert_eq!(results.len(), 1);
+    let result = &results[0];
+    assert_eq!(result.name, "[SubsPlease] Test Show - 01 (1080p) [ABCD1234].mkv");
+    assert_eq!(result.magnet, MAGNET);
+    assert_eq!(result.size, "1.4 GiB");
+    assert_eq!((result.seeders, result.leechers), (1234, 56));
+    assert_eq!(result.category.as_deref(), Some("Anime - English-translated"));
+    assert_eq!(result.url.as_deref(), Some("https://nyaa.si/view/1800000"));
+}